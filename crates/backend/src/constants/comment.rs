@@ -0,0 +1,2 @@
+/// How long after posting a commenter can still edit their comment
+pub const COMMENT_EDIT_WINDOW_MINUTES: i64 = 15;
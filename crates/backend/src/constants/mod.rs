@@ -1,3 +1,4 @@
 pub mod auth;
+pub mod comment;
 pub mod like;
 pub mod time;
@@ -0,0 +1,239 @@
+#[cfg(test)]
+mod tests {
+
+    use axum::{
+        body::Body,
+        http::{header, Request, StatusCode},
+        Router,
+    };
+    use bson::oid::ObjectId;
+    use chrono::{DateTime, Duration, Utc};
+    use http_body_util::BodyExt;
+    use tower::ServiceExt;
+
+    use crate::{
+        auth::token::Token,
+        constants::{
+            auth::TOKEN_COOKIE_KEY, comment::COMMENT_EDIT_WINDOW_MINUTES, like::VISITOR_COOKIE_KEY,
+        },
+        controllers::app::{app, API_VERSION_PREFIX},
+        env::state::AppState,
+        models::{
+            comment::Comment,
+            user::{User, UserRole},
+        },
+        utils::ip::hash_ip,
+    };
+
+    async fn create_user(state: &AppState, role: UserRole) -> String {
+        let user = User::create(
+            &state.db,
+            User {
+                id: None,
+                name: format!("test-{}", ObjectId::new()),
+                password: String::new(),
+                role,
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+            },
+        )
+        .await
+        .unwrap();
+
+        format!(
+            "{}={}",
+            TOKEN_COOKIE_KEY,
+            Token::from_user(&user, &state.jwt_secret).unwrap()
+        )
+    }
+
+    const AUTHOR_IP: &str = "203.0.113.9";
+    const AUTHOR_VISITOR_ID: &str = "test-visitor";
+
+    async fn create_comment(state: &AppState, created_at: DateTime<Utc>) -> Comment {
+        Comment::create(
+            &state.db,
+            Comment {
+                id: None,
+                name: "Spammer".to_string(),
+                post_slug: "delete-test".to_string(),
+                by_post_author: false,
+                email: "spam@example.com".to_string(),
+                url: "https://spam.example.com".to_string(),
+                body: "Buy now".to_string(),
+                ip_hash: hash_ip(AUTHOR_IP),
+                visitor_id: AUTHOR_VISITOR_ID.to_string(),
+                parent_comment_id: None,
+                created_at,
+                updated_at: created_at,
+                deleted_at: None,
+                replies: None,
+            },
+        )
+        .await
+        .unwrap()
+    }
+
+    fn delete_request(id: &str, cookie: &str) -> Request<Body> {
+        Request::builder()
+            .method("DELETE")
+            .uri(format!("{}/comment/{}", API_VERSION_PREFIX, id))
+            .header(header::COOKIE, cookie)
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    fn edit_request(id: &str, cookie: &str) -> Request<Body> {
+        Request::builder()
+            .method("PATCH")
+            .uri(format!("{}/comment/{}", API_VERSION_PREFIX, id))
+            .header(header::COOKIE, cookie)
+            .header(header::CONTENT_TYPE, "application/json")
+            .header("x-real-ip", AUTHOR_IP)
+            .body(Body::from(r#"{"body":"Edited"}"#))
+            .unwrap()
+    }
+
+    fn author_cookie() -> String {
+        format!("{}={}", VISITOR_COOKIE_KEY, AUTHOR_VISITOR_ID)
+    }
+
+    fn expired() -> DateTime<Utc> {
+        Utc::now() - Duration::minutes(COMMENT_EDIT_WINDOW_MINUTES + 1)
+    }
+
+    #[tokio::test]
+    async fn should_soft_delete_comment_as_root() {
+        let state = AppState::new().await.unwrap();
+        let cookie = create_user(&state, UserRole::Root).await;
+        let comment = create_comment(&state, Utc::now()).await;
+        let id = comment.id.unwrap();
+
+        let app: Router<AppState> = app();
+        let response = app
+            .with_state(state.clone())
+            .oneshot(delete_request(&id.to_string(), &cookie))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let html = String::from_utf8(body.to_vec()).unwrap();
+        assert!(html.contains("삭제된 댓글입니다."));
+        assert!(!html.contains("Buy now"));
+        assert!(!html.contains("spam.example.com"));
+
+        let stored = Comment::find_by_id(&state.db, &id).await.unwrap().unwrap();
+        assert!(stored.deleted_at.is_some());
+        assert!(stored.body.is_empty());
+        assert!(stored.email.is_empty());
+        assert!(stored.url.is_empty());
+    }
+
+    #[tokio::test]
+    async fn should_forbid_non_root_users() {
+        let state = AppState::new().await.unwrap();
+        let cookie = create_user(&state, UserRole::User).await;
+        let comment = create_comment(&state, Utc::now()).await;
+        let id = comment.id.unwrap();
+
+        let app: Router<AppState> = app();
+        let response = app
+            .with_state(state.clone())
+            .oneshot(delete_request(&id.to_string(), &cookie))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        let stored = Comment::find_by_id(&state.db, &id).await.unwrap().unwrap();
+        assert!(stored.deleted_at.is_none());
+    }
+
+    #[tokio::test]
+    async fn should_return_not_found_for_missing_comment() {
+        let state = AppState::new().await.unwrap();
+        let cookie = create_user(&state, UserRole::Root).await;
+
+        let app: Router<AppState> = app();
+        let response = app
+            .with_state(state)
+            .oneshot(delete_request(&ObjectId::new().to_string(), &cookie))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn should_let_author_edit_within_window() {
+        let state = AppState::new().await.unwrap();
+        let comment = create_comment(&state, Utc::now()).await;
+        let id = comment.id.unwrap();
+
+        let app: Router<AppState> = app();
+        let response = app
+            .with_state(state.clone())
+            .oneshot(edit_request(&id.to_string(), &author_cookie()))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let stored = Comment::find_by_id(&state.db, &id).await.unwrap().unwrap();
+        assert_eq!(stored.body, "Edited");
+    }
+
+    #[tokio::test]
+    async fn should_reject_author_edit_after_window() {
+        let state = AppState::new().await.unwrap();
+        let comment = create_comment(&state, expired()).await;
+        let id = comment.id.unwrap();
+
+        let app: Router<AppState> = app();
+        let response = app
+            .with_state(state.clone())
+            .oneshot(edit_request(&id.to_string(), &author_cookie()))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        let stored = Comment::find_by_id(&state.db, &id).await.unwrap().unwrap();
+        assert_eq!(stored.body, "Buy now");
+    }
+
+    #[tokio::test]
+    async fn should_let_root_edit_after_window() {
+        let state = AppState::new().await.unwrap();
+        let cookie = create_user(&state, UserRole::Root).await;
+        let comment = create_comment(&state, expired()).await;
+        let id = comment.id.unwrap();
+
+        let app: Router<AppState> = app();
+        let response = app
+            .with_state(state.clone())
+            .oneshot(edit_request(&id.to_string(), &cookie))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let stored = Comment::find_by_id(&state.db, &id).await.unwrap().unwrap();
+        assert_eq!(stored.body, "Edited");
+    }
+
+    #[tokio::test]
+    async fn should_not_edit_comment_deleted_after_lookup() {
+        let state = AppState::new().await.unwrap();
+        let comment = create_comment(&state, Utc::now()).await;
+        let id = comment.id.unwrap();
+        Comment::soft_delete(&state.db, &id.to_string())
+            .await
+            .unwrap();
+
+        let updated = Comment::update_body(&state.db, &id, "Edited")
+            .await
+            .unwrap();
+
+        assert!(updated.is_none());
+        let stored = Comment::find_by_id(&state.db, &id).await.unwrap().unwrap();
+        assert!(stored.body.is_empty());
+    }
+}
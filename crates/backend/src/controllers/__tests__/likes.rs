@@ -0,0 +1,71 @@
+#[cfg(test)]
+mod tests {
+
+    use axum::{
+        body::Body,
+        http::{header, Request, StatusCode},
+        Router,
+    };
+    use bson::oid::ObjectId;
+    use http_body_util::BodyExt;
+    use tower::ServiceExt;
+
+    use crate::{
+        constants::like::VISITOR_COOKIE_KEY,
+        controllers::app::{app, API_VERSION_PREFIX},
+        env::state::AppState,
+        models::like::Like,
+        utils::ip::hash_ip,
+    };
+
+    const LIKER_IP: &str = "198.51.100.7";
+    const LIKER_VISITOR_ID: &str = "liked-visitor";
+
+    async fn get_status(state: AppState, post_slug: &str, ip: &str, visitor_id: &str) -> String {
+        let app: Router<AppState> = app();
+        let response = app
+            .with_state(state)
+            .oneshot(
+                Request::builder()
+                    .uri(format!(
+                        "{}/like/status?postSlug={}",
+                        API_VERSION_PREFIX, post_slug
+                    ))
+                    .header(
+                        header::COOKIE,
+                        format!("{}={}", VISITOR_COOKIE_KEY, visitor_id),
+                    )
+                    .header("x-real-ip", ip)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        String::from_utf8(body.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn should_render_liked_state_for_visitor() {
+        let state = AppState::new().await.unwrap();
+        let post_slug = format!("like-test-{}", ObjectId::new());
+        Like::toggle(&state.db, &post_slug, &hash_ip(LIKER_IP), LIKER_VISITOR_ID)
+            .await
+            .unwrap();
+
+        let liked = get_status(
+            state.clone(),
+            &format!("{}/", post_slug),
+            LIKER_IP,
+            LIKER_VISITOR_ID,
+        )
+        .await;
+        let unknown = get_status(state, &post_slug, "192.0.2.1", "unknown-visitor").await;
+
+        assert!(liked.contains(r#"aria-pressed="true""#));
+        assert!(unknown.contains(r#"aria-pressed="false""#));
+        assert!(unknown.contains(">1</span>"));
+    }
+}
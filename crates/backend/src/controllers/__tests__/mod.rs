@@ -1 +1,3 @@
+mod comments;
 mod index;
+mod likes;
@@ -32,7 +32,7 @@ pub fn app() -> Router<AppState> {
         )
         .route(
             &format!("{}/comment/:id", API_VERSION_PREFIX),
-            delete(super::comments::delete::delete),
+            delete(super::comments::delete::delete).patch(super::comments::update::patch),
         )
         .route(
             &format!("{}/recent", API_VERSION_PREFIX),
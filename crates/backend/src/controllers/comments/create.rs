@@ -1,6 +1,6 @@
 use axum::{
     extract::State,
-    http::{header, StatusCode},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
     response::IntoResponse,
 };
 use bson::oid::ObjectId;
@@ -12,11 +12,16 @@ use validator::Validate;
 use crate::{
     auth::guard::AuthUserOrPublic,
     env::state::AppState,
-    models::{comment::Comment, user::UserRole},
+    models::{
+        comment::{Comment, ReplyTooDeep},
+        user::UserRole,
+    },
     templates::TEMPLATES,
     utils::{
+        ip::{hash_ip, ClientIp},
         slug::normalize_slug,
         validator::ValidatedJson,
+        visitor::{visitor_cookie, visitor_id},
         webhook::{send_message, DiscordEmbed, DiscordField},
     },
 };
@@ -46,18 +51,58 @@ pub struct AddCommentPayload {
 pub async fn post(
     AuthUserOrPublic { user }: AuthUserOrPublic,
     State(state): State<AppState>,
+    ClientIp(ip): ClientIp,
+    headers: HeaderMap,
     ValidatedJson(payload): ValidatedJson<AddCommentPayload>,
 ) -> impl IntoResponse {
     let is_root = user.is_some() && user.unwrap().role == UserRole::Root;
+    let ip_hash = hash_ip(&ip);
+
+    if !is_root && !state.comment_rate_limiter.check(&ip_hash) {
+        return (
+            StatusCode::TOO_MANY_REQUESTS,
+            [(header::CONTENT_TYPE, "text/html; charset=utf-8")],
+            "<p class=\"comment-form__error\">댓글을 너무 자주 작성하고 있습니다. 잠시 후 다시 시도해 주세요.</p>".to_string(),
+        )
+            .into_response();
+    }
+
     let post_slug = normalize_slug(&payload.post_slug).to_string();
+    let (visitor_id, is_new_visitor) = visitor_id(&headers);
 
-    // Replies are always 1-depth: normalize the parent to the top-level comment
-    // so replying to a reply still threads under the root comment.
+    // Replies deeper than the configured depth are re-attached to an allowed
+    // ancestor or rejected, so threads can't nest past what the layout fits.
     let parent_comment_id = match payload
         .parent_comment_id
         .and_then(|id| ObjectId::parse_str(&id).ok())
     {
-        Some(parent_id) => Some(Comment::resolve_root_parent(&state.db, parent_id).await),
+        Some(parent_id) => {
+            let ancestors = match Comment::ancestors(&state.db, parent_id).await {
+                Ok(ancestors) => ancestors,
+                Err(e) => {
+                    log::error!("Failed to resolve comment parent: {}", e);
+                    return (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        [(header::CONTENT_TYPE, "text/html; charset=utf-8")],
+                        "<p class=\"comment-form__error\">댓글 등록에 실패했습니다.</p>"
+                            .to_string(),
+                    )
+                        .into_response();
+                }
+            };
+
+            match Comment::place_reply(&ancestors, state.max_reply_depth, state.reply_overflow) {
+                Ok(parent) => parent,
+                Err(ReplyTooDeep) => {
+                    return (
+                        StatusCode::UNPROCESSABLE_ENTITY,
+                        [(header::CONTENT_TYPE, "text/html; charset=utf-8")],
+                        "<p class=\"comment-form__error\">이 댓글에는 더 이상 답글을 달 수 없습니다.</p>".to_string(),
+                    )
+                        .into_response();
+                }
+            }
+        }
         None => None,
     };
 
@@ -68,10 +113,13 @@ pub async fn post(
         email: payload.email.unwrap_or_default(),
         url: payload.url.unwrap_or_default(),
         body: payload.body,
+        ip_hash,
+        visitor_id: visitor_id.clone(),
         parent_comment_id,
         by_post_author: is_root,
         created_at: Utc::now(),
         updated_at: Utc::now(),
+        deleted_at: None,
         replies: None,
     };
 
@@ -122,12 +170,22 @@ pub async fn post(
     context.insert("border", &true);
 
     match TEMPLATES.render("comments/comment.html", &context) {
-        Ok(html) => (
-            StatusCode::CREATED,
-            [(header::CONTENT_TYPE, "text/html; charset=utf-8")],
-            html,
-        )
-            .into_response(),
+        Ok(html) => {
+            let mut response_headers = HeaderMap::new();
+            response_headers.insert(
+                header::CONTENT_TYPE,
+                HeaderValue::from_static("text/html; charset=utf-8"),
+            );
+
+            // Lets the commenter be recognised when editing within the window
+            if is_new_visitor {
+                if let Some(value) = visitor_cookie(visitor_id, &state.cookie_domain) {
+                    response_headers.insert(header::SET_COOKIE, value);
+                }
+            }
+
+            (StatusCode::CREATED, response_headers, html).into_response()
+        }
         Err(e) => {
             log::error!("Template render error: {}", e);
             (
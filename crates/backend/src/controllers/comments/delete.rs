@@ -1,15 +1,18 @@
 use axum::{
     extract::{Path, State},
-    http::StatusCode,
+    http::{header, StatusCode},
     response::IntoResponse,
     Json,
 };
 use serde_json::json;
+use tera::Context;
 
 use crate::{
     auth::guard::AuthUser,
     env::state::AppState,
     models::{comment::Comment, user::UserRole},
+    templates::TEMPLATES,
+    utils::webhook::{send_message, DiscordEmbed, DiscordField},
 };
 
 pub async fn delete(
@@ -25,14 +28,53 @@ pub async fn delete(
             .into_response();
     }
 
-    match Comment::delete(&state.db, &id).await {
-        Ok(_) => (
+    let deleted_comment = match Comment::soft_delete(&state.db, &id).await {
+        Ok(Some(comment)) => comment,
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(json!({ "message": "Comment not found" })),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            log::error!("Failed to delete comment: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "message": "Failed to delete comment" })),
+            )
+                .into_response();
+        }
+    };
+
+    send_message(DiscordEmbed {
+        embed_type: "rich".to_string(),
+        title: "Comment deleted".to_string(),
+        description: format!(
+            "Comment by {} on {} was deleted",
+            deleted_comment.name, deleted_comment.post_slug
+        ),
+        color: None,
+        fields: vec![DiscordField {
+            name: "ID".to_string(),
+            value: id,
+        }],
+        footer: None,
+    });
+
+    let mut context = Context::new();
+    context.insert("comment", &deleted_comment.to_response());
+    context.insert("border", &deleted_comment.parent_comment_id.is_none());
+
+    match TEMPLATES.render("comments/comment.html", &context) {
+        Ok(html) => (
             StatusCode::OK,
-            Json(json!({ "message": "Comment deleted successfully" })),
+            [(header::CONTENT_TYPE, "text/html; charset=utf-8")],
+            html,
         )
             .into_response(),
         Err(e) => {
-            log::error!("Failed to delete comment: {}", e);
+            log::error!("Template render error: {}", e);
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 Json(json!({ "message": "Failed to delete comment" })),
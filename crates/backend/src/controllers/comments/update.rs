@@ -0,0 +1,105 @@
+use axum::{
+    extract::{Path, State},
+    http::{header, HeaderMap, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use bson::oid::ObjectId;
+use chrono::Utc;
+use serde::Deserialize;
+use serde_json::json;
+use tera::Context;
+use validator::Validate;
+
+use crate::{
+    auth::guard::AuthUserOrPublic,
+    env::state::AppState,
+    models::{comment::Comment, user::UserRole},
+    templates::TEMPLATES,
+    utils::{
+        ip::{hash_ip, ClientIp},
+        validator::ValidatedJson,
+        visitor::visitor_id,
+    },
+};
+
+#[derive(Deserialize, Validate)]
+pub struct EditCommentPayload {
+    #[validate(length(min = 1, message = "Comment body cannot be empty"))]
+    pub body: String,
+}
+
+pub async fn patch(
+    AuthUserOrPublic { user }: AuthUserOrPublic,
+    State(state): State<AppState>,
+    ClientIp(ip): ClientIp,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    ValidatedJson(payload): ValidatedJson<EditCommentPayload>,
+) -> impl IntoResponse {
+    let not_found = || {
+        (
+            StatusCode::NOT_FOUND,
+            Json(json!({ "message": "Comment not found" })),
+        )
+            .into_response()
+    };
+    let internal_error = || {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "message": "Failed to edit comment" })),
+        )
+            .into_response()
+    };
+
+    let Ok(object_id) = ObjectId::parse_str(&id) else {
+        return not_found();
+    };
+    let comment = match Comment::find_by_id(&state.db, &object_id).await {
+        Ok(Some(comment)) if comment.deleted_at.is_none() => comment,
+        Ok(_) => return not_found(),
+        Err(e) => {
+            log::error!("Failed to find comment: {}", e);
+            return internal_error();
+        }
+    };
+
+    let is_root = user.is_some_and(|user| user.role == UserRole::Root);
+    let (visitor_id, _) = visitor_id(&headers);
+    let can_edit = is_root
+        || (comment.is_author(&hash_ip(&ip), &visitor_id) && comment.is_editable_at(Utc::now()));
+
+    if !can_edit {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(json!({ "message": "You can no longer edit this comment" })),
+        )
+            .into_response();
+    }
+
+    let updated_comment = match Comment::update_body(&state.db, &object_id, &payload.body).await {
+        Ok(Some(comment)) => comment,
+        Ok(None) => return not_found(),
+        Err(e) => {
+            log::error!("Failed to edit comment: {}", e);
+            return internal_error();
+        }
+    };
+
+    let mut context = Context::new();
+    context.insert("comment", &updated_comment.to_response());
+    context.insert("border", &updated_comment.parent_comment_id.is_none());
+
+    match TEMPLATES.render("comments/comment.html", &context) {
+        Ok(html) => (
+            StatusCode::OK,
+            [(header::CONTENT_TYPE, "text/html; charset=utf-8")],
+            html,
+        )
+            .into_response(),
+        Err(e) => {
+            log::error!("Template render error: {}", e);
+            internal_error()
+        }
+    }
+}
@@ -3,20 +3,21 @@ use axum::{
     http::{header, HeaderMap, HeaderValue, StatusCode},
     response::IntoResponse,
 };
-use axum_extra::extract::cookie::CookieJar;
-use cookie::{Cookie, SameSite};
 use serde::Deserialize;
 use tera::Context;
-use time::Duration;
-use uuid::Uuid;
 use validator::Validate;
 
 use crate::{
-    constants::like::VISITOR_COOKIE_KEY,
     env::state::AppState,
-    models::like::Like,
+    models::like::{Like, LikeMilestones},
     templates::TEMPLATES,
-    utils::{ip::ClientIp, slug::normalize_slug, validator::ValidatedJson},
+    utils::{
+        ip::ClientIp,
+        slug::normalize_slug,
+        validator::ValidatedJson,
+        visitor::{visitor_cookie, visitor_id},
+        webhook::{send_message, DiscordEmbed, DiscordField},
+    },
 };
 
 #[derive(Deserialize, Validate)]
@@ -32,14 +33,10 @@ pub async fn post(
     headers: HeaderMap,
     ValidatedJson(payload): ValidatedJson<ToggleLikePayload>,
 ) -> impl IntoResponse {
-    let cookie_jar = CookieJar::from_headers(&headers);
     let post_slug = normalize_slug(&payload.post_slug).to_string();
     let ip_hash = crate::utils::ip::hash_ip(&ip);
 
-    let (visitor_id, is_new_visitor) = match cookie_jar.get(VISITOR_COOKIE_KEY) {
-        Some(cookie) => (cookie.value().to_string(), false),
-        None => (Uuid::new_v4().to_string(), true),
-    };
+    let (visitor_id, is_new_visitor) = visitor_id(&headers);
 
     let (liked, count) = match Like::toggle(&state.db, &post_slug, &ip_hash, &visitor_id).await {
         Ok(result) => result,
@@ -54,6 +51,14 @@ pub async fn post(
         }
     };
 
+    if let Some(milestone) = reached_milestone(liked, count, &state.like_milestones) {
+        match LikeMilestones::claim(&state.db, &post_slug, milestone).await {
+            Ok(true) => send_message(milestone_message(&post_slug, milestone)),
+            Ok(false) => {}
+            Err(e) => log::error!("Failed to record like milestone: {}", e),
+        }
+    }
+
     let mut context = Context::new();
     context.insert("liked", &liked);
     context.insert("count", &count);
@@ -78,18 +83,77 @@ pub async fn post(
     );
 
     if is_new_visitor {
-        let cookie = Cookie::build((VISITOR_COOKIE_KEY, visitor_id))
-            .path("/")
-            .secure(true)
-            .http_only(true)
-            .max_age(Duration::days(365 * 2))
-            .same_site(SameSite::None)
-            .domain(state.cookie_domain.clone());
-
-        if let Ok(value) = HeaderValue::from_str(&cookie.to_string()) {
+        if let Some(value) = visitor_cookie(visitor_id, &state.cookie_domain) {
             response_headers.insert(header::SET_COOKIE, value);
         }
     }
 
     (StatusCode::OK, response_headers, html).into_response()
 }
+
+/// The milestone a new like brings the post exactly to. Unlikes and counts
+/// between milestones stay silent so busy posts don't flood the channel;
+/// `LikeMilestones::claim` then keeps each milestone to a single announcement.
+fn reached_milestone(liked: bool, count: u64, milestones: &[u64]) -> Option<u64> {
+    (liked && milestones.contains(&count)).then_some(count)
+}
+
+fn milestone_message(post_slug: &str, count: u64) -> DiscordEmbed {
+    DiscordEmbed {
+        embed_type: "rich".to_string(),
+        title: "Like milestone reached".to_string(),
+        description: format!("{} reached {} likes", post_slug, count),
+        color: None,
+        fields: vec![DiscordField {
+            name: "Likes".to_string(),
+            value: count.to_string(),
+        }],
+        footer: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MILESTONES: [u64; 3] = [10, 50, 100];
+
+    #[test]
+    fn notifies_when_like_reaches_milestone() {
+        let milestone = reached_milestone(true, 50, &MILESTONES).unwrap();
+
+        assert_eq!(
+            milestone_message("hello", milestone).description,
+            "hello reached 50 likes"
+        );
+    }
+
+    #[test]
+    fn ignores_likes_between_milestones() {
+        assert!(reached_milestone(true, 11, &MILESTONES).is_none());
+    }
+
+    #[test]
+    fn ignores_unlikes() {
+        assert!(reached_milestone(false, 10, &MILESTONES).is_none());
+    }
+
+    #[test]
+    fn announces_milestone_on_first_claim() {
+        let milestone = reached_milestone(true, 10, &MILESTONES).unwrap();
+
+        assert!(LikeMilestones::newly_notified(None, milestone));
+    }
+
+    #[test]
+    fn does_not_reannounce_milestone_after_relike() {
+        let before = LikeMilestones {
+            post_slug: "hello".to_string(),
+            notified_milestones: vec![10],
+        };
+        let milestone = reached_milestone(true, 10, &MILESTONES).unwrap();
+
+        assert!(!LikeMilestones::newly_notified(Some(&before), milestone));
+        assert!(LikeMilestones::newly_notified(Some(&before), 50));
+    }
+}
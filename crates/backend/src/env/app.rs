@@ -1,12 +1,27 @@
 use anyhow::{Context, Result};
 use std::borrow::Cow;
 
+use crate::models::comment::ReplyOverflow;
+
+const DEFAULT_COMMENT_RATE_LIMIT: usize = 5;
+const DEFAULT_COMMENT_RATE_WINDOW_SECONDS: u64 = 600;
+const DEFAULT_LIKE_MILESTONES: [u64; 3] = [10, 50, 100];
+const DEFAULT_MAX_REPLY_DEPTH: usize = 1;
+
 #[derive(Clone, Debug)]
 pub struct Env {
     pub port: u16,
     pub host: Cow<'static, str>,
     pub jwt_secret: Cow<'static, str>,
     pub cookie_domain: Cow<'static, str>,
+    /// Comments accepted per IP within `comment_rate_window_seconds`
+    pub comment_rate_limit: usize,
+    pub comment_rate_window_seconds: u64,
+    /// Like counts that trigger a Discord notification when reached
+    pub like_milestones: Vec<u64>,
+    /// Deepest level a reply may sit at (top-level comments are depth 0)
+    pub max_reply_depth: usize,
+    pub reply_overflow: ReplyOverflow,
 }
 
 impl Env {
@@ -26,12 +41,50 @@ impl Env {
             Ok(cookie_domain) => Cow::Owned(cookie_domain),
             Err(_) => Cow::Owned("localhost".to_string()),
         };
+        let comment_rate_limit = std::env::var("COMMENT_RATE_LIMIT")
+            .ok()
+            .and_then(|limit| limit.parse().ok())
+            .unwrap_or(DEFAULT_COMMENT_RATE_LIMIT);
+        let comment_rate_window_seconds = std::env::var("COMMENT_RATE_WINDOW_SECONDS")
+            .ok()
+            .and_then(|seconds| seconds.parse().ok())
+            .unwrap_or(DEFAULT_COMMENT_RATE_WINDOW_SECONDS);
+        let like_milestones = match std::env::var("LIKE_MILESTONES") {
+            Ok(milestones) => parse_milestones(&milestones),
+            Err(_) => DEFAULT_LIKE_MILESTONES.to_vec(),
+        };
+        let max_reply_depth = std::env::var("COMMENT_MAX_REPLY_DEPTH")
+            .ok()
+            .and_then(|depth| depth.parse().ok())
+            .unwrap_or(DEFAULT_MAX_REPLY_DEPTH);
+        let reply_overflow = match std::env::var("COMMENT_REPLY_OVERFLOW") {
+            Ok(overflow) => ReplyOverflow::parse(&overflow).with_context(|| {
+                format!(
+                    "COMMENT_REPLY_OVERFLOW must be `reparent` or `reject` (got `{}`)",
+                    overflow
+                )
+            })?,
+            Err(_) => ReplyOverflow::Reparent,
+        };
 
         Ok(Self {
             port,
             host,
             jwt_secret,
             cookie_domain,
+            comment_rate_limit,
+            comment_rate_window_seconds,
+            like_milestones,
+            max_reply_depth,
+            reply_overflow,
         })
     }
 }
+
+/// Parses a comma-separated list like `10,50,100`, skipping invalid entries
+fn parse_milestones(value: &str) -> Vec<u64> {
+    value
+        .split(',')
+        .filter_map(|milestone| milestone.trim().parse().ok())
+        .collect()
+}
@@ -1,4 +1,6 @@
-use crate::database::init_db;
+use std::{sync::Arc, time::Duration};
+
+use crate::{database::init_db, models::comment::ReplyOverflow, utils::rate_limit::RateLimiter};
 
 use super::app::Env;
 use anyhow::Result;
@@ -12,6 +14,11 @@ pub struct AppState {
     pub db: Database,
     pub jwt_secret: String,
     pub cookie_domain: String,
+    /// Per-IP-hash limit on comment creation
+    pub comment_rate_limiter: Arc<RateLimiter>,
+    pub like_milestones: Vec<u64>,
+    pub max_reply_depth: usize,
+    pub reply_overflow: ReplyOverflow,
 }
 
 impl AppState {
@@ -27,6 +34,13 @@ impl AppState {
             db,
             jwt_secret: env.jwt_secret.into_owned(),
             cookie_domain: env.cookie_domain.into_owned(),
+            comment_rate_limiter: Arc::new(RateLimiter::new(
+                env.comment_rate_limit,
+                Duration::from_secs(env.comment_rate_window_seconds),
+            )),
+            like_milestones: env.like_milestones,
+            max_reply_depth: env.max_reply_depth,
+            reply_overflow: env.reply_overflow,
         })
     }
 }
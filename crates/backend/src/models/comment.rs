@@ -1,11 +1,40 @@
 use bson::doc;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use futures::TryStreamExt;
-use mongodb::{bson::oid::ObjectId, error::Error, Database};
+use mongodb::{bson::oid::ObjectId, error::Error, options::ReturnDocument, Database};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::constants::comment::COMMENT_EDIT_WINDOW_MINUTES;
 
 const COLLECTION_NAME: &str = "comment";
 
+/// Upper bound on parent links followed when computing a comment's depth
+const MAX_ANCESTOR_WALK: usize = 32;
+
+/// What to do with a reply that would exceed the maximum reply depth
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReplyOverflow {
+    /// Attach it to the deepest ancestor that still allows replies
+    Reparent,
+    /// Refuse to create it
+    Reject,
+}
+
+impl ReplyOverflow {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "reparent" => Some(Self::Reparent),
+            "reject" => Some(Self::Reject),
+            _ => None,
+        }
+    }
+}
+
+/// A reply was rejected for exceeding the maximum reply depth
+#[derive(Debug, PartialEq)]
+pub struct ReplyTooDeep;
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Comment {
     #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
@@ -34,6 +63,15 @@ pub struct Comment {
     /// The comment content
     pub body: String,
 
+    /// Hashed IP of the commenter, used with `visitor_id` to recognise them
+    /// when they edit the comment
+    #[serde(rename = "ipHash", default)]
+    pub ip_hash: String,
+
+    /// Visitor cookie of the commenter
+    #[serde(rename = "visitorId", default)]
+    pub visitor_id: String,
+
     /// Reference to parent comment if this is a reply
     #[serde(rename = "parentCommentId", skip_serializing_if = "Option::is_none")]
     pub parent_comment_id: Option<ObjectId>,
@@ -54,6 +92,11 @@ pub struct Comment {
     )]
     pub updated_at: DateTime<Utc>,
 
+    /// Set when the comment was removed by the root user. The document is
+    /// kept with a blank body so replies stay attached to it.
+    #[serde(rename = "deletedAt", default, skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<bson::DateTime>,
+
     /// Replies to this comment
     #[serde(skip_serializing_if = "Option::is_none")]
     pub replies: Option<Vec<Self>>,
@@ -92,6 +135,9 @@ pub struct CommentResponse {
     #[serde(rename = "updatedAt")]
     pub updated_at: Option<String>,
 
+    #[serde(rename = "deletedAt", skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<String>,
+
     #[serde(skip_serializing_if = "Option::is_none")]
     pub replies: Option<Vec<CommentResponse>>,
 }
@@ -120,16 +166,45 @@ impl Comment {
         collection.find_one(doc! {"_id": id}).await
     }
 
-    /// Resolves the top-level comment a reply should attach to. Replies are kept
-    /// 1-depth: if `parent_id` points at a comment that is itself a reply, the
-    /// grandparent (the root comment) is returned instead.
-    pub async fn resolve_root_parent(db: &Database, parent_id: ObjectId) -> ObjectId {
-        match Self::find_by_id(db, &parent_id).await {
-            Ok(Some(parent)) => parent
-                .parent_comment_id
-                .filter(|p| *p != ObjectId::default())
-                .unwrap_or(parent_id),
-            _ => parent_id,
+    /// The chain of comments from `parent_id` up to its top-level comment:
+    /// `[parent, grandparent, ..., top-level]`. Stops at a missing comment and
+    /// after `MAX_ANCESTOR_WALK` steps so a corrupted cycle can't loop.
+    pub async fn ancestors(db: &Database, parent_id: ObjectId) -> Result<Vec<ObjectId>, Error> {
+        let mut chain = vec![parent_id];
+
+        while chain.len() < MAX_ANCESTOR_WALK {
+            let current = chain[chain.len() - 1];
+            let next = Self::find_by_id(db, &current)
+                .await?
+                .and_then(|comment| comment.parent_comment_id)
+                .filter(|p| *p != ObjectId::default() && !chain.contains(p));
+
+            match next {
+                Some(next) => chain.push(next),
+                None => break,
+            }
+        }
+
+        Ok(chain)
+    }
+
+    /// Picks the parent for a reply to `ancestors[0]` so it sits at most
+    /// `max_depth` levels deep (top-level comments are depth 0). Too-deep
+    /// replies are re-attached to the deepest allowed ancestor, or rejected,
+    /// per `overflow`.
+    pub fn place_reply(
+        ancestors: &[ObjectId],
+        max_depth: usize,
+        overflow: ReplyOverflow,
+    ) -> Result<Option<ObjectId>, ReplyTooDeep> {
+        if ancestors.len() <= max_depth {
+            return Ok(ancestors.first().copied());
+        }
+
+        match overflow {
+            ReplyOverflow::Reject => Err(ReplyTooDeep),
+            ReplyOverflow::Reparent if max_depth == 0 => Ok(None),
+            ReplyOverflow::Reparent => Ok(Some(ancestors[ancestors.len() - max_depth])),
         }
     }
 
@@ -145,6 +220,7 @@ impl Comment {
             parent_comment_id: self.parent_comment_id.map(|id| id.to_string()),
             created_at: self.created_at.to_rfc3339(),
             updated_at: Some(self.updated_at.to_rfc3339()),
+            deleted_at: self.deleted_at.map(|at| at.to_chrono().to_rfc3339()),
             replies: None,
         }
     }
@@ -163,39 +239,53 @@ impl Comment {
             all_comments.push(comment);
         }
 
-        let mut root_comments: Vec<CommentResponse> = Vec::new();
-        let mut replies: Vec<Self> = Vec::new();
+        Ok(Self::build_tree(all_comments))
+    }
+
+    /// Nests each comment under its `parent_comment_id`, at any depth.
+    /// Top-level comments are newest first and replies oldest first; replies
+    /// whose parent no longer exists are dropped.
+    pub fn build_tree(comments: Vec<Self>) -> Vec<CommentResponse> {
+        let mut children: HashMap<ObjectId, Vec<Self>> = HashMap::new();
+        let mut roots: Vec<Self> = Vec::new();
 
-        for comment in all_comments {
-            if comment.parent_comment_id.is_some()
-                && comment.parent_comment_id.unwrap() != ObjectId::default()
+        for comment in comments {
+            match comment
+                .parent_comment_id
+                .filter(|p| *p != ObjectId::default())
             {
-                replies.push(comment);
-            } else {
-                root_comments.push(comment.to_response());
+                Some(parent_id) => children.entry(parent_id).or_default().push(comment),
+                None => roots.push(comment),
             }
         }
 
-        for comment in &mut root_comments {
-            let mut replies_for_comment: Vec<Self> = Vec::new();
-
-            for reply in replies.iter().rev() {
-                if reply.parent_comment_id.is_some()
-                    && reply.parent_comment_id.unwrap().to_string() == comment.id
-                {
-                    replies_for_comment.push(reply.clone());
-                }
-            }
-
-            comment.replies = Some(
-                replies_for_comment
-                    .into_iter()
-                    .map(|r| r.to_response())
-                    .collect(),
-            );
-        }
+        roots.sort_by_key(|c| std::cmp::Reverse(c.created_at));
+        roots
+            .iter()
+            .map(|root| root.to_response_with_replies(&mut children))
+            .collect()
+    }
 
-        Ok(root_comments)
+    /// Removes this comment's replies from `children` as it goes, so a
+    /// corrupted parent cycle can't recurse forever.
+    fn to_response_with_replies(
+        &self,
+        children: &mut HashMap<ObjectId, Vec<Self>>,
+    ) -> CommentResponse {
+        let mut replies = self
+            .id
+            .and_then(|id| children.remove(&id))
+            .unwrap_or_default();
+        replies.sort_by_key(|c| c.created_at);
+
+        let mut response = self.to_response();
+        response.replies = Some(
+            replies
+                .iter()
+                .map(|reply| reply.to_response_with_replies(children))
+                .collect(),
+        );
+        response
     }
 
     pub async fn get_recent(db: &Database, limit: i64) -> Result<Vec<CommentResponse>, Error> {
@@ -214,16 +304,182 @@ impl Comment {
         Ok(all_comments)
     }
 
-    pub async fn delete(db: &Database, id: &str) -> Result<(), Error> {
+    /// Whether the request's IP hash and visitor cookie both match the ones
+    /// recorded when the comment was posted, as likes identify visitors
+    pub fn is_author(&self, ip_hash: &str, visitor_id: &str) -> bool {
+        !self.visitor_id.is_empty() && self.visitor_id == visitor_id && self.ip_hash == ip_hash
+    }
+
+    /// Whether the author may still edit the comment at `now`
+    pub fn is_editable_at(&self, now: DateTime<Utc>) -> bool {
+        self.deleted_at.is_none()
+            && now - self.created_at <= Duration::minutes(COMMENT_EDIT_WINDOW_MINUTES)
+    }
+
+    /// Replaces the body of a comment that isn't deleted. Returns `None` when
+    /// there's no such comment, including one soft-deleted after the caller
+    /// looked it up, so an edit can't bring a deleted body back.
+    pub async fn update_body(
+        db: &Database,
+        id: &ObjectId,
+        body: &str,
+    ) -> Result<Option<Self>, Error> {
         let collection = db.collection::<Self>(COLLECTION_NAME);
-        let object_id = ObjectId::parse_str(id).map_err(|_| Error::custom("Invalid comment ID"))?;
+        collection
+            .find_one_and_update(
+                doc! {"_id": id, "deletedAt": null},
+                doc! {"$set": {"body": body, "updatedAt": bson::DateTime::now()}},
+            )
+            .return_document(ReturnDocument::After)
+            .await
+    }
 
-        let result = collection.delete_one(doc! {"_id": object_id}).await?;
+    /// Blanks the body and the author's email and link, and stamps
+    /// `deletedAt`, keeping the document so its replies stay threaded.
+    /// Returns `None` when no comment has that id.
+    pub async fn soft_delete(db: &Database, id: &str) -> Result<Option<Self>, Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let Ok(object_id) = ObjectId::parse_str(id) else {
+            return Ok(None);
+        };
+        let now = bson::DateTime::now();
+
+        collection
+            .find_one_and_update(
+                doc! {"_id": object_id},
+                doc! {"$set": {
+                    "body": "",
+                    "email": "",
+                    "url": "",
+                    "deletedAt": now,
+                    "updatedAt": now,
+                }},
+            )
+            .return_document(ReturnDocument::After)
+            .await
+    }
+}
 
-        if result.deleted_count == 0 {
-            return Err(Error::custom("Comment not found"));
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn comment(name: &str, minute: i64, parent: Option<ObjectId>) -> Comment {
+        let created_at =
+            Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap() + Duration::minutes(minute);
+        Comment {
+            id: Some(ObjectId::new()),
+            name: name.to_string(),
+            post_slug: "hello".to_string(),
+            by_post_author: false,
+            email: String::new(),
+            url: String::new(),
+            body: name.to_string(),
+            ip_hash: "ip".to_string(),
+            visitor_id: "visitor".to_string(),
+            parent_comment_id: parent,
+            created_at,
+            updated_at: created_at,
+            deleted_at: None,
+            replies: None,
         }
+    }
+
+    fn names(responses: &[CommentResponse]) -> Vec<&str> {
+        responses.iter().map(|c| c.name.as_str()).collect()
+    }
+
+    #[test]
+    fn nests_two_levels_of_replies() {
+        let root = comment("root", 0, None);
+        let reply = comment("reply", 2, root.id);
+        let early_reply = comment("early-reply", 1, root.id);
+        let nested = comment("nested", 3, reply.id);
+        let newer_root = comment("newer-root", 4, None);
+        let orphan = comment("orphan", 5, Some(ObjectId::new()));
+
+        let tree = Comment::build_tree(vec![nested, reply, orphan, root, newer_root, early_reply]);
+
+        assert_eq!(names(&tree), vec!["newer-root", "root"]);
+        let replies = tree[1].replies.as_ref().unwrap();
+        assert_eq!(names(replies), vec!["early-reply", "reply"]);
+        assert_eq!(names(replies[1].replies.as_ref().unwrap()), vec!["nested"]);
+        assert!(replies[0].replies.as_ref().unwrap().is_empty());
+    }
+
+    #[test]
+    fn empty_post_has_no_comments() {
+        assert!(Comment::build_tree(Vec::new()).is_empty());
+    }
+
+    #[test]
+    fn author_needs_matching_ip_and_visitor() {
+        let comment = comment("author", 0, None);
+
+        assert!(comment.is_author("ip", "visitor"));
+        assert!(!comment.is_author("other-ip", "visitor"));
+        assert!(!comment.is_author("ip", "other-visitor"));
+
+        let legacy = Comment {
+            visitor_id: String::new(),
+            ..comment
+        };
+        assert!(!legacy.is_author("ip", ""));
+    }
+
+    #[test]
+    fn edit_window_closes_after_limit() {
+        let comment = comment("author", 0, None);
+        let window = Duration::minutes(COMMENT_EDIT_WINDOW_MINUTES);
+
+        assert!(comment.is_editable_at(comment.created_at + window));
+        assert!(!comment.is_editable_at(comment.created_at + window + Duration::seconds(1)));
+
+        let deleted = Comment {
+            deleted_at: Some(bson::DateTime::now()),
+            ..comment.clone()
+        };
+        assert!(!deleted.is_editable_at(deleted.created_at));
+    }
+
+    #[test]
+    fn places_reply_within_depth_under_its_parent() {
+        let root = ObjectId::new();
+        let reply = ObjectId::new();
+
+        assert_eq!(
+            Comment::place_reply(&[root], 1, ReplyOverflow::Reject),
+            Ok(Some(root))
+        );
+        assert_eq!(
+            Comment::place_reply(&[reply, root], 2, ReplyOverflow::Reject),
+            Ok(Some(reply))
+        );
+    }
 
-        Ok(())
+    #[test]
+    fn reparents_or_rejects_replies_past_max_depth() {
+        let root = ObjectId::new();
+        let reply = ObjectId::new();
+        let nested = ObjectId::new();
+        let ancestors = [nested, reply, root];
+
+        assert_eq!(
+            Comment::place_reply(&ancestors, 1, ReplyOverflow::Reparent),
+            Ok(Some(root))
+        );
+        assert_eq!(
+            Comment::place_reply(&ancestors, 2, ReplyOverflow::Reparent),
+            Ok(Some(reply))
+        );
+        assert_eq!(
+            Comment::place_reply(&ancestors, 0, ReplyOverflow::Reparent),
+            Ok(None)
+        );
+        assert_eq!(
+            Comment::place_reply(&ancestors, 2, ReplyOverflow::Reject),
+            Err(ReplyTooDeep)
+        );
     }
 }
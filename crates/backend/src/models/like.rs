@@ -1,9 +1,10 @@
 use bson::doc;
 use chrono::{DateTime, Utc};
-use mongodb::{bson::oid::ObjectId, error::Error, Database};
+use mongodb::{bson::oid::ObjectId, error::Error, options::ReturnDocument, Database};
 use serde::{Deserialize, Serialize};
 
 const COLLECTION_NAME: &str = "like";
+const MILESTONE_COLLECTION_NAME: &str = "like_milestone";
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Like {
@@ -100,6 +101,51 @@ impl Like {
     }
 }
 
+/// The like milestones already announced for a post. Keyed by slug through
+/// `_id`, so there is exactly one document per post.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LikeMilestones {
+    #[serde(rename = "_id")]
+    pub post_slug: String,
+
+    #[serde(rename = "notifiedMilestones", default)]
+    pub notified_milestones: Vec<u64>,
+}
+
+impl LikeMilestones {
+    /// Records `milestone` as announced for `post_slug` and returns whether
+    /// this call was the first to do so. `$addToSet` makes the claim atomic,
+    /// so unliking and liking again around a milestone never announces it twice.
+    pub async fn claim(db: &Database, post_slug: &str, milestone: u64) -> Result<bool, Error> {
+        let collection = db.collection::<Self>(MILESTONE_COLLECTION_NAME);
+
+        loop {
+            let result = collection
+                .find_one_and_update(
+                    doc! { "_id": post_slug },
+                    doc! { "$addToSet": { "notifiedMilestones": milestone as i64 } },
+                )
+                .upsert(true)
+                .return_document(ReturnDocument::Before)
+                .await;
+
+            match result {
+                Ok(before) => return Ok(Self::newly_notified(before.as_ref(), milestone)),
+                // A concurrent claim created the document first; retrying
+                // updates it instead
+                Err(e) if is_duplicate_key_error(&e) => continue,
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Whether `milestone` was still unannounced in the document as it was
+    /// before the claim
+    pub fn newly_notified(before: Option<&Self>, milestone: u64) -> bool {
+        before.is_none_or(|milestones| !milestones.notified_milestones.contains(&milestone))
+    }
+}
+
 pub fn is_duplicate_key_error(error: &Error) -> bool {
     use mongodb::error::{ErrorKind, WriteFailure};
 
@@ -2,6 +2,8 @@ pub mod encode;
 pub mod encryption;
 pub mod ip;
 pub mod log;
+pub mod rate_limit;
 pub mod slug;
 pub mod validator;
+pub mod visitor;
 pub mod webhook;
@@ -0,0 +1,100 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// Once this many keys are tracked, keys with no hits inside the window are
+/// dropped so one-off visitors don't accumulate forever
+const SWEEP_THRESHOLD: usize = 1024;
+
+/// In-memory sliding-window limiter allowing `limit` hits per key per
+/// `window`. Keys should be hashed (see `utils::ip::hash_ip`), never raw IPs.
+pub struct RateLimiter {
+    limit: usize,
+    window: Duration,
+    hits: Mutex<HashMap<String, VecDeque<Instant>>>,
+}
+
+impl RateLimiter {
+    pub fn new(limit: usize, window: Duration) -> Self {
+        Self {
+            limit,
+            window,
+            hits: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Records a hit for `key` and returns whether it is within the limit.
+    /// Rejected hits are not recorded.
+    pub fn check(&self, key: &str) -> bool {
+        self.check_at(key, Instant::now())
+    }
+
+    fn check_at(&self, key: &str, now: Instant) -> bool {
+        let mut hits = self.hits.lock().unwrap_or_else(|e| e.into_inner());
+
+        if hits.len() >= SWEEP_THRESHOLD {
+            hits.retain(|_, times| {
+                times
+                    .back()
+                    .is_some_and(|last| now.duration_since(*last) < self.window)
+            });
+        }
+
+        let times = hits.entry(key.to_string()).or_default();
+        while times
+            .front()
+            .is_some_and(|first| now.duration_since(*first) >= self.window)
+        {
+            times.pop_front();
+        }
+
+        if times.len() >= self.limit {
+            return false;
+        }
+
+        times.push_back(now);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_rapid_hits_over_limit() {
+        let limiter = RateLimiter::new(3, Duration::from_secs(60));
+        let now = Instant::now();
+
+        assert!(limiter.check_at("a", now));
+        assert!(limiter.check_at("a", now));
+        assert!(limiter.check_at("a", now));
+        assert!(!limiter.check_at("a", now + Duration::from_secs(1)));
+        assert!(limiter.check_at("b", now + Duration::from_secs(1)));
+    }
+
+    #[test]
+    fn allows_again_after_window() {
+        let limiter = RateLimiter::new(1, Duration::from_secs(60));
+        let now = Instant::now();
+
+        assert!(limiter.check_at("a", now));
+        assert!(!limiter.check_at("a", now + Duration::from_secs(59)));
+        assert!(limiter.check_at("a", now + Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn sweeps_idle_keys() {
+        let limiter = RateLimiter::new(1, Duration::from_secs(60));
+        let now = Instant::now();
+
+        for i in 0..SWEEP_THRESHOLD {
+            limiter.check_at(&i.to_string(), now);
+        }
+        limiter.check_at("late", now + Duration::from_secs(60));
+
+        assert_eq!(limiter.hits.lock().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,30 @@
+use axum::http::{HeaderMap, HeaderValue};
+use axum_extra::extract::cookie::CookieJar;
+use cookie::{Cookie, SameSite};
+use time::Duration;
+use uuid::Uuid;
+
+use crate::constants::like::VISITOR_COOKIE_KEY;
+
+/// Reads the visitor id cookie, minting a new id when the request has none.
+/// The flag is `true` for a new id, which must be sent back with
+/// [`visitor_cookie`].
+pub fn visitor_id(headers: &HeaderMap) -> (String, bool) {
+    match CookieJar::from_headers(headers).get(VISITOR_COOKIE_KEY) {
+        Some(cookie) => (cookie.value().to_string(), false),
+        None => (Uuid::new_v4().to_string(), true),
+    }
+}
+
+/// `Set-Cookie` value persisting `visitor_id` for two years
+pub fn visitor_cookie(visitor_id: String, domain: &str) -> Option<HeaderValue> {
+    let cookie = Cookie::build((VISITOR_COOKIE_KEY, visitor_id))
+        .path("/")
+        .secure(true)
+        .http_only(true)
+        .max_age(Duration::days(365 * 2))
+        .same_site(SameSite::None)
+        .domain(domain.to_string());
+
+    HeaderValue::from_str(&cookie.to_string()).ok()
+}
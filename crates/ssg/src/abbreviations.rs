@@ -0,0 +1,259 @@
+use crate::shortcodes::escape_html;
+use pulldown_cmark::{CowStr, Event, Tag};
+use std::collections::HashSet;
+
+/// An `*[HTML]: HyperText Markup Language` definition
+#[derive(Debug, Clone, PartialEq)]
+pub struct Abbreviation {
+    pub abbr: String,
+    pub title: String,
+}
+
+/// Remove abbreviation definition lines from `markdown` and return them.
+/// Lines inside code fences are left alone, so the syntax can be shown in
+/// code samples.
+pub fn extract(markdown: &str) -> (String, Vec<Abbreviation>) {
+    let mut output = String::with_capacity(markdown.len());
+    let mut abbreviations: Vec<Abbreviation> = Vec::new();
+    let mut fence: Option<(char, usize)> = None;
+
+    for line in markdown.split_inclusive('\n') {
+        let trimmed = line.trim();
+
+        if let Some((fence_char, fence_len)) = fence {
+            if trimmed.len() >= fence_len && trimmed.chars().all(|c| c == fence_char) {
+                fence = None;
+            }
+            output.push_str(line);
+            continue;
+        }
+
+        if let Some(fence_char) = trimmed.chars().next().filter(|c| *c == '`' || *c == '~') {
+            let fence_len = trimmed.chars().take_while(|c| *c == fence_char).count();
+            if fence_len >= 3 {
+                fence = Some((fence_char, fence_len));
+                output.push_str(line);
+                continue;
+            }
+        }
+
+        match parse_definition(line) {
+            Some(abbreviation) => {
+                // A later definition of the same abbreviation wins
+                abbreviations.retain(|existing| existing.abbr != abbreviation.abbr);
+                abbreviations.push(abbreviation);
+            }
+            None => output.push_str(line),
+        }
+    }
+
+    // Longest first, so `HTML5` is matched before `HTML`
+    abbreviations.sort_by_key(|a| std::cmp::Reverse(a.abbr.len()));
+    (output, abbreviations)
+}
+
+fn parse_definition(line: &str) -> Option<Abbreviation> {
+    let indent = line.len() - line.trim_start_matches(' ').len();
+    if indent > 3 {
+        return None;
+    }
+
+    let rest = line.trim().strip_prefix("*[")?;
+    let (abbr, title) = rest.split_once("]:")?;
+    let abbr = abbr.trim();
+    if abbr.is_empty() || abbr.contains('[') {
+        return None;
+    }
+
+    Some(Abbreviation {
+        abbr: abbr.to_string(),
+        title: title.trim().to_string(),
+    })
+}
+
+/// Wrap whole-word occurrences of each abbreviation in body text with
+/// `<abbr title="...">`. Code spans, raw HTML and image alt text are never
+/// touched, nor is anything inside a code block. With `first_only`, only
+/// the first occurrence in the document is wrapped.
+pub fn apply<'a>(
+    events: impl IntoIterator<Item = Event<'a>>,
+    abbreviations: &[Abbreviation],
+    first_only: bool,
+) -> Vec<Event<'a>> {
+    let mut output = Vec::new();
+    let mut in_code_block = false;
+    // Alt text ends up in an attribute, where markup doesn't belong
+    let mut image_depth = 0usize;
+    let mut used = HashSet::new();
+
+    for event in events {
+        match event {
+            Event::Start(Tag::CodeBlock(_)) => in_code_block = true,
+            Event::End(Tag::CodeBlock(_)) => in_code_block = false,
+            Event::Start(Tag::Image(..)) => image_depth += 1,
+            Event::End(Tag::Image(..)) => image_depth -= 1,
+            Event::Text(ref text)
+                if !in_code_block && image_depth == 0 && !abbreviations.is_empty() =>
+            {
+                wrap_text(text, abbreviations, first_only, &mut used, &mut output);
+                continue;
+            }
+            _ => {}
+        }
+        output.push(event);
+    }
+
+    output
+}
+
+fn wrap_text<'a>(
+    text: &str,
+    abbreviations: &[Abbreviation],
+    first_only: bool,
+    used: &mut HashSet<String>,
+    output: &mut Vec<Event<'a>>,
+) {
+    let mut plain_start = 0;
+    let mut pos = 0;
+    let mut wrapped = false;
+
+    while pos < text.len() {
+        let at_boundary = text[..pos]
+            .chars()
+            .next_back()
+            .is_none_or(|c| !is_word_char(c));
+        let found = at_boundary
+            .then(|| {
+                abbreviations.iter().find(|a| {
+                    text[pos..].starts_with(&a.abbr)
+                        && text[pos + a.abbr.len()..]
+                            .chars()
+                            .next()
+                            .is_none_or(|c| !is_word_char(c))
+                        && !(first_only && used.contains(&a.abbr))
+                })
+            })
+            .flatten();
+
+        match found {
+            Some(abbreviation) => {
+                if plain_start < pos {
+                    output.push(Event::Text(CowStr::from(
+                        text[plain_start..pos].to_string(),
+                    )));
+                }
+                output.push(Event::Html(CowStr::from(format!(
+                    "<abbr data-md title=\"{}\">{}</abbr>",
+                    escape_html(&abbreviation.title),
+                    escape_html(&abbreviation.abbr)
+                ))));
+                used.insert(abbreviation.abbr.clone());
+                pos += abbreviation.abbr.len();
+                plain_start = pos;
+                wrapped = true;
+            }
+            None => pos += text[pos..].chars().next().map_or(1, char::len_utf8),
+        }
+    }
+
+    if !wrapped {
+        output.push(Event::Text(CowStr::from(text.to_string())));
+    } else if plain_start < text.len() {
+        output.push(Event::Text(CowStr::from(text[plain_start..].to_string())));
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pulldown_cmark::{Options, Parser};
+
+    fn render(markdown: &str, first_only: bool) -> String {
+        let (markdown, abbreviations) = extract(markdown);
+        let events = apply(
+            Parser::new_ext(&markdown, Options::empty()),
+            &abbreviations,
+            first_only,
+        );
+        let mut html = String::new();
+        pulldown_cmark::html::push_html(&mut html, events.into_iter());
+        html
+    }
+
+    #[test]
+    fn test_extract_removes_definitions() {
+        let (markdown, abbreviations) = extract(
+            "Text\n\n*[HTML]: HyperText Markup Language\n*[CSS]:  Cascading Style Sheets\n",
+        );
+
+        assert_eq!(markdown, "Text\n\n");
+        assert_eq!(
+            abbreviations,
+            vec![
+                Abbreviation {
+                    abbr: "HTML".to_string(),
+                    title: "HyperText Markup Language".to_string(),
+                },
+                Abbreviation {
+                    abbr: "CSS".to_string(),
+                    title: "Cascading Style Sheets".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_wraps_whole_words_in_body_text() {
+        let html = render(
+            "HTML and XHTML, or HTML_ids. Write HTML!\n\n*[HTML]: HyperText Markup Language",
+            false,
+        );
+
+        assert_eq!(
+            html,
+            "<p><abbr data-md title=\"HyperText Markup Language\">HTML</abbr> and XHTML, or HTML_ids. \
+             Write <abbr data-md title=\"HyperText Markup Language\">HTML</abbr>!</p>\n"
+        );
+    }
+
+    #[test]
+    fn test_first_only_wraps_one_occurrence() {
+        let html = render(
+            "HTML\n\nMore HTML\n\n*[HTML]: HyperText Markup Language",
+            true,
+        );
+
+        assert_eq!(
+            html,
+            "<p><abbr data-md title=\"HyperText Markup Language\">HTML</abbr></p>\n<p>More HTML</p>\n"
+        );
+    }
+
+    #[test]
+    fn test_image_alt_text_is_untouched() {
+        let html = render(
+            "![HTML logo](logo.png)
+
+*[HTML]: HyperText Markup Language",
+            false,
+        );
+
+        assert_eq!(html, "<p><img src=\"logo.png\" alt=\"HTML logo\" /></p>\n");
+    }
+
+    #[test]
+    fn test_code_is_untouched() {
+        let markdown =
+            "`HTML` here\n\n```\nHTML\n*[CSS]: kept\n```\n\n*[HTML]: HyperText Markup Language";
+        let html = render(markdown, false);
+
+        assert_eq!(
+            html,
+            "<p><code>HTML</code> here</p>\n<pre><code>HTML\n*[CSS]: kept\n</code></pre>\n"
+        );
+    }
+}
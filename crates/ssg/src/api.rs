@@ -0,0 +1,232 @@
+use crate::config::SsgConfig;
+use crate::image::{category_relative_src, ImageProcessor};
+use crate::metadata::{compare_posts_desc, MetadataCache, PostMetadata};
+use crate::permalink;
+use crate::renderer::Renderer;
+use crate::slug::encode_for_url;
+use crate::types::Category;
+use crate::verbosity::status;
+use anyhow::Result;
+use serde::Serialize;
+use std::fs;
+use std::path::PathBuf;
+
+/// One page of a post listing, paginated like the HTML listings: page 1 is
+/// `api/posts.json` (or `api/category/<slug>.json`), later pages are
+/// `api/posts/page/<n>.json` (or `api/category/<slug>/page/<n>.json`)
+#[derive(Debug, Serialize)]
+pub struct PostsPage {
+    /// The category being listed, on category endpoints
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<ApiCategory>,
+    pub page: usize,
+    pub total_pages: usize,
+    pub total_posts: usize,
+    /// Absolute URL of the previous page's JSON
+    pub prev: Option<String>,
+    /// Absolute URL of the next page's JSON
+    pub next: Option<String>,
+    pub posts: Vec<ApiPost>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ApiPost {
+    pub title: String,
+    pub url: String,
+    pub date: String,
+    pub category: String,
+    pub tags: Vec<String>,
+    /// Description, or the start of the post text without one
+    pub excerpt: String,
+    /// CDN thumbnail of the cover (or OG) image, when a CDN is configured
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thumbnail: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ApiCategory {
+    pub slug: String,
+    pub name: String,
+    pub description: String,
+    /// Absolute URL of the category page
+    pub url: String,
+    /// Absolute URL of the cover image (CDN thumbnail when a CDN is set)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cover_image: Option<String>,
+}
+
+pub struct ApiGenerator {
+    config: SsgConfig,
+    image_processor: Option<ImageProcessor>,
+    content_dir: PathBuf,
+}
+
+impl ApiGenerator {
+    pub fn new(config: SsgConfig) -> Self {
+        let image_processor = ImageProcessor::from_config(&config);
+        let content_dir = PathBuf::from(&config.build.content_dir);
+
+        Self {
+            config,
+            image_processor,
+            content_dir,
+        }
+    }
+
+    /// Path of a listing page's JSON under `base` (e.g. `api/posts`)
+    fn page_path(base: &str, page: usize) -> String {
+        if page == 1 {
+            format!("{}.json", base)
+        } else {
+            format!("{}/page/{}.json", base, page)
+        }
+    }
+
+    /// Filesystem path segment for a slug, following `build.encode_filenames`
+    fn maybe_encode(&self, s: &str) -> String {
+        if self.config.build.encode_filenames {
+            encode_for_url(s)
+        } else {
+            s.to_string()
+        }
+    }
+
+    fn api_category(&self, category: &Category) -> ApiCategory {
+        let cover_image = category.cover_image.as_ref().map(|cover| {
+            let resolved = Renderer::resolve_path(cover, &category.slug);
+            let relative_src = category_relative_src(cover, &category.slug);
+            let cdn_thumbnail = self.image_processor.as_ref().and_then(|processor| {
+                processor
+                    .process_thumbnail(
+                        &relative_src,
+                        &self.content_dir.join(&category.slug),
+                        &category.slug,
+                    )
+                    .ok()
+                    .flatten()
+            });
+            match cdn_thumbnail {
+                Some(thumbnail) => thumbnail.src,
+                None if resolved.starts_with('/') => {
+                    format!("{}{}", self.config.site.url, resolved)
+                }
+                None => resolved,
+            }
+        });
+
+        ApiCategory {
+            slug: category.slug.clone(),
+            name: category.name.clone(),
+            description: category.description.clone(),
+            url: format!(
+                "{}{}",
+                self.config.site.url,
+                permalink::page_url(
+                    &format!("/{}/", encode_for_url(&category.slug)),
+                    self.config.build.ugly_urls
+                )
+            ),
+            cover_image,
+        }
+    }
+
+    fn api_post(&self, post: &PostMetadata) -> ApiPost {
+        let url = permalink::post_url(
+            &self.config.build,
+            &post.category,
+            &post.slug,
+            &post.frontmatter.date.posted,
+            self.config.build.encode_filenames,
+        );
+
+        ApiPost {
+            title: post.frontmatter.title.clone(),
+            url: format!("{}{}", self.config.site.url, url),
+            date: post.frontmatter.date.posted.to_rfc3339(),
+            category: post.category.clone(),
+            tags: post.frontmatter.tags.clone(),
+            excerpt: post.summary().unwrap_or_default().to_string(),
+            thumbnail: self
+                .image_processor
+                .as_ref()
+                .and_then(|processor| processor.post_thumbnail(post, &self.content_dir)),
+        }
+    }
+
+    pub fn generate(&self, metadata: &MetadataCache) -> Result<()> {
+        if !self.config.build.json_api {
+            return Ok(());
+        }
+
+        status!("\n📡 Generating posts API...");
+
+        let posts: Vec<_> = metadata.posts.iter().collect();
+        let total_pages = self.write_listing("api/posts", posts, None)?;
+        status!("   ✓ {} post listing pages", total_pages);
+
+        let categories: Vec<_> = metadata
+            .get_category_info()
+            .iter()
+            .filter(|c| !c.hidden)
+            .collect();
+        for category in &categories {
+            let posts = metadata.get_posts_by_category_tree(&category.slug);
+            let base = format!("api/category/{}", category.slug);
+            self.write_listing(&base, posts, Some(self.api_category(category)))?;
+        }
+        status!("   ✓ {} category listings", categories.len());
+
+        Ok(())
+    }
+
+    /// Write the visible `posts`, newest first, as pages under `base`.
+    /// Returns the page count.
+    fn write_listing(
+        &self,
+        base: &str,
+        mut posts: Vec<&PostMetadata>,
+        category: Option<ApiCategory>,
+    ) -> Result<usize> {
+        posts.retain(|p| !p.frontmatter.hidden);
+        posts.sort_by(|a, b| compare_posts_desc(a, b));
+
+        let per_page = self.config.build.posts_per_page.max(1);
+        let total_pages = posts.len().div_ceil(per_page).max(1);
+        let output_dir = PathBuf::from(&self.config.build.output_dir);
+        let url_base = encode_for_url(base);
+        let page_url = |page| {
+            format!(
+                "{}/{}",
+                self.config.site.url,
+                Self::page_path(&url_base, page)
+            )
+        };
+
+        for page in 1..=total_pages {
+            let page_posts = posts
+                .iter()
+                .skip((page - 1) * per_page)
+                .take(per_page)
+                .map(|post| self.api_post(post))
+                .collect();
+
+            let json = PostsPage {
+                category: category.clone(),
+                page,
+                total_pages,
+                total_posts: posts.len(),
+                prev: (page > 1).then(|| page_url(page - 1)),
+                next: (page < total_pages).then(|| page_url(page + 1)),
+                posts: page_posts,
+            };
+
+            let output_path = output_dir.join(Self::page_path(&self.maybe_encode(base), page));
+            if let Some(parent) = output_path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&output_path, serde_json::to_string(&json)?)?;
+        }
+
+        Ok(total_pages)
+    }
+}
@@ -0,0 +1,187 @@
+use crate::cache::{hash_file, normalize_path};
+use crate::config::AssetsConfig;
+use crate::shortcodes::escape_html;
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::path::Path;
+use walkdir::WalkDir;
+
+/// `config.assets` package holding fingerprinted `static/` files, keyed by
+/// their path relative to `static/`
+pub const STATIC_ASSETS_KEY: &str = "static";
+
+/// Extensions that get fingerprinted with `build.fingerprint_assets`
+const FINGERPRINT_EXTENSIONS: &[&str] = &["css", "js", "mjs"];
+
+const FINGERPRINT_LENGTH: usize = 10;
+
+/// Map each CSS/JS file under `static_dir` to the site-absolute URL of its
+/// fingerprinted copy: `css/style.css` -> `/css/style.<hash>.css`
+pub fn fingerprint_static_assets(static_dir: &Path) -> Result<HashMap<String, String>> {
+    let mut fingerprints = HashMap::new();
+    if !static_dir.exists() {
+        return Ok(fingerprints);
+    }
+
+    for entry in WalkDir::new(static_dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+    {
+        let path = entry.path();
+        let fingerprintable = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| FINGERPRINT_EXTENSIONS.contains(&ext.to_lowercase().as_str()));
+        if !fingerprintable {
+            continue;
+        }
+
+        let hash =
+            hash_file(path).with_context(|| format!("Failed to fingerprint {}", path.display()))?;
+        let logical = normalize_path(path.strip_prefix(static_dir)?);
+        let url = format!(
+            "/{}",
+            fingerprinted_path(&logical, &hash[..FINGERPRINT_LENGTH])
+        );
+        fingerprints.insert(logical, url);
+    }
+
+    Ok(fingerprints)
+}
+
+/// Insert `hash` before the extension: `css/style.css` -> `css/style.<hash>.css`
+fn fingerprinted_path(logical: &str, hash: &str) -> String {
+    let file_start = logical.rfind('/').map_or(0, |i| i + 1);
+    match logical[file_start..].rfind('.') {
+        Some(dot) if dot > 0 => {
+            let dot = file_start + dot;
+            format!("{}.{}{}", &logical[..dot], hash, &logical[dot..])
+        }
+        _ => format!("{}.{}", logical, hash),
+    }
+}
+
+/// Whether the file name in `path` carries a fingerprint added by
+/// `build.fingerprint_assets`: `css/style.<hash>.css`
+pub fn is_fingerprinted(path: &str) -> bool {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    let segments: Vec<&str> = name.split('.').collect();
+    segments.len() >= 3
+        && segments[segments.len() - 2].len() == FINGERPRINT_LENGTH
+        && segments[segments.len() - 2]
+            .chars()
+            .all(|c| c.is_ascii_hexdigit())
+}
+
+/// Tera function looking up a `config.assets` entry, as
+/// `asset(pkg="styles", key="theme")`. Unknown packages or keys fail the
+/// render instead of emitting an empty URL.
+pub struct Asset {
+    pub assets: AssetsConfig,
+}
+
+impl tera::Function for Asset {
+    fn call(&self, args: &HashMap<String, tera::Value>) -> tera::Result<tera::Value> {
+        let arg = |name: &str| {
+            args.get(name)
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| tera::Error::msg(format!("asset requires a `{}` argument", name)))
+        };
+        let pkg = arg("pkg")?;
+        let key = arg("key")?;
+
+        let package = self.assets.get(pkg).ok_or_else(|| {
+            tera::Error::msg(format!("asset: unknown package '{}' in manifest.json", pkg))
+        })?;
+        let path = package.get(key).ok_or_else(|| {
+            tera::Error::msg(format!(
+                "asset: package '{}' has no key '{}' in manifest.json",
+                pkg, key
+            ))
+        })?;
+
+        Ok(tera::Value::String(escape_html(path)))
+    }
+
+    fn is_safe(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_fingerprinted_path() {
+        assert_eq!(fingerprinted_path("style.css", "abc"), "style.abc.css");
+        assert_eq!(
+            fingerprinted_path("js/app.min.js", "abc"),
+            "js/app.min.abc.js"
+        );
+        assert_eq!(
+            fingerprinted_path("v1.2/LICENSE", "abc"),
+            "v1.2/LICENSE.abc"
+        );
+    }
+
+    #[test]
+    fn test_is_fingerprinted() {
+        assert!(is_fingerprinted("/css/style.0123456789.css"));
+        assert!(is_fingerprinted("js/app.min.abcdef0123.js"));
+        assert!(!is_fingerprinted("/css/style.css"));
+        assert!(!is_fingerprinted("/0123456789.css"));
+        assert!(!is_fingerprinted("/css/style.notahash00.css"));
+    }
+
+    #[test]
+    fn test_fingerprints_css_and_js_only() {
+        let temp = TempDir::new().unwrap();
+        fs::create_dir_all(temp.path().join("css")).unwrap();
+        fs::write(temp.path().join("css/style.css"), "body {}").unwrap();
+        fs::write(temp.path().join("logo.png"), "png").unwrap();
+
+        let fingerprints = fingerprint_static_assets(temp.path()).unwrap();
+
+        assert_eq!(fingerprints.len(), 1);
+        let url = &fingerprints["css/style.css"];
+        assert!(url.starts_with("/css/style."));
+        assert!(url.ends_with(".css"));
+        assert_eq!(url.len(), "/css/style..css".len() + FINGERPRINT_LENGTH);
+
+        // Content changes produce a new name
+        fs::write(temp.path().join("css/style.css"), "body { margin: 0 }").unwrap();
+        assert_ne!(
+            &fingerprint_static_assets(temp.path()).unwrap()["css/style.css"],
+            url
+        );
+    }
+
+    fn render_asset(template: &str) -> tera::Result<String> {
+        let assets: AssetsConfig = [(
+            "styles".to_string(),
+            [("theme".to_string(), "/styles/0.1.0/theme.css".to_string())].into(),
+        )]
+        .into();
+        let mut tera = tera::Tera::default();
+        tera.register_function("asset", Asset { assets });
+        tera.add_raw_template("t", template)?;
+        tera.render("t", &tera::Context::new())
+    }
+
+    #[test]
+    fn test_asset_function() {
+        assert_eq!(
+            render_asset(r#"{{ asset(pkg="styles", key="theme") }}"#).unwrap(),
+            "/styles/0.1.0/theme.css"
+        );
+
+        let err = render_asset(r#"{{ asset(pkg="styles", key="missing") }}"#).unwrap_err();
+        let message = format!("{:?}", err);
+        assert!(message.contains("no key 'missing'"), "{}", message);
+        assert!(render_asset(r#"{{ asset(pkg="fonts", key="theme") }}"#).is_err());
+    }
+}
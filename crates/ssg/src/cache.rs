@@ -1,3 +1,5 @@
+use crate::config::SsgConfig;
+use crate::verbosity::status;
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
@@ -17,6 +19,8 @@ pub struct BuildCache {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CacheEntry {
     pub file_hash: String,
+    #[serde(default)]
+    pub template_hash: String,
     pub output_path: String,
     pub built_at: String,
 }
@@ -41,7 +45,7 @@ impl BuildCache {
                 cache
             }
             Some(old) => {
-                println!("♻️  Cache invalidated (environment changed) - full rebuild");
+                status!("♻️  Cache invalidated (environment changed) - full rebuild");
                 // Keep the old entries: their output paths are still needed to
                 // clean up posts deleted since the last build.
                 Self {
@@ -74,22 +78,31 @@ impl BuildCache {
         Ok(())
     }
 
-    pub fn needs_rebuild(&self, path: &Path, current_hash: &str) -> bool {
+    /// A post is rebuilt when either its source or the templates it was
+    /// rendered with changed since the entry was recorded.
+    pub fn needs_rebuild(&self, path: &Path, file_hash: &str, template_hash: &str) -> bool {
         if self.force_rebuild_all {
             return true;
         }
 
         match self.entries.get(&normalize_path(path)) {
             None => true,
-            Some(entry) => entry.file_hash != current_hash,
+            Some(entry) => entry.file_hash != file_hash || entry.template_hash != template_hash,
         }
     }
 
-    pub fn update_entry(&mut self, path: &Path, hash: String, output: String) {
+    pub fn update_entry(
+        &mut self,
+        path: &Path,
+        file_hash: String,
+        template_hash: String,
+        output: String,
+    ) {
         self.entries.insert(
             normalize_path(path),
             CacheEntry {
-                file_hash: hash,
+                file_hash,
+                template_hash,
                 output_path: output,
                 built_at: chrono::Utc::now().to_rfc3339(),
             },
@@ -114,6 +127,109 @@ impl BuildCache {
     }
 }
 
+const IMAGE_DIMENSIONS_PATH: &str = ".build-cache/image-dims.json";
+
+/// Persistent cache of decoded image dimensions and blurhash placeholders,
+/// so unchanged images aren't decoded again on every build. Entries are
+/// keyed by path and invalidated when the file's size or modification time
+/// changes.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ImageDimensionCache {
+    entries: HashMap<String, ImageDimensionEntry>,
+    #[serde(skip)]
+    dirty: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct ImageDimensionEntry {
+    size: u64,
+    modified_nanos: u64,
+    width: u32,
+    height: u32,
+    /// Blurhash and average color, once computed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    placeholder: Option<(String, String)>,
+}
+
+impl ImageDimensionCache {
+    pub fn load() -> Self {
+        fs::read_to_string(IMAGE_DIMENSIONS_PATH)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<()> {
+        if !self.dirty {
+            return Ok(());
+        }
+        fs::create_dir_all(".build-cache")?;
+        let json = serde_json::to_string(self)?;
+        write_atomic(Path::new(IMAGE_DIMENSIONS_PATH), &json)?;
+        Ok(())
+    }
+
+    /// Cached `(width, height)` for `path`, if the file is unchanged since it
+    /// was recorded.
+    pub fn get(&self, path: &Path) -> Option<(u32, u32)> {
+        self.fresh_entry(path)
+            .map(|entry| (entry.width, entry.height))
+    }
+
+    /// Cached blurhash and average color for `path`, if the file is unchanged
+    /// since it was recorded.
+    pub fn get_placeholder(&self, path: &Path) -> Option<(String, String)> {
+        self.fresh_entry(path)?.placeholder.clone()
+    }
+
+    /// Record the placeholder for `path` alongside its dimensions. Ignored
+    /// when the dimensions aren't cached for the file as it is now.
+    pub fn insert_placeholder(&mut self, path: &Path, placeholder: (String, String)) {
+        let Some((size, modified_nanos)) = file_stamp(path) else {
+            return;
+        };
+        if let Some(entry) = self.entries.get_mut(&normalize_path(path)) {
+            if entry.size == size && entry.modified_nanos == modified_nanos {
+                entry.placeholder = Some(placeholder);
+                self.dirty = true;
+            }
+        }
+    }
+
+    fn fresh_entry(&self, path: &Path) -> Option<&ImageDimensionEntry> {
+        let entry = self.entries.get(&normalize_path(path))?;
+        let (size, modified_nanos) = file_stamp(path)?;
+        (entry.size == size && entry.modified_nanos == modified_nanos).then_some(entry)
+    }
+
+    pub fn insert(&mut self, path: &Path, (width, height): (u32, u32)) {
+        let Some((size, modified_nanos)) = file_stamp(path) else {
+            return;
+        };
+        self.entries.insert(
+            normalize_path(path),
+            ImageDimensionEntry {
+                size,
+                modified_nanos,
+                width,
+                height,
+                placeholder: None,
+            },
+        );
+        self.dirty = true;
+    }
+}
+
+fn file_stamp(path: &Path) -> Option<(u64, u64)> {
+    let metadata = fs::metadata(path).ok()?;
+    let modified = metadata
+        .modified()
+        .ok()?
+        .duration_since(std::time::UNIX_EPOCH)
+        .ok()?;
+    Some((metadata.len(), modified.as_nanos() as u64))
+}
+
 pub fn normalize_path(path: &Path) -> String {
     path.to_string_lossy().replace('\\', "/")
 }
@@ -154,11 +270,24 @@ pub fn hash_directory(dir: &Path) -> Result<String> {
     Ok(hasher.finalize().to_hex().to_string())
 }
 
-/// Combined hash of every input (besides the post files themselves) that
-/// affects rendered output. A mismatch invalidates the whole cache.
-pub fn compute_environment_hash(content_dir: &Path) -> Result<String> {
+/// Hash of the template directory, recorded per cache entry so a
+/// template-only edit rebuilds every post rendered with the old templates.
+pub fn compute_template_hash(config: &SsgConfig) -> Result<String> {
+    let templates = Path::new(&config.build.template_dir);
+    if !templates.exists() {
+        return Ok(String::new());
+    }
+    hash_directory(templates)
+}
+
+/// Combined hash of every input (besides the post files and templates, see
+/// [`compute_template_hash`]) that affects rendered output. A mismatch
+/// invalidates the whole cache.
+pub fn compute_environment_hash(config: &SsgConfig) -> Result<String> {
     use walkdir::WalkDir;
 
+    let content_dir = Path::new(&config.build.content_dir);
+    let includes_dir = Path::new(&config.build.includes_dir);
     let mut hasher = blake3::Hasher::new();
 
     // A new binary may render differently (shortcodes, templates logic),
@@ -172,10 +301,15 @@ pub fn compute_environment_hash(content_dir: &Path) -> Result<String> {
         }
     }
 
-    if Path::new("templates").exists() {
-        hasher.update(hash_directory(Path::new("templates"))?.as_bytes());
+    // Posts inline these via [include], so any change affects every post
+    if includes_dir.exists() {
+        hasher.update(hash_directory(includes_dir)?.as_bytes());
     }
 
+    // The effective config covers `--env` overlays and defaults; the raw file
+    // below still catches edits that don't change any parsed value.
+    hasher.update(hash_config(config)?.as_bytes());
+
     for file in ["config.yaml", "manifest.json"] {
         let path = Path::new(file);
         if path.exists() {
@@ -199,6 +333,34 @@ pub fn compute_environment_hash(content_dir: &Path) -> Result<String> {
     Ok(hasher.finalize().to_hex().to_string())
 }
 
+/// Hash of the loaded config with map keys sorted, so `HashMap` iteration
+/// order can't change it between runs.
+fn hash_config(config: &SsgConfig) -> Result<String> {
+    fn canonicalize(value: serde_json::Value) -> serde_json::Value {
+        match value {
+            serde_json::Value::Object(map) => {
+                let mut entries: Vec<_> = map.into_iter().collect();
+                entries.sort_by(|a, b| a.0.cmp(&b.0));
+                serde_json::Value::Object(
+                    entries
+                        .into_iter()
+                        .map(|(key, value)| (key, canonicalize(value)))
+                        .collect(),
+                )
+            }
+            serde_json::Value::Array(items) => {
+                serde_json::Value::Array(items.into_iter().map(canonicalize).collect())
+            }
+            other => other,
+        }
+    }
+
+    let value = canonicalize(serde_json::to_value(config)?);
+    Ok(blake3::hash(value.to_string().as_bytes())
+        .to_hex()
+        .to_string())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -222,7 +384,7 @@ mod tests {
         let cache = BuildCache::new("env_hash");
         let path = Path::new("test.md");
 
-        assert!(cache.needs_rebuild(path, "abc123"));
+        assert!(cache.needs_rebuild(path, "abc123", "tpl"));
     }
 
     #[test]
@@ -233,11 +395,27 @@ mod tests {
         cache.update_entry(
             path,
             "abc123".to_string(),
+            "tpl".to_string(),
+            "dist/test/index.html".to_string(),
+        );
+
+        assert!(!cache.needs_rebuild(path, "abc123", "tpl"));
+        assert!(cache.needs_rebuild(path, "different_hash", "tpl"));
+    }
+
+    #[test]
+    fn test_cache_rebuilds_on_template_change() {
+        let mut cache = BuildCache::new("env_hash");
+        let path = Path::new("test.md");
+
+        cache.update_entry(
+            path,
+            "abc123".to_string(),
+            "tpl_v1".to_string(),
             "dist/test/index.html".to_string(),
         );
 
-        assert!(!cache.needs_rebuild(path, "abc123"));
-        assert!(cache.needs_rebuild(path, "different_hash"));
+        assert!(cache.needs_rebuild(path, "abc123", "tpl_v2"));
     }
 
     #[test]
@@ -246,11 +424,13 @@ mod tests {
         cache.update_entry(
             Path::new("content/posts/dev/kept.md"),
             "hash1".to_string(),
+            "tpl".to_string(),
             "dist/dev/kept/index.html".to_string(),
         );
         cache.update_entry(
             Path::new("content/posts/dev/deleted.md"),
             "hash2".to_string(),
+            "tpl".to_string(),
             "dist/dev/deleted/index.html".to_string(),
         );
 
@@ -260,8 +440,27 @@ mod tests {
 
         assert_eq!(orphaned, vec!["dist/dev/deleted/index.html".to_string()]);
         assert_eq!(cache.entries.len(), 1);
-        assert!(cache.needs_rebuild(Path::new("content/posts/dev/deleted.md"), "hash2"));
-        assert!(!cache.needs_rebuild(Path::new("content/posts/dev/kept.md"), "hash1"));
+        assert!(cache.needs_rebuild(Path::new("content/posts/dev/deleted.md"), "hash2", "tpl"));
+        assert!(!cache.needs_rebuild(Path::new("content/posts/dev/kept.md"), "hash1", "tpl"));
+    }
+
+    #[test]
+    fn test_hash_config_tracks_effective_values() {
+        let mut config = SsgConfig::default();
+        config
+            .build
+            .shortcodes
+            .insert("a".to_string(), "a.html".to_string());
+        config
+            .build
+            .shortcodes
+            .insert("b".to_string(), "b.html".to_string());
+        let original = hash_config(&config).unwrap();
+
+        assert_eq!(original, hash_config(&config.clone()).unwrap());
+
+        config.build.posts_per_page += 1;
+        assert_ne!(original, hash_config(&config).unwrap());
     }
 
     #[test]
@@ -271,4 +470,56 @@ mod tests {
             "content/posts/dev/test.md"
         );
     }
+
+    #[test]
+    fn test_image_dimension_cache_hit_and_invalidation() {
+        let mut file = NamedTempFile::new().unwrap();
+        write!(file, "fake image bytes").unwrap();
+
+        let mut cache = ImageDimensionCache::default();
+        assert_eq!(cache.get(file.path()), None);
+
+        cache.insert(file.path(), (640, 480));
+        assert_eq!(cache.get(file.path()), Some((640, 480)));
+
+        // Changing the file (size differs) must miss
+        write!(file, " with more data").unwrap();
+        file.flush().unwrap();
+        assert_eq!(cache.get(file.path()), None);
+    }
+
+    #[test]
+    fn test_image_dimension_cache_roundtrip() {
+        let mut file = NamedTempFile::new().unwrap();
+        write!(file, "fake image bytes").unwrap();
+
+        let mut cache = ImageDimensionCache::default();
+        cache.insert(file.path(), (10, 20));
+
+        let json = serde_json::to_string(&cache).unwrap();
+        let restored: ImageDimensionCache = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.get(file.path()), Some((10, 20)));
+    }
+
+    #[test]
+    fn test_image_placeholder_cached_with_dimensions() {
+        let mut file = NamedTempFile::new().unwrap();
+        write!(file, "fake image bytes").unwrap();
+        let placeholder = ("LKO2?U%2Tw=w".to_string(), "#c86432".to_string());
+
+        let mut cache = ImageDimensionCache::default();
+        // Without dimensions there is no entry to attach it to
+        cache.insert_placeholder(file.path(), placeholder.clone());
+        assert_eq!(cache.get_placeholder(file.path()), None);
+
+        cache.insert(file.path(), (10, 20));
+        cache.insert_placeholder(file.path(), placeholder.clone());
+        let json = serde_json::to_string(&cache).unwrap();
+        let restored: ImageDimensionCache = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.get_placeholder(file.path()), Some(placeholder));
+
+        write!(file, " changed").unwrap();
+        file.flush().unwrap();
+        assert_eq!(restored.get_placeholder(file.path()), None);
+    }
 }
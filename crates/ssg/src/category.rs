@@ -1,4 +1,5 @@
 use anyhow::{Context, Result};
+use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
 
@@ -8,14 +9,27 @@ pub fn discover_categories(content_dir: &Path) -> Result<Vec<Category>> {
     let mut categories = Vec::new();
     discover_categories_recursive(content_dir, content_dir, None, &mut categories)?;
 
-    categories.sort_by(|a, b| match a.index.cmp(&b.index) {
-        std::cmp::Ordering::Equal => a.name.cmp(&b.name),
-        other => other,
-    });
+    // Sort as a tree: by index, then name, among siblings, with every nested
+    // category right after its parent
+    let keys: HashMap<String, (i32, String)> = categories
+        .iter()
+        .map(|c| (c.slug.clone(), (c.index, c.name.clone())))
+        .collect();
+    categories.sort_by_cached_key(|category| sort_path(&category.slug, &keys));
 
     Ok(categories)
 }
 
+/// `(index, name)` of each category from the top-level ancestor down to
+/// `slug` itself
+fn sort_path(slug: &str, keys: &HashMap<String, (i32, String)>) -> Vec<(i32, String)> {
+    slug.match_indices('/')
+        .map(|(end, _)| &slug[..end])
+        .chain(std::iter::once(slug))
+        .filter_map(|ancestor| keys.get(ancestor).cloned())
+        .collect()
+}
+
 fn discover_categories_recursive(
     base_dir: &Path,
     current_dir: &Path,
@@ -74,7 +88,9 @@ fn load_category_metadata(dir: &Path, slug: &str, parent: Option<&Category>) ->
             slug: slug.to_string(),
             name: capitalize(name_part),
             description: String::new(),
-            index: 999,
+            // Nested categories are sorted under their parent anyway; this
+            // only orders them among their siblings
+            index: parent.map_or(999, |parent| parent.index),
             hidden: false,
             icon: None,
             color: None,
@@ -84,11 +100,18 @@ fn load_category_metadata(dir: &Path, slug: &str, parent: Option<&Category>) ->
 
     category.slug = slug.to_string();
 
-    // Inherit hidden status from parent if parent is hidden
+    // Inherit hidden status from parent if parent is hidden, and its look
+    // unless the category sets its own
     if let Some(parent) = parent {
         if parent.hidden && !category.hidden {
             category.hidden = true;
         }
+        if category.color.is_none() {
+            category.color = parent.color.clone();
+        }
+        if category.icon.is_none() {
+            category.icon = parent.icon.clone();
+        }
     }
 
     Ok(category)
@@ -104,12 +127,8 @@ fn is_hidden(path: &Path) -> bool {
 fn has_markdown_files(dir: &Path) -> Result<bool> {
     for entry in fs::read_dir(dir)? {
         let path = entry?.path();
-        if path.is_file() {
-            if let Some(ext) = path.extension() {
-                if ext == "md" || ext == "markdown" {
-                    return Ok(true);
-                }
-            }
+        if path.is_file() && crate::parser::is_markdown(&path) {
+            return Ok(true);
         }
     }
     Ok(false)
@@ -240,6 +259,75 @@ index: 0
         assert_eq!(categories[2].slug, "aaa");
     }
 
+    #[test]
+    fn test_nested_category_without_metadata_inherits_look() {
+        let temp = TempDir::new().unwrap();
+        let content = temp.path();
+
+        let parent_dir = content.join("dev");
+        fs::create_dir(&parent_dir).unwrap();
+        fs::write(
+            parent_dir.join(".category.yaml"),
+            "name: Development\nindex: 1\ncolor: \"#66b3ff\"\nicon: code-blocks",
+        )
+        .unwrap();
+        let nested_dir = parent_dir.join("rust");
+        fs::create_dir(&nested_dir).unwrap();
+        fs::write(nested_dir.join("post.md"), "# Test").unwrap();
+
+        let categories = discover_categories(content).unwrap();
+        let rust = categories.iter().find(|c| c.slug == "dev/rust").unwrap();
+
+        assert_eq!(rust.name, "Rust");
+        assert_eq!(rust.index, 1);
+        assert_eq!(rust.color.as_deref(), Some("#66b3ff"));
+        assert_eq!(rust.icon.as_deref(), Some("code-blocks"));
+    }
+
+    #[test]
+    fn test_own_color_overrides_parent() {
+        let temp = TempDir::new().unwrap();
+        let content = temp.path();
+
+        let nested_dir = content.join("dev/rust");
+        fs::create_dir_all(&nested_dir).unwrap();
+        fs::write(content.join("dev/.category.yaml"), "color: \"#66b3ff\"").unwrap();
+        fs::write(nested_dir.join(".category.yaml"), "color: \"#dea584\"").unwrap();
+        fs::write(nested_dir.join("post.md"), "# Test").unwrap();
+
+        let categories = discover_categories(content).unwrap();
+        let rust = categories.iter().find(|c| c.slug == "dev/rust").unwrap();
+
+        assert_eq!(rust.color.as_deref(), Some("#dea584"));
+    }
+
+    #[test]
+    fn test_nested_categories_sort_under_parent() {
+        let temp = TempDir::new().unwrap();
+        let content = temp.path();
+
+        for (slug, index) in &[
+            ("dev", Some(1)),
+            ("life", Some(0)),
+            ("dev/web", None),
+            ("dev/go", Some(0)),
+        ] {
+            let dir = content.join(slug);
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(dir.join("post.md"), "# Test").unwrap();
+            if let Some(index) = index {
+                fs::write(dir.join(".category.yaml"), format!("index: {}", index)).unwrap();
+            }
+        }
+
+        let slugs: Vec<_> = discover_categories(content)
+            .unwrap()
+            .into_iter()
+            .map(|c| c.slug)
+            .collect();
+        assert_eq!(slugs, vec!["life", "dev", "dev/go", "dev/web"]);
+    }
+
     #[test]
     fn test_validate_category() {
         let categories = vec![
@@ -1,8 +1,12 @@
+use crate::assets;
+use crate::data;
+use crate::permalink;
 use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// Contact information for the site
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -35,6 +39,9 @@ pub struct SiteConfig {
     /// Google Analytics ID (optional)
     #[serde(default)]
     pub google_analytics_id: Option<String>,
+    /// Language tag for `<html lang>` and feeds (default: "ko-KR")
+    #[serde(default = "default_language")]
+    pub language: String,
     /// Contact information (optional)
     #[serde(default)]
     pub contacts: Contacts,
@@ -46,6 +53,136 @@ pub struct SearchConfig {
     /// Enable search index generation (default: true)
     #[serde(default = "default_search_enabled")]
     pub enabled: bool,
+    /// Add a `tokens` list to each entry with whitespace tokens plus bigrams
+    /// and trigrams of CJK runs, so a noun inside a longer Korean word still
+    /// matches (default: false)
+    #[serde(default)]
+    pub cjk_ngram: bool,
+    /// Split the index into `search-index-<n>.json` files of this many posts,
+    /// with `search-index.json` becoming a manifest of the shards. Sites with
+    /// no more posts than this keep a single file (default: none)
+    #[serde(default)]
+    pub shard_size: Option<usize>,
+}
+
+/// RSS/Atom feed configuration
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct FeedsConfig {
+    /// WebSub hub announced with `rel="hub"` in the site-wide RSS and Atom
+    /// feeds, for push delivery to subscribers (default: none)
+    #[serde(default)]
+    pub hub_url: Option<String>,
+    /// XSL stylesheet every feed references with `<?xml-stylesheet?>` so
+    /// browsers render it as a page, e.g. "/feed.xsl" served from `static/`
+    /// (default: none)
+    #[serde(default)]
+    pub stylesheet: Option<String>,
+}
+
+/// `[react]` shortcode options
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ReactConfig {
+    /// Components the shortcode may reference; any other name fails the
+    /// build. Unset allows every name (default: none)
+    #[serde(default)]
+    pub components: Option<Vec<String>>,
+}
+
+/// recent.json configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecentConfig {
+    /// Output path relative to the output directory (default: "recent.json")
+    #[serde(default = "default_recent_output_path")]
+    pub output_path: String,
+    /// Prefix post URLs with `site.url` (default: true); false writes
+    /// site-relative URLs
+    #[serde(default = "default_recent_absolute_urls")]
+    pub absolute_urls: bool,
+}
+
+impl Default for RecentConfig {
+    fn default() -> Self {
+        Self {
+            output_path: default_recent_output_path(),
+            absolute_urls: default_recent_absolute_urls(),
+        }
+    }
+}
+
+fn default_recent_output_path() -> String {
+    "recent.json".to_string()
+}
+
+fn default_recent_absolute_urls() -> bool {
+    true
+}
+
+/// Responsive image configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImagesConfig {
+    /// Widths generated for responsive `<source>` sets (default: 480, 600, 860, 1180)
+    #[serde(default = "default_image_sizes")]
+    pub sizes: Vec<u32>,
+    /// Width of post card/navigation thumbnails (default: 500)
+    #[serde(default = "default_thumbnail_size")]
+    pub thumbnail_size: u32,
+    /// Alternate formats served alongside the original (default: webp)
+    #[serde(default = "default_image_formats")]
+    pub formats: Vec<String>,
+    /// Compute a Blurhash and average color from local images for inline
+    /// placeholders (default: false, requires decoding every image)
+    #[serde(default)]
+    pub blurhash: bool,
+    /// CDN URL pattern for resized/converted images, with `{cdn}`, `{path}`,
+    /// `{width}` and `{format}` placeholders. Unset keeps the default
+    /// `{cdn}/images/{path}.w{width}.{ext}.{format}` naming.
+    #[serde(default)]
+    pub url_template: Option<String>,
+}
+
+impl Default for ImagesConfig {
+    fn default() -> Self {
+        Self {
+            sizes: default_image_sizes(),
+            thumbnail_size: default_thumbnail_size(),
+            formats: default_image_formats(),
+            blurhash: false,
+            url_template: None,
+        }
+    }
+}
+
+fn default_image_sizes() -> Vec<u32> {
+    vec![480, 600, 860, 1180]
+}
+
+fn default_thumbnail_size() -> u32 {
+    500
+}
+
+fn default_image_formats() -> Vec<String> {
+    vec!["webp".to_string()]
+}
+
+/// A robots.txt group for one user agent
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RobotsRule {
+    pub agent: String,
+    #[serde(default)]
+    pub allow: Vec<String>,
+    #[serde(default)]
+    pub disallow: Vec<String>,
+}
+
+/// robots.txt configuration
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct RobotsConfig {
+    /// Per-agent rules (default: allow everything for `*`)
+    #[serde(default)]
+    pub user_agents: Vec<RobotsRule>,
+    /// Extra sitemap URLs listed after the site's own sitemap
+    #[serde(default)]
+    pub sitemaps: Vec<String>,
 }
 
 /// Assets configuration from manifest.json
@@ -58,6 +195,8 @@ impl Default for SearchConfig {
     fn default() -> Self {
         Self {
             enabled: default_search_enabled(),
+            cjk_ngram: false,
+            shard_size: None,
         }
     }
 }
@@ -73,6 +212,19 @@ pub struct BuildConfig {
     pub content_dir: String,
     #[serde(default = "default_output_dir")]
     pub output_dir: String,
+    /// Directory holding the Tera templates (default: templates)
+    #[serde(default = "default_template_dir")]
+    pub template_dir: String,
+    /// Extra files or directories `blog watch` rebuilds on (default: none)
+    #[serde(default)]
+    pub watch_paths: Vec<String>,
+    /// Write precompressed siblings of text output for each listed format,
+    /// `gzip` (`.gz`) and/or `brotli` (`.br`) (default: none)
+    #[serde(default)]
+    pub precompress: Vec<String>,
+    /// Files smaller than this many bytes are not precompressed (default: 1024)
+    #[serde(default = "default_precompress_min_size")]
+    pub precompress_min_size: u64,
     #[serde(default = "default_posts_per_page")]
     pub posts_per_page: usize,
     /// Maximum number of page links to show in pagination (default: 5)
@@ -82,6 +234,22 @@ pub struct BuildConfig {
     /// Number of posts to show on the homepage (default: posts_per_page)
     #[serde(default)]
     pub homepage_posts_limit: Option<usize>,
+    /// Number of posts written to recent.json (default: 6)
+    #[serde(default = "default_recent_count")]
+    pub recent_count: usize,
+    /// recent.json location and URL style
+    #[serde(default)]
+    pub recent: RecentConfig,
+    /// Feed options
+    #[serde(default)]
+    pub feeds: FeedsConfig,
+    /// `[react]` shortcode options
+    #[serde(default)]
+    pub react: ReactConfig,
+    /// Write paginated post listings for apps to api/posts.json and
+    /// api/posts/page/<n>.json (default: true)
+    #[serde(default = "default_json_api")]
+    pub json_api: bool,
     /// Percent-encode filenames for URL safety (default: false)
     /// Set to true for compatibility with older web servers
     #[serde(default)]
@@ -95,6 +263,86 @@ pub struct BuildConfig {
     /// Directory name for partial files (default: "html")
     #[serde(default = "default_partial_dir")]
     pub partial_dir: String,
+    /// Use the last git commit date as the modified date for posts without
+    /// `date.modified` in the sitemap and Atom feed (default: false)
+    #[serde(default)]
+    pub git_lastmod: bool,
+    /// Keep the site out of search engines: robots.txt disallows everything
+    /// and templates get `config.noindex` to emit a robots meta tag
+    /// (default: false)
+    #[serde(default)]
+    pub noindex: bool,
+    /// robots.txt rules (ignored when `noindex` is set)
+    #[serde(default)]
+    pub robots: RobotsConfig,
+    /// Responsive image sizes and formats
+    #[serde(default)]
+    pub images: ImagesConfig,
+    /// Custom shortcodes: name -> HTML template with `{attr}` placeholders
+    /// (`{content}` is the body of a block shortcode)
+    #[serde(default)]
+    pub shortcodes: HashMap<String, String>,
+    /// Directory for `[include file="..."]` snippets (default: "content/includes")
+    #[serde(default = "default_includes_dir")]
+    pub includes_dir: String,
+    /// URL pattern for posts (default: "/:category/:slug/").
+    /// Placeholders: `:category`, `:slug`, `:year`, `:month`, `:day`
+    #[serde(default = "default_permalink")]
+    pub permalink: String,
+    /// Emit `<slug>.html` files and link to them instead of `<slug>/index.html`
+    #[serde(default)]
+    pub ugly_urls: bool,
+    /// Canonicalize page 2+ of category/tag listings to page 1 instead of
+    /// themselves (default: false)
+    #[serde(default)]
+    pub canonical_first_page: bool,
+    /// Tag aliases: canonical tag -> spellings folded into it
+    #[serde(default)]
+    pub tag_aliases: HashMap<String, Vec<String>>,
+    /// Treat tags differing only in case as one tag (default: false)
+    #[serde(default)]
+    pub case_insensitive_tags: bool,
+    /// Collapse whitespace and drop comments in generated HTML, leaving
+    /// `<pre>`, `<code>`, `<textarea>`, `<script>` and `<style>` untouched
+    /// (default: false)
+    #[serde(default)]
+    pub minify_html: bool,
+    /// Copy CSS/JS from `static/` under content-hashed names as well and
+    /// expose them as `config.assets.static["css/style.css"]` (default: false)
+    #[serde(default)]
+    pub fingerprint_assets: bool,
+    /// Extra file extensions copied from the content directory alongside the
+    /// built-in image/media/document/font/data types (e.g. `["csv"]`)
+    #[serde(default)]
+    pub copy_extensions: Vec<String>,
+    /// Curly quotes, en/em dashes and ellipses in post prose (default: true,
+    /// since posts were always rendered with smart punctuation; set `false`
+    /// to opt out)
+    #[serde(default = "default_typography")]
+    pub typography: bool,
+    /// Drop HTML comments written in markdown from rendered posts (default: true)
+    #[serde(default = "default_strip_comments")]
+    pub strip_comments: bool,
+    /// Wrap only the first occurrence of each `*[ABBR]: ...` abbreviation in
+    /// a post with `<abbr>` instead of every one (default: false)
+    #[serde(default)]
+    pub abbreviations_first_only: bool,
+    /// chrono format used by the `date_format` template filter when no
+    /// `format` argument is given (default: "%Y-%m-%d")
+    #[serde(default = "default_date_format")]
+    pub date_format: String,
+    /// Plugins to enable, run in the order listed
+    #[serde(default)]
+    pub plugins: Vec<PluginConfig>,
+}
+
+/// An entry of `build.plugins`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginConfig {
+    pub name: String,
+    /// Passed to the plugin as `PluginContext::settings`
+    #[serde(default)]
+    pub settings: serde_json::Value,
 }
 
 /// Complete config.yaml structure
@@ -106,6 +354,14 @@ pub struct SsgConfig {
     pub build: BuildConfig,
     #[serde(default)]
     pub assets: AssetsConfig,
+    /// Contents of the `data/` directory, keyed by file stem. Serialized so
+    /// data changes count as config changes for the build cache.
+    #[serde(default, skip_deserializing)]
+    pub data: serde_json::Map<String, serde_json::Value>,
+    /// When the build started, exposed to templates as `build_time` and
+    /// `build_year`. Not serialized, so it never invalidates the build cache.
+    #[serde(skip)]
+    pub build_time: DateTime<Utc>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -117,7 +373,9 @@ pub struct TemplateConfig<'a> {
     pub assets: &'a AssetsConfig,
     pub api_url: Option<&'a str>,
     pub google_analytics_id: Option<&'a str>,
+    pub language: &'a str,
     pub contacts: &'a Contacts,
+    pub noindex: bool,
 }
 
 impl SsgConfig {
@@ -130,7 +388,9 @@ impl SsgConfig {
             assets: &self.assets,
             api_url: self.site.api_url.as_deref(),
             google_analytics_id: self.site.google_analytics_id.as_deref(),
+            language: &self.site.language,
             contacts: &self.site.contacts,
+            noindex: self.build.noindex,
         }
     }
 }
@@ -145,27 +405,71 @@ impl Default for SiteConfig {
             cdn_url: None,
             api_url: None,
             google_analytics_id: None,
+            language: default_language(),
             contacts: Contacts::default(),
         }
     }
 }
 
+impl BuildConfig {
+    /// Standalone pages live next to the posts tree, so the default
+    /// `content/posts` puts them in `content/pages`
+    pub fn pages_dir(&self) -> PathBuf {
+        Path::new(&self.content_dir)
+            .parent()
+            .unwrap_or(Path::new(""))
+            .join("pages")
+    }
+}
+
 impl Default for BuildConfig {
     fn default() -> Self {
         Self {
             content_dir: default_content_dir(),
             output_dir: default_output_dir(),
+            template_dir: default_template_dir(),
+            watch_paths: Vec::new(),
+            precompress: Vec::new(),
+            precompress_min_size: default_precompress_min_size(),
             posts_per_page: default_posts_per_page(),
             pagination_window: default_pagination_window(),
             homepage_posts_limit: None,
+            recent_count: default_recent_count(),
+            recent: RecentConfig::default(),
+            feeds: FeedsConfig::default(),
+            react: ReactConfig::default(),
+            json_api: default_json_api(),
             encode_filenames: false,
             search: SearchConfig::default(),
             generate_partials: false,
             partial_dir: default_partial_dir(),
+            git_lastmod: false,
+            noindex: false,
+            robots: RobotsConfig::default(),
+            images: ImagesConfig::default(),
+            shortcodes: HashMap::new(),
+            includes_dir: default_includes_dir(),
+            permalink: default_permalink(),
+            ugly_urls: false,
+            canonical_first_page: false,
+            tag_aliases: HashMap::new(),
+            case_insensitive_tags: false,
+            minify_html: false,
+            fingerprint_assets: false,
+            copy_extensions: Vec::new(),
+            typography: default_typography(),
+            strip_comments: default_strip_comments(),
+            abbreviations_first_only: false,
+            date_format: default_date_format(),
+            plugins: Vec::new(),
         }
     }
 }
 
+fn default_language() -> String {
+    "ko-KR".to_string()
+}
+
 fn default_site_title() -> String {
     "marshallku blog".to_string()
 }
@@ -190,10 +494,41 @@ fn default_output_dir() -> String {
     "dist".to_string()
 }
 
+fn default_template_dir() -> String {
+    "templates".to_string()
+}
+
+fn default_precompress_min_size() -> u64 {
+    1024
+}
+
 fn default_posts_per_page() -> usize {
     10
 }
 
+/// On, because the renderer always parsed with `Options::all()`, which
+/// includes smart punctuation; turning it off by default would change every
+/// existing post
+fn default_typography() -> bool {
+    true
+}
+
+fn default_strip_comments() -> bool {
+    true
+}
+
+fn default_date_format() -> String {
+    "%Y-%m-%d".to_string()
+}
+
+fn default_recent_count() -> usize {
+    6
+}
+
+fn default_json_api() -> bool {
+    true
+}
+
 fn default_pagination_window() -> usize {
     5
 }
@@ -202,18 +537,51 @@ fn default_partial_dir() -> String {
     "html".to_string()
 }
 
-pub fn load_config() -> Result<SsgConfig> {
-    let config_path = Path::new("config.yaml");
+fn default_includes_dir() -> String {
+    "content/includes".to_string()
+}
+
+fn default_permalink() -> String {
+    permalink::DEFAULT_PERMALINK.to_string()
+}
+
+/// Load `config.yaml`, deep-merging `config.<env>.yaml` over it when an
+/// environment is given.
+pub fn load_config(env: Option<&str>) -> Result<SsgConfig> {
+    load_config_from(Path::new("."), env)
+}
+
+fn load_config_from(dir: &Path, env: Option<&str>) -> Result<SsgConfig> {
+    let config_path = dir.join("config.yaml");
 
-    let mut config = if config_path.exists() {
-        let content = fs::read_to_string(config_path).context("Failed to read config.yaml")?;
-        serde_yaml::from_str(&content).context("Failed to parse config.yaml")?
+    let mut value = if config_path.exists() {
+        read_config_value(&config_path)?
     } else {
+        serde_yaml::Value::Null
+    };
+
+    if let Some(env) = env {
+        let env_path = dir.join(format!("config.{}.yaml", env));
+        if !env_path.exists() {
+            anyhow::bail!(
+                "Config for environment '{}' not found: {}",
+                env,
+                env_path.display()
+            );
+        }
+        merge_yaml(&mut value, read_config_value(&env_path)?);
+    }
+
+    let mut config: SsgConfig = if value.is_null() {
         SsgConfig::default()
+    } else {
+        serde_yaml::from_value(value).context("Failed to parse config.yaml")?
     };
 
+    permalink::validate(&config.build.permalink)?;
+
     // Load manifest.json if it exists - directly deserialize as HashMap
-    let manifest_path = Path::new("manifest.json");
+    let manifest_path = dir.join("manifest.json");
     if manifest_path.exists() {
         let manifest_content =
             fs::read_to_string(manifest_path).context("Failed to read manifest.json")?;
@@ -221,9 +589,145 @@ pub fn load_config() -> Result<SsgConfig> {
             serde_json::from_str(&manifest_content).context("Failed to parse manifest.json")?;
     }
 
+    if config.build.fingerprint_assets {
+        let fingerprints = assets::fingerprint_static_assets(&dir.join("static"))?;
+        config
+            .assets
+            .entry(assets::STATIC_ASSETS_KEY.to_string())
+            .or_default()
+            .extend(fingerprints);
+    }
+
+    config.data = data::load_data_dir(&dir.join(data::DATA_DIR))?;
+    config.build_time = Utc::now();
+
     Ok(config)
 }
 
+fn read_config_value(path: &Path) -> Result<serde_yaml::Value> {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let content = fs::read_to_string(path).with_context(|| format!("Failed to read {}", name))?;
+    let mut value: serde_yaml::Value =
+        serde_yaml::from_str(&content).with_context(|| format!("Failed to parse {}", name))?;
+    interpolate_env_values(&mut value, &|var| std::env::var(var).ok())
+        .with_context(|| format!("Failed to expand environment variables in {}", name))?;
+    Ok(value)
+}
+
+/// Expands environment references in every string scalar of a parsed config.
+/// Comments are gone by then and values never pass through the YAML parser,
+/// so a value containing `: `, `#` or a newline can't change the document's
+/// structure. A scalar that is a single reference (`port: ${PORT}`) becomes a
+/// number or boolean when that's what it expands to, as it would in YAML.
+fn interpolate_env_values<F>(value: &mut serde_yaml::Value, lookup: &F) -> Result<()>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    use serde_yaml::Value;
+
+    match value {
+        Value::String(raw) => {
+            let expanded = interpolate_env(raw, lookup)?;
+            let whole_reference = raw.starts_with("${") && raw.find('}') == Some(raw.len() - 1);
+            let typed = whole_reference
+                .then(|| serde_yaml::from_str::<Value>(&expanded).ok())
+                .flatten()
+                .filter(|typed| matches!(typed, Value::Bool(_) | Value::Number(_)));
+            *value = typed.unwrap_or(Value::String(expanded));
+        }
+        Value::Sequence(items) => {
+            for item in items {
+                interpolate_env_values(item, lookup)?;
+            }
+        }
+        Value::Mapping(map) => {
+            for (_, item) in map.iter_mut() {
+                interpolate_env_values(item, lookup)?;
+            }
+        }
+        Value::Tagged(tagged) => interpolate_env_values(&mut tagged.value, lookup)?,
+        Value::Null | Value::Bool(_) | Value::Number(_) => {}
+    }
+
+    Ok(())
+}
+
+/// Recursively merges `overlay` into `base`. Mappings merge key by key; any
+/// other value in the overlay replaces the base value outright.
+fn merge_yaml(base: &mut serde_yaml::Value, overlay: serde_yaml::Value) {
+    use serde_yaml::Value;
+
+    match (base, overlay) {
+        (Value::Mapping(base), Value::Mapping(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_yaml(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+/// Expands `${VAR}` and `${VAR:-default}` references using `lookup`, so
+/// secrets like the CDN/API URLs can come from the environment. `$$` yields a
+/// literal `$`; any other `$` is kept as-is.
+fn interpolate_env<F>(content: &str, lookup: F) -> Result<String>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let mut output = String::with_capacity(content.len());
+    let mut rest = content;
+
+    while let Some(pos) = rest.find('$') {
+        output.push_str(&rest[..pos]);
+        rest = &rest[pos..];
+
+        if let Some(after) = rest.strip_prefix("$$") {
+            output.push('$');
+            rest = after;
+            continue;
+        }
+
+        let Some(after) = rest.strip_prefix("${") else {
+            output.push('$');
+            rest = &rest[1..];
+            continue;
+        };
+
+        let end = after.find('}').ok_or_else(|| {
+            anyhow::anyhow!(
+                "Unterminated `${{` in `${{{}`",
+                after.lines().next().unwrap_or("")
+            )
+        })?;
+        let expr = &after[..end];
+        let (name, default) = match expr.split_once(":-") {
+            Some((name, default)) => (name, Some(default)),
+            None => (expr, None),
+        };
+
+        let value = match (lookup(name), default) {
+            (Some(value), _) => value,
+            (None, Some(default)) => default.to_string(),
+            (None, None) => anyhow::bail!(
+                "Environment variable `{}` is not set (use `${{{}:-default}}` to provide a fallback)",
+                name,
+                name
+            ),
+        };
+
+        output.push_str(&value);
+        rest = &after[end + 1..];
+    }
+
+    output.push_str(rest);
+    Ok(output)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -234,4 +738,118 @@ mod tests {
         assert_eq!(config.site.title, "marshallku blog");
         assert_eq!(config.build.posts_per_page, 10);
     }
+
+    #[test]
+    fn test_template_config_exposes_noindex() {
+        let mut config = SsgConfig::default();
+        let context = serde_json::to_value(config.to_template_config()).unwrap();
+        assert_eq!(context["noindex"], false);
+
+        config.build.noindex = true;
+        let context = serde_json::to_value(config.to_template_config()).unwrap();
+        assert_eq!(context["noindex"], true);
+    }
+
+    #[test]
+    fn test_env_overlay_overrides_present_keys_only() {
+        let dir = tempfile::TempDir::new().unwrap();
+        fs::write(
+            dir.path().join("config.yaml"),
+            "site:\n  title: \"Blog\"\n  url: \"https://example.com\"\nbuild:\n  posts_per_page: 5\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("config.staging.yaml"),
+            "site:\n  url: \"https://staging.example.com\"\n",
+        )
+        .unwrap();
+
+        let config = load_config_from(dir.path(), Some("staging")).unwrap();
+        assert_eq!(config.site.url, "https://staging.example.com");
+        assert_eq!(config.site.title, "Blog");
+        assert_eq!(config.build.posts_per_page, 5);
+
+        let config = load_config_from(dir.path(), None).unwrap();
+        assert_eq!(config.site.url, "https://example.com");
+    }
+
+    #[test]
+    fn test_env_overlay_missing_file_fails() {
+        let dir = tempfile::TempDir::new().unwrap();
+        fs::write(dir.path().join("config.yaml"), "site:\n  title: \"Blog\"\n").unwrap();
+
+        let err = load_config_from(dir.path(), Some("prod"))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("config.prod.yaml"), "got: {}", err);
+    }
+
+    fn lookup(name: &str) -> Option<String> {
+        (name == "CDN_URL").then(|| "https://cdn.example.com".to_string())
+    }
+
+    #[test]
+    fn test_interpolate_set_var() {
+        let result = interpolate_env("cdn_url: \"${CDN_URL}\"", lookup).unwrap();
+        assert_eq!(result, "cdn_url: \"https://cdn.example.com\"");
+    }
+
+    #[test]
+    fn test_interpolate_unset_var_with_default() {
+        let result = interpolate_env("api_url: ${API_URL:-http://localhost:8080}", lookup).unwrap();
+        assert_eq!(result, "api_url: http://localhost:8080");
+
+        // A set variable ignores its default
+        let result = interpolate_env("${CDN_URL:-unused}", lookup).unwrap();
+        assert_eq!(result, "https://cdn.example.com");
+    }
+
+    #[test]
+    fn test_interpolate_unset_var_without_default_fails() {
+        let err = interpolate_env("api_url: ${API_URL}", lookup)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("API_URL"), "got: {}", err);
+    }
+
+    fn interpolated_config(yaml: &str) -> serde_yaml::Value {
+        let mut value = serde_yaml::from_str(yaml).unwrap();
+        interpolate_env_values(&mut value, &|name: &str| match name {
+            "TITLE" => Some("Notes: on Rust # and more\nlast_key: injected".to_string()),
+            "PORT" => Some("8080".to_string()),
+            _ => None,
+        })
+        .unwrap();
+        value
+    }
+
+    #[test]
+    fn test_interpolated_values_cannot_change_structure() {
+        let value =
+            interpolated_config("site:\n  title: ${TITLE}\n  description: \"About ${TITLE}\"\n");
+
+        assert_eq!(
+            value["site"]["title"].as_str(),
+            Some("Notes: on Rust # and more\nlast_key: injected")
+        );
+        assert_eq!(
+            value["site"]["description"].as_str(),
+            Some("About Notes: on Rust # and more\nlast_key: injected")
+        );
+        assert!(value["site"].get("last_key").is_none());
+        assert!(value.get("last_key").is_none());
+    }
+
+    #[test]
+    fn test_comments_are_not_interpolated() {
+        let value = interpolated_config("# Set ${MISSING} before building\nport: ${PORT}\n");
+
+        assert_eq!(value["port"].as_u64(), Some(8080));
+    }
+
+    #[test]
+    fn test_interpolate_escaped_dollar() {
+        let result = interpolate_env("price: $$5 and $HOME", lookup).unwrap();
+        assert_eq!(result, "price: $5 and $HOME");
+    }
 }
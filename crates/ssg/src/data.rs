@@ -0,0 +1,108 @@
+use anyhow::{Context, Result};
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::Path;
+
+/// Directory of site-wide data files, relative to the site root
+pub const DATA_DIR: &str = "data";
+
+/// Load every YAML/JSON/TOML file directly under `dir`, keyed by file stem:
+/// `data/links.yaml` becomes `data.links` in templates. Other files are
+/// ignored; a file that fails to parse aborts the build.
+pub fn load_data_dir(dir: &Path) -> Result<Map<String, Value>> {
+    let mut data = Map::new();
+    if !dir.exists() {
+        return Ok(data);
+    }
+
+    let mut paths: Vec<_> = fs::read_dir(dir)
+        .with_context(|| format!("Failed to read {}", dir.display()))?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.is_file())
+        .collect();
+    paths.sort();
+
+    for path in paths {
+        let Some(stem) = path.file_stem().and_then(|s| s.to_str()) else {
+            continue;
+        };
+        let Some(value) = parse_data_file(&path)? else {
+            continue;
+        };
+
+        if data.insert(stem.to_string(), value).is_some() {
+            anyhow::bail!(
+                "Data key '{}' is defined by more than one file in {}",
+                stem,
+                dir.display()
+            );
+        }
+    }
+
+    Ok(data)
+}
+
+/// Parse a data file by extension, or `None` for unsupported file types
+fn parse_data_file(path: &Path) -> Result<Option<Value>> {
+    let ext = match path.extension().and_then(|e| e.to_str()) {
+        Some(ext) => ext.to_lowercase(),
+        None => return Ok(None),
+    };
+    if !matches!(ext.as_str(), "yaml" | "yml" | "json" | "toml") {
+        return Ok(None);
+    }
+
+    let content =
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let parse_error = || format!("Failed to parse data file {}", path.display());
+
+    let value = match ext.as_str() {
+        "json" => serde_json::from_str(&content).with_context(parse_error)?,
+        "toml" => {
+            let table: toml::Table = toml::from_str(&content).with_context(parse_error)?;
+            serde_json::to_value(table).with_context(parse_error)?
+        }
+        _ => serde_yaml::from_str(&content).with_context(parse_error)?,
+    };
+
+    Ok(Some(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_loads_each_format_by_stem() {
+        let temp = TempDir::new().unwrap();
+        fs::write(temp.path().join("links.yaml"), "- name: GitHub\n").unwrap();
+        fs::write(temp.path().join("stats.json"), r#"{"posts": 3}"#).unwrap();
+        fs::write(temp.path().join("site.toml"), "theme = \"dark\"\n").unwrap();
+        fs::write(temp.path().join("README.md"), "ignored").unwrap();
+
+        let data = load_data_dir(temp.path()).unwrap();
+
+        assert_eq!(data.len(), 3);
+        assert_eq!(data["links"][0]["name"], "GitHub");
+        assert_eq!(data["stats"]["posts"], 3);
+        assert_eq!(data["site"]["theme"], "dark");
+    }
+
+    #[test]
+    fn test_malformed_file_names_the_file() {
+        let temp = TempDir::new().unwrap();
+        fs::write(temp.path().join("broken.json"), "{ nope").unwrap();
+
+        let err = load_data_dir(temp.path()).unwrap_err();
+
+        assert!(err.to_string().contains("broken.json"), "{}", err);
+    }
+
+    #[test]
+    fn test_missing_dir_is_empty() {
+        let temp = TempDir::new().unwrap();
+        assert!(load_data_dir(&temp.path().join("data")).unwrap().is_empty());
+    }
+}
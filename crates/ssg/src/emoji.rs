@@ -0,0 +1,213 @@
+use pulldown_cmark::{CowStr, Event, Tag};
+use regex::Regex;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// GitHub-style `:name:` codes supported out of the box. Not exhaustive;
+/// unknown codes are left as written.
+const EMOJI: &[(&str, &str)] = &[
+    ("+1", "👍"),
+    ("-1", "👎"),
+    ("100", "💯"),
+    ("bug", "🐛"),
+    ("bulb", "💡"),
+    ("clap", "👏"),
+    ("coffee", "☕"),
+    ("confused", "😕"),
+    ("cry", "😢"),
+    ("eyes", "👀"),
+    ("fire", "🔥"),
+    ("heart", "❤️"),
+    ("heavy_check_mark", "✔️"),
+    ("hammer", "🔨"),
+    ("joy", "😂"),
+    ("laughing", "😆"),
+    ("memo", "📝"),
+    ("no_entry", "⛔"),
+    ("ok_hand", "👌"),
+    ("package", "📦"),
+    ("pray", "🙏"),
+    ("question", "❓"),
+    ("rocket", "🚀"),
+    ("see_no_evil", "🙈"),
+    ("smile", "😄"),
+    ("smiley", "😃"),
+    ("sparkles", "✨"),
+    ("star", "⭐"),
+    ("sweat_smile", "😅"),
+    ("tada", "🎉"),
+    ("thinking", "🤔"),
+    ("thumbsdown", "👎"),
+    ("thumbsup", "👍"),
+    ("warning", "⚠️"),
+    ("wave", "👋"),
+    ("white_check_mark", "✅"),
+    ("wink", "😉"),
+    ("wrench", "🔧"),
+    ("x", "❌"),
+    ("zap", "⚡"),
+];
+
+fn lookup(name: &str) -> Option<&'static str> {
+    static MAP: OnceLock<HashMap<&'static str, &'static str>> = OnceLock::new();
+    MAP.get_or_init(|| EMOJI.iter().copied().collect())
+        .get(name)
+        .copied()
+}
+
+/// Expand `:name:` codes in the text of a markdown event stream. Only text
+/// events are touched, so code spans, code blocks, raw HTML and link
+/// destinations keep their colons.
+pub fn apply<'a>(events: impl IntoIterator<Item = Event<'a>>) -> Vec<Event<'a>> {
+    let mut output = Vec::new();
+    let mut in_code_block = false;
+    // The parser may split one run of text into several events
+    let mut pending: Option<String> = None;
+
+    for event in events {
+        match event {
+            Event::Text(text) if !in_code_block => {
+                pending.get_or_insert_with(String::new).push_str(&text);
+                continue;
+            }
+            Event::Start(Tag::CodeBlock(_)) => in_code_block = true,
+            Event::End(Tag::CodeBlock(_)) => in_code_block = false,
+            _ => {}
+        }
+        if let Some(text) = pending.take() {
+            output.push(Event::Text(CowStr::from(expand(&text))));
+        }
+        output.push(event);
+    }
+    if let Some(text) = pending {
+        output.push(Event::Text(CowStr::from(expand(&text))));
+    }
+
+    output
+}
+
+/// Replace known `:name:` codes with Unicode emoji. A code must stand apart
+/// from surrounding words and colons (so `std::x::y` and `10:30:45` stay as
+/// written), except that codes may follow each other (`:+1::tada:`). Codes
+/// inside URLs (any whitespace-delimited token containing `://`) are left
+/// alone so links aren't corrupted.
+pub fn expand(text: &str) -> String {
+    static CODE_RE: OnceLock<Regex> = OnceLock::new();
+    let re = CODE_RE.get_or_init(|| Regex::new(r":([a-z0-9_+\-]+):").unwrap());
+
+    if !text.contains(':') {
+        return text.to_string();
+    }
+
+    let mut result = String::with_capacity(text.len());
+    let mut last_end = 0;
+    let mut search_from = 0;
+
+    while let Some(cap) = re.captures_at(text, search_from) {
+        let full_match = cap.get(0).unwrap();
+
+        let (start, end) = (full_match.start(), full_match.end());
+        let standalone = (start > 0 && start == last_end)
+            || text[..start].chars().next_back().is_none_or(is_separator);
+        let emoji = lookup(&cap[1])
+            .filter(|_| standalone && followed_by_separator(text, end, re))
+            .filter(|_| !inside_url(text, start));
+
+        match emoji {
+            Some(emoji) => {
+                result.push_str(&text[last_end..start]);
+                result.push_str(emoji);
+                last_end = end;
+                search_from = end;
+            }
+            // The closing colon may open the next code (`:+1::tada:`)
+            None => search_from = end - 1,
+        }
+    }
+
+    result.push_str(&text[last_end..]);
+    result
+}
+
+fn is_separator(c: char) -> bool {
+    !c.is_alphanumeric() && c != ':'
+}
+
+/// Whether the code ending at `end` is followed by a separator or by another
+/// known code
+fn followed_by_separator(text: &str, end: usize, re: &Regex) -> bool {
+    match text[end..].chars().next() {
+        None => true,
+        Some(':') => re
+            .captures_at(text, end)
+            .is_some_and(|next| next.get(0).unwrap().start() == end && lookup(&next[1]).is_some()),
+        Some(c) => is_separator(c),
+    }
+}
+
+fn inside_url(text: &str, pos: usize) -> bool {
+    let token_start = text[..pos]
+        .rfind(char::is_whitespace)
+        .map(|i| i + 1)
+        .unwrap_or(0);
+    let token_end = text[pos..]
+        .find(char::is_whitespace)
+        .map(|i| pos + i)
+        .unwrap_or(text.len());
+
+    text[token_start..token_end].contains("://")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_known_emoji_expanded() {
+        assert_eq!(
+            expand("Shipped :rocket: today :tada:"),
+            "Shipped 🚀 today 🎉"
+        );
+    }
+
+    #[test]
+    fn test_unknown_code_left_literal() {
+        assert_eq!(
+            expand("Meet at 10:30:45 :notanemoji:"),
+            "Meet at 10:30:45 :notanemoji:"
+        );
+    }
+
+    #[test]
+    fn test_adjacent_codes() {
+        assert_eq!(expand(":+1::tada: done"), "👍🎉 done");
+        assert_eq!(expand(":not:smile:"), ":not:smile:");
+    }
+
+    #[test]
+    fn test_codes_inside_words_and_paths_untouched() {
+        let text = "Use std::x::y or a:smile:b, not :smile:s";
+        assert_eq!(expand(text), text);
+    }
+
+    #[test]
+    fn test_only_text_events_expanded() {
+        let markdown = "Done :white_check_mark: but `:x:` and <span title=\":x:\">:x:</span>\n\n```\n:fire:\n```\n";
+        let mut html = String::new();
+        pulldown_cmark::html::push_html(
+            &mut html,
+            apply(pulldown_cmark::Parser::new(markdown)).into_iter(),
+        );
+
+        assert_eq!(
+            html,
+            "<p>Done ✅ but <code>:x:</code> and <span title=\":x:\">❌</span></p>\n<pre><code>:fire:\n</code></pre>\n"
+        );
+    }
+
+    #[test]
+    fn test_urls_untouched() {
+        let text = "See https://example.com/:smile:/page and [x](https://a.io/:fire:)";
+        assert_eq!(expand(text), text);
+    }
+}
@@ -1,12 +1,17 @@
 use crate::config::SsgConfig;
-use crate::metadata::{compare_posts_desc, MetadataCache};
-use crate::parser::Parser;
+use crate::git;
+use crate::image::ImageProcessor;
+use crate::metadata::{compare_posts_desc, MetadataCache, PostMetadata};
+use crate::parser::{post_source_paths, Parser};
+use crate::permalink;
+use crate::renderer::Renderer;
 use crate::slug::encode_for_url;
+use crate::types::{Audio, Post};
 use anyhow::{Context, Result};
 use std::collections::HashMap;
-use std::fs;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
 use std::path::{Path, PathBuf};
-use walkdir::WalkDir;
 
 pub struct FeedGenerator;
 
@@ -18,11 +23,16 @@ impl FeedGenerator {
         output_dir: &Path,
     ) -> Result<()> {
         // Build slug -> path lookup map once (O(N) instead of O(N*M))
-        let post_paths = Self::build_post_path_map(content_dir);
+        let post_paths = post_source_paths(content_dir);
+        Self::check_stylesheet(config);
+        let thumbnails = Thumbnails {
+            image_processor: ImageProcessor::from_config(config),
+            content_dir,
+        };
 
         // RSS feeds
-        Self::generate_global_feed(config, metadata, &post_paths, output_dir)?;
-        Self::generate_category_feeds(config, metadata, &post_paths, output_dir)?;
+        Self::generate_global_feed(config, metadata, &post_paths, &thumbnails, output_dir)?;
+        Self::generate_category_feeds(config, metadata, &post_paths, &thumbnails, output_dir)?;
 
         // Atom feeds
         Self::generate_global_atom_feed(config, metadata, &post_paths, output_dir)?;
@@ -30,141 +40,61 @@ impl FeedGenerator {
         Ok(())
     }
 
-    fn build_post_path_map(content_dir: &Path) -> HashMap<String, PathBuf> {
-        let mut map = HashMap::new();
-        for entry in WalkDir::new(content_dir)
-            .follow_links(true)
-            .into_iter()
-            .filter_map(|e| e.ok())
-        {
-            let path = entry.path();
-            if path.extension().is_some_and(|ext| ext == "md") {
-                if let Some(stem) = path.file_stem() {
-                    let slug = stem.to_string_lossy().to_string();
-                    map.insert(slug, path.to_path_buf());
-                }
-            }
-        }
-        map
+    fn post_path<'a>(
+        post_paths: &'a HashMap<String, PathBuf>,
+        post_meta: &PostMetadata,
+    ) -> Result<&'a PathBuf> {
+        post_paths
+            .get(&format!("{}/{}", post_meta.category, post_meta.slug))
+            .ok_or_else(|| anyhow::anyhow!("Post file not found: {}", post_meta.slug))
     }
 
     fn generate_global_feed(
         config: &SsgConfig,
         metadata: &MetadataCache,
         post_paths: &HashMap<String, PathBuf>,
+        thumbnails: &Thumbnails,
         output_dir: &Path,
     ) -> Result<()> {
-        let recent_posts = metadata.get_recent_posts(10);
+        let recent_posts = metadata.get_recent_visible_posts(10);
 
         if recent_posts.is_empty() {
             return Ok(());
         }
 
-        let last_build_date = chrono::Utc::now().to_rfc2822();
-
-        let mut items = Vec::new();
-
-        for post_meta in recent_posts {
-            if post_meta.frontmatter.hidden {
-                continue;
-            }
-
-            let post_path = post_paths
-                .get(&post_meta.slug)
-                .ok_or_else(|| anyhow::anyhow!("Post file not found: {}", post_meta.slug))?;
-            let post = Parser::parse_file(post_path)
-                .with_context(|| format!("Failed to parse post: {}", post_meta.slug))?;
-
-            let rendered_content = Self::render_markdown_simple(&post.content);
-            let url = format!(
-                "{}/{}/{}",
-                config.site.url,
-                encode_for_url(&post.category),
-                encode_for_url(&post.slug)
-            );
-
+        let channel = RssChannel {
+            title: &config.site.title,
+            description: &config.site.description,
+            language: &config.site.language,
+            feed_url: &format!("{}/feed.xml", config.site.url),
+            link: &config.site.url,
+            last_build_date: &chrono::Utc::now().to_rfc2822(),
+            hub_url: config.build.feeds.hub_url.as_deref(),
+            stylesheet: config.build.feeds.stylesheet.as_deref(),
+        };
+        let items = recent_posts.into_iter().map(|post_meta| {
+            let post_path = Self::post_path(post_paths, post_meta)?;
+            let post = Self::parse_post(post_paths, post_meta)?;
             let category_name = metadata
                 .get_category_info()
                 .iter()
                 .find(|c| c.slug == post.category)
                 .map(|c| c.name.clone())
                 .unwrap_or_else(|| post.category.clone());
-
-            let tags_xml = if !post.frontmatter.tags.is_empty() {
-                post.frontmatter
-                    .tags
-                    .iter()
-                    .map(|tag| format!("        <category>{}</category>", Self::cdata(tag)))
-                    .collect::<Vec<_>>()
-                    .join("\n")
-            } else {
-                String::new()
-            };
-
-            let description = post
-                .frontmatter
-                .description
-                .as_deref()
-                .unwrap_or(&post.frontmatter.title);
-
-            let pub_date = post.frontmatter.date.to_rfc2822();
-
-            let item = format!(
-                r#"    <item>
-        <title>{}</title>
-        <link>{}</link>
-        <dc:creator>{}</dc:creator>
-        <pubDate>{}</pubDate>
-        <category>{}</category>{}{}
-        <guid isPermaLink="false">{}</guid>
-        <description>{}</description>
-        <content:encoded>{}</content:encoded>
-    </item>"#,
-                Self::escape_xml(&post.frontmatter.title),
-                url,
-                Self::cdata(&config.site.author),
-                pub_date,
-                Self::cdata(&category_name),
-                if tags_xml.is_empty() { "" } else { "\n" },
-                tags_xml,
-                url,
-                Self::cdata(description),
-                Self::cdata(&rendered_content)
-            );
-
-            items.push(item);
-        }
-
-        let feed_url = format!("{}/feed.xml", config.site.url);
-
-        let rss_xml = format!(
-            r#"<?xml version="1.0" encoding="UTF-8"?>
-<rss version="2.0" xmlns:content="http://purl.org/rss/1.0/modules/content/" xmlns:wfw="http://wellformedweb.org/CommentAPI/" xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:atom="http://www.w3.org/2005/Atom" xmlns:sy="http://purl.org/rss/1.0/modules/syndication/" xmlns:slash="http://purl.org/rss/1.0/modules/slash/"
->
-<channel>
-    <title>{}</title>
-    <description>{}</description>
-    <language>ko-KR</language>
-    <atom:link href="{}" rel="self" type="application/rss+xml" />
-    <link>{}</link>
-    <lastBuildDate>{}</lastBuildDate>
-    <sy:updatePeriod>hourly</sy:updatePeriod>
-    <sy:updateFrequency>1</sy:updateFrequency>
-{}
-</channel>
-</rss>
-"#,
-            Self::escape_xml(&config.site.title),
-            Self::escape_xml(&config.site.description),
-            feed_url,
-            config.site.url,
-            last_build_date,
-            items.join("\n")
-        );
-
-        fs::create_dir_all(output_dir)?;
-        let output_path = output_dir.join("feed.xml");
-        fs::write(&output_path, rss_xml)?;
+            let thumbnail = thumbnails.url(config, post_meta);
+            Ok(Self::rss_item(
+                config,
+                post_meta,
+                &post,
+                post_path,
+                &category_name,
+                thumbnail.as_deref(),
+            ))
+        });
+
+        let mut out = Self::create_output(&output_dir.join("feed.xml"))?;
+        Self::write_rss(&mut out, &channel, items)?;
+        out.flush()?;
 
         Ok(())
     }
@@ -173,6 +103,7 @@ impl FeedGenerator {
         config: &SsgConfig,
         metadata: &MetadataCache,
         post_paths: &HashMap<String, PathBuf>,
+        thumbnails: &Thumbnails,
         output_dir: &Path,
     ) -> Result<()> {
         let categories = metadata.get_categories();
@@ -202,76 +133,19 @@ impl FeedGenerator {
                 .map(|c| c.name.clone())
                 .unwrap_or_else(|| category_slug.clone());
 
-            let last_build_date = chrono::Utc::now().to_rfc2822();
-
-            let mut items = Vec::new();
-
-            for post_meta in category_posts {
-                let post_path = post_paths
-                    .get(&post_meta.slug)
-                    .ok_or_else(|| anyhow::anyhow!("Post file not found: {}", post_meta.slug))?;
-                let post = Parser::parse_file(post_path)
-                    .with_context(|| format!("Failed to parse post: {}", post_meta.slug))?;
-
-                let rendered_content = Self::render_markdown_simple(&post.content);
-                let url = format!(
-                    "{}/{}/{}",
-                    config.site.url,
-                    encode_for_url(&post.category),
-                    encode_for_url(&post.slug)
-                );
-
-                let tags_xml = if !post.frontmatter.tags.is_empty() {
-                    post.frontmatter
-                        .tags
-                        .iter()
-                        .map(|tag| format!("        <category>{}</category>", Self::cdata(tag)))
-                        .collect::<Vec<_>>()
-                        .join("\n")
-                } else {
-                    String::new()
-                };
-
-                let description = post
-                    .frontmatter
-                    .description
-                    .as_deref()
-                    .unwrap_or(&post.frontmatter.title);
-
-                let pub_date = post.frontmatter.date.to_rfc2822();
-
-                let item = format!(
-                    r#"    <item>
-        <title>{}</title>
-        <link>{}</link>
-        <dc:creator>{}</dc:creator>
-        <pubDate>{}</pubDate>
-        <category>{}</category>{}{}
-        <guid isPermaLink="false">{}</guid>
-        <description>{}</description>
-        <content:encoded>{}</content:encoded>
-    </item>"#,
-                    Self::escape_xml(&post.frontmatter.title),
-                    url,
-                    Self::cdata(&config.site.author),
-                    pub_date,
-                    Self::cdata(&category_name),
-                    if tags_xml.is_empty() { "" } else { "\n" },
-                    tags_xml,
-                    url,
-                    Self::cdata(description),
-                    Self::cdata(&rendered_content)
-                );
-
-                items.push(item);
-            }
-
             let feed_url = format!(
                 "{}/{}/feed.xml",
                 config.site.url,
                 encode_for_url(&category_slug)
             );
-            let category_url = format!("{}/{}/", config.site.url, encode_for_url(&category_slug));
+            let category_url = format!(
+                "{}{}",
+                config.site.url,
+                permalink::page_url(
+                    &format!("/{}/", encode_for_url(&category_slug)),
+                    config.build.ugly_urls
+                )
+            );
             let feed_title = format!("{} - {}", config.site.title, category_name);
             let feed_description = category_info
                 .as_ref()
@@ -284,35 +158,34 @@ impl FeedGenerator {
                 })
                 .unwrap_or_else(|| format!("{} posts from {}", category_name, config.site.title));
 
-            let rss_xml = format!(
-                r#"<?xml version="1.0" encoding="UTF-8"?>
-<rss version="2.0" xmlns:content="http://purl.org/rss/1.0/modules/content/" xmlns:wfw="http://wellformedweb.org/CommentAPI/" xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:atom="http://www.w3.org/2005/Atom" xmlns:sy="http://purl.org/rss/1.0/modules/syndication/" xmlns:slash="http://purl.org/rss/1.0/modules/slash/"
->
-<channel>
-    <title>{}</title>
-    <description>{}</description>
-    <language>ko-KR</language>
-    <atom:link href="{}" rel="self" type="application/rss+xml" />
-    <link>{}</link>
-    <lastBuildDate>{}</lastBuildDate>
-    <sy:updatePeriod>hourly</sy:updatePeriod>
-    <sy:updateFrequency>1</sy:updateFrequency>
-{}
-</channel>
-</rss>
-"#,
-                Self::escape_xml(&feed_title),
-                Self::escape_xml(&feed_description),
-                feed_url,
-                category_url,
-                last_build_date,
-                items.join("\n")
-            );
+            let channel = RssChannel {
+                title: &feed_title,
+                description: &feed_description,
+                language: &config.site.language,
+                feed_url: &feed_url,
+                link: &category_url,
+                last_build_date: &chrono::Utc::now().to_rfc2822(),
+                hub_url: None,
+                stylesheet: config.build.feeds.stylesheet.as_deref(),
+            };
+            let items = category_posts.into_iter().map(|post_meta| {
+                let post_path = Self::post_path(post_paths, post_meta)?;
+                let post = Self::parse_post(post_paths, post_meta)?;
+                let thumbnail = thumbnails.url(config, post_meta);
+                Ok(Self::rss_item(
+                    config,
+                    post_meta,
+                    &post,
+                    post_path,
+                    &category_name,
+                    thumbnail.as_deref(),
+                ))
+            });
 
             let category_dir = output_dir.join(&category_slug);
-            fs::create_dir_all(&category_dir)?;
-            let output_path = category_dir.join("feed.xml");
-            fs::write(&output_path, rss_xml)?;
+            let mut out = Self::create_output(&category_dir.join("feed.xml"))?;
+            Self::write_rss(&mut out, &channel, items)?;
+            out.flush()?;
         }
 
         Ok(())
@@ -324,120 +197,350 @@ impl FeedGenerator {
         post_paths: &HashMap<String, PathBuf>,
         output_dir: &Path,
     ) -> Result<()> {
-        let recent_posts = metadata.get_recent_posts(10);
+        let recent_posts = metadata.get_recent_visible_posts(10);
 
         if recent_posts.is_empty() {
             return Ok(());
         }
 
         let updated = chrono::Utc::now().to_rfc3339();
+        let entries = recent_posts.into_iter().map(|post_meta| {
+            let post_path = Self::post_path(post_paths, post_meta)?;
+            let post = Self::parse_post(post_paths, post_meta)?;
+            Ok(Self::atom_entry(config, post_meta, &post, post_path))
+        });
 
-        let mut entries = Vec::new();
+        let mut out = Self::create_output(&output_dir.join("atom.xml"))?;
+        Self::write_atom(&mut out, config, &updated, entries)?;
+        out.flush()?;
 
-        for post_meta in recent_posts {
-            if post_meta.frontmatter.hidden {
-                continue;
-            }
+        Ok(())
+    }
 
-            let post_path = post_paths
-                .get(&post_meta.slug)
-                .ok_or_else(|| anyhow::anyhow!("Post file not found: {}", post_meta.slug))?;
-            let post = Parser::parse_file(post_path)
-                .with_context(|| format!("Failed to parse post: {}", post_meta.slug))?;
+    fn parse_post(post_paths: &HashMap<String, PathBuf>, post_meta: &PostMetadata) -> Result<Post> {
+        let post_path = Self::post_path(post_paths, post_meta)?;
+        Parser::parse_file(post_path)
+            .with_context(|| format!("Failed to parse post: {}", post_meta.slug))
+    }
 
-            let rendered_content = Self::render_markdown_simple(&post.content);
-            let url = format!(
-                "{}/{}/{}/",
-                config.site.url,
-                encode_for_url(&post.category),
-                encode_for_url(&post.slug)
-            );
+    /// Buffered writer for a feed file, creating its directory first
+    fn create_output(path: &Path) -> Result<BufWriter<File>> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file =
+            File::create(path).with_context(|| format!("Failed to create {}", path.display()))?;
+        Ok(BufWriter::new(file))
+    }
 
-            let summary = post
-                .frontmatter
-                .description
-                .as_deref()
-                .unwrap_or(&post.frontmatter.title);
+    /// `<?xml-stylesheet?>` line for `build.feeds.stylesheet`, or nothing
+    fn stylesheet_pi(stylesheet: Option<&str>) -> String {
+        stylesheet
+            .map(|href| {
+                format!(
+                    "<?xml-stylesheet type=\"text/xsl\" href=\"{}\"?>\n",
+                    Self::escape_xml(href)
+                )
+            })
+            .unwrap_or_default()
+    }
 
-            let published = post.frontmatter.date.posted.to_rfc3339();
-            let entry_updated = post
-                .frontmatter
-                .date
-                .modified
-                .as_ref()
-                .map(|d| d.to_rfc3339())
-                .unwrap_or_else(|| published.clone());
+    /// Warn when `build.feeds.stylesheet` points at a file `static/` doesn't
+    /// have, since it's copied to the output with the other static assets
+    fn check_stylesheet(config: &SsgConfig) {
+        let Some(stylesheet) = config.build.feeds.stylesheet.as_deref() else {
+            return;
+        };
+        if stylesheet.contains("://") || stylesheet.starts_with("//") {
+            return;
+        }
+        if !Path::new("static")
+            .join(stylesheet.trim_start_matches('/'))
+            .is_file()
+        {
+            eprintln!(
+                "⚠️  Feed stylesheet {} not found in static/; feeds will reference a missing file",
+                stylesheet
+            );
+        }
+    }
 
-            let categories_xml: String = post
+    /// Write an RSS document, emitting each item as the iterator produces it
+    fn write_rss<W: Write>(
+        out: &mut W,
+        channel: &RssChannel,
+        items: impl IntoIterator<Item = Result<String>>,
+    ) -> Result<()> {
+        write!(
+            out,
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+{}<rss version="2.0" xmlns:content="http://purl.org/rss/1.0/modules/content/" xmlns:wfw="http://wellformedweb.org/CommentAPI/" xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:atom="http://www.w3.org/2005/Atom" xmlns:sy="http://purl.org/rss/1.0/modules/syndication/" xmlns:slash="http://purl.org/rss/1.0/modules/slash/" xmlns:media="http://search.yahoo.com/mrss/"
+>
+<channel>
+    <title>{}</title>
+    <description>{}</description>
+    <language>{}</language>
+    <atom:link href="{}" rel="self" type="application/rss+xml" />
+{}    <link>{}</link>
+    <lastBuildDate>{}</lastBuildDate>
+    <sy:updatePeriod>hourly</sy:updatePeriod>
+    <sy:updateFrequency>1</sy:updateFrequency>
+"#,
+            Self::stylesheet_pi(channel.stylesheet),
+            Self::escape_xml(channel.title),
+            Self::escape_xml(channel.description),
+            Self::escape_xml(channel.language),
+            channel.feed_url,
+            channel
+                .hub_url
+                .map(|hub| format!(
+                    "    <atom:link href=\"{}\" rel=\"hub\" />\n",
+                    Self::escape_xml(hub)
+                ))
+                .unwrap_or_default(),
+            channel.link,
+            channel.last_build_date,
+        )?;
+        Self::write_joined(out, items)?;
+        out.write_all(b"\n</channel>\n</rss>\n")?;
+        Ok(())
+    }
+
+    fn rss_item(
+        config: &SsgConfig,
+        post_meta: &PostMetadata,
+        post: &Post,
+        post_path: &Path,
+        category_name: &str,
+        thumbnail: Option<&str>,
+    ) -> String {
+        let rendered_content = Self::render_markdown_simple(&post.content);
+        let url = Self::post_url(config, post);
+
+        let tags_xml = if !post_meta.frontmatter.tags.is_empty() {
+            post_meta
                 .frontmatter
                 .tags
                 .iter()
-                .map(|tag| format!(r#"    <category term="{}" />"#, Self::escape_xml(tag)))
+                .map(|tag| format!("        <category>{}</category>", Self::cdata(tag)))
                 .collect::<Vec<_>>()
-                .join("\n");
-
-            let entry = format!(
-                r#"  <entry>
-    <title>{}</title>
-    <link href="{}" rel="alternate" type="text/html" />
-    <id>{}</id>
-    <published>{}</published>
-    <updated>{}</updated>
-    <author>
-      <name>{}</name>
-    </author>
-    <summary type="text">{}</summary>
-    <content type="html">{}</content>
-{}
-  </entry>"#,
-                Self::escape_xml(&post.frontmatter.title),
-                url,
-                url,
-                published,
-                entry_updated,
-                Self::escape_xml(&config.site.author),
-                Self::escape_xml(summary),
-                Self::cdata(&rendered_content),
-                categories_xml
-            );
+                .join("\n")
+        } else {
+            String::new()
+        };
+
+        let description = post_meta.summary().unwrap_or(&post.frontmatter.title);
+
+        let pub_date = post.frontmatter.date.to_rfc2822();
+
+        let enclosure_xml = post
+            .frontmatter
+            .audio
+            .as_ref()
+            .map(|audio| Self::enclosure(config, audio, &post.category, post_path))
+            .unwrap_or_default();
+
+        let media_xml = thumbnail
+            .map(|url| {
+                format!(
+                    "\n        <media:thumbnail url=\"{}\" />",
+                    Self::escape_xml(url)
+                )
+            })
+            .unwrap_or_default();
+
+        format!(
+            r#"    <item>
+        <title>{}</title>
+        <link>{}</link>
+        <dc:creator>{}</dc:creator>
+        <pubDate>{}</pubDate>
+        <category>{}</category>{}{}
+        <guid isPermaLink="false">{}</guid>
+        <description>{}</description>
+        <content:encoded>{}</content:encoded>{}{}
+    </item>"#,
+            Self::escape_xml(&post.frontmatter.title),
+            url,
+            Self::cdata(&config.site.author),
+            pub_date,
+            Self::cdata(category_name),
+            if tags_xml.is_empty() { "" } else { "\n" },
+            tags_xml,
+            url,
+            Self::cdata(description),
+            Self::cdata(&rendered_content),
+            enclosure_xml,
+            media_xml
+        )
+    }
 
-            entries.push(entry);
-        }
+    /// `<enclosure>` for a post's audio. The URL resolves like an image path
+    /// and is made absolute; a missing length is read from a file beside the
+    /// post (0 when it can't be), a missing type is guessed from the extension.
+    fn enclosure(config: &SsgConfig, audio: &Audio, category: &str, post_path: &Path) -> String {
+        let resolved = Renderer::resolve_path(&audio.url, category);
+        let url = if resolved.starts_with('/') && !resolved.starts_with("//") {
+            format!("{}{}", config.site.url, resolved)
+        } else {
+            resolved
+        };
+
+        let is_local = !audio.url.contains("://") && !audio.url.starts_with('/');
+        let length = audio
+            .length
+            .or_else(|| {
+                let dir = post_path.parent()?;
+                is_local
+                    .then(|| fs::metadata(dir.join(&audio.url)).ok())
+                    .flatten()
+                    .map(|m| m.len())
+            })
+            .unwrap_or(0);
+
+        let mime_type = audio
+            .mime_type
+            .clone()
+            .unwrap_or_else(|| audio_mime_type(&audio.url).to_string());
+
+        format!(
+            "\n        <enclosure url=\"{}\" length=\"{}\" type=\"{}\" />",
+            Self::escape_xml(&url),
+            length,
+            Self::escape_xml(&mime_type)
+        )
+    }
 
+    /// Write an Atom document, emitting each entry as the iterator produces it
+    fn write_atom<W: Write>(
+        out: &mut W,
+        config: &SsgConfig,
+        updated: &str,
+        entries: impl IntoIterator<Item = Result<String>>,
+    ) -> Result<()> {
         let feed_url = format!("{}/atom.xml", config.site.url);
 
-        let atom_xml = format!(
+        write!(
+            out,
             r#"<?xml version="1.0" encoding="UTF-8"?>
-<feed xmlns="http://www.w3.org/2005/Atom" xml:lang="ko">
+{}<feed xmlns="http://www.w3.org/2005/Atom" xml:lang="{}">
   <title>{}</title>
   <subtitle>{}</subtitle>
   <link href="{}" rel="self" type="application/atom+xml" />
-  <link href="{}" rel="alternate" type="text/html" />
+{}  <link href="{}" rel="alternate" type="text/html" />
   <id>{}</id>
   <updated>{}</updated>
   <author>
     <name>{}</name>
   </author>
-{}
-</feed>
 "#,
+            Self::stylesheet_pi(config.build.feeds.stylesheet.as_deref()),
+            Self::escape_xml(&config.site.language),
             Self::escape_xml(&config.site.title),
             Self::escape_xml(&config.site.description),
             feed_url,
+            config
+                .build
+                .feeds
+                .hub_url
+                .as_ref()
+                .map(|hub| format!(
+                    "  <link href=\"{}\" rel=\"hub\" />\n",
+                    Self::escape_xml(hub)
+                ))
+                .unwrap_or_default(),
             config.site.url,
             config.site.url,
             updated,
             Self::escape_xml(&config.site.author),
-            entries.join("\n")
-        );
+        )?;
+        Self::write_joined(out, entries)?;
+        out.write_all(b"\n</feed>\n")?;
+        Ok(())
+    }
 
-        fs::create_dir_all(output_dir)?;
-        let output_path = output_dir.join("atom.xml");
-        fs::write(&output_path, atom_xml)?;
+    fn atom_entry(
+        config: &SsgConfig,
+        post_meta: &PostMetadata,
+        post: &Post,
+        post_path: &Path,
+    ) -> String {
+        let rendered_content = Self::render_markdown_simple(&post.content);
+        let url = Self::post_url(config, post);
+
+        let summary = post_meta.summary().unwrap_or(&post.frontmatter.title);
+
+        let published = post.frontmatter.date.posted.to_rfc3339();
+        let entry_updated = git::resolve_modified(
+            &post.frontmatter.date,
+            Some(post_path),
+            config.build.git_lastmod,
+        )
+        .to_rfc3339();
+
+        // Metadata tags are already folded through tag aliases
+        let categories_xml: String = post_meta
+            .frontmatter
+            .tags
+            .iter()
+            .map(|tag| format!(r#"    <category term="{}" />"#, Self::escape_xml(tag)))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        format!(
+            r#"  <entry>
+    <title>{}</title>
+    <link href="{}" rel="alternate" type="text/html" />
+    <id>{}</id>
+    <published>{}</published>
+    <updated>{}</updated>
+    <author>
+      <name>{}</name>
+    </author>
+    <summary type="text">{}</summary>
+    <content type="html">{}</content>
+{}
+  </entry>"#,
+            Self::escape_xml(&post.frontmatter.title),
+            url,
+            url,
+            published,
+            entry_updated,
+            Self::escape_xml(&config.site.author),
+            Self::escape_xml(summary),
+            Self::cdata(&rendered_content),
+            categories_xml
+        )
+    }
 
+    /// Write `parts` separated by newlines, stopping at the first error
+    fn write_joined<W: Write>(
+        out: &mut W,
+        parts: impl IntoIterator<Item = Result<String>>,
+    ) -> Result<()> {
+        for (i, part) in parts.into_iter().enumerate() {
+            if i > 0 {
+                out.write_all(b"\n")?;
+            }
+            out.write_all(part?.as_bytes())?;
+        }
         Ok(())
     }
 
+    fn post_url(config: &SsgConfig, post: &Post) -> String {
+        format!(
+            "{}{}",
+            config.site.url,
+            permalink::post_url(
+                &config.build,
+                &post.category,
+                &post.slug,
+                &post.frontmatter.date.posted,
+                true,
+            )
+        )
+    }
+
     fn render_markdown_simple(markdown: &str) -> String {
         use pulldown_cmark::{html, Options, Parser};
         let options = Options::all();
@@ -464,6 +567,67 @@ impl FeedGenerator {
     }
 }
 
+/// MIME type for an audio file by extension, defaulting to MP3
+fn audio_mime_type(url: &str) -> &'static str {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    let ext = path.rsplit_once('.').map(|(_, ext)| ext.to_lowercase());
+    match ext.as_deref() {
+        Some("m4a") | Some("mp4") => "audio/mp4",
+        Some("aac") => "audio/aac",
+        Some("ogg") | Some("oga") => "audio/ogg",
+        Some("opus") => "audio/opus",
+        Some("wav") => "audio/wav",
+        Some("flac") => "audio/flac",
+        _ => "audio/mpeg",
+    }
+}
+
+/// Resolves absolute thumbnail URLs for `<media:thumbnail>`
+struct Thumbnails<'a> {
+    image_processor: Option<ImageProcessor>,
+    content_dir: &'a Path,
+}
+
+impl Thumbnails<'_> {
+    /// The post's cover (or OG) image as an absolute URL: the CDN thumbnail
+    /// when a CDN is configured, otherwise the image under `site.url`
+    fn url(&self, config: &SsgConfig, post: &PostMetadata) -> Option<String> {
+        let cover_src = post
+            .frontmatter
+            .cover_image
+            .as_ref()
+            .or(post.frontmatter.og_image.as_ref())?;
+
+        if cover_src.starts_with("http://") || cover_src.starts_with("https://") {
+            return Some(cover_src.clone());
+        }
+
+        let cdn_thumbnail = self
+            .image_processor
+            .as_ref()
+            .and_then(|processor| processor.post_thumbnail(post, self.content_dir));
+
+        cdn_thumbnail.or_else(|| {
+            cover_src
+                .starts_with('/')
+                .then(|| format!("{}{}", config.site.url, cover_src))
+        })
+    }
+}
+
+/// Channel-level fields shared by the site-wide and per-category RSS feeds
+struct RssChannel<'a> {
+    title: &'a str,
+    description: &'a str,
+    language: &'a str,
+    feed_url: &'a str,
+    link: &'a str,
+    last_build_date: &'a str,
+    /// WebSub hub; only the site-wide feed announces one
+    hub_url: Option<&'a str>,
+    stylesheet: Option<&'a str>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -475,6 +639,16 @@ mod tests {
         assert_eq!(FeedGenerator::escape_xml(input), expected);
     }
 
+    #[test]
+    fn test_audio_mime_type() {
+        assert_eq!(audio_mime_type("./episode-1.MP3"), "audio/mpeg");
+        assert_eq!(
+            audio_mime_type("https://cdn.example.com/e.m4a?v=2"),
+            "audio/mp4"
+        );
+        assert_eq!(audio_mime_type("episode.ogg"), "audio/ogg");
+    }
+
     #[test]
     fn test_cdata_keeps_content_verbatim() {
         assert_eq!(
@@ -490,4 +664,102 @@ mod tests {
             "<![CDATA[a]]]]><![CDATA[>b]]>"
         );
     }
+
+    fn items() -> Vec<String> {
+        vec![
+            "    <item>\n        <title>One</title>\n    </item>".to_string(),
+            "    <item>\n        <title>Two &amp; three</title>\n    </item>".to_string(),
+        ]
+    }
+
+    #[test]
+    fn test_streamed_rss_matches_string_built() {
+        let channel = RssChannel {
+            title: "Blog & co",
+            description: "Posts",
+            language: "ko-KR",
+            feed_url: "https://example.com/feed.xml",
+            link: "https://example.com",
+            last_build_date: "Mon, 01 Jan 2024 00:00:00 +0000",
+            hub_url: None,
+            stylesheet: None,
+        };
+        let expected = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<rss version="2.0" xmlns:content="http://purl.org/rss/1.0/modules/content/" xmlns:wfw="http://wellformedweb.org/CommentAPI/" xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:atom="http://www.w3.org/2005/Atom" xmlns:sy="http://purl.org/rss/1.0/modules/syndication/" xmlns:slash="http://purl.org/rss/1.0/modules/slash/" xmlns:media="http://search.yahoo.com/mrss/"
+>
+<channel>
+    <title>{}</title>
+    <description>{}</description>
+    <language>ko-KR</language>
+    <atom:link href="{}" rel="self" type="application/rss+xml" />
+    <link>{}</link>
+    <lastBuildDate>{}</lastBuildDate>
+    <sy:updatePeriod>hourly</sy:updatePeriod>
+    <sy:updateFrequency>1</sy:updateFrequency>
+{}
+</channel>
+</rss>
+"#,
+            FeedGenerator::escape_xml(channel.title),
+            FeedGenerator::escape_xml(channel.description),
+            channel.feed_url,
+            channel.link,
+            channel.last_build_date,
+            items().join("\n")
+        );
+
+        let mut out = Vec::new();
+        FeedGenerator::write_rss(&mut out, &channel, items().into_iter().map(Ok)).unwrap();
+
+        assert_eq!(String::from_utf8(out).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_streamed_atom_matches_string_built() {
+        let config = SsgConfig::default();
+        let updated = "2024-01-01T00:00:00+00:00";
+        let feed_url = format!("{}/atom.xml", config.site.url);
+        let expected = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom" xml:lang="{}">
+  <title>{}</title>
+  <subtitle>{}</subtitle>
+  <link href="{}" rel="self" type="application/atom+xml" />
+  <link href="{}" rel="alternate" type="text/html" />
+  <id>{}</id>
+  <updated>{}</updated>
+  <author>
+    <name>{}</name>
+  </author>
+{}
+</feed>
+"#,
+            config.site.language,
+            FeedGenerator::escape_xml(&config.site.title),
+            FeedGenerator::escape_xml(&config.site.description),
+            feed_url,
+            config.site.url,
+            config.site.url,
+            updated,
+            FeedGenerator::escape_xml(&config.site.author),
+            items().join("\n")
+        );
+
+        let mut out = Vec::new();
+        FeedGenerator::write_atom(&mut out, &config, updated, items().into_iter().map(Ok)).unwrap();
+
+        assert_eq!(String::from_utf8(out).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_stream_stops_at_failed_item() {
+        let mut out = Vec::new();
+        let items = vec![Ok("a".to_string()), Err(anyhow::anyhow!("bad post"))];
+
+        let err = FeedGenerator::write_joined(&mut out, items).unwrap_err();
+
+        assert_eq!(err.to_string(), "bad post");
+        assert_eq!(out, b"a\n");
+    }
 }
@@ -1,9 +1,16 @@
+use crate::assets;
 use crate::config::SsgConfig;
+use crate::minify::write_html;
+use crate::permalink;
+use crate::plugin::{PluginContext, PluginManager};
 use crate::slug;
 use crate::types::{Page, Post};
+use crate::verbosity::status;
 use anyhow::{Context, Result};
+use chrono::{DateTime, Datelike, NaiveDate, SecondsFormat};
 use serde_json::Value as JsonValue;
 use std::collections::HashMap;
+use std::fmt::Write as _;
 use std::fs;
 use std::path::{Path, PathBuf};
 use tera::{Context as TeraContext, Tera, Value};
@@ -11,13 +18,19 @@ use tera::{Context as TeraContext, Tera, Value};
 pub struct Generator {
     tera: Tera,
     config: SsgConfig,
+    plugins: PluginManager,
 }
 
 impl Generator {
     pub fn new(config: SsgConfig) -> Result<Self> {
-        let tera = create_tera_engine()?;
-
-        Ok(Self { tera, config })
+        let tera = create_tera_engine(&config)?;
+        let plugins = PluginManager::from_config(&config.build)?;
+
+        Ok(Self {
+            tera,
+            config,
+            plugins,
+        })
     }
 
     pub fn generate_post(
@@ -25,129 +38,155 @@ impl Generator {
         post: &Post,
         plugin_data: &HashMap<String, JsonValue>,
     ) -> Result<PathBuf> {
-        let html = post
-            .rendered_html
-            .as_ref()
-            .ok_or_else(|| anyhow::anyhow!("Post not rendered: {}", post.slug))?;
-
-        let mut context = TeraContext::new();
-        context.insert("post", post);
-        context.insert("slug", &post.slug);
-        context.insert("category", &post.category);
-        context.insert("content", html);
-        context.insert("config", &self.config.to_template_config());
-
-        for (key, value) in plugin_data {
-            context.insert(key, value);
-        }
+        self.render_post(post, plugin_data, false)
+    }
 
-        let output = self.tera.render("post.html", &context)?;
+    pub fn generate_post_partial(
+        &self,
+        post: &Post,
+        plugin_data: &HashMap<String, JsonValue>,
+    ) -> Result<PathBuf> {
+        self.render_post(post, plugin_data, true)
+    }
 
-        let output_path = self.get_post_path(post);
-        fs::create_dir_all(output_path.parent().unwrap())?;
-        fs::write(&output_path, output)?;
+    pub fn generate_page(
+        &self,
+        page: &Page,
+        plugin_data: &HashMap<String, JsonValue>,
+    ) -> Result<PathBuf> {
+        self.render_page(page, plugin_data, false)
+    }
 
-        Ok(output_path)
+    pub fn generate_page_partial(
+        &self,
+        page: &Page,
+        plugin_data: &HashMap<String, JsonValue>,
+    ) -> Result<PathBuf> {
+        self.render_page(page, plugin_data, true)
     }
 
-    pub fn generate_post_partial(
+    /// Full pages and SPA partials share this path, so a partial always
+    /// sees the same context as the document it stands in for
+    fn render_post(
         &self,
         post: &Post,
         plugin_data: &HashMap<String, JsonValue>,
+        partial: bool,
     ) -> Result<PathBuf> {
         let html = post
             .rendered_html
             .as_ref()
             .ok_or_else(|| anyhow::anyhow!("Post not rendered: {}", post.slug))?;
 
+        let url = self.post_url(post);
+        let plugin_ctx = self.plugin_context(&post.slug, &url, partial);
+
         let mut context = TeraContext::new();
         context.insert("post", post);
         context.insert("slug", &post.slug);
         context.insert("category", &post.category);
+        context.insert("post_url", &url);
+        context.insert("canonical_url", &self.post_canonical_url(post));
         context.insert("content", html);
         context.insert("config", &self.config.to_template_config());
+        insert_globals(&mut context, &self.config);
 
         for (key, value) in plugin_data {
             context.insert(key, value);
         }
+        for (key, value) in self.plugins.template_context(&plugin_ctx)? {
+            context.insert(key, &value);
+        }
 
-        let output = self.tera.render("partials/post.html", &context)?;
+        let template_name =
+            self.template_for("post", post.frontmatter.template.as_deref(), partial)?;
+        let output = self.tera.render(&template_name, &context)?;
+        let output = self.plugins.transform_html(&plugin_ctx, output)?;
 
-        let output_path = self.get_post_partial_path(post);
-        fs::create_dir_all(output_path.parent().unwrap())?;
-        fs::write(&output_path, output)?;
+        let output_path = if partial {
+            self.get_post_partial_path(post)
+        } else {
+            self.get_post_path(post)
+        };
+        write_html(&output_path, &output, &self.config.build)?;
 
         Ok(output_path)
     }
 
-    pub fn generate_page(
+    fn render_page(
         &self,
         page: &Page,
         plugin_data: &HashMap<String, JsonValue>,
+        partial: bool,
     ) -> Result<PathBuf> {
         let html = page
             .rendered_html
             .as_ref()
             .ok_or_else(|| anyhow::anyhow!("Page not rendered: {}", page.slug))?;
 
+        let url = self.page_url(page);
+        let plugin_ctx = self.plugin_context(&page.slug, &url, partial);
+
         let mut context = TeraContext::new();
         context.insert("page", &page.frontmatter);
         context.insert("slug", &page.slug);
+        context.insert("page_url", &url);
+        context.insert("canonical_url", &self.page_canonical_url(page));
         context.insert("content", html);
         context.insert("config", &self.config.to_template_config());
+        insert_globals(&mut context, &self.config);
 
         for (key, value) in plugin_data {
             context.insert(key, value);
         }
+        for (key, value) in self.plugins.template_context(&plugin_ctx)? {
+            context.insert(key, &value);
+        }
 
-        let template_name = page
-            .frontmatter
-            .template
-            .as_ref()
-            .map(|t| format!("page-{}.html", t))
-            .unwrap_or_else(|| "page.html".to_string());
+        let template_name =
+            self.template_for("page", page.frontmatter.template.as_deref(), partial)?;
         let output = self.tera.render(&template_name, &context)?;
+        let output = self.plugins.transform_html(&plugin_ctx, output)?;
 
-        let output_path = self.get_page_path(page);
-        fs::create_dir_all(output_path.parent().unwrap())?;
-        fs::write(&output_path, output)?;
+        let output_path = if partial {
+            self.get_page_partial_path(page)
+        } else {
+            self.get_page_path(page)
+        };
+        write_html(&output_path, &output, &self.config.build)?;
 
         Ok(output_path)
     }
 
-    pub fn generate_page_partial(
-        &self,
-        page: &Page,
-        plugin_data: &HashMap<String, JsonValue>,
-    ) -> Result<PathBuf> {
-        let html = page
-            .rendered_html
-            .as_ref()
-            .ok_or_else(|| anyhow::anyhow!("Page not rendered: {}", page.slug))?;
-
-        let mut context = TeraContext::new();
-        context.insert("page", &page.frontmatter);
-        context.insert("slug", &page.slug);
-        context.insert("content", html);
-        context.insert("config", &self.config.to_template_config());
-
-        for (key, value) in plugin_data {
-            context.insert(key, value);
+    /// Template for a post or page: `{kind}.html` by default, or the one named
+    /// by the `template:` frontmatter key, either in full (`post-wide.html`)
+    /// or by suffix (`wide`). A custom partial is optional and falls back to
+    /// the default one, but a missing full template is an error.
+    fn template_for(&self, kind: &str, custom: Option<&str>, partial: bool) -> Result<String> {
+        let prefix = if partial { "partials/" } else { "" };
+        let default = format!("{}{}.html", prefix, kind);
+        let Some(custom) = custom else {
+            return Ok(default);
+        };
+
+        let name = if custom.ends_with(".html") {
+            format!("{}{}", prefix, custom)
+        } else {
+            format!("{}{}-{}.html", prefix, kind, custom)
+        };
+        if self.tera.get_template_names().any(|n| n == name) {
+            Ok(name)
+        } else if partial {
+            Ok(default)
+        } else {
+            anyhow::bail!(
+                "Template '{}' not found for `template: {}` (expected {}/{})",
+                name,
+                custom,
+                self.config.build.template_dir,
+                name
+            )
         }
-
-        let template_name = page
-            .frontmatter
-            .template
-            .as_ref()
-            .map(|t| format!("partials/page-{}.html", t))
-            .unwrap_or_else(|| "partials/page.html".to_string());
-        let output = self.tera.render(&template_name, &context)?;
-
-        let output_path = self.get_page_partial_path(page);
-        fs::create_dir_all(output_path.parent().unwrap())?;
-        fs::write(&output_path, output)?;
-
-        Ok(output_path)
     }
 
     pub fn should_generate_partials(&self) -> bool {
@@ -158,42 +197,97 @@ impl Generator {
         &self.tera
     }
 
+    /// Site-relative post URL with raw segments, as templates have always used
+    fn post_url(&self, post: &Post) -> String {
+        permalink::post_url(
+            &self.config.build,
+            &post.category,
+            &post.slug,
+            &post.frontmatter.date.posted,
+            false,
+        )
+    }
+
+    fn post_canonical_url(&self, post: &Post) -> String {
+        permalink::canonical_url(
+            &self.config.site.url,
+            &permalink::post_url(
+                &self.config.build,
+                &post.category,
+                &post.slug,
+                &post.frontmatter.date.posted,
+                true,
+            ),
+        )
+    }
+
+    fn page_canonical_url(&self, page: &Page) -> String {
+        permalink::canonical_url(
+            &self.config.site.url,
+            &permalink::page_url(
+                &format!("/{}/", slug::encode_for_url(&page.slug)),
+                self.config.build.ugly_urls,
+            ),
+        )
+    }
+
+    fn plugin_context<'a>(
+        &'a self,
+        slug: &'a str,
+        url: &'a str,
+        partial: bool,
+    ) -> PluginContext<'a> {
+        PluginContext {
+            config: &self.config,
+            slug,
+            url,
+            partial,
+            settings: &JsonValue::Null,
+        }
+    }
+
+    fn page_url(&self, page: &Page) -> String {
+        permalink::page_url(&format!("/{}/", page.slug), self.config.build.ugly_urls)
+    }
+
     fn get_post_path(&self, post: &Post) -> PathBuf {
-        let category = self.maybe_encode(&post.category);
-        let slug = self.maybe_encode(&post.slug);
+        let dir = self.join_permalink(PathBuf::from(&self.config.build.output_dir), post);
 
-        PathBuf::from(&self.config.build.output_dir)
-            .join(category)
-            .join(slug)
-            .join("index.html")
+        permalink::page_file(dir, self.config.build.ugly_urls)
     }
 
     fn get_post_partial_path(&self, post: &Post) -> PathBuf {
-        let category = self.maybe_encode(&post.category);
-        let slug = self.maybe_encode(&post.slug);
+        let base =
+            PathBuf::from(&self.config.build.output_dir).join(&self.config.build.partial_dir);
 
-        PathBuf::from(&self.config.build.output_dir)
-            .join(&self.config.build.partial_dir)
-            .join(category)
-            .join(slug)
-            .join("index.html")
+        permalink::page_file(self.join_permalink(base, post), self.config.build.ugly_urls)
+    }
+
+    fn join_permalink(&self, base: PathBuf, post: &Post) -> PathBuf {
+        permalink::segments(
+            &self.config.build.permalink,
+            &post.category,
+            &post.slug,
+            &post.frontmatter.date.posted,
+        )
+        .iter()
+        .fold(base, |path, segment| path.join(self.maybe_encode(segment)))
     }
 
     fn get_page_path(&self, page: &Page) -> PathBuf {
         let slug = self.maybe_encode(&page.slug);
+        let dir = PathBuf::from(&self.config.build.output_dir).join(slug);
 
-        PathBuf::from(&self.config.build.output_dir)
-            .join(slug)
-            .join("index.html")
+        permalink::page_file(dir, self.config.build.ugly_urls)
     }
 
     fn get_page_partial_path(&self, page: &Page) -> PathBuf {
         let slug = self.maybe_encode(&page.slug);
-
-        PathBuf::from(&self.config.build.output_dir)
+        let dir = PathBuf::from(&self.config.build.output_dir)
             .join(&self.config.build.partial_dir)
-            .join(slug)
-            .join("index.html")
+            .join(slug);
+
+        permalink::page_file(dir, self.config.build.ugly_urls)
     }
 
     fn maybe_encode(&self, s: &str) -> String {
@@ -210,7 +304,27 @@ impl Generator {
         let src = Path::new("static");
         if src.exists() {
             Self::copy_dir_all(src, dst)?;
-            println!("📦 Copied static assets");
+            self.copy_fingerprinted_assets(src, dst)?;
+            status!("📦 Copied static assets");
+        }
+
+        Ok(())
+    }
+
+    /// Originals stay in place for hand-written references; the hashed copies
+    /// are what `config.assets.static` points at
+    fn copy_fingerprinted_assets(&self, src: &Path, dst: &Path) -> Result<()> {
+        if !self.config.build.fingerprint_assets {
+            return Ok(());
+        }
+        let Some(fingerprints) = self.config.assets.get(assets::STATIC_ASSETS_KEY) else {
+            return Ok(());
+        };
+
+        for (logical, url) in fingerprints {
+            let target = dst.join(url.trim_start_matches('/'));
+            fs::copy(src.join(logical), &target)
+                .with_context(|| format!("Failed to copy {} to {}", logical, target.display()))?;
         }
 
         Ok(())
@@ -238,7 +352,7 @@ impl Generator {
 
             if let Some(ext) = path.extension() {
                 let ext_str = ext.to_string_lossy().to_lowercase();
-                if ext_str == "md" {
+                if ext_str == "md" || ext_str == "markdown" {
                     continue;
                 }
 
@@ -251,7 +365,16 @@ impl Generator {
 
                 let is_document = matches!(ext_str.as_str(), "pdf" | "zip" | "tar" | "gz");
 
-                if is_image || is_media || is_document {
+                let is_font = matches!(ext_str.as_str(), "woff" | "woff2" | "ttf" | "otf");
+
+                let is_data = matches!(ext_str.as_str(), "json" | "txt" | "xml");
+
+                let is_configured =
+                    self.config.build.copy_extensions.iter().any(|configured| {
+                        configured.trim_start_matches('.').to_lowercase() == ext_str
+                    });
+
+                if is_image || is_media || is_document || is_font || is_data || is_configured {
                     let relative_path = path.strip_prefix(content_dir)?;
                     let encoded_path = Self::encode_asset_path(relative_path);
                     let full_output_path = output_dir.join(&encoded_path);
@@ -275,7 +398,7 @@ impl Generator {
         }
 
         if copied_count > 0 {
-            println!("📦 Copied {} asset(s) from content directory", copied_count);
+            status!("📦 Copied {} asset(s) from content directory", copied_count);
         }
 
         Ok(())
@@ -305,8 +428,19 @@ impl Generator {
     }
 }
 
-fn create_tera_engine() -> Result<Tera> {
-    let template_dir = Path::new("templates");
+/// Insert the values every template can use regardless of page type:
+/// `data` files and the build timestamp
+pub fn insert_globals(context: &mut TeraContext, config: &SsgConfig) {
+    context.insert("data", &config.data);
+    context.insert(
+        "build_time",
+        &config.build_time.to_rfc3339_opts(SecondsFormat::Secs, true),
+    );
+    context.insert("build_year", &config.build_time.year());
+}
+
+fn create_tera_engine(config: &SsgConfig) -> Result<Tera> {
+    let template_dir = Path::new(&config.build.template_dir);
 
     if !template_dir.exists() {
         anyhow::bail!(
@@ -320,6 +454,31 @@ fn create_tera_engine() -> Result<Tera> {
         .context(format!("Failed to load templates from {:?}", template_dir))?;
 
     tera.register_filter("urldecode", urldecode_filter);
+    tera.register_filter(
+        "date_format",
+        DateFormat {
+            default_format: config.build.date_format.clone(),
+        },
+    );
+    tera.register_function(
+        "url_for",
+        permalink::UrlFor {
+            ugly_urls: config.build.ugly_urls,
+        },
+    );
+    tera.register_function(
+        "url",
+        permalink::AbsoluteUrl {
+            site_url: config.site.url.clone(),
+            ugly_urls: config.build.ugly_urls,
+        },
+    );
+    tera.register_function(
+        "asset",
+        assets::Asset {
+            assets: config.assets.clone(),
+        },
+    );
 
     Ok(tera)
 }
@@ -329,3 +488,252 @@ fn urldecode_filter(value: &Value, _args: &HashMap<String, Value>) -> tera::Resu
     let decoded = slug::decode_from_url(&s);
     Ok(Value::String(decoded))
 }
+
+/// Tera filter formatting a stored datetime for display, as
+/// `{{ post.frontmatter.date.posted | date_format(format="%Y.%m.%d") }}`.
+/// Without `format` it uses `build.date_format`. Values that aren't RFC3339
+/// datetimes or `YYYY-MM-DD` dates are passed through unchanged rather than
+/// failing the page.
+pub struct DateFormat {
+    pub default_format: String,
+}
+
+impl tera::Filter for DateFormat {
+    fn filter(&self, value: &Value, args: &HashMap<String, Value>) -> tera::Result<Value> {
+        let format = match args.get("format") {
+            Some(format) => format
+                .as_str()
+                .ok_or_else(|| tera::Error::msg("date_format's `format` must be a string"))?,
+            None => &self.default_format,
+        };
+
+        let Some(raw) = value.as_str() else {
+            return Ok(value.clone());
+        };
+        let parsed = DateTime::parse_from_rfc3339(raw)
+            .map(|dt| dt.naive_local())
+            .or_else(|_| {
+                NaiveDate::parse_from_str(raw, "%Y-%m-%d")
+                    .map(|date| date.and_hms_opt(0, 0, 0).unwrap_or_default())
+            });
+        let Ok(datetime) = parsed else {
+            return Ok(value.clone());
+        };
+
+        let mut formatted = String::new();
+        write!(formatted, "{}", datetime.format(format))
+            .map_err(|_| tera::Error::msg(format!("Invalid date format '{}'", format)))?;
+        Ok(Value::String(formatted))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::PluginConfig;
+    use crate::plugin::Plugin;
+    use crate::types::{Frontmatter, PostDate};
+    use chrono::{TimeZone, Utc};
+    use tempfile::TempDir;
+
+    fn post(posted: DateTime<Utc>) -> Post {
+        Post {
+            slug: "hello".to_string(),
+            category: "dev".to_string(),
+            frontmatter: Frontmatter {
+                title: "Hello".to_string(),
+                date: PostDate::new(posted),
+                tags: vec![],
+                cover_image: None,
+                og_image: None,
+                description: None,
+                display_ad: false,
+                hidden: false,
+                unlisted: false,
+                template: None,
+                comments: true,
+                audio: None,
+                lang: None,
+                translations: Default::default(),
+            },
+            content: String::new(),
+            rendered_html: Some("<p>Hi</p>".to_string()),
+        }
+    }
+
+    struct MarkerPlugin;
+
+    impl Plugin for MarkerPlugin {
+        fn name(&self) -> &str {
+            "marker"
+        }
+
+        fn transform_html(&self, ctx: &PluginContext, html: &str) -> Result<String> {
+            Ok(format!("{}<!-- marker:{} -->", html, ctx.slug))
+        }
+    }
+
+    #[test]
+    fn test_plugins_transform_post_output() {
+        let temp = TempDir::new().unwrap();
+        let mut config = SsgConfig::default();
+        config.build.output_dir = temp.path().to_string_lossy().into_owned();
+
+        let mut tera = Tera::default();
+        tera.add_raw_template("post.html", "<main>{{ content | safe }}</main>")
+            .unwrap();
+        let generator = Generator {
+            tera,
+            config,
+            plugins: PluginManager::select(
+                vec![Box::new(MarkerPlugin)],
+                &[PluginConfig {
+                    name: "marker".to_string(),
+                    settings: JsonValue::Null,
+                }],
+            )
+            .unwrap(),
+        };
+
+        let post = post(Utc::now());
+
+        let output_path = generator.generate_post(&post, &HashMap::new()).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(output_path).unwrap(),
+            "<main><p>Hi</p></main><!-- marker:hello -->"
+        );
+    }
+
+    fn template_generator(output_dir: &Path) -> Generator {
+        let mut config = SsgConfig::default();
+        config.build.output_dir = output_dir.to_string_lossy().into_owned();
+        let plugins = PluginManager::from_config(&config.build).unwrap();
+
+        let mut tera = Tera::default();
+        tera.add_raw_templates(vec![
+            ("post.html", "default:{{ content | safe }}"),
+            ("post-wide.html", "wide:{{ content | safe }}"),
+            ("partials/post.html", "partial:{{ content | safe }}"),
+        ])
+        .unwrap();
+        Generator {
+            tera,
+            config,
+            plugins,
+        }
+    }
+
+    #[test]
+    fn test_post_template_frontmatter_selects_alternate_template() {
+        let temp = TempDir::new().unwrap();
+        let generator = template_generator(temp.path());
+
+        for template in ["wide", "post-wide.html"] {
+            let mut post = post(Utc::now());
+            post.frontmatter.template = Some(template.to_string());
+
+            let output_path = generator.generate_post(&post, &HashMap::new()).unwrap();
+            assert_eq!(fs::read_to_string(&output_path).unwrap(), "wide:<p>Hi</p>");
+
+            // No partials/post-wide.html, so the default partial is used
+            let partial_path = generator
+                .generate_post_partial(&post, &HashMap::new())
+                .unwrap();
+            assert_eq!(
+                fs::read_to_string(partial_path).unwrap(),
+                "partial:<p>Hi</p>"
+            );
+        }
+    }
+
+    #[test]
+    fn test_post_without_template_uses_default() {
+        let temp = TempDir::new().unwrap();
+        let generator = template_generator(temp.path());
+
+        let output_path = generator
+            .generate_post(&post(Utc::now()), &HashMap::new())
+            .unwrap();
+
+        assert_eq!(
+            fs::read_to_string(output_path).unwrap(),
+            "default:<p>Hi</p>"
+        );
+    }
+
+    #[test]
+    fn test_missing_post_template_is_an_error() {
+        let temp = TempDir::new().unwrap();
+        let generator = template_generator(temp.path());
+        let mut post = post(Utc::now());
+        post.frontmatter.template = Some("narrow".to_string());
+
+        let err = generator.generate_post(&post, &HashMap::new()).unwrap_err();
+
+        assert!(
+            err.to_string().contains("'post-narrow.html'"),
+            "got: {}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_date_format_filter_renders_post_date() {
+        let temp = TempDir::new().unwrap();
+        let mut config = SsgConfig::default();
+        config.build.output_dir = temp.path().to_string_lossy().into_owned();
+        let plugins = PluginManager::from_config(&config.build).unwrap();
+
+        let mut tera = Tera::default();
+        tera.register_filter(
+            "date_format",
+            DateFormat {
+                default_format: "%Y-%m-%d".to_string(),
+            },
+        );
+        tera.add_raw_template(
+            "post.html",
+            "{{ post.frontmatter.date.posted | date_format }}|\
+             {{ post.frontmatter.date.posted | date_format(format=\"%Y.%m.%d %H:%M\") }}",
+        )
+        .unwrap();
+        let generator = Generator {
+            tera,
+            config,
+            plugins,
+        };
+
+        let post = post(Utc.with_ymd_and_hms(2024, 3, 7, 9, 30, 0).unwrap());
+        let output_path = generator.generate_post(&post, &HashMap::new()).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(output_path).unwrap(),
+            "2024-03-07|2024.03.07 09:30"
+        );
+    }
+
+    #[test]
+    fn test_date_format_filter_passes_through_invalid_input() {
+        let filter = DateFormat {
+            default_format: "%d/%m/%Y".to_string(),
+        };
+        let args = HashMap::new();
+
+        assert_eq!(
+            tera::Filter::filter(&filter, &Value::from("2024-03-07"), &args).unwrap(),
+            Value::from("07/03/2024")
+        );
+        assert_eq!(
+            tera::Filter::filter(&filter, &Value::from("not a date"), &args).unwrap(),
+            Value::from("not a date")
+        );
+        assert_eq!(
+            tera::Filter::filter(&filter, &Value::Null, &args).unwrap(),
+            Value::Null
+        );
+
+        let bad_format = HashMap::from([("format".to_string(), Value::from("%Q"))]);
+        assert!(tera::Filter::filter(&filter, &Value::from("2024-03-07"), &bad_format).is_err());
+    }
+}
@@ -0,0 +1,83 @@
+use crate::types::PostDate;
+use chrono::{DateTime, Utc};
+use std::path::Path;
+use std::process::Command;
+
+/// Last commit timestamp touching `path` (`git log -1 --format=%cI`). `None`
+/// when git is missing, the path is outside a repository, or the file has
+/// never been committed. Git runs from the file's directory, so the content
+/// may live in a different repository than the working directory.
+pub fn last_commit_date(path: &Path) -> Option<DateTime<Utc>> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(path.parent().unwrap_or(Path::new("")))
+        .args(["log", "-1", "--format=%cI", "--"])
+        .arg(path.file_name()?)
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    let stdout = String::from_utf8(output.stdout).ok()?;
+    DateTime::parse_from_rfc3339(stdout.trim())
+        .ok()
+        .map(|date| date.with_timezone(&Utc))
+}
+
+/// When a post was last modified: frontmatter `date.modified` wins; otherwise
+/// (if `use_git` is set) the last commit touching `source`; otherwise `posted`.
+pub fn resolve_modified(date: &PostDate, source: Option<&Path>, use_git: bool) -> DateTime<Utc> {
+    if let Some(modified) = date.modified {
+        return modified;
+    }
+
+    source
+        .filter(|_| use_git)
+        .and_then(last_commit_date)
+        .unwrap_or(date.posted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_frontmatter_modified_wins() {
+        let posted = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+        let modified = Utc.with_ymd_and_hms(2025, 2, 1, 0, 0, 0).unwrap();
+        let date = PostDate {
+            posted,
+            modified: Some(modified),
+        };
+
+        assert_eq!(resolve_modified(&date, None, true), modified);
+    }
+
+    #[test]
+    fn test_untracked_file_falls_back_to_posted() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("post.md");
+        std::fs::write(&path, "---\ntitle: x\n---\n").unwrap();
+
+        let posted = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+        let date = PostDate::new(posted);
+
+        assert!(last_commit_date(&path).is_none());
+        assert_eq!(resolve_modified(&date, Some(&path), true), posted);
+    }
+
+    #[test]
+    fn test_git_disabled_uses_posted() {
+        let posted = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+        let date = PostDate::new(posted);
+
+        assert_eq!(
+            resolve_modified(&date, Some(Path::new("Cargo.toml")), false),
+            posted
+        );
+    }
+}
@@ -1,17 +1,18 @@
+use crate::cache::ImageDimensionCache;
+use crate::config::{ImagesConfig, SsgConfig};
+use crate::metadata::PostMetadata;
 use anyhow::Result;
 use serde::Serialize;
 use std::path::Path;
+use std::sync::{Arc, Mutex};
 use unicode_normalization::UnicodeNormalization;
 
-/// Standard image sizes for responsive images
-pub const IMAGE_SIZES: [u32; 4] = [480, 600, 860, 1180];
-
-/// Thumbnail size for post cards and navigation
-pub const THUMBNAIL_SIZE: u32 = 500;
-
 /// LQIP (Low-Quality Image Placeholder) size
 pub const LQIP_SIZE: u32 = 10;
 
+/// Images are downscaled to this size before computing the Blurhash
+const BLURHASH_SAMPLE_SIZE: u32 = 32;
+
 /// Individual image source with URL and width
 #[derive(Debug, Clone, Serialize)]
 pub struct ImageSource {
@@ -30,28 +31,78 @@ pub struct ImageMetadata {
     pub src: String,
     /// LQIP placeholder URL (w10)
     pub lqip: String,
+    /// Blurhash of the image, when `build.images.blurhash` is enabled
+    pub lqip_blurhash: Option<String>,
+    /// Average color as `#rrggbb`, when `build.images.blurhash` is enabled
+    pub placeholder_color: Option<String>,
     /// Sources for original format (ascending by width)
     pub sources: Vec<ImageSource>,
-    /// Sources for WebP format (ascending by width)
-    pub webp_sources: Vec<ImageSource>,
+    /// Sources for each configured alternate format, AVIF before WebP
+    pub format_sources: Vec<FormatSources>,
+}
+
+/// Responsive sources for one alternate format (e.g. WebP)
+#[derive(Debug, Clone, Serialize)]
+pub struct FormatSources {
+    /// Format extension appended to the URL (e.g. "webp")
+    pub format: String,
+    /// MIME type for `<source type="...">`
+    pub mime_type: String,
+    /// Sources ascending by width
+    pub sources: Vec<ImageSource>,
+}
+
+/// Thumbnail URL for one alternate format
+#[derive(Debug, Clone, Serialize)]
+pub struct ThumbnailSource {
+    pub format: String,
+    pub mime_type: String,
+    pub src: String,
 }
 
-/// Thumbnail metadata for post cards and navigation (500px)
+/// Thumbnail metadata for post cards and navigation
 #[derive(Debug, Clone, Serialize)]
 pub struct ThumbnailMetadata {
-    /// 500px original format URL
+    /// Thumbnail URL in the original format
     pub src: String,
-    /// 500px WebP format URL
-    pub webp_src: String,
+    /// Thumbnail URLs for each configured alternate format, AVIF before WebP
+    pub format_sources: Vec<ThumbnailSource>,
 }
 
 pub struct ImageProcessor {
     cdn_url: Option<String>,
+    options: ImagesConfig,
+    dimension_cache: Option<Arc<Mutex<ImageDimensionCache>>>,
 }
 
 impl ImageProcessor {
     pub fn new(cdn_url: Option<String>) -> Self {
-        Self { cdn_url }
+        Self {
+            cdn_url,
+            options: ImagesConfig::default(),
+            dimension_cache: None,
+        }
+    }
+
+    /// Look up image dimensions in a shared cache before decoding
+    pub fn with_dimension_cache(mut self, cache: Arc<Mutex<ImageDimensionCache>>) -> Self {
+        self.dimension_cache = Some(cache);
+        self
+    }
+
+    /// Use configured sizes and formats instead of the defaults
+    pub fn with_options(mut self, options: ImagesConfig) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Create a processor from the site config, or `None` without a CDN
+    pub fn from_config(config: &SsgConfig) -> Option<Self> {
+        config
+            .site
+            .cdn_url
+            .as_ref()
+            .map(|url| Self::new(Some(url.clone())).with_options(config.build.images.clone()))
     }
 
     /// Process an image and generate metadata with separate sources
@@ -82,39 +133,77 @@ impl ImageProcessor {
         let (filename, ext) = self.parse_image_path(src);
         let is_svg = ext.eq_ignore_ascii_case("svg");
 
-        let (sources, webp_sources, lqip) = if is_svg {
-            let src_url = self.build_cdn_url(cdn_url, base_path, &filename, None, &ext, false);
+        // Custom URL templates always need a width, so the "full-size" URLs
+        // request the original width explicitly
+        let full_size = self.options.url_template.as_ref().map(|_| width);
+
+        let (sources, format_sources, lqip) = if is_svg {
+            let src_url = self.build_cdn_url(cdn_url, base_path, &filename, None, &ext, None);
             (vec![], vec![], src_url)
         } else {
-            let sizes: Vec<u32> = IMAGE_SIZES
+            let mut sizes: Vec<u32> = self
+                .options
+                .sizes
                 .iter()
                 .copied()
                 .filter(|&s| s <= width)
                 .collect();
-
-            let sources = self.generate_sources(cdn_url, base_path, &filename, &ext, &sizes, false);
-            let webp_sources =
-                self.generate_sources(cdn_url, base_path, &filename, &ext, &sizes, true);
+            sizes.sort_unstable();
+            sizes.dedup();
+
+            let sources =
+                self.generate_sources(base_path, &filename, &ext, &sizes, full_size, None);
+            let format_sources = self
+                .formats()
+                .into_iter()
+                .map(|format| FormatSources {
+                    format: format.to_string(),
+                    mime_type: mime_type(format),
+                    sources: self.generate_sources(
+                        base_path,
+                        &filename,
+                        &ext,
+                        &sizes,
+                        full_size,
+                        Some(format),
+                    ),
+                })
+                .collect();
             let lqip =
-                self.build_cdn_url(cdn_url, base_path, &filename, Some(LQIP_SIZE), &ext, false);
+                self.build_cdn_url(cdn_url, base_path, &filename, Some(LQIP_SIZE), &ext, None);
 
-            (sources, webp_sources, lqip)
+            (sources, format_sources, lqip)
         };
 
         // Full-size fallback (original)
-        let src_url = self.build_cdn_url(cdn_url, base_path, &filename, None, &ext, false);
+        let src_url = if is_svg {
+            lqip.clone()
+        } else {
+            self.build_cdn_url(cdn_url, base_path, &filename, full_size, &ext, None)
+        };
+
+        let (lqip_blurhash, placeholder_color) = if self.options.blurhash && !is_svg {
+            match self.placeholder(&image_path) {
+                Some((hash, color)) => (Some(hash), Some(color)),
+                None => (None, None),
+            }
+        } else {
+            (None, None)
+        };
 
         Ok(Some(ImageMetadata {
             width,
             height,
             src: src_url,
             lqip,
+            lqip_blurhash,
+            placeholder_color,
             sources,
-            webp_sources,
+            format_sources,
         }))
     }
 
-    /// Process an image and generate thumbnail metadata (500px)
+    /// Process an image and generate thumbnail metadata
     pub fn process_thumbnail(
         &self,
         src: &str,
@@ -140,46 +229,108 @@ impl ImageProcessor {
         let (filename, ext) = self.parse_image_path(src);
         let is_svg = ext.eq_ignore_ascii_case("svg");
 
-        // SVG files don't need resizing or WebP conversion - use original URL
-        let (src_url, webp_src) = if is_svg {
-            let url = self.build_cdn_url(cdn_url, base_path, &filename, None, &ext, false);
-            (url.clone(), url)
-        } else {
-            let src_url = self.build_cdn_url(
-                cdn_url,
-                base_path,
-                &filename,
-                Some(THUMBNAIL_SIZE),
-                &ext,
-                false,
-            );
-            let webp_src = self.build_cdn_url(
-                cdn_url,
-                base_path,
-                &filename,
-                Some(THUMBNAIL_SIZE),
-                &ext,
-                true,
-            );
-            (src_url, webp_src)
-        };
+        // SVG files don't need resizing or format conversion - use original URL
+        if is_svg {
+            let url = self.build_cdn_url(cdn_url, base_path, &filename, None, &ext, None);
+            return Ok(Some(ThumbnailMetadata {
+                src: url,
+                format_sources: vec![],
+            }));
+        }
+
+        let size = Some(self.options.thumbnail_size);
+        let src_url = self.build_cdn_url(cdn_url, base_path, &filename, size, &ext, None);
+        let format_sources = self
+            .formats()
+            .into_iter()
+            .map(|format| ThumbnailSource {
+                format: format.to_string(),
+                mime_type: mime_type(format),
+                src: self.build_cdn_url(cdn_url, base_path, &filename, size, &ext, Some(format)),
+            })
+            .collect();
 
         Ok(Some(ThumbnailMetadata {
             src: src_url,
-            webp_src,
+            format_sources,
         }))
     }
 
+    /// Thumbnail URL of a post's cover (or OG) image. Metadata holds resolved
+    /// paths (/category/...), so they're made relative to the category
+    /// directory under `content_dir` first.
+    pub fn post_thumbnail(&self, post: &PostMetadata, content_dir: &Path) -> Option<String> {
+        let cover_src = post
+            .frontmatter
+            .cover_image
+            .as_ref()
+            .or(post.frontmatter.og_image.as_ref())?;
+
+        let relative_src = category_relative_src(cover_src, &post.category);
+
+        self.process_thumbnail(
+            &relative_src,
+            &content_dir.join(&post.category),
+            &post.category,
+        )
+        .ok()
+        .flatten()
+        .map(|thumbnail| thumbnail.src)
+    }
+
+    /// Configured alternate formats, ordered so browsers pick the smallest
+    /// supported one first: AVIF, then WebP, then anything else as configured.
+    fn formats(&self) -> Vec<&str> {
+        let mut formats: Vec<&str> = Vec::new();
+        for format in &self.options.formats {
+            let format = format.trim_start_matches('.');
+            if !formats.iter().any(|f| f.eq_ignore_ascii_case(format)) {
+                formats.push(format);
+            }
+        }
+        formats.sort_by_key(|format| format_priority(format));
+        formats
+    }
+
     fn resolve_local_path(&self, src: &str, content_dir: &Path) -> std::path::PathBuf {
         let src = src.trim_start_matches("./");
         content_dir.join(src)
     }
 
     fn get_image_dimensions(&self, path: &Path) -> Result<(u32, u32)> {
+        let Some(cache) = &self.dimension_cache else {
+            return Ok(image::image_dimensions(path)?);
+        };
+
+        if let Some(dimensions) = cache.lock().unwrap().get(path) {
+            return Ok(dimensions);
+        }
+
+        // Decode without holding the lock so other workers aren't blocked
         let dimensions = image::image_dimensions(path)?;
+        cache.lock().unwrap().insert(path, dimensions);
         Ok(dimensions)
     }
 
+    /// Blurhash and average color for `path`, from the dimension cache when
+    /// the file is unchanged
+    fn placeholder(&self, path: &Path) -> Option<(String, String)> {
+        let Some(cache) = &self.dimension_cache else {
+            return compute_placeholder(path);
+        };
+
+        if let Some(placeholder) = cache.lock().unwrap().get_placeholder(path) {
+            return Some(placeholder);
+        }
+
+        let placeholder = compute_placeholder(path)?;
+        cache
+            .lock()
+            .unwrap()
+            .insert_placeholder(path, placeholder.clone());
+        Some(placeholder)
+    }
+
     fn parse_image_path(&self, src: &str) -> (String, String) {
         let src = src.trim_start_matches("./");
 
@@ -193,7 +344,10 @@ impl ImageProcessor {
     }
 
     /// Build CDN URL with images/ prefix and base_path
-    /// Format: {cdn_url}/images/{base_path}/{filename}.w{size}.{ext}[.webp]
+    /// Format: {cdn_url}/images/{base_path}/{filename}.w{size}.{ext}[.{format}]
+    ///
+    /// With `url_template` configured, the template is expanded instead;
+    /// requests without a size (SVGs) link to `{cdn}/{path}` untransformed.
     fn build_cdn_url(
         &self,
         cdn_url: &str,
@@ -201,39 +355,54 @@ impl ImageProcessor {
         filename: &str,
         size: Option<u32>,
         ext: &str,
-        is_webp: bool,
+        format: Option<&str>,
     ) -> String {
-        let size_suffix = size.map(|s| format!(".w{}", s)).unwrap_or_default();
-        let webp_suffix = if is_webp { ".webp" } else { "" };
         // Normalize to NFC for consistent Unicode representation
         let base_path_nfc: String = base_path.nfc().collect();
         let filename_nfc: String = filename.nfc().collect();
+
+        if let Some(template) = &self.options.url_template {
+            let path = format!("{}/{}.{}", base_path_nfc, filename_nfc, ext);
+            return match size {
+                Some(width) => template
+                    .replace("{cdn}", cdn_url)
+                    .replace("{path}", &path)
+                    .replace("{width}", &width.to_string())
+                    .replace("{format}", format.unwrap_or(ext)),
+                None => format!("{}/{}", cdn_url, path),
+            };
+        }
+
+        let size_suffix = size.map(|s| format!(".w{}", s)).unwrap_or_default();
+        let format_suffix = format.map(|f| format!(".{}", f)).unwrap_or_default();
         format!(
             "{}/images/{}/{}{}.{}{}",
-            cdn_url, base_path_nfc, filename_nfc, size_suffix, ext, webp_suffix
+            cdn_url, base_path_nfc, filename_nfc, size_suffix, ext, format_suffix
         )
     }
 
     fn generate_sources(
         &self,
-        cdn_url: &str,
         base_path: &str,
         filename: &str,
         ext: &str,
         sizes: &[u32],
-        is_webp: bool,
+        full_size: Option<u32>,
+        format: Option<&str>,
     ) -> Vec<ImageSource> {
+        let cdn_url = self.cdn_url.as_deref().unwrap_or_default();
+        let cdn_url = cdn_url.trim_end_matches('/');
         let mut sources: Vec<ImageSource> = sizes
             .iter()
             .map(|&size| ImageSource {
-                url: self.build_cdn_url(cdn_url, base_path, filename, Some(size), ext, is_webp),
+                url: self.build_cdn_url(cdn_url, base_path, filename, Some(size), ext, format),
                 width: size,
             })
             .collect();
 
         // Add full-size fallback at the end
         sources.push(ImageSource {
-            url: self.build_cdn_url(cdn_url, base_path, filename, None, ext, is_webp),
+            url: self.build_cdn_url(cdn_url, base_path, filename, full_size, ext, format),
             width: u32::MAX, // Indicates fallback (no media query)
         });
 
@@ -241,10 +410,81 @@ impl ImageProcessor {
     }
 }
 
+/// A cover image path relative to its `category` directory, which is what
+/// `process_image` and `process_thumbnail` resolve against. Site-absolute
+/// paths lose a leading `/<category>/`, other absolute paths are taken from
+/// the category directory too, and relative paths pass through.
+pub fn category_relative_src(src: &str, category: &str) -> String {
+    let src = src.trim();
+    match src.strip_prefix('/') {
+        Some(absolute) => {
+            let rest = absolute
+                .strip_prefix(category)
+                .and_then(|rest| rest.strip_prefix('/'))
+                .unwrap_or(absolute);
+            format!("./{}", rest)
+        }
+        None => src.to_string(),
+    }
+}
+
+/// Blurhash and average `#rrggbb` color of a local image
+fn compute_placeholder(path: &Path) -> Option<(String, String)> {
+    let sample = image::open(path)
+        .ok()?
+        .thumbnail(BLURHASH_SAMPLE_SIZE, BLURHASH_SAMPLE_SIZE)
+        .to_rgba8();
+
+    let hash = blurhash::encode(4, 3, sample.width(), sample.height(), sample.as_raw()).ok()?;
+
+    let pixel_count = u64::from(sample.width()) * u64::from(sample.height());
+    let mut totals = [0u64; 3];
+    for pixel in sample.pixels() {
+        for (total, &channel) in totals.iter_mut().zip(&pixel.0[..3]) {
+            *total += u64::from(channel);
+        }
+    }
+    let [r, g, b] = totals.map(|total| total / pixel_count.max(1));
+    let color = format!("#{:02x}{:02x}{:02x}", r, g, b);
+
+    Some((hash, color))
+}
+
+fn format_priority(format: &str) -> u8 {
+    match format.to_ascii_lowercase().as_str() {
+        "avif" => 0,
+        "webp" => 1,
+        _ => 2,
+    }
+}
+
+fn mime_type(format: &str) -> String {
+    match format.to_ascii_lowercase().as_str() {
+        "jpg" => "image/jpeg".to_string(),
+        "svg" => "image/svg+xml".to_string(),
+        other => format!("image/{}", other),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_category_relative_src() {
+        assert_eq!(category_relative_src("./cover.png", "dev"), "./cover.png");
+        assert_eq!(category_relative_src("cover.png", "dev"), "cover.png");
+        assert_eq!(
+            category_relative_src("/dev/cover.png", "dev"),
+            "./cover.png"
+        );
+        assert_eq!(
+            category_relative_src("/devlog/cover.png", "dev"),
+            "./devlog/cover.png"
+        );
+        assert_eq!(category_relative_src("/cover.png", "dev"), "./cover.png");
+    }
+
     #[test]
     fn test_parse_image_path() {
         let processor = ImageProcessor::new(Some("https://cdn.example.com".to_string()));
@@ -269,7 +509,7 @@ mod tests {
             "my-post/images/photo",
             Some(480),
             "jpg",
-            false,
+            None,
         );
         assert_eq!(
             url,
@@ -283,7 +523,7 @@ mod tests {
             "my-post/photo",
             None,
             "png",
-            false,
+            None,
         );
         assert_eq!(url, "https://cdn.example.com/images/dev/my-post/photo.png");
 
@@ -294,7 +534,7 @@ mod tests {
             "my-post/photo",
             Some(600),
             "png",
-            true,
+            Some("webp"),
         );
         assert_eq!(
             url,
@@ -306,14 +546,8 @@ mod tests {
     fn test_generate_sources() {
         let processor = ImageProcessor::new(Some("https://cdn.example.com".to_string()));
 
-        let sources = processor.generate_sources(
-            "https://cdn.example.com",
-            "dev",
-            "post/photo",
-            "jpg",
-            &[480, 600],
-            false,
-        );
+        let sources =
+            processor.generate_sources("dev", "post/photo", "jpg", &[480, 600], None, None);
 
         assert_eq!(sources.len(), 3); // 2 sizes + 1 fallback
         assert_eq!(sources[0].width, 480);
@@ -353,10 +587,297 @@ mod tests {
             "my-post/icon",
             None,
             "svg",
-            false,
+            None,
         );
         assert_eq!(url, "https://cdn.example.com/images/dev/my-post/icon.svg");
         assert!(!url.contains(".webp"));
         assert!(!url.contains(".w"));
     }
+
+    fn write_test_image(dir: &Path, name: &str, width: u32, height: u32) {
+        image::RgbImage::new(width, height)
+            .save(dir.join(name))
+            .unwrap();
+    }
+
+    #[test]
+    fn test_process_image_uses_configured_sizes_and_formats() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        write_test_image(temp_dir.path(), "photo.png", 1000, 500);
+
+        let processor = ImageProcessor::new(Some("https://cdn.example.com".to_string()))
+            .with_options(ImagesConfig {
+                sizes: vec![800, 320, 1600],
+                thumbnail_size: 300,
+                formats: vec!["avif".to_string(), "webp".to_string()],
+                ..Default::default()
+            });
+
+        let metadata = processor
+            .process_image("./photo.png", temp_dir.path(), "dev")
+            .unwrap()
+            .unwrap();
+
+        // Sizes wider than the original are skipped, the rest ascend
+        let widths: Vec<u32> = metadata.sources.iter().map(|s| s.width).collect();
+        assert_eq!(widths, vec![320, 800, u32::MAX]);
+
+        let formats: Vec<&str> = metadata
+            .format_sources
+            .iter()
+            .map(|f| f.format.as_str())
+            .collect();
+        assert_eq!(formats, vec!["avif", "webp"]);
+        assert_eq!(metadata.format_sources[0].mime_type, "image/avif");
+        assert_eq!(
+            metadata.format_sources[1].sources[0].url,
+            "https://cdn.example.com/images/dev/photo.w320.png.webp"
+        );
+        assert_eq!(metadata.format_sources[1].sources.len(), 3);
+
+        let thumbnail = processor
+            .process_thumbnail("./photo.png", temp_dir.path(), "dev")
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            thumbnail.src,
+            "https://cdn.example.com/images/dev/photo.w300.png"
+        );
+        assert_eq!(
+            thumbnail.format_sources[0].src,
+            "https://cdn.example.com/images/dev/photo.w300.png.avif"
+        );
+    }
+
+    #[test]
+    fn test_process_image_defaults() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        write_test_image(temp_dir.path(), "photo.jpg", 900, 600);
+
+        let processor = ImageProcessor::new(Some("https://cdn.example.com".to_string()));
+        let metadata = processor
+            .process_image("./photo.jpg", temp_dir.path(), "dev")
+            .unwrap()
+            .unwrap();
+
+        let widths: Vec<u32> = metadata.sources.iter().map(|s| s.width).collect();
+        assert_eq!(widths, vec![480, 600, 860, u32::MAX]);
+        assert_eq!(metadata.format_sources.len(), 1);
+        assert_eq!(metadata.format_sources[0].format, "webp");
+        assert_eq!(metadata.format_sources[0].mime_type, "image/webp");
+    }
+
+    #[test]
+    fn test_build_cdn_url_avif() {
+        let processor = ImageProcessor::new(Some("https://cdn.example.com".to_string()));
+
+        let url = processor.build_cdn_url(
+            "https://cdn.example.com",
+            "dev",
+            "my-post/photo",
+            Some(860),
+            "jpg",
+            Some("avif"),
+        );
+        assert_eq!(
+            url,
+            "https://cdn.example.com/images/dev/my-post/photo.w860.jpg.avif"
+        );
+    }
+
+    #[test]
+    fn test_avif_sources_come_before_webp_and_original() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        write_test_image(temp_dir.path(), "photo.jpg", 700, 400);
+
+        let processor = ImageProcessor::new(Some("https://cdn.example.com".to_string()))
+            .with_options(ImagesConfig {
+                formats: vec!["webp".to_string(), "avif".to_string()],
+                ..Default::default()
+            });
+
+        let metadata = processor
+            .process_image("./photo.jpg", temp_dir.path(), "dev")
+            .unwrap()
+            .unwrap();
+
+        let mime_types: Vec<&str> = metadata
+            .format_sources
+            .iter()
+            .map(|f| f.mime_type.as_str())
+            .collect();
+        assert_eq!(mime_types, vec!["image/avif", "image/webp"]);
+        assert!(metadata.format_sources[0].sources[0]
+            .url
+            .ends_with("photo.w480.jpg.avif"));
+        assert!(metadata.sources[0].url.ends_with("photo.w480.jpg"));
+
+        let thumbnail = processor
+            .process_thumbnail("./photo.jpg", temp_dir.path(), "dev")
+            .unwrap()
+            .unwrap();
+        let formats: Vec<&str> = thumbnail
+            .format_sources
+            .iter()
+            .map(|f| f.format.as_str())
+            .collect();
+        assert_eq!(formats, vec!["avif", "webp"]);
+    }
+
+    #[test]
+    fn test_dimension_cache_is_consulted() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        write_test_image(temp_dir.path(), "photo.png", 40, 30);
+        let path = temp_dir.path().join("photo.png");
+
+        let cache = Arc::new(Mutex::new(ImageDimensionCache::default()));
+        let processor = ImageProcessor::new(Some("https://cdn.example.com".to_string()))
+            .with_dimension_cache(Arc::clone(&cache));
+
+        assert_eq!(processor.get_image_dimensions(&path).unwrap(), (40, 30));
+        assert_eq!(cache.lock().unwrap().get(&path), Some((40, 30)));
+
+        // A cached entry is returned without decoding the file again
+        cache.lock().unwrap().insert(&path, (1, 1));
+        assert_eq!(processor.get_image_dimensions(&path).unwrap(), (1, 1));
+    }
+
+    #[test]
+    fn test_blurhash_placeholder() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        image::RgbImage::from_pixel(16, 8, image::Rgb([200, 100, 50]))
+            .save(temp_dir.path().join("photo.png"))
+            .unwrap();
+
+        let processor = ImageProcessor::new(Some("https://cdn.example.com".to_string()))
+            .with_options(ImagesConfig {
+                blurhash: true,
+                ..Default::default()
+            });
+
+        let metadata = processor
+            .process_image("./photo.png", temp_dir.path(), "dev")
+            .unwrap()
+            .unwrap();
+
+        assert!(!metadata.lqip_blurhash.unwrap().is_empty());
+        assert_eq!(metadata.placeholder_color.as_deref(), Some("#c86432"));
+    }
+
+    #[test]
+    fn test_blurhash_placeholder_is_cached() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        write_test_image(temp_dir.path(), "photo.png", 16, 8);
+        let path = temp_dir.path().join("photo.png");
+
+        let cache = Arc::new(Mutex::new(ImageDimensionCache::default()));
+        let processor = ImageProcessor::new(Some("https://cdn.example.com".to_string()))
+            .with_options(ImagesConfig {
+                blurhash: true,
+                ..Default::default()
+            })
+            .with_dimension_cache(Arc::clone(&cache));
+
+        let first = processor
+            .process_image("./photo.png", temp_dir.path(), "dev")
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            cache.lock().unwrap().get_placeholder(&path),
+            first.lqip_blurhash.zip(first.placeholder_color)
+        );
+
+        // A cached placeholder is returned without decoding the file again
+        let cached = ("cached".to_string(), "#000000".to_string());
+        cache.lock().unwrap().insert_placeholder(&path, cached);
+        let second = processor
+            .process_image("./photo.png", temp_dir.path(), "dev")
+            .unwrap()
+            .unwrap();
+        assert_eq!(second.lqip_blurhash.as_deref(), Some("cached"));
+        assert_eq!(second.placeholder_color.as_deref(), Some("#000000"));
+    }
+
+    #[test]
+    fn test_blurhash_disabled_by_default() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        write_test_image(temp_dir.path(), "photo.png", 16, 8);
+
+        let processor = ImageProcessor::new(Some("https://cdn.example.com".to_string()));
+        let metadata = processor
+            .process_image("./photo.png", temp_dir.path(), "dev")
+            .unwrap()
+            .unwrap();
+
+        assert!(metadata.lqip_blurhash.is_none());
+        assert!(metadata.placeholder_color.is_none());
+    }
+
+    #[test]
+    fn test_url_template_falls_back_to_default_scheme() {
+        let cdn = "https://cdn.example.com";
+        let untemplated = ImageProcessor::new(Some(cdn.to_string())).with_options(ImagesConfig {
+            url_template: None,
+            ..Default::default()
+        });
+        let templated = ImageProcessor::new(Some(cdn.to_string())).with_options(ImagesConfig {
+            url_template: Some("{cdn}/resize/w={width},f={format}/{path}".to_string()),
+            ..Default::default()
+        });
+
+        let url = |processor: &ImageProcessor, size| {
+            processor.build_cdn_url(cdn, "dev", "post/photo", size, "jpg", Some("webp"))
+        };
+
+        assert_eq!(
+            url(&untemplated, Some(480)),
+            "https://cdn.example.com/images/dev/post/photo.w480.jpg.webp"
+        );
+        assert_eq!(
+            url(&templated, Some(480)),
+            "https://cdn.example.com/resize/w=480,f=webp/dev/post/photo.jpg"
+        );
+        assert_eq!(
+            url(&templated, None),
+            "https://cdn.example.com/dev/post/photo.jpg"
+        );
+    }
+
+    #[test]
+    fn test_url_template_cloudflare_style() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        write_test_image(temp_dir.path(), "photo.jpg", 700, 400);
+
+        let processor = ImageProcessor::new(Some("https://example.com".to_string())).with_options(
+            ImagesConfig {
+                url_template: Some(
+                    "{cdn}/cdn-cgi/image/width={width},format={format}/{path}".to_string(),
+                ),
+                ..Default::default()
+            },
+        );
+
+        let metadata = processor
+            .process_image("./photo.jpg", temp_dir.path(), "dev")
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(
+            metadata.sources[0].url,
+            "https://example.com/cdn-cgi/image/width=480,format=jpg/dev/photo.jpg"
+        );
+        assert_eq!(
+            metadata.format_sources[0].sources[1].url,
+            "https://example.com/cdn-cgi/image/width=600,format=webp/dev/photo.jpg"
+        );
+        // Full-size URLs request the original width
+        assert_eq!(
+            metadata.src,
+            "https://example.com/cdn-cgi/image/width=700,format=jpg/dev/photo.jpg"
+        );
+
+        // SVGs are linked untransformed
+        let url = processor.build_cdn_url("https://example.com", "dev", "icon", None, "svg", None);
+        assert_eq!(url, "https://example.com/dev/icon.svg");
+    }
 }
@@ -1,8 +1,13 @@
+use crate::assets;
 use crate::config::SsgConfig;
-use crate::image::{ImageProcessor, ThumbnailMetadata};
+use crate::generator;
+use crate::image::{category_relative_src, ImageMetadata, ImageProcessor, ThumbnailMetadata};
 use crate::metadata::{compare_posts_desc, MetadataCache, PostMetadata};
+use crate::minify::write_html;
+use crate::permalink;
 use crate::slug;
 use crate::types::Category;
+use crate::verbosity::{detail, status};
 use anyhow::{Context, Result};
 use serde::Serialize;
 use std::collections::{HashMap, HashSet};
@@ -46,9 +51,38 @@ struct CategoryPosts<'a> {
 struct PostCardData<'a> {
     #[serde(flatten)]
     post: &'a PostMetadata,
+    url: String,
     thumbnail_metadata: Option<ThumbnailMetadata>,
 }
 
+/// Category page header: the `.category.yaml` description rendered from
+/// markdown and the cover image processed for the CDN
+#[derive(Debug, Clone, Default, Serialize)]
+struct CategoryHeader {
+    description_html: Option<String>,
+    cover_image_metadata: Option<ImageMetadata>,
+}
+
+/// OpenGraph data for listing pages (`opengraph` in templates). Posts build
+/// theirs in the post template from the frontmatter.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct OpenGraph {
+    title: String,
+    description: String,
+    url: String,
+    #[serde(rename = "type")]
+    og_type: &'static str,
+    image: Option<String>,
+}
+
+/// Tag with a 1-5 weight for sizing tag clouds
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct WeightedTag<'a> {
+    tag: &'a str,
+    count: usize,
+    weight: u8,
+}
+
 pub struct IndexGenerator {
     tera: Tera,
     config: SsgConfig,
@@ -58,13 +92,9 @@ pub struct IndexGenerator {
 
 impl IndexGenerator {
     pub fn new(config: SsgConfig) -> Result<Self> {
-        let tera = create_tera_engine()?;
+        let tera = create_tera_engine(&config)?;
 
-        let image_processor = config
-            .site
-            .cdn_url
-            .as_ref()
-            .map(|url| ImageProcessor::new(Some(url.clone())));
+        let image_processor = ImageProcessor::from_config(&config);
         let content_dir = PathBuf::from(&config.build.content_dir);
 
         Ok(Self {
@@ -103,36 +133,29 @@ impl IndexGenerator {
                 .flatten()
         });
 
+        let url = permalink::post_url(
+            &self.config.build,
+            &post.category,
+            &post.slug,
+            &post.frontmatter.date.posted,
+            false,
+        );
+
         PostCardData {
             post,
+            url,
             thumbnail_metadata,
         }
     }
 
     pub fn generate_all(&self, metadata: &MetadataCache) -> Result<()> {
-        println!("\n📑 Generating indices...");
-
-        self.generate_homepage(metadata)?;
-
-        let category_count = metadata.get_category_info().len();
-        for category in metadata.get_category_info() {
-            self.generate_category_page(category, metadata)?;
-        }
+        status!("\n📑 Generating indices...");
 
-        for tag in metadata.get_tags() {
-            self.generate_tag_page(&tag, metadata)?;
-        }
-
-        self.remove_stale_tag_dirs(
-            &PathBuf::from(&self.config.build.output_dir).join("tag"),
-            metadata,
-        );
+        self.generate_listings(metadata, false)?;
 
-        self.generate_tags_overview(metadata)?;
-
-        println!("   ✓ Homepage");
-        println!("   ✓ {} category pages", category_count);
-        println!("   ✓ {} tag pages", metadata.get_tags().len());
+        status!("   ✓ Homepage");
+        status!("   ✓ {} category pages", metadata.get_category_info().len());
+        status!("   ✓ {} tag pages", metadata.get_tags().len());
 
         Ok(())
     }
@@ -142,31 +165,39 @@ impl IndexGenerator {
             return Ok(());
         }
 
-        println!("\n📄 Generating index partials...");
+        status!("\n📄 Generating index partials...");
+
+        self.generate_listings(metadata, true)?;
 
-        self.generate_homepage_partial(metadata)?;
+        status!("   ✓ Homepage partial");
+        status!(
+            "   ✓ {} category partials",
+            metadata.get_category_info().len()
+        );
+        status!("   ✓ {} tag partials", metadata.get_tags().len());
+
+        Ok(())
+    }
+
+    /// Full listing pages and their SPA partials (`partial`) go through the
+    /// same code, so partials always see the context of the page they replace
+    fn generate_listings(&self, metadata: &MetadataCache, partial: bool) -> Result<()> {
+        self.generate_homepage(metadata, partial)?;
 
-        let category_count = metadata.get_category_info().len();
         for category in metadata.get_category_info() {
-            self.generate_category_partial(category, metadata)?;
+            self.generate_category_page(category, metadata, partial)?;
         }
 
         for tag in metadata.get_tags() {
-            self.generate_tag_partial(&tag, metadata)?;
+            self.generate_tag_page(&tag, metadata, partial)?;
         }
 
-        self.remove_stale_tag_dirs(&self.get_partial_path("tag"), metadata);
-
-        self.generate_tags_overview_partial(metadata)?;
-
-        println!("   ✓ Homepage partial");
-        println!("   ✓ {} category partials", category_count);
-        println!("   ✓ {} tag partials", metadata.get_tags().len());
+        self.remove_stale_tag_dirs(&self.listing_path("tag", partial), metadata);
 
-        Ok(())
+        self.generate_tags_overview(metadata, partial)
     }
 
-    fn generate_homepage(&self, metadata: &MetadataCache) -> Result<()> {
+    fn generate_homepage(&self, metadata: &MetadataCache, partial: bool) -> Result<()> {
         let posts_limit = self
             .config
             .build
@@ -210,12 +241,20 @@ impl IndexGenerator {
         context.insert("posts", &all_recent_posts);
         context.insert("category_posts", &category_posts);
         context.insert("categories", &visible_categories);
+        context.insert("canonical_url", &self.config.site.url);
+        context.insert(
+            "opengraph",
+            &site_opengraph(&self.config, &self.config.site.title, &self.config.site.url),
+        );
         context.insert("config", &self.config.to_template_config());
+        generator::insert_globals(&mut context, &self.config);
 
-        let output = self.tera.render("index.html", &context)?;
-        let output_path = PathBuf::from(&self.config.build.output_dir).join("index.html");
+        let output = self
+            .tera
+            .render(&listing_template("index.html", partial), &context)?;
+        let output_path = self.listing_path("index.html", partial);
 
-        fs::write(&output_path, output)?;
+        write_html(&output_path, &output, &self.config.build)?;
 
         Ok(())
     }
@@ -224,6 +263,7 @@ impl IndexGenerator {
         &self,
         category_info: &crate::types::Category,
         metadata: &MetadataCache,
+        partial: bool,
     ) -> Result<()> {
         let mut posts = metadata.get_posts_by_category_tree(&category_info.slug);
 
@@ -251,6 +291,11 @@ impl IndexGenerator {
             .collect();
 
         let template_config = self.config.to_template_config();
+        let header = build_category_header(
+            category_info,
+            self.image_processor.as_ref(),
+            &self.content_dir,
+        );
 
         for page_num in 1..=total_pages {
             let start_idx = (page_num - 1) * posts_per_page;
@@ -259,44 +304,44 @@ impl IndexGenerator {
 
             let mut context = TeraContext::new();
             context.insert("category", category_info);
+            let canonical_url = self.listing_canonical_url(&category_info.slug, page_num);
+            context.insert("canonical_url", &canonical_url);
+            context.insert(
+                "opengraph",
+                &category_opengraph(category_info, &header, &self.config, &canonical_url),
+            );
+            context.insert("category_description_html", &header.description_html);
+            context.insert("category_cover_metadata", &header.cover_image_metadata);
             context.insert("posts", &page_posts);
             context.insert("post_count", &total_posts);
             context.insert("categories", &visible_categories);
             context.insert("config", &template_config);
+            generator::insert_globals(&mut context, &self.config);
 
             if total_pages > 1 {
                 let pagination = self.build_pagination_context(page_num, total_posts, &base_url);
                 context.insert("pagination", &pagination);
             }
 
-            let output = self.tera.render("category.html", &context)?;
+            let output = self
+                .tera
+                .render(&listing_template("category.html", partial), &context)?;
 
             let category_slug = self.maybe_encode(&category_info.slug);
 
-            let output_path = if page_num == 1 {
-                PathBuf::from(&self.config.build.output_dir)
-                    .join(&category_slug)
-                    .join("index.html")
-            } else {
-                PathBuf::from(&self.config.build.output_dir)
-                    .join(&category_slug)
-                    .join("page")
-                    .join(page_num.to_string())
-                    .join("index.html")
-            };
+            let section_dir = self.listing_path(&category_slug, partial);
+            let output_path = self.listing_page_file(section_dir, page_num);
 
-            fs::create_dir_all(output_path.parent().unwrap())?;
-            fs::write(&output_path, output)?;
+            write_html(&output_path, &output, &self.config.build)?;
         }
 
-        let section_dir = PathBuf::from(&self.config.build.output_dir)
-            .join(self.maybe_encode(&category_info.slug));
+        let section_dir = self.listing_path(&self.maybe_encode(&category_info.slug), partial);
         Self::remove_stale_pagination(&section_dir, total_pages);
 
         Ok(())
     }
 
-    fn generate_tag_page(&self, tag: &str, metadata: &MetadataCache) -> Result<()> {
+    fn generate_tag_page(&self, tag: &str, metadata: &MetadataCache, partial: bool) -> Result<()> {
         let mut posts = metadata.get_posts_by_tag(tag);
 
         posts.sort_by(|a, b| compare_posts_desc(a, b));
@@ -331,47 +376,42 @@ impl IndexGenerator {
 
             let mut context = TeraContext::new();
             context.insert("tag", tag);
+            let canonical_url = self.listing_canonical_url(&format!("tag/{}", tag), page_num);
+            context.insert("canonical_url", &canonical_url);
+            context.insert(
+                "opengraph",
+                &tag_opengraph(tag, &self.config, &canonical_url),
+            );
             context.insert("posts", &page_posts);
             context.insert("post_count", &total_posts);
             context.insert("categories", &visible_categories);
             context.insert("config", &template_config);
+            generator::insert_globals(&mut context, &self.config);
 
             if total_pages > 1 {
                 let pagination = self.build_pagination_context(page_num, total_posts, &base_url);
                 context.insert("pagination", &pagination);
             }
 
-            let output = self.tera.render("tag.html", &context)?;
+            let output = self
+                .tera
+                .render(&listing_template("tag.html", partial), &context)?;
 
             let encoded_tag = self.maybe_encode(tag);
 
-            let output_path = if page_num == 1 {
-                PathBuf::from(&self.config.build.output_dir)
-                    .join("tag")
-                    .join(&encoded_tag)
-                    .join("index.html")
-            } else {
-                PathBuf::from(&self.config.build.output_dir)
-                    .join("tag")
-                    .join(&encoded_tag)
-                    .join("page")
-                    .join(page_num.to_string())
-                    .join("index.html")
-            };
+            let section_dir = self.listing_path(&format!("tag/{}", encoded_tag), partial);
+            let output_path = self.listing_page_file(section_dir, page_num);
 
-            fs::create_dir_all(output_path.parent().unwrap())?;
-            fs::write(&output_path, output)?;
+            write_html(&output_path, &output, &self.config.build)?;
         }
 
-        let section_dir = PathBuf::from(&self.config.build.output_dir)
-            .join("tag")
-            .join(self.maybe_encode(tag));
+        let section_dir = self.listing_path(&format!("tag/{}", self.maybe_encode(tag)), partial);
         Self::remove_stale_pagination(&section_dir, total_pages);
 
         Ok(())
     }
 
-    fn generate_tags_overview(&self, metadata: &MetadataCache) -> Result<()> {
+    fn generate_tags_overview(&self, metadata: &MetadataCache, partial: bool) -> Result<()> {
         let mut tags_with_counts: Vec<_> = metadata.tags.iter().collect();
         tags_with_counts.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
 
@@ -383,240 +423,90 @@ impl IndexGenerator {
 
         let mut context = TeraContext::new();
         context.insert("tags", &tags_with_counts);
+        context.insert("tags_weighted", &weight_tags(&tags_with_counts));
+        let canonical_url = permalink::canonical_url(
+            &self.config.site.url,
+            &permalink::page_url("/tags/", self.config.build.ugly_urls),
+        );
+        context.insert("canonical_url", &canonical_url);
+        context.insert(
+            "opengraph",
+            &site_opengraph(
+                &self.config,
+                &format!("All Tags - {}", self.config.site.title),
+                &canonical_url,
+            ),
+        );
         context.insert("categories", &visible_categories);
         context.insert("config", &self.config.to_template_config());
+        generator::insert_globals(&mut context, &self.config);
+
+        let output = self
+            .tera
+            .render(&listing_template("tags.html", partial), &context)?;
+        let output_path = permalink::page_file(
+            self.listing_path("tags", partial),
+            self.config.build.ugly_urls,
+        );
 
-        let output = self.tera.render("tags.html", &context)?;
-        let output_path = PathBuf::from(&self.config.build.output_dir)
-            .join("tags")
-            .join("index.html");
-
-        fs::create_dir_all(output_path.parent().unwrap())?;
-        fs::write(&output_path, output)?;
-
-        Ok(())
-    }
-
-    fn generate_homepage_partial(&self, metadata: &MetadataCache) -> Result<()> {
-        let posts_limit = self
-            .config
-            .build
-            .homepage_posts_limit
-            .unwrap_or(self.config.build.posts_per_page);
-
-        let mut visible_categories: Vec<_> = metadata
-            .get_category_info()
-            .iter()
-            .filter(|c| !c.hidden)
-            .collect();
-        visible_categories.sort_by_key(|c| c.index);
-
-        let visible_category_slugs: HashSet<_> =
-            visible_categories.iter().map(|c| c.slug.as_str()).collect();
-
-        let all_recent_posts: Vec<_> = metadata
-            .get_recent_posts(posts_limit)
-            .into_iter()
-            .filter(|p| visible_category_slugs.contains(p.category.as_str()))
-            .map(|p| self.create_post_card_data(p))
-            .collect();
-
-        let category_posts: Vec<CategoryPosts> = visible_categories
-            .iter()
-            .map(|cat| {
-                let mut posts = metadata.get_posts_by_category(&cat.slug);
-                posts.sort_by(|a, b| compare_posts_desc(a, b));
-                CategoryPosts {
-                    category: cat,
-                    posts: posts
-                        .into_iter()
-                        .take(posts_limit)
-                        .map(|p| self.create_post_card_data(p))
-                        .collect(),
-                }
-            })
-            .collect();
-
-        let mut context = TeraContext::new();
-        context.insert("posts", &all_recent_posts);
-        context.insert("category_posts", &category_posts);
-        context.insert("categories", &visible_categories);
-        context.insert("config", &self.config.to_template_config());
-
-        let output = self.tera.render("partials/index.html", &context)?;
-        let output_path = self.get_partial_path("index.html");
-
-        fs::create_dir_all(output_path.parent().unwrap())?;
-        fs::write(&output_path, output)?;
+        write_html(&output_path, &output, &self.config.build)?;
 
         Ok(())
     }
 
-    fn generate_category_partial(
-        &self,
-        category_info: &crate::types::Category,
-        metadata: &MetadataCache,
-    ) -> Result<()> {
-        let mut posts = metadata.get_posts_by_category_tree(&category_info.slug);
-        posts.sort_by(|a, b| compare_posts_desc(a, b));
-
-        let posts_with_thumbnails: Vec<_> = posts
-            .iter()
-            .map(|p| self.create_post_card_data(p))
-            .collect();
-
-        let total_posts = posts_with_thumbnails.len();
-        let posts_per_page = self.config.build.posts_per_page;
-        let total_pages = if total_posts == 0 {
-            1
+    /// Output file for page `page_num` of a paginated listing rooted at
+    /// `section_dir` (page 1 is the section itself, later pages `page/N`)
+    fn listing_page_file(&self, section_dir: PathBuf, page_num: usize) -> PathBuf {
+        let dir = if page_num == 1 {
+            section_dir
         } else {
-            total_posts.div_ceil(posts_per_page)
+            section_dir.join("page").join(page_num.to_string())
         };
 
-        let base_url = format!("/{}/", category_info.slug);
-
-        let visible_categories: Vec<_> = metadata
-            .get_category_info()
-            .iter()
-            .filter(|c| !c.hidden)
-            .collect();
-
-        let template_config = self.config.to_template_config();
-
-        for page_num in 1..=total_pages {
-            let start_idx = (page_num - 1) * posts_per_page;
-            let end_idx = std::cmp::min(start_idx + posts_per_page, total_posts);
-            let page_posts = &posts_with_thumbnails[start_idx..end_idx];
-
-            let mut context = TeraContext::new();
-            context.insert("category", category_info);
-            context.insert("posts", &page_posts);
-            context.insert("post_count", &total_posts);
-            context.insert("categories", &visible_categories);
-            context.insert("config", &template_config);
-
-            if total_pages > 1 {
-                let pagination = self.build_pagination_context(page_num, total_posts, &base_url);
-                context.insert("pagination", &pagination);
-            }
-
-            let output = self.tera.render("partials/category.html", &context)?;
-
-            let category_slug = self.maybe_encode(&category_info.slug);
-
-            let output_path = if page_num == 1 {
-                self.get_partial_path(&format!("{}/index.html", category_slug))
-            } else {
-                self.get_partial_path(&format!("{}/page/{}/index.html", category_slug, page_num))
-            };
-
-            fs::create_dir_all(output_path.parent().unwrap())?;
-            fs::write(&output_path, output)?;
-        }
-
-        let section_dir = self.get_partial_path(&self.maybe_encode(&category_info.slug));
-        Self::remove_stale_pagination(&section_dir, total_pages);
-
-        Ok(())
+        permalink::page_file(dir, self.config.build.ugly_urls)
     }
 
-    fn generate_tag_partial(&self, tag: &str, metadata: &MetadataCache) -> Result<()> {
-        let mut posts = metadata.get_posts_by_tag(tag);
-        posts.sort_by(|a, b| compare_posts_desc(a, b));
-
-        let posts_with_thumbnails: Vec<_> = posts
-            .iter()
-            .map(|p| self.create_post_card_data(p))
-            .collect();
-
-        let total_posts = posts_with_thumbnails.len();
-        let posts_per_page = self.config.build.posts_per_page;
-        let total_pages = if total_posts == 0 {
-            1
+    /// URL of page `page_num` for a listing at `base_url` (`/dev/`)
+    fn listing_page_url(&self, base_url: &str, page_num: usize) -> String {
+        if page_num == 1 {
+            permalink::page_url(base_url, self.config.build.ugly_urls)
+        } else if self.config.build.ugly_urls {
+            format!("{}page/{}.html", base_url, page_num)
         } else {
-            total_posts.div_ceil(posts_per_page)
-        };
-
-        let base_url = format!("/tag/{}/", tag);
-
-        let visible_categories: Vec<_> = metadata
-            .get_category_info()
-            .iter()
-            .filter(|c| !c.hidden)
-            .collect();
-
-        let template_config = self.config.to_template_config();
-
-        for page_num in 1..=total_pages {
-            let start_idx = (page_num - 1) * posts_per_page;
-            let end_idx = std::cmp::min(start_idx + posts_per_page, total_posts);
-            let page_posts = &posts_with_thumbnails[start_idx..end_idx];
-
-            let mut context = TeraContext::new();
-            context.insert("tag", tag);
-            context.insert("posts", &page_posts);
-            context.insert("post_count", &total_posts);
-            context.insert("categories", &visible_categories);
-            context.insert("config", &template_config);
-
-            if total_pages > 1 {
-                let pagination = self.build_pagination_context(page_num, total_posts, &base_url);
-                context.insert("pagination", &pagination);
-            }
-
-            let output = self.tera.render("partials/tag.html", &context)?;
-
-            let encoded_tag = self.maybe_encode(tag);
-
-            let output_path = if page_num == 1 {
-                self.get_partial_path(&format!("tag/{}/index.html", encoded_tag))
-            } else {
-                self.get_partial_path(&format!("tag/{}/page/{}/index.html", encoded_tag, page_num))
-            };
-
-            fs::create_dir_all(output_path.parent().unwrap())?;
-            fs::write(&output_path, output)?;
+            format!("{}page/{}", base_url, page_num)
         }
-
-        let section_dir = self.get_partial_path(&format!("tag/{}", self.maybe_encode(tag)));
-        Self::remove_stale_pagination(&section_dir, total_pages);
-
-        Ok(())
     }
 
-    fn generate_tags_overview_partial(&self, metadata: &MetadataCache) -> Result<()> {
-        let mut tags_with_counts: Vec<_> = metadata.tags.iter().collect();
-        tags_with_counts.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
-
-        let visible_categories: Vec<_> = metadata
-            .get_category_info()
-            .iter()
-            .filter(|c| !c.hidden)
-            .collect();
-
-        let mut context = TeraContext::new();
-        context.insert("tags", &tags_with_counts);
-        context.insert("categories", &visible_categories);
-        context.insert("config", &self.config.to_template_config());
-
-        let output = self.tera.render("partials/tags.html", &context)?;
-        let output_path = self.get_partial_path("tags/index.html");
-
-        fs::create_dir_all(output_path.parent().unwrap())?;
-        fs::write(&output_path, output)?;
+    /// Canonical URL of page `page_num` of the listing at `section`
+    /// (`dev`, `tag/rust`): the page itself, or page 1 with
+    /// `build.canonical_first_page`
+    fn listing_canonical_url(&self, section: &str, page_num: usize) -> String {
+        let section = slug::encode_for_url(section);
+        let path = if page_num == 1 || self.config.build.canonical_first_page {
+            format!("/{}/", section)
+        } else {
+            format!("/{}/page/{}/", section, page_num)
+        };
 
-        Ok(())
+        permalink::canonical_url(
+            &self.config.site.url,
+            &permalink::page_url(&path, self.config.build.ugly_urls),
+        )
     }
 
-    fn get_partial_path(&self, relative: &str) -> PathBuf {
-        PathBuf::from(&self.config.build.output_dir)
-            .join(&self.config.build.partial_dir)
-            .join(relative)
+    /// Output location of `relative`, under `build.partial_dir` for partials
+    fn listing_path(&self, relative: &str, partial: bool) -> PathBuf {
+        let root = PathBuf::from(&self.config.build.output_dir);
+        if partial {
+            root.join(&self.config.build.partial_dir).join(relative)
+        } else {
+            root.join(relative)
+        }
     }
 
-    /// Removes `page/N` directories beyond the current page count: posts
-    /// removed since the last build would otherwise leave orphaned
-    /// pagination pages serving stale content.
+    /// Removes `page/N` directories (or `page/N.html` files with ugly URLs)
+    /// beyond the current page count: posts removed since the last build
+    /// would otherwise leave orphaned pagination pages serving stale content.
     fn remove_stale_pagination(section_dir: &Path, total_pages: usize) {
         let page_dir = section_dir.join("page");
         let Ok(entries) = fs::read_dir(&page_dir) else {
@@ -627,18 +517,26 @@ impl IndexGenerator {
             let is_stale = entry
                 .file_name()
                 .to_str()
+                .map(|name| name.strip_suffix(".html").unwrap_or(name))
                 .and_then(|name| name.parse::<usize>().ok())
                 .is_some_and(|num| num > total_pages);
 
             if is_stale {
-                if let Err(e) = fs::remove_dir_all(entry.path()) {
+                let path = entry.path();
+                let removed = if path.is_dir() {
+                    fs::remove_dir_all(&path)
+                } else {
+                    fs::remove_file(&path)
+                };
+
+                if let Err(e) = removed {
                     eprintln!(
                         "⚠️  Failed to remove stale pagination {}: {}",
-                        entry.path().display(),
+                        path.display(),
                         e
                     );
                 } else {
-                    println!("🧹 Removed stale pagination: {}", entry.path().display());
+                    detail!("🧹 Removed stale pagination: {}", path.display());
                 }
             }
         }
@@ -648,9 +546,10 @@ impl IndexGenerator {
     }
 
     /// Removes listing pages of tags no longer used by any post. Tags may
-    /// contain `/` and nest directories, so every generated `index.html` is
-    /// mapped back to its tag (normalizing a `page/N` suffix) and removed
-    /// when that tag is gone; emptied directories are pruned afterwards.
+    /// contain `/` and nest directories, so every generated page (`index.html`
+    /// or `<name>.html` with ugly URLs) is mapped back to its tag (normalizing
+    /// a `page/N` suffix) and removed when that tag is gone; emptied
+    /// directories are pruned afterwards.
     fn remove_stale_tag_dirs(&self, tag_base_dir: &Path, metadata: &MetadataCache) {
         use walkdir::WalkDir;
 
@@ -667,9 +566,9 @@ impl IndexGenerator {
         let stale_files: Vec<PathBuf> = WalkDir::new(tag_base_dir)
             .into_iter()
             .filter_map(|e| e.ok())
-            .filter(|e| e.file_name() == "index.html")
+            .filter(|e| e.file_type().is_file())
             .filter_map(|e| {
-                let dir = e.path().parent()?;
+                let dir = Self::page_location(e.path())?;
                 let relative = dir.strip_prefix(tag_base_dir).ok()?;
                 // Live when the dir is a tag path itself (covers tags whose
                 // name happens to end in `page/N`) or a pagination dir of one
@@ -688,7 +587,7 @@ impl IndexGenerator {
                 );
                 continue;
             }
-            println!("🧹 Removed stale tag page: {}", file.display());
+            detail!("🧹 Removed stale tag page: {}", file.display());
 
             let mut dir = file.parent();
             while let Some(d) = dir {
@@ -700,6 +599,16 @@ impl IndexGenerator {
         }
     }
 
+    /// Path a generated page is served at: the parent of `index.html`, or
+    /// the file minus its extension for ugly URLs. `None` for non-HTML files.
+    fn page_location(file: &Path) -> Option<PathBuf> {
+        if file.file_name()? == "index.html" {
+            return file.parent().map(Path::to_path_buf);
+        }
+
+        (file.extension()? == "html").then(|| file.with_extension(""))
+    }
+
     /// Maps a pagination directory (`<tag>/page/N`) back to its tag path.
     fn strip_page_suffix(relative: &Path) -> &Path {
         let is_page_number = relative
@@ -734,12 +643,8 @@ impl IndexGenerator {
             total_posts.div_ceil(posts_per_page)
         };
 
-        let first_url = base_url.to_string();
-        let last_url = if total_pages == 1 {
-            base_url.to_string()
-        } else {
-            format!("{}page/{}", base_url, total_pages)
-        };
+        let first_url = self.listing_page_url(base_url, 1);
+        let last_url = self.listing_page_url(base_url, total_pages);
 
         let window = self.config.build.pagination_window;
         let half_window = window / 2;
@@ -757,49 +662,22 @@ impl IndexGenerator {
         let pages = (start_page..=end_page)
             .map(|num| PageLink {
                 number: num,
-                url: if num == 1 {
-                    base_url.to_string()
-                } else {
-                    format!("{}page/{}", base_url, num)
-                },
+                url: self.listing_page_url(base_url, num),
                 is_current: num == current_page,
             })
             .collect();
 
-        let jump_prev_url = if start_page > 1 {
-            let jump_page = start_page - 1;
-            Some(if jump_page == 1 {
-                base_url.to_string()
-            } else {
-                format!("{}page/{}", base_url, jump_page)
-            })
-        } else {
-            None
-        };
+        let jump_prev_url =
+            (start_page > 1).then(|| self.listing_page_url(base_url, start_page - 1));
 
-        let jump_next_url = if end_page < total_pages {
-            Some(format!("{}page/{}", base_url, end_page + 1))
-        } else {
-            None
-        };
+        let jump_next_url =
+            (end_page < total_pages).then(|| self.listing_page_url(base_url, end_page + 1));
 
         let prev_url = jump_prev_url.clone().or_else(|| {
-            if current_page > 1 {
-                Some(if current_page == 2 {
-                    base_url.to_string()
-                } else {
-                    format!("{}page/{}", base_url, current_page - 1)
-                })
-            } else {
-                None
-            }
+            (current_page > 1).then(|| self.listing_page_url(base_url, current_page - 1))
         });
         let next_url = jump_next_url.clone().or_else(|| {
-            if current_page < total_pages {
-                Some(format!("{}page/{}", base_url, current_page + 1))
-            } else {
-                None
-            }
+            (current_page < total_pages).then(|| self.listing_page_url(base_url, current_page + 1))
         });
         let has_prev = prev_url.is_some();
         let has_next = next_url.is_some();
@@ -830,8 +708,8 @@ impl IndexGenerator {
     }
 }
 
-fn create_tera_engine() -> Result<Tera> {
-    let template_dir = Path::new("templates");
+fn create_tera_engine(config: &SsgConfig) -> Result<Tera> {
+    let template_dir = Path::new(&config.build.template_dir);
 
     if !template_dir.exists() {
         anyhow::bail!(
@@ -845,12 +723,297 @@ fn create_tera_engine() -> Result<Tera> {
         .context(format!("Failed to load templates from {:?}", template_dir))?;
 
     tera.register_filter("urldecode", urldecode_filter);
+    tera.register_filter(
+        "date_format",
+        generator::DateFormat {
+            default_format: config.build.date_format.clone(),
+        },
+    );
+    tera.register_function(
+        "url_for",
+        permalink::UrlFor {
+            ugly_urls: config.build.ugly_urls,
+        },
+    );
+    tera.register_function(
+        "url",
+        permalink::AbsoluteUrl {
+            site_url: config.site.url.clone(),
+            ugly_urls: config.build.ugly_urls,
+        },
+    );
+    tera.register_function(
+        "asset",
+        assets::Asset {
+            assets: config.assets.clone(),
+        },
+    );
 
     Ok(tera)
 }
 
+/// `name`, or its `partials/` counterpart when rendering an SPA partial
+fn listing_template(name: &str, partial: bool) -> String {
+    if partial {
+        format!("partials/{}", name)
+    } else {
+        name.to_string()
+    }
+}
+
 fn urldecode_filter(value: &Value, _args: &HashMap<String, Value>) -> tera::Result<Value> {
     let s = tera::try_get_value!("urldecode", "value", String, value);
     let decoded = slug::decode_from_url(&s);
     Ok(Value::String(decoded))
 }
+
+const TAG_WEIGHT_BUCKETS: usize = 5;
+
+/// Bucket tags into weights 1-5 by count quantile: a tag's weight grows with
+/// the share of tags used less often than it. Ties share a weight, and when
+/// every tag has the same count they all get the middle weight.
+fn weight_tags<'a>(tags_with_counts: &[(&'a String, &usize)]) -> Vec<WeightedTag<'a>> {
+    let counts: Vec<usize> = tags_with_counts.iter().map(|(_, c)| **c).collect();
+    let min = counts.iter().copied().min().unwrap_or(0);
+    let max = counts.iter().copied().max().unwrap_or(0);
+    let total = counts.len();
+
+    tags_with_counts
+        .iter()
+        .map(|(tag, count)| {
+            let weight = if min == max {
+                TAG_WEIGHT_BUCKETS.div_ceil(2)
+            } else {
+                let below = counts.iter().filter(|c| **c < **count).count();
+                1 + below * TAG_WEIGHT_BUCKETS / total
+            };
+            WeightedTag {
+                tag,
+                count: **count,
+                weight: weight as u8,
+            }
+        })
+        .collect()
+}
+
+/// Render a category's description and process its cover image, which is
+/// resolved relative to the category's content directory. Both are `None`
+/// when unset (or when the cover can't be processed, e.g. without a CDN).
+fn build_category_header(
+    category: &Category,
+    image_processor: Option<&ImageProcessor>,
+    content_dir: &Path,
+) -> CategoryHeader {
+    let description_html = (!category.description.trim().is_empty()).then(|| {
+        use pulldown_cmark::{html, Options, Parser};
+        let mut html_output = String::new();
+        html::push_html(
+            &mut html_output,
+            Parser::new_ext(&category.description, Options::all()),
+        );
+        html_output
+    });
+
+    let cover_image_metadata =
+        image_processor
+            .zip(category.cover_image.as_ref())
+            .and_then(|(processor, cover)| {
+                let relative_src = category_relative_src(cover, &category.slug);
+
+                processor
+                    .process_image(
+                        &relative_src,
+                        &content_dir.join(&category.slug),
+                        &category.slug,
+                    )
+                    .ok()
+                    .flatten()
+            });
+
+    CategoryHeader {
+        description_html,
+        cover_image_metadata,
+    }
+}
+
+/// OpenGraph data for the homepage and other site-wide listings
+fn site_opengraph(config: &SsgConfig, title: &str, url: &str) -> OpenGraph {
+    OpenGraph {
+        title: title.to_string(),
+        description: config.site.description.clone(),
+        url: url.to_string(),
+        og_type: "website",
+        image: None,
+    }
+}
+
+/// OpenGraph data for a category listing: its name, its description as plain
+/// text (the site description when unset) and its processed cover image
+fn category_opengraph(
+    category: &Category,
+    header: &CategoryHeader,
+    config: &SsgConfig,
+    url: &str,
+) -> OpenGraph {
+    let description = plain_text(&category.description);
+    OpenGraph {
+        title: format!("{} - {}", category.name, config.site.title),
+        description: if description.is_empty() {
+            config.site.description.clone()
+        } else {
+            description
+        },
+        url: url.to_string(),
+        og_type: "website",
+        image: header
+            .cover_image_metadata
+            .as_ref()
+            .map(|cover| cover.src.clone()),
+    }
+}
+
+fn tag_opengraph(tag: &str, config: &SsgConfig, url: &str) -> OpenGraph {
+    OpenGraph {
+        title: format!("Tag: {} - {}", tag, config.site.title),
+        description: format!("Posts tagged {} on {}", tag, config.site.title),
+        url: url.to_string(),
+        og_type: "website",
+        image: None,
+    }
+}
+
+/// Text content of a markdown snippet, with whitespace collapsed
+fn plain_text(markdown: &str) -> String {
+    use pulldown_cmark::{Event, Parser};
+
+    let text: String = Parser::new(markdown)
+        .filter_map(|event| match event {
+            Event::Text(text) | Event::Code(text) => Some(text.into_string()),
+            Event::SoftBreak | Event::HardBreak | Event::End(_) => Some(" ".to_string()),
+            _ => None,
+        })
+        .collect();
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn category(description: &str, cover_image: Option<&str>) -> Category {
+        Category {
+            slug: "dev".to_string(),
+            name: "Dev".to_string(),
+            description: description.to_string(),
+            index: 1,
+            hidden: false,
+            icon: None,
+            color: None,
+            cover_image: cover_image.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_category_header_renders_description_and_cover() {
+        let temp = TempDir::new().unwrap();
+        let category_dir = temp.path().join("dev");
+        fs::create_dir_all(&category_dir).unwrap();
+        image::RgbImage::new(4, 3)
+            .save(category_dir.join("cover.png"))
+            .unwrap();
+        let processor = ImageProcessor::new(Some("https://cdn.example.com".to_string()));
+
+        let header = build_category_header(
+            &category("Notes on **Rust**", Some("./cover.png")),
+            Some(&processor),
+            temp.path(),
+        );
+
+        assert_eq!(
+            header.description_html.as_deref(),
+            Some("<p>Notes on <strong>Rust</strong></p>\n")
+        );
+        let cover = header.cover_image_metadata.unwrap();
+        assert_eq!((cover.width, cover.height), (4, 3));
+        assert!(cover.src.starts_with("https://cdn.example.com/"));
+        assert!(cover.src.contains("/dev/cover"));
+    }
+
+    fn weights(counts: &[usize]) -> Vec<u8> {
+        let names: Vec<String> = (0..counts.len()).map(|i| format!("tag{}", i)).collect();
+        let pairs: Vec<_> = names.iter().zip(counts.iter()).collect();
+        weight_tags(&pairs).iter().map(|t| t.weight).collect()
+    }
+
+    #[test]
+    fn test_weight_tags_spread() {
+        assert_eq!(weights(&[50, 20, 10, 5, 1]), vec![5, 4, 3, 2, 1]);
+        assert_eq!(
+            weights(&[9, 9, 4, 4, 4, 1, 1, 1, 1, 1]),
+            vec![5, 5, 3, 3, 3, 1, 1, 1, 1, 1]
+        );
+    }
+
+    #[test]
+    fn test_weight_tags_degenerate_counts() {
+        assert_eq!(weights(&[]), Vec::<u8>::new());
+        assert_eq!(weights(&[7]), vec![3]);
+        assert_eq!(weights(&[2, 2, 2]), vec![3, 3, 3]);
+    }
+
+    #[test]
+    fn test_category_header_without_description_or_cover() {
+        let temp = TempDir::new().unwrap();
+        let processor = ImageProcessor::new(Some("https://cdn.example.com".to_string()));
+
+        let header = build_category_header(&category("  ", None), Some(&processor), temp.path());
+
+        assert!(header.description_html.is_none());
+        assert!(header.cover_image_metadata.is_none());
+    }
+
+    #[test]
+    fn test_category_opengraph_uses_category_details() {
+        let mut config = SsgConfig::default();
+        config.site.title = "Blog".to_string();
+        config.site.description = "Site description".to_string();
+        let header = CategoryHeader {
+            description_html: None,
+            cover_image_metadata: Some(ImageMetadata {
+                width: 4,
+                height: 3,
+                src: "https://cdn.example.com/dev/cover.png".to_string(),
+                lqip: String::new(),
+                lqip_blurhash: None,
+                placeholder_color: None,
+                sources: Vec::new(),
+                format_sources: Vec::new(),
+            }),
+        };
+
+        let opengraph = category_opengraph(
+            &category("Notes on **Rust**\nand more", None),
+            &header,
+            &config,
+            "https://example.com/dev/",
+        );
+
+        assert_eq!(opengraph.title, "Dev - Blog");
+        assert_eq!(opengraph.description, "Notes on Rust and more");
+        assert_eq!(opengraph.url, "https://example.com/dev/");
+        assert_eq!(
+            opengraph.image.as_deref(),
+            Some("https://cdn.example.com/dev/cover.png")
+        );
+
+        let opengraph = category_opengraph(
+            &category("", None),
+            &CategoryHeader::default(),
+            &config,
+            "https://example.com/dev/",
+        );
+        assert_eq!(opengraph.description, "Site description");
+        assert_eq!(opengraph.image, None);
+    }
+}
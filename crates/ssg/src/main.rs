@@ -1,14 +1,24 @@
+mod abbreviations;
+mod api;
+mod assets;
 mod cache;
 mod category;
 mod config;
+mod data;
+mod emoji;
 mod feeds;
 mod generator;
+mod git;
 mod image;
 mod indices;
 mod metadata;
+mod minify;
 mod navigation;
 mod parallel;
 mod parser;
+mod permalink;
+mod plugin;
+mod precompress;
 mod reading_time;
 mod recent;
 mod renderer;
@@ -19,30 +29,39 @@ mod sitemap;
 mod slug;
 mod slug_index;
 mod syntax_highlighter;
+mod tags;
 mod types;
+mod verbosity;
+mod wikilinks;
 
-use anyhow::Result;
-use clap::{Parser as ClapParser, Subcommand};
+use anyhow::{Context, Result};
+use clap::{Args, Parser as ClapParser, Subcommand};
 use serde::Serialize;
 use serde_json::json;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::sync::{mpsc, Arc, Mutex};
 use walkdir::WalkDir;
 
-use crate::cache::{compute_environment_hash, hash_file, normalize_path, BuildCache};
+use crate::api::ApiGenerator;
+use crate::cache::{
+    compute_environment_hash, compute_template_hash, hash_file, normalize_path, BuildCache,
+    ImageDimensionCache,
+};
 use crate::category::{discover_categories, validate_category};
 use crate::config::{load_config, SsgConfig};
 use crate::feeds::FeedGenerator;
 use crate::generator::Generator;
 use crate::image::{ImageProcessor, ThumbnailMetadata};
 use crate::indices::IndexGenerator;
-use crate::metadata::{compare_posts_desc, MetadataCache};
+use crate::metadata::{compare_posts_desc, find_duplicate_slugs, MetadataCache};
 use crate::navigation::{build_post_navigation, build_post_navigation_with_cdn};
 use crate::parallel::{
-    get_thread_count, BuildProgress, BuildResult, SkipReason, WorkQueue, WorkerPool,
+    get_thread_count, parallel_map, BuildProgress, BuildResult, ProgressBar, SkipReason, WorkQueue,
+    WorkerPool,
 };
 use crate::parser::Parser;
+use crate::precompress::Precompressor;
 use crate::recent::RecentGenerator;
 use crate::renderer::Renderer;
 use crate::robots::RobotsGenerator;
@@ -50,7 +69,9 @@ use crate::search::SearchIndexGenerator;
 use crate::shortcodes::ShortcodeRegistry;
 use crate::sitemap::SitemapGenerator;
 use crate::slug_index::SlugIndexGenerator;
+use crate::tags::TagNormalizer;
 use crate::types::Post;
+use crate::verbosity::{detail, status, Verbosity};
 
 const RELATED_POSTS_COUNT: usize = 4;
 const DEV_SERVER_BUFFER_SIZE: usize = 1024;
@@ -78,23 +99,23 @@ fn build_pages(
     renderer: &Renderer,
     generator: &Generator,
     page_data: &HashMap<String, serde_json::Value>,
+    pages_dir: &Path,
 ) -> Vec<(std::path::PathBuf, String)> {
-    let pages_dir = Path::new("content/pages");
     if !pages_dir.exists() {
         return Vec::new();
     }
 
-    println!("\n📄 Building pages...");
+    status!("\n📄 Building pages...");
     let mut pages_built = 0;
     let mut errors = Vec::new();
 
     for entry in WalkDir::new(pages_dir)
         .into_iter()
         .filter_map(|e| e.ok())
-        .filter(|e| e.path().extension().is_some_and(|ext| ext == "md"))
+        .filter(|e| parser::is_markdown(e.path()))
     {
         let path = entry.path();
-        println!("🔨 Building page: {}", path.display());
+        detail!("🔨 Building page: {}", path.display());
 
         match build_single_page(path, shortcode_registry, renderer, generator, page_data) {
             Ok(true) => pages_built += 1,
@@ -107,7 +128,7 @@ fn build_pages(
     }
 
     if pages_built > 0 {
-        println!("✅ Built {} page(s)", pages_built);
+        status!("✅ Built {} page(s)", pages_built);
     }
 
     errors
@@ -123,11 +144,13 @@ fn build_single_page(
     let mut page = Parser::parse_page_file(path)?;
 
     if page.frontmatter.hidden {
-        println!("   ⚠  Hidden - skipping output");
+        detail!("   ⚠  Hidden - skipping output");
         return Ok(false);
     }
 
-    let processed_content = shortcode_registry.process(&page.content)?;
+    let processed_content = shortcode_registry
+        .process(&page.content)
+        .with_context(|| format!("Failed to process shortcodes in {}", path.display()))?;
     let (html, _headings) = renderer.render_markdown_with_components(
         &processed_content,
         generator.get_tera(),
@@ -136,7 +159,7 @@ fn build_single_page(
     page.rendered_html = Some(html);
 
     let output_path = generator.generate_page(&page, page_data)?;
-    println!("   ✓ {}", output_path.display());
+    detail!("   ✓ {}", output_path.display());
 
     if generator.should_generate_partials() {
         generator.generate_page_partial(&page, page_data)?;
@@ -145,6 +168,162 @@ fn build_single_page(
     Ok(true)
 }
 
+/// Resolve `[[wikilinks]]`, warning about targets that match no post (or
+/// failing under `--strict`)
+fn resolve_wikilinks(
+    content: &str,
+    metadata: &MetadataCache,
+    config: &SsgConfig,
+    path: &Path,
+    strict: bool,
+) -> Result<String> {
+    let (content, unresolved) = wikilinks::resolve(content, metadata, &config.build);
+    if strict && !unresolved.is_empty() {
+        anyhow::bail!(
+            "Unresolved wikilinks in {}: {}",
+            path.display(),
+            unresolved
+                .iter()
+                .map(|target| format!("[[{}]]", target))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+    for target in unresolved {
+        eprintln!(
+            "⚠️  Unresolved wikilink [[{}]] in {}",
+            target,
+            path.display()
+        );
+    }
+    Ok(content)
+}
+
+/// Warn about (or fail on, under `--strict`) a cover or OG image that exists
+/// neither in the content directory nor in `static/`. Expects the paths
+/// already resolved by [`resolve_post_images`]; remote images aren't checked.
+fn check_post_images(post: &Post, config: &SsgConfig, path: &Path, strict: bool) -> Result<()> {
+    let content_dir = Path::new(&config.build.content_dir);
+    let images = [
+        ("cover image", post.frontmatter.cover_image.as_deref()),
+        ("OG image", post.frontmatter.og_image.as_deref()),
+    ];
+
+    for (kind, src) in images {
+        let Some(src) = src.filter(|src| src.starts_with('/') && !src.starts_with("//")) else {
+            continue;
+        };
+        let relative = slug::decode_from_url(src.trim_start_matches('/'));
+        if content_dir.join(&relative).exists() || Path::new("static").join(&relative).exists() {
+            continue;
+        }
+
+        if strict {
+            anyhow::bail!("Missing {} {} in {}", kind, src, path.display());
+        }
+        eprintln!("⚠️  Missing {} {} in {}", kind, src, path.display());
+    }
+
+    Ok(())
+}
+
+/// Drop markdown files sitting directly in `posts_dir`: without a category
+/// directory they can't be built. Each is warned about and skipped, or under
+/// `--strict` the build fails listing all of them.
+fn without_mislocated_posts(
+    posts_dir: &Path,
+    paths: Vec<PathBuf>,
+    strict: bool,
+) -> Result<Vec<PathBuf>> {
+    let (mislocated, paths): (Vec<_>, Vec<_>) = paths
+        .into_iter()
+        .partition(|path| path.parent() == Some(posts_dir));
+
+    if mislocated.is_empty() {
+        return Ok(paths);
+    }
+
+    let hint = format!(
+        "Move posts into a category directory, e.g. {}",
+        posts_dir.join("dev").display()
+    );
+    if strict {
+        anyhow::bail!(
+            "Posts outside a category directory:\n{}\n{}",
+            mislocated
+                .iter()
+                .map(|path| format!("  {}", path.display()))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            hint
+        );
+    }
+    for path in &mislocated {
+        eprintln!(
+            "⚠️  Skipping {}: posts must be inside a category directory",
+            path.display()
+        );
+    }
+    eprintln!("   {}", hint);
+
+    Ok(paths)
+}
+
+/// Markdown files under `posts_dir`, sorted by file name for reproducible
+/// builds
+fn markdown_files(posts_dir: &Path) -> Vec<PathBuf> {
+    WalkDir::new(posts_dir)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| parser::is_markdown(e.path()))
+        .map(|e| e.path().to_path_buf())
+        .collect()
+}
+
+/// What the metadata pass saw: the normalized source paths it parsed, and
+/// each post's slug with its path for the duplicate-slug check
+type MetadataSources = (HashSet<String>, Vec<(String, PathBuf)>);
+
+/// Parse `paths` on `threads` threads and upsert every post that belongs in
+/// the build into `metadata`, in `paths` order. A file that fails to parse
+/// fails the pass with its path; with several, the first in `paths` order
+/// is reported regardless of thread count.
+fn populate_metadata(
+    metadata: &mut MetadataCache,
+    paths: &[PathBuf],
+    drafts: bool,
+    threads: usize,
+) -> Result<MetadataSources> {
+    let parsed = parallel_map(paths, threads, |path| -> Result<_> {
+        let mut post = Parser::parse_file(path)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+        if post.frontmatter.hidden && !drafts {
+            return Ok(None);
+        }
+        resolve_post_images(&mut post);
+        let stats = reading_time::analyze(&post.content);
+        Ok(Some((post, stats)))
+    });
+
+    let mut existing_sources = HashSet::new();
+    let mut slug_sources = Vec::new();
+    for (path, parsed) in paths.iter().zip(parsed) {
+        let Some((post, stats)) = parsed? else {
+            continue;
+        };
+        existing_sources.insert(normalize_path(path));
+        slug_sources.push((post.slug.clone(), path.clone()));
+        // Unlisted posts get a page but stay out of everything built from
+        // the metadata: listings, navigation, feeds, sitemap and search
+        if !post.frontmatter.unlisted {
+            metadata.upsert_post(post.slug, post.category, post.frontmatter, Some(stats));
+        }
+    }
+
+    Ok((existing_sources, slug_sources))
+}
+
 fn resolve_post_images(post: &mut Post) {
     post.frontmatter.cover_image = post
         .frontmatter
@@ -162,10 +341,14 @@ struct PostProcessingContext<'a> {
     renderer: &'a Renderer,
     generator: &'a Generator,
     shortcode_registry: &'a ShortcodeRegistry,
+    image_processor: Option<&'a ImageProcessor>,
     config: &'a SsgConfig,
     cache: &'a Arc<Mutex<BuildCache>>,
     metadata: &'a MetadataCache,
+    template_hash: &'a str,
     use_cache: bool,
+    drafts: bool,
+    strict: bool,
 }
 
 #[derive(ClapParser)]
@@ -177,6 +360,39 @@ struct Cli {
     command: Commands,
 }
 
+/// Directory overrides for `build` and `watch`, applied over config.yaml
+#[derive(Args, Default)]
+struct DirArgs {
+    /// Read posts from DIR instead of build.content_dir
+    #[arg(long, value_name = "DIR")]
+    content_dir: Option<String>,
+
+    /// Load templates from DIR instead of build.template_dir
+    #[arg(long, value_name = "DIR")]
+    template_dir: Option<String>,
+
+    /// Write the site to DIR instead of build.output_dir
+    #[arg(long, value_name = "DIR")]
+    output_dir: Option<String>,
+}
+
+impl DirArgs {
+    /// Load the config for `env` and apply the overrides to it
+    fn load_config(&self, env: Option<&str>) -> Result<SsgConfig> {
+        let mut config = load_config(env)?;
+        if let Some(dir) = &self.content_dir {
+            config.build.content_dir = dir.clone();
+        }
+        if let Some(dir) = &self.template_dir {
+            config.build.template_dir = dir.clone();
+        }
+        if let Some(dir) = &self.output_dir {
+            config.build.output_dir = dir.clone();
+        }
+        Ok(config)
+    }
+}
+
 #[derive(Subcommand)]
 enum Commands {
     /// Build the site
@@ -192,6 +408,30 @@ enum Commands {
         /// Use parallel processing for faster builds
         #[arg(long, default_value_t = true, action = clap::ArgAction::Set)]
         parallel: bool,
+
+        /// Merge config.<ENV>.yaml over config.yaml (e.g. staging, prod)
+        #[arg(long)]
+        env: Option<String>,
+
+        /// Include hidden posts in the output (never in sitemap or feeds)
+        #[arg(long)]
+        drafts: bool,
+
+        /// Fail on content problems that are otherwise warnings (missing
+        /// cover/OG images, unresolved wikilinks)
+        #[arg(long)]
+        strict: bool,
+
+        /// Only print errors (no progress bar)
+        #[arg(short, long, conflicts_with = "verbose")]
+        quiet: bool,
+
+        /// Print a line for every post as it is built or skipped
+        #[arg(short, long)]
+        verbose: bool,
+
+        #[command(flatten)]
+        dirs: DirArgs,
     },
 
     /// Watch for changes and rebuild
@@ -199,6 +439,17 @@ enum Commands {
         /// Port for dev server
         #[arg(short, long, default_value = "8080")]
         port: u16,
+
+        /// Merge config.<ENV>.yaml over config.yaml (e.g. staging, prod)
+        #[arg(long)]
+        env: Option<String>,
+
+        /// Include hidden posts in the output (never in sitemap or feeds)
+        #[arg(long)]
+        drafts: bool,
+
+        #[command(flatten)]
+        dirs: DirArgs,
     },
 
     /// Create a new post
@@ -208,6 +459,32 @@ enum Commands {
 
         /// Post title
         title: String,
+
+        /// Comma-separated tags (e.g. rust,web)
+        #[arg(long, value_delimiter = ',')]
+        tags: Vec<String>,
+
+        /// Short description for the frontmatter
+        #[arg(long)]
+        description: Option<String>,
+
+        /// Publish date as YYYY-MM-DD or RFC 3339 (default: now)
+        #[arg(long)]
+        date: Option<String>,
+
+        /// Create the post under DIR instead of build.content_dir
+        #[arg(long, value_name = "DIR")]
+        content_dir: Option<String>,
+    },
+
+    /// Create a new standalone page in the pages dir next to build.content_dir
+    NewPage {
+        /// Page title
+        title: String,
+
+        /// Create the page next to DIR instead of build.content_dir
+        #[arg(long, value_name = "DIR")]
+        content_dir: Option<String>,
     },
 }
 
@@ -219,35 +496,85 @@ fn main() -> Result<()> {
             incremental,
             post,
             parallel,
+            env,
+            drafts,
+            strict,
+            quiet,
+            verbose,
+            dirs,
         } => {
+            let env = env.as_deref();
+            verbosity::set(Verbosity::from_flags(quiet, verbose));
+
             if let Some(post_path) = post {
-                return build_single_post(&post_path);
+                return build_single_post(&post_path, env, &dirs, strict);
             }
 
             if incremental {
-                println!("Note: Incremental build uses cache to skip unchanged files");
+                status!("Note: Incremental build uses cache to skip unchanged files");
             }
 
             if parallel {
-                build_all_parallel(incremental)?;
+                build_all_parallel(incremental, env, &dirs, drafts, strict)?;
             } else {
-                build_all(incremental)?;
+                build_all(incremental, env, &dirs, drafts, strict)?;
             }
         }
-        Commands::Watch { port } => watch_mode(port)?,
-        Commands::New { category, title } => create_new_post(&category, &title)?,
+        Commands::Watch {
+            port,
+            env,
+            drafts,
+            dirs,
+        } => watch_mode(port, env.as_deref(), &dirs, drafts)?,
+        Commands::New {
+            category,
+            title,
+            tags,
+            description,
+            date,
+            content_dir,
+        } => {
+            let dirs = DirArgs {
+                content_dir,
+                ..DirArgs::default()
+            };
+            create_new_post(
+                &category,
+                &title,
+                &tags,
+                description.as_deref(),
+                date.as_deref(),
+                &dirs,
+            )?
+        }
+        Commands::NewPage { title, content_dir } => {
+            let dirs = DirArgs {
+                content_dir,
+                ..DirArgs::default()
+            };
+            create_new_page(&title, &dirs)?
+        }
     }
 
     Ok(())
 }
 
-fn build_all(use_cache: bool) -> Result<()> {
-    println!("Building site...\n");
-
-    let config = load_config()?;
-    let renderer = Renderer::new();
-    let shortcode_registry = ShortcodeRegistry::new();
+fn build_all(
+    use_cache: bool,
+    env: Option<&str>,
+    dirs: &DirArgs,
+    drafts: bool,
+    strict: bool,
+) -> Result<()> {
+    status!("Building site...\n");
+
+    let config = dirs.load_config(env)?;
+    let renderer = Renderer::from_config(&config);
+    let shortcode_registry = ShortcodeRegistry::from_config(&config);
     let generator = Generator::new(config.clone())?;
+    let dimension_cache = Arc::new(Mutex::new(ImageDimensionCache::load()));
+    let image_processor = ImageProcessor::from_config(&config)
+        .map(|p| p.with_dimension_cache(Arc::clone(&dimension_cache)));
 
     let posts_dir = Path::new(&config.build.content_dir);
 
@@ -259,13 +586,21 @@ fn build_all(use_cache: bool) -> Result<()> {
         );
     }
 
-    let environment_hash = compute_environment_hash(posts_dir)?;
+    let mut environment_hash = compute_environment_hash(&config)?;
+    if drafts {
+        // Pages built with drafts link to hidden posts, so they must not be
+        // reused by (or reuse) a regular build's cache
+        environment_hash.push_str("-drafts");
+        status!("📝 Drafts mode: hidden posts are included\n");
+    }
+    let template_hash = compute_template_hash(&config)?;
     let mut cache = if use_cache {
         BuildCache::load(&environment_hash)
     } else {
         BuildCache::new(&environment_hash)
     };
     let mut metadata = MetadataCache::new();
+    metadata.set_tag_normalizer(TagNormalizer::from_config(&config.build));
 
     let categories = discover_categories(posts_dir)?;
     if categories.is_empty() {
@@ -275,56 +610,39 @@ fn build_all(use_cache: bool) -> Result<()> {
     }
     metadata.set_category_info(categories);
 
-    let mut existing_sources = std::collections::HashSet::new();
-
-    for entry in WalkDir::new(posts_dir)
-        .into_iter()
-        .filter_map(|e| e.ok())
-        .filter(|e| e.path().extension().is_some_and(|ext| ext == "md"))
-    {
-        if let Ok(mut post) = Parser::parse_file(entry.path()) {
-            if !post.frontmatter.hidden {
-                existing_sources.insert(normalize_path(entry.path()));
-                resolve_post_images(&mut post);
-                let reading_time = reading_time::estimate(&post.content);
-                metadata.upsert_post(
-                    post.slug,
-                    post.category,
-                    post.frontmatter,
-                    Some(reading_time),
-                );
-            }
-        }
-    }
+    let file_paths = without_mislocated_posts(posts_dir, markdown_files(posts_dir), strict)?;
+    let (existing_sources, slug_sources) =
+        populate_metadata(&mut metadata, &file_paths, drafts, 1)?;
+    ensure_unique_slugs(&slug_sources)?;
 
     let mut built_count = 0;
     let mut skipped_count = 0;
 
-    for entry in WalkDir::new(posts_dir)
-        .into_iter()
-        .filter_map(|e| e.ok())
-        .filter(|e| e.path().extension().is_some_and(|ext| ext == "md"))
-    {
-        let path = entry.path();
+    for path in &file_paths {
+        let path = path.as_path();
         let file_hash = hash_file(path)?;
 
-        if use_cache && !cache.needs_rebuild(path, &file_hash) {
-            println!("⏭  Skipping (unchanged): {}", path.display());
+        if use_cache && !cache.needs_rebuild(path, &file_hash, &template_hash) {
+            detail!("⏭  Skipping (unchanged): {}", path.display());
             skipped_count += 1;
             continue;
         }
 
-        println!("🔨 Building: {}", path.display());
+        detail!("🔨 Building: {}", path.display());
 
         let mut post = Parser::parse_file(path)?;
 
-        if post.frontmatter.hidden {
-            println!("   ⚠  Hidden - skipping output");
+        if post.frontmatter.hidden && !drafts {
+            detail!("   ⚠  Hidden - skipping output");
             skipped_count += 1;
             continue;
         }
 
-        let processed_content = shortcode_registry.process(&post.content)?;
+        let processed_content = shortcode_registry
+            .process(&post.content)
+            .with_context(|| format!("Failed to process shortcodes in {}", path.display()))?;
+        let processed_content =
+            resolve_wikilinks(&processed_content, &metadata, &config, path, strict)?;
 
         let base_path = post.category.clone();
         let content_dir = Path::new(&config.build.content_dir);
@@ -332,8 +650,9 @@ fn build_all(use_cache: bool) -> Result<()> {
             &processed_content,
             generator.get_tera(),
             &base_path,
-            config.site.cdn_url.as_deref(),
+            image_processor.as_ref(),
             Some(content_dir),
+            post.frontmatter.cover_image.as_deref(),
         )?;
 
         post.rendered_html = Some(html);
@@ -344,12 +663,14 @@ fn build_all(use_cache: bool) -> Result<()> {
             og_image: post.frontmatter.og_image.clone(),
         };
         resolve_post_images(&mut post);
+        check_post_images(&post, &config, path, strict)?;
+        post.frontmatter.tags = metadata.normalize_tags(&post.frontmatter.tags);
 
         let mut extra_data = build_post_extra_data(
             &post,
             &metadata,
-            config.site.cdn_url.as_deref(),
-            content_dir,
+            image_processor.as_ref(),
+            &config,
             Some(&original_paths),
         );
         extra_data.insert("toc".to_string(), json!(headings));
@@ -359,15 +680,22 @@ fn build_all(use_cache: bool) -> Result<()> {
             generator.generate_post_partial(&post, &extra_data)?;
         }
 
-        cache.update_entry(path, file_hash, output_path.to_string_lossy().to_string());
-
-        metadata.upsert_post(
-            post.slug.clone(),
-            post.category.clone(),
-            post.frontmatter.clone(),
-            None,
+        cache.update_entry(
+            path,
+            file_hash,
+            template_hash.clone(),
+            output_path.to_string_lossy().to_string(),
         );
 
+        if !post.frontmatter.unlisted {
+            metadata.upsert_post(
+                post.slug.clone(),
+                post.category.clone(),
+                post.frontmatter.clone(),
+                None,
+            );
+        }
+
         built_count += 1;
     }
 
@@ -376,16 +704,23 @@ fn build_all(use_cache: bool) -> Result<()> {
     if use_cache {
         cache.save()?;
     }
+    dimension_cache.lock().unwrap().save()?;
     metadata.save()?;
 
     let page_data = build_page_data(&metadata);
-    let page_errors = build_pages(&shortcode_registry, &renderer, &generator, &page_data);
+    let page_errors = build_pages(
+        &shortcode_registry,
+        &renderer,
+        &generator,
+        &page_data,
+        &config.build.pages_dir(),
+    );
 
     let index_generator = IndexGenerator::new(config.clone())?;
     index_generator.generate_all(&metadata)?;
     index_generator.generate_all_partials(&metadata)?;
 
-    println!("📄 Generating RSS feeds...");
+    status!("📄 Generating RSS feeds...");
     FeedGenerator::generate_all_feeds(
         &config,
         &metadata,
@@ -393,10 +728,10 @@ fn build_all(use_cache: bool) -> Result<()> {
         Path::new(&config.build.output_dir),
     )?;
 
-    println!("🗺  Generating sitemap...");
+    status!("🗺  Generating sitemap...");
     SitemapGenerator::generate(&config, &metadata, Path::new(&config.build.output_dir))?;
 
-    println!("🤖 Generating robots.txt...");
+    status!("🤖 Generating robots.txt...");
     RobotsGenerator::generate(&config, Path::new(&config.build.output_dir))?;
 
     if config.build.search.enabled {
@@ -407,25 +742,41 @@ fn build_all(use_cache: bool) -> Result<()> {
     let recent_generator = RecentGenerator::new(config.clone());
     recent_generator.generate(&metadata)?;
 
+    let api_generator = ApiGenerator::new(config.clone());
+    api_generator.generate(&metadata)?;
+
     let slug_index_generator = SlugIndexGenerator::new(config.clone());
     slug_index_generator.generate(&metadata)?;
 
     generator.copy_content_assets()?;
     generator.copy_static_assets()?;
 
+    precompress_output(&config)?;
+
     report_page_errors(&page_errors)?;
 
-    println!("\n✅ Build complete!");
-    println!("   Built: {}", built_count);
+    status!("\n✅ Build complete!");
+    status!("   Built: {}", built_count);
     if use_cache {
-        println!("   Skipped: {}", skipped_count);
+        status!("   Skipped: {}", skipped_count);
     }
-    println!("   Categories: {}", metadata.get_categories().len());
-    println!("   Tags: {}", metadata.get_tags().len());
+    status!("   Categories: {}", metadata.get_categories().len());
+    status!("   Tags: {}", metadata.get_tags().len());
 
     Ok(())
 }
 
+/// Post-build phase: write `.gz`/`.br` siblings when `build.precompress` is set
+fn precompress_output(config: &SsgConfig) -> Result<()> {
+    // Runs even when precompression is off, to clear out old siblings
+    let written = Precompressor::generate(config, Path::new(&config.build.output_dir))?;
+    if !config.build.precompress.is_empty() {
+        status!("🗜  Precompressing output...");
+        detail!("   Wrote {} compressed files", written);
+    }
+    Ok(())
+}
+
 /// Fails the build after every other output has been generated, so a broken
 /// page still exits nonzero (deploy gates) without leaving feeds/indices stale.
 fn report_page_errors(errors: &[(PathBuf, String)]) -> Result<()> {
@@ -440,12 +791,18 @@ fn report_page_errors(errors: &[(PathBuf, String)]) -> Result<()> {
     anyhow::bail!("{} pages failed to build", errors.len());
 }
 
-fn build_all_parallel(use_cache: bool) -> Result<()> {
+fn build_all_parallel(
+    use_cache: bool,
+    env: Option<&str>,
+    dirs: &DirArgs,
+    drafts: bool,
+    strict: bool,
+) -> Result<()> {
     let start_time = std::time::Instant::now();
     let num_threads = get_thread_count();
-    println!("Building site with {} threads...\n", num_threads);
+    status!("Building site with {} threads...\n", num_threads);
 
-    let config = Arc::new(load_config()?);
+    let config = Arc::new(dirs.load_config(env)?);
     let posts_dir = Path::new(&config.build.content_dir);
 
     if !posts_dir.exists() {
@@ -455,44 +812,34 @@ fn build_all_parallel(use_cache: bool) -> Result<()> {
         );
     }
 
-    let environment_hash = compute_environment_hash(posts_dir)?;
+    let mut environment_hash = compute_environment_hash(&config)?;
+    if drafts {
+        // Pages built with drafts link to hidden posts, so they must not be
+        // reused by (or reuse) a regular build's cache
+        environment_hash.push_str("-drafts");
+        status!("📝 Drafts mode: hidden posts are included\n");
+    }
 
     let categories = discover_categories(posts_dir)?;
     let mut metadata = MetadataCache::new();
+    metadata.set_tag_normalizer(TagNormalizer::from_config(&config.build));
     metadata.set_category_info(categories);
 
+    let template_hash = compute_template_hash(&config)?;
     let cache = Arc::new(Mutex::new(if use_cache {
         BuildCache::load(&environment_hash)
     } else {
         BuildCache::new(&environment_hash)
     }));
 
-    let shortcode_registry = Arc::new(ShortcodeRegistry::new());
+    let shortcode_registry = Arc::new(ShortcodeRegistry::from_config(&config));
+    let dimension_cache = Arc::new(Mutex::new(ImageDimensionCache::load()));
 
-    let file_paths: Vec<PathBuf> = WalkDir::new(posts_dir)
-        .into_iter()
-        .filter_map(|e| e.ok())
-        .filter(|e| e.path().extension().is_some_and(|ext| ext == "md"))
-        .map(|e| e.path().to_path_buf())
-        .collect();
+    let file_paths = without_mislocated_posts(posts_dir, markdown_files(posts_dir), strict)?;
 
-    let mut existing_sources = std::collections::HashSet::new();
-
-    for path in &file_paths {
-        if let Ok(mut post) = Parser::parse_file(path) {
-            if !post.frontmatter.hidden {
-                existing_sources.insert(normalize_path(path));
-                resolve_post_images(&mut post);
-                let reading_time = reading_time::estimate(&post.content);
-                metadata.upsert_post(
-                    post.slug,
-                    post.category,
-                    post.frontmatter,
-                    Some(reading_time),
-                );
-            }
-        }
-    }
+    let (existing_sources, slug_sources) =
+        populate_metadata(&mut metadata, &file_paths, drafts, num_threads)?;
+    ensure_unique_slugs(&slug_sources)?;
 
     let metadata_for_nav = Arc::new(metadata.clone());
 
@@ -502,6 +849,7 @@ fn build_all_parallel(use_cache: bool) -> Result<()> {
     let work_rx = work_queue.get_receiver();
     let (result_tx, result_rx) = mpsc::channel();
 
+    let progress_bar = ProgressBar::new(file_paths.len(), !verbosity::current().is_quiet());
     for path in file_paths {
         work_queue.send(path)?;
     }
@@ -517,9 +865,13 @@ fn build_all_parallel(use_cache: bool) -> Result<()> {
         let shortcode_registry = Arc::clone(&shortcode_registry);
         let progress = Arc::clone(&progress);
         let metadata_for_nav = Arc::clone(&metadata_for_nav);
+        let dimension_cache = Arc::clone(&dimension_cache);
+        let template_hash = template_hash.clone();
 
         pool.spawn(move || {
-            let renderer = Renderer::new();
+            let renderer = Renderer::from_config(&config);
+            let image_processor = ImageProcessor::from_config(&config)
+                .map(|p| p.with_dimension_cache(dimension_cache));
             let generator = match Generator::new((*config).clone()) {
                 Ok(g) => g,
                 Err(e) => {
@@ -543,10 +895,14 @@ fn build_all_parallel(use_cache: bool) -> Result<()> {
                     renderer: &renderer,
                     generator: &generator,
                     shortcode_registry: &shortcode_registry,
+                    image_processor: image_processor.as_ref(),
                     config: &config,
                     cache: &cache,
                     metadata: &metadata_for_nav,
+                    template_hash: &template_hash,
                     use_cache,
+                    drafts,
+                    strict,
                 };
                 let result = process_post_parallel(&path, &ctx);
 
@@ -566,10 +922,16 @@ fn build_all_parallel(use_cache: bool) -> Result<()> {
     let mut results = Vec::new();
     for result in result_rx {
         results.push(result);
+        progress_bar.update(results.len());
     }
+    progress_bar.finish();
 
     pool.join().map_err(|e| anyhow::anyhow!(e))?;
 
+    // Workers finish in arbitrary order; apply results by path so logs and
+    // metadata insertion order are reproducible
+    results.sort_by(|a, b| a.path().cmp(b.path()));
+
     let mut errors = Vec::new();
     for result in results {
         match result {
@@ -581,16 +943,20 @@ fn build_all_parallel(use_cache: bool) -> Result<()> {
                 file_hash,
                 output_path,
             } => {
-                println!("🔨 Built: {}", path.display());
-                metadata.upsert_post(slug, category, *frontmatter, None);
-                cache
-                    .lock()
-                    .unwrap()
-                    .update_entry(&path, file_hash, output_path);
+                detail!("🔨 Built: {}", path.display());
+                if !frontmatter.unlisted {
+                    metadata.upsert_post(slug, category, *frontmatter, None);
+                }
+                cache.lock().unwrap().update_entry(
+                    &path,
+                    file_hash,
+                    template_hash.clone(),
+                    output_path,
+                );
             }
             BuildResult::Skipped { path, reason } => match reason {
-                SkipReason::Cached => println!("⏭  Skipped (unchanged): {}", path.display()),
-                SkipReason::Draft => println!("   ⚠  Draft - skipping: {}", path.display()),
+                SkipReason::Cached => detail!("⏭  Skipped (unchanged): {}", path.display()),
+                SkipReason::Draft => detail!("   ⚠  Draft - skipping: {}", path.display()),
             },
             BuildResult::Error { path, error } => {
                 eprintln!("❌ Error building {}: {}", path.display(), error);
@@ -608,18 +974,25 @@ fn build_all_parallel(use_cache: bool) -> Result<()> {
     if use_cache {
         cache.lock().unwrap().save()?;
     }
+    dimension_cache.lock().unwrap().save()?;
     metadata.save()?;
 
-    let renderer = Renderer::new();
+    let renderer = Renderer::from_config(&config);
     let generator = Generator::new((*config).clone())?;
     let page_data = build_page_data(&metadata);
-    let page_errors = build_pages(&shortcode_registry, &renderer, &generator, &page_data);
+    let page_errors = build_pages(
+        &shortcode_registry,
+        &renderer,
+        &generator,
+        &page_data,
+        &config.build.pages_dir(),
+    );
 
     let index_generator = IndexGenerator::new((*config).clone())?;
     index_generator.generate_all(&metadata)?;
     index_generator.generate_all_partials(&metadata)?;
 
-    println!("📄 Generating RSS feeds...");
+    status!("📄 Generating RSS feeds...");
     FeedGenerator::generate_all_feeds(
         &config,
         &metadata,
@@ -627,10 +1000,10 @@ fn build_all_parallel(use_cache: bool) -> Result<()> {
         Path::new(&config.build.output_dir),
     )?;
 
-    println!("🗺  Generating sitemap...");
+    status!("🗺  Generating sitemap...");
     SitemapGenerator::generate(&config, &metadata, Path::new(&config.build.output_dir))?;
 
-    println!("🤖 Generating robots.txt...");
+    status!("🤖 Generating robots.txt...");
     RobotsGenerator::generate(&config, Path::new(&config.build.output_dir))?;
 
     if config.build.search.enabled {
@@ -641,22 +1014,27 @@ fn build_all_parallel(use_cache: bool) -> Result<()> {
     let recent_generator = RecentGenerator::new((*config).clone());
     recent_generator.generate(&metadata)?;
 
+    let api_generator = ApiGenerator::new((*config).clone());
+    api_generator.generate(&metadata)?;
+
     let slug_index_generator = SlugIndexGenerator::new((*config).clone());
     slug_index_generator.generate(&metadata)?;
 
     generator.copy_content_assets()?;
     generator.copy_static_assets()?;
 
+    precompress_output(&config)?;
+
     report_page_errors(&page_errors)?;
 
     let elapsed = start_time.elapsed();
-    println!("\n✅ Build complete in {:.2}s!", elapsed.as_secs_f64());
-    println!("   Built: {}", progress.get_built());
+    status!("\n✅ Build complete in {:.2}s!", elapsed.as_secs_f64());
+    status!("   Built: {}", progress.get_built());
     if use_cache {
-        println!("   Skipped: {}", progress.get_skipped());
+        status!("   Skipped: {}", progress.get_skipped());
     }
-    println!("   Categories: {}", metadata.get_categories().len());
-    println!("   Tags: {}", metadata.get_tags().len());
+    status!("   Categories: {}", metadata.get_categories().len());
+    status!("   Tags: {}", metadata.get_tags().len());
 
     Ok(())
 }
@@ -668,7 +1046,7 @@ macro_rules! try_or_error {
             Err(e) => {
                 return BuildResult::Error {
                     path: $path.to_path_buf(),
-                    error: e.to_string(),
+                    error: format!("{:#}", e),
                 }
             }
         }
@@ -680,7 +1058,7 @@ fn process_post_parallel(path: &Path, ctx: &PostProcessingContext) -> BuildResul
 
     if ctx.use_cache {
         let cache = ctx.cache.lock().unwrap();
-        if !cache.needs_rebuild(path, &file_hash) {
+        if !cache.needs_rebuild(path, &file_hash, ctx.template_hash) {
             return BuildResult::Skipped {
                 path: path.to_path_buf(),
                 reason: SkipReason::Cached,
@@ -690,7 +1068,7 @@ fn process_post_parallel(path: &Path, ctx: &PostProcessingContext) -> BuildResul
 
     let mut post = try_or_error!(path, Parser::parse_file(path));
 
-    if post.frontmatter.hidden {
+    if post.frontmatter.hidden && !ctx.drafts {
         return BuildResult::Skipped {
             path: path.to_path_buf(),
             reason: SkipReason::Draft,
@@ -698,6 +1076,16 @@ fn process_post_parallel(path: &Path, ctx: &PostProcessingContext) -> BuildResul
     }
 
     let processed_content = try_or_error!(path, ctx.shortcode_registry.process(&post.content));
+    let processed_content = try_or_error!(
+        path,
+        resolve_wikilinks(
+            &processed_content,
+            ctx.metadata,
+            ctx.config,
+            path,
+            ctx.strict
+        )
+    );
 
     let base_path = post.category.clone();
     let content_dir = Path::new(&ctx.config.build.content_dir);
@@ -707,8 +1095,9 @@ fn process_post_parallel(path: &Path, ctx: &PostProcessingContext) -> BuildResul
             &processed_content,
             ctx.generator.get_tera(),
             &base_path,
-            ctx.config.site.cdn_url.as_deref(),
+            ctx.image_processor,
             Some(content_dir),
+            post.frontmatter.cover_image.as_deref(),
         )
     );
 
@@ -720,12 +1109,14 @@ fn process_post_parallel(path: &Path, ctx: &PostProcessingContext) -> BuildResul
         og_image: post.frontmatter.og_image.clone(),
     };
     resolve_post_images(&mut post);
+    try_or_error!(path, check_post_images(&post, ctx.config, path, ctx.strict));
+    post.frontmatter.tags = ctx.metadata.normalize_tags(&post.frontmatter.tags);
 
     let mut extra_data = build_post_extra_data(
         &post,
         ctx.metadata,
-        ctx.config.site.cdn_url.as_deref(),
-        content_dir,
+        ctx.image_processor,
+        ctx.config,
         Some(&original_paths),
     );
     extra_data.insert("toc".to_string(), json!(headings));
@@ -754,7 +1145,7 @@ fn process_post_parallel(path: &Path, ctx: &PostProcessingContext) -> BuildResul
 /// was removed or became hidden since the last cached build.
 fn remove_stale_outputs(
     cache: &mut BuildCache,
-    existing_sources: &std::collections::HashSet<String>,
+    existing_sources: &HashSet<String>,
     config: &SsgConfig,
 ) {
     let output_dir = Path::new(&config.build.output_dir);
@@ -779,7 +1170,13 @@ fn remove_output_file(path: &Path, output_dir: &Path) {
         eprintln!("⚠️  Failed to remove stale output: {}", path.display());
         return;
     }
-    println!("🧹 Removed stale output: {}", path.display());
+    detail!("🧹 Removed stale output: {}", path.display());
+
+    for sibling in Precompressor::siblings(path) {
+        if sibling.exists() && std::fs::remove_file(&sibling).is_err() {
+            eprintln!("⚠️  Failed to remove stale output: {}", sibling.display());
+        }
+    }
 
     // Drop now-empty directories (e.g. dist/dev/deleted-post/)
     let mut dir = path.parent();
@@ -791,14 +1188,23 @@ fn remove_output_file(path: &Path, output_dir: &Path) {
     }
 }
 
-fn build_single_post(post_path: &str) -> Result<()> {
-    println!("Building single post: {}\n", post_path);
-
-    let config = load_config()?;
-    let renderer = Renderer::new();
-    let shortcode_registry = ShortcodeRegistry::new();
+fn build_single_post(
+    post_path: &str,
+    env: Option<&str>,
+    dirs: &DirArgs,
+    strict: bool,
+) -> Result<()> {
+    status!("Building single post: {}\n", post_path);
+
+    let config = dirs.load_config(env)?;
+    let renderer = Renderer::from_config(&config);
+    let shortcode_registry = ShortcodeRegistry::from_config(&config);
     let generator = Generator::new(config.clone())?;
-    let metadata = MetadataCache::load().unwrap_or_else(|_| MetadataCache::new());
+    let dimension_cache = Arc::new(Mutex::new(ImageDimensionCache::load()));
+    let image_processor = ImageProcessor::from_config(&config)
+        .map(|p| p.with_dimension_cache(Arc::clone(&dimension_cache)));
+    let mut metadata = MetadataCache::load().unwrap_or_else(|_| MetadataCache::new());
+    metadata.set_tag_normalizer(TagNormalizer::from_config(&config.build));
 
     let path = Path::new(post_path);
 
@@ -809,10 +1215,14 @@ fn build_single_post(post_path: &str) -> Result<()> {
     let mut post = Parser::parse_file(path)?;
 
     if post.frontmatter.hidden {
-        println!("⚠  This is a hidden post");
+        status!("⚠  This is a hidden post");
     }
 
-    let processed_content = shortcode_registry.process(&post.content)?;
+    let processed_content = shortcode_registry
+        .process(&post.content)
+        .with_context(|| format!("Failed to process shortcodes in {}", path.display()))?;
+    let processed_content =
+        resolve_wikilinks(&processed_content, &metadata, &config, path, strict)?;
 
     let base_path = post.category.clone();
     let content_dir = Path::new(&config.build.content_dir);
@@ -820,8 +1230,9 @@ fn build_single_post(post_path: &str) -> Result<()> {
         &processed_content,
         generator.get_tera(),
         &base_path,
-        config.site.cdn_url.as_deref(),
+        image_processor.as_ref(),
         Some(content_dir),
+        post.frontmatter.cover_image.as_deref(),
     )?;
 
     post.rendered_html = Some(html);
@@ -832,18 +1243,24 @@ fn build_single_post(post_path: &str) -> Result<()> {
         og_image: post.frontmatter.og_image.clone(),
     };
     resolve_post_images(&mut post);
+    check_post_images(&post, &config, path, strict)?;
+    post.frontmatter.tags = metadata.normalize_tags(&post.frontmatter.tags);
 
     let mut extra_data = build_post_extra_data(
         &post,
         &metadata,
-        config.site.cdn_url.as_deref(),
-        content_dir,
+        image_processor.as_ref(),
+        &config,
         Some(&original_paths),
     );
     extra_data.insert("toc".to_string(), json!(headings));
     let output_path = generator.generate_post(&post, &extra_data)?;
+    if generator.should_generate_partials() {
+        generator.generate_post_partial(&post, &extra_data)?;
+    }
+    dimension_cache.lock().unwrap().save()?;
 
-    println!("\n✅ Built: {}", output_path.display());
+    status!("\n✅ Built: {}", output_path.display());
 
     Ok(())
 }
@@ -857,21 +1274,48 @@ struct OriginalImagePaths {
 struct RelatedPostData {
     #[serde(flatten)]
     post: crate::metadata::PostMetadata,
+    url: String,
     thumbnail_metadata: Option<ThumbnailMetadata>,
 }
 
 fn build_post_extra_data(
     post: &crate::types::Post,
     metadata: &MetadataCache,
-    cdn_url: Option<&str>,
-    content_dir: &Path,
+    image_processor: Option<&ImageProcessor>,
+    config: &SsgConfig,
     original_paths: Option<&OriginalImagePaths>,
 ) -> HashMap<String, serde_json::Value> {
+    let content_dir = Path::new(&config.build.content_dir);
     let mut data = HashMap::new();
 
+    // Reuse the counts from the metadata pass instead of re-parsing the body
+    let stats = metadata
+        .posts
+        .iter()
+        .find(|p| p.slug == post.slug)
+        .map(|p| (p.reading_time, p.word_count, p.excerpt.clone()));
+    let (reading_time, word_count, excerpt) = stats.unwrap_or_else(|| {
+        let stats = reading_time::analyze(&post.content);
+        (stats.reading_time, stats.word_count, stats.excerpt)
+    });
+    data.insert("reading_time".to_string(), json!(reading_time));
+    data.insert("word_count".to_string(), json!(word_count));
+    data.insert("excerpt".to_string(), json!(excerpt));
+    data.insert("noindex".to_string(), json!(post.frontmatter.unlisted));
+
+    let post_url = permalink::post_url(
+        &config.build,
+        &post.category,
+        &post.slug,
+        &post.frontmatter.date.posted,
+        false,
+    );
     data.insert(
-        "reading_time".to_string(),
-        json!(reading_time::estimate(&post.content)),
+        "alternates".to_string(),
+        json!(post.frontmatter.alternates(
+            &config.site.url,
+            &format!("{}{}", config.site.url, post_url)
+        )),
     );
 
     if let Some(cat_info) = metadata
@@ -883,25 +1327,24 @@ fn build_post_extra_data(
     }
 
     // Build navigation with or without CDN processing
-    let navigation = if let Some(url) = cdn_url {
-        let image_processor = ImageProcessor::new(Some(url.to_string()));
+    let navigation = if let Some(image_processor) = image_processor {
         build_post_navigation_with_cdn(
             &post.slug,
             &post.category,
             metadata,
             true,
-            &image_processor,
+            &config.build,
+            image_processor,
             content_dir,
         )
     } else {
-        build_post_navigation(&post.slug, &post.category, metadata, true)
+        build_post_navigation(&post.slug, &post.category, metadata, true, &config.build)
     };
     data.insert("prev_post".to_string(), json!(navigation.prev));
     data.insert("next_post".to_string(), json!(navigation.next));
 
     // Process cover image for CDN if available
-    if let (Some(url), Some(paths)) = (cdn_url, original_paths) {
-        let image_processor = ImageProcessor::new(Some(url.to_string()));
+    if let (Some(image_processor), Some(paths)) = (image_processor, original_paths) {
         let base_path = post.category.clone();
         let post_content_dir = content_dir.join(&post.category);
 
@@ -935,8 +1378,7 @@ fn build_post_extra_data(
         .into_iter()
         .take(RELATED_POSTS_COUNT)
         .map(|p| {
-            let thumbnail_metadata = cdn_url.and_then(|url| {
-                let image_processor = ImageProcessor::new(Some(url.to_string()));
+            let thumbnail_metadata = image_processor.and_then(|image_processor| {
                 let cover_src = p
                     .frontmatter
                     .cover_image
@@ -965,6 +1407,13 @@ fn build_post_extra_data(
 
             RelatedPostData {
                 post: p.clone(),
+                url: permalink::post_url(
+                    &config.build,
+                    &p.category,
+                    &p.slug,
+                    &p.frontmatter.date.posted,
+                    false,
+                ),
                 thumbnail_metadata,
             }
         })
@@ -974,8 +1423,25 @@ fn build_post_extra_data(
     data
 }
 
-fn create_new_post(category: &str, title: &str) -> Result<()> {
-    let config = load_config()?;
+fn create_new_post(
+    category: &str,
+    title: &str,
+    tags: &[String],
+    description: Option<&str>,
+    date: Option<&str>,
+    dirs: &DirArgs,
+) -> Result<()> {
+    let date = match date {
+        Some(input) => parse_post_date(input)?,
+        None => chrono::Utc::now(),
+    };
+    let tags: Vec<&str> = tags
+        .iter()
+        .map(|t| t.trim())
+        .filter(|t| !t.is_empty())
+        .collect();
+
+    let config = dirs.load_config(None)?;
     let posts_dir = Path::new(&config.build.content_dir);
 
     let categories = discover_categories(posts_dir)?;
@@ -1030,39 +1496,42 @@ fn create_new_post(category: &str, title: &str) -> Result<()> {
         std::process::exit(0);
     }
 
-    let slug = title
-        .to_lowercase()
-        .replace(' ', "-")
-        .chars()
-        .filter(|c| c.is_alphanumeric() || *c == '-')
-        .collect::<String>();
+    let slug = slug::slugify_title(title);
 
-    let filename = format!("content/posts/{}/{}.md", category, slug);
+    let category_dir = posts_dir.join(category);
+    let filename = category_dir.join(format!("{}.md", slug));
 
-    if Path::new(&filename).exists() {
-        anyhow::bail!("Post already exists: {}", filename);
+    if filename.exists() {
+        anyhow::bail!("Post already exists: {}", filename.display());
     }
 
+    // JSON strings and arrays are valid YAML flow scalars, so they quote safely
+    let description_line = description
+        .map(|d| format!("description: {}\n", serde_json::to_string(d).unwrap()))
+        .unwrap_or_default();
+
     let content = format!(
         r#"---
-title: "{}"
+title: {}
 date: {}
 category: {}
-tags: []
-hidden: false
+tags: {}
+{}hidden: false
 ---
 
 Write your post here...
 "#,
-        title,
-        chrono::Utc::now().to_rfc3339(),
-        category
+        serde_json::to_string(title).unwrap(),
+        date.to_rfc3339(),
+        category,
+        serde_json::to_string(&tags).unwrap(),
+        description_line
     );
 
-    std::fs::create_dir_all(format!("content/posts/{}", category))?;
+    std::fs::create_dir_all(&category_dir)?;
     std::fs::write(&filename, content)?;
 
-    println!("✅ Created: {}", filename);
+    println!("✅ Created: {}", filename.display());
     println!("   Title: {}", title);
     println!("   Category: {}", category);
     println!("   Slug: {}", slug);
@@ -1070,25 +1539,121 @@ Write your post here...
     Ok(())
 }
 
-fn watch_mode(port: u16) -> Result<()> {
+fn create_new_page(title: &str, dirs: &DirArgs) -> Result<()> {
+    let config = dirs.load_config(None)?;
+    let slug = slug::slugify_title(title);
+    let pages_dir = config.build.pages_dir();
+    let filename = pages_dir.join(format!("{}.md", slug));
+
+    if filename.exists() {
+        anyhow::bail!("Page already exists: {}", filename.display());
+    }
+
+    // A JSON string is a valid YAML flow scalar, so quotes and backslashes
+    // in the title survive
+    let content = format!(
+        r#"---
+title: {}
+hidden: false
+---
+
+Write your page here...
+"#,
+        serde_json::to_string(title).unwrap()
+    );
+
+    std::fs::create_dir_all(&pages_dir)?;
+    std::fs::write(&filename, content)?;
+
+    println!("✅ Created: {}", filename.display());
+    println!("   Title: {}", title);
+    println!("   Slug: {}", slug);
+
+    Ok(())
+}
+
+/// Fails the build when two source files share a slug, listing every
+/// conflicting file.
+fn ensure_unique_slugs(sources: &[(String, PathBuf)]) -> Result<()> {
+    let duplicates = find_duplicate_slugs(sources);
+    if duplicates.is_empty() {
+        return Ok(());
+    }
+
+    let mut message = String::from("Duplicate post slugs found (slugs must be unique site-wide):");
+    for (slug, paths) in duplicates {
+        message.push_str(&format!("\n  '{}':", slug));
+        for path in paths {
+            message.push_str(&format!("\n    - {}", path.display()));
+        }
+    }
+
+    anyhow::bail!(message)
+}
+
+/// Accepts a plain `YYYY-MM-DD` (midnight UTC) or a full RFC 3339 timestamp.
+fn parse_post_date(input: &str) -> Result<chrono::DateTime<chrono::Utc>> {
+    if let Ok(date) = chrono::DateTime::parse_from_rfc3339(input) {
+        return Ok(date.with_timezone(&chrono::Utc));
+    }
+
+    chrono::NaiveDate::parse_from_str(input, "%Y-%m-%d")
+        .map(|date| date.and_hms_opt(0, 0, 0).unwrap().and_utc())
+        .map_err(|_| {
+            anyhow::anyhow!(
+                "Invalid --date '{}': expected YYYY-MM-DD or an RFC 3339 timestamp",
+                input
+            )
+        })
+}
+
+fn watch_mode(port: u16, env: Option<&str>, dirs: &DirArgs, drafts: bool) -> Result<()> {
     use notify::{Event, RecursiveMode, Result as NotifyResult, Watcher};
     use std::sync::mpsc::channel;
     use std::time::Duration;
 
+    let config = dirs.load_config(env)?;
+    let root = std::env::current_dir()?;
+
+    // Config and manifest changes can affect every page, so they force a
+    // full rebuild
+    let mut config_files = vec![root.join("config.yaml"), root.join("manifest.json")];
+    if let Some(env) = env {
+        config_files.push(root.join(format!("config.{}.yaml", env)));
+    }
+    config_files.retain(|path| path.exists());
+
+    let mut watch_dirs = vec![
+        config.build.content_dir.clone(),
+        config.build.pages_dir().to_string_lossy().into_owned(),
+        config.build.includes_dir.clone(),
+        config.build.template_dir.clone(),
+        "static".to_string(),
+        data::DATA_DIR.to_string(),
+    ];
+    watch_dirs.extend(config.build.watch_paths.iter().cloned());
+    watch_dirs.retain(|dir| Path::new(dir).exists());
+
     println!("🔍 Watch mode starting...");
     println!("   Watching for changes in:");
-    println!("   - content/");
-    println!("   - templates/");
-    println!("   - static/");
+    for dir in &watch_dirs {
+        println!("   - {}/", dir.trim_end_matches('/'));
+    }
+    for path in &config_files {
+        if let Some(name) = path.file_name() {
+            println!("   - {}", name.to_string_lossy());
+        }
+    }
     println!("\n   Serving on http://localhost:{}", port);
     println!("   Press Ctrl+C to stop\n");
 
     println!("📦 Initial build...");
-    build_all(true)?;
+    build_all(true, env, dirs, drafts, false)?;
     println!();
 
+    let server_root = PathBuf::from(&config.build.output_dir);
     let server_thread = std::thread::spawn(move || {
-        if let Err(e) = start_dev_server(port) {
+        if let Err(e) = start_dev_server(port, &server_root) {
             eprintln!("Dev server error: {}", e);
         }
     });
@@ -1101,22 +1666,29 @@ fn watch_mode(port: u16) -> Result<()> {
         }
     })?;
 
-    watcher.watch(Path::new("content"), RecursiveMode::Recursive)?;
-    watcher.watch(Path::new("templates"), RecursiveMode::Recursive)?;
-
-    if Path::new("static").exists() {
-        watcher.watch(Path::new("static"), RecursiveMode::Recursive)?;
+    for dir in &watch_dirs {
+        watcher.watch(&root.join(dir), RecursiveMode::Recursive)?;
+    }
+    for path in &config_files {
+        watcher.watch(path, RecursiveMode::NonRecursive)?;
     }
 
     loop {
         match rx.recv_timeout(Duration::from_secs(1)) {
             Ok(event) => {
-                if !should_rebuild(&event) {
-                    continue;
-                }
+                let use_cache = match rebuild_for(&event, &root, &config_files) {
+                    Some(Rebuild::Incremental) => {
+                        println!("📝 File changed, rebuilding...");
+                        true
+                    }
+                    Some(Rebuild::Full) => {
+                        println!("⚙️  Config changed, rebuilding everything...");
+                        false
+                    }
+                    None => continue,
+                };
 
-                println!("📝 File changed, rebuilding...");
-                match build_all(true) {
+                match build_all(use_cache, env, dirs, drafts, false) {
                     Ok(_) => println!("✅ Rebuild complete!\n"),
                     Err(e) => eprintln!("❌ Build error: {}\n", e),
                 }
@@ -1134,26 +1706,61 @@ fn watch_mode(port: u16) -> Result<()> {
     }
 }
 
-fn should_rebuild(event: &notify::Event) -> bool {
+/// How much of the site a watched change invalidates
+#[derive(Debug, PartialEq)]
+enum Rebuild {
+    /// Rebuild with the cache, skipping unchanged posts
+    Incremental,
+    /// Ignore the cache and rebuild every post
+    Full,
+}
+
+/// Classify a watch event under the project `root`. Changes to any of
+/// `config_files` need a full rebuild; build output and editor swap/backup
+/// files are ignored.
+fn rebuild_for(event: &notify::Event, root: &Path, config_files: &[PathBuf]) -> Option<Rebuild> {
     use notify::EventKind;
 
-    match event.kind {
-        EventKind::Modify(_) | EventKind::Create(_) | EventKind::Remove(_) => {
-            for path in &event.paths {
-                let path_str = path.to_string_lossy();
-                if path_str.contains(".build-cache") || path_str.contains("dist/") {
-                    return false;
-                }
-            }
-            true
+    if !matches!(
+        event.kind,
+        EventKind::Modify(_) | EventKind::Create(_) | EventKind::Remove(_)
+    ) {
+        return None;
+    }
+
+    let mut rebuild = None;
+    for path in &event.paths {
+        let path_str = path.strip_prefix(root).unwrap_or(path).to_string_lossy();
+        if path_str.contains(".build-cache") || path_str.contains("dist/") {
+            return None;
+        }
+        if is_editor_temp_file(path) {
+            continue;
+        }
+        if config_files.contains(path) {
+            rebuild = Some(Rebuild::Full);
+        } else if rebuild.is_none() {
+            rebuild = Some(Rebuild::Incremental);
         }
-        _ => false,
     }
+    rebuild
 }
 
-fn start_dev_server(port: u16) -> Result<()> {
+/// Vim swap files and write probes (`4913`), and `~` backups left by most
+/// editors
+fn is_editor_temp_file(path: &Path) -> bool {
+    let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+        return false;
+    };
+    name == "4913"
+        || name.ends_with('~')
+        || [".swp", ".swo", ".swx"]
+            .iter()
+            .any(|ext| name.ends_with(ext))
+}
+
+fn start_dev_server(port: u16, root: &Path) -> Result<()> {
     use anyhow::Context as _;
-    use std::io::Read;
     use std::net::TcpListener;
 
     let listener =
@@ -1170,81 +1777,592 @@ fn start_dev_server(port: u16) -> Result<()> {
             }
         };
 
-        let mut buffer = [0; DEV_SERVER_BUFFER_SIZE];
-        if stream.read(&mut buffer).is_err() {
-            continue;
+        handle_request(&mut stream, root);
+    }
+
+    Ok(())
+}
+
+/// Read one request from `stream` and answer it with a file under `root`
+fn handle_request(stream: &mut std::net::TcpStream, root: &Path) {
+    use std::io::Read;
+
+    let mut buffer = [0; DEV_SERVER_BUFFER_SIZE];
+    if stream.read(&mut buffer).is_err() {
+        return;
+    }
+
+    let request = String::from_utf8_lossy(&buffer);
+    let mut lines = request.lines();
+    let request_line = lines.next().unwrap_or("");
+
+    let path = if let Some(path_part) = request_line.split_whitespace().nth(1) {
+        slug::decode_from_url(path_part)
+    } else {
+        "/".to_string()
+    };
+
+    let accept_encoding = lines
+        .find_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            name.trim()
+                .eq_ignore_ascii_case("accept-encoding")
+                .then(|| value.trim().to_string())
+        })
+        .unwrap_or_default();
+
+    serve_file(stream, root, &path, &accept_encoding);
+}
+
+/// Where a dev server request path leads
+#[derive(Debug, PartialEq)]
+enum DevServerTarget {
+    File(PathBuf),
+    Redirect(String),
+    Forbidden,
+    NotFound,
+}
+
+/// Map a decoded request path to a file under `root`. Clean URLs work with
+/// and without a trailing slash: `/dev/post` and `/dev/post/` both serve
+/// `dev/post/index.html`, and `/dev/post` also serves `dev/post.html` (ugly
+/// URLs), with `/dev/post/` redirecting there. Paths escaping `root`, by
+/// `..` or through a symlink, are forbidden.
+fn resolve_dev_server_path(root: &Path, path: &str) -> DevServerTarget {
+    match resolve_dev_server_file(root, path) {
+        DevServerTarget::File(file_path) => {
+            let inside_root = root.canonicalize().is_ok_and(|root| {
+                file_path
+                    .canonicalize()
+                    .is_ok_and(|file_path| file_path.starts_with(root))
+            });
+            if inside_root {
+                DevServerTarget::File(file_path)
+            } else {
+                DevServerTarget::Forbidden
+            }
         }
+        target => target,
+    }
+}
+
+fn resolve_dev_server_file(root: &Path, path: &str) -> DevServerTarget {
+    use std::path::Component;
 
-        let request = String::from_utf8_lossy(&buffer);
-        let request_line = request.lines().next().unwrap_or("");
+    let path = path.split('?').next().unwrap_or(path);
+    let relative = Path::new(path.trim_start_matches('/'));
+    if !relative
+        .components()
+        .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+    {
+        return DevServerTarget::Forbidden;
+    }
+
+    let file_path = root.join(relative);
+    let index = file_path.join("index.html");
+    if index.is_file() {
+        return DevServerTarget::File(index);
+    }
+    if !path.ends_with('/') && file_path.is_file() {
+        return DevServerTarget::File(file_path);
+    }
 
-        let path = if let Some(path_part) = request_line.split_whitespace().nth(1) {
-            slug::decode_from_url(path_part)
+    let trimmed = path.trim_end_matches('/');
+    let html = root.join(format!("{}.html", trimmed.trim_start_matches('/')));
+    if !trimmed.is_empty() && html.is_file() {
+        return if path.ends_with('/') {
+            DevServerTarget::Redirect(trimmed.to_string())
         } else {
-            "/".to_string()
+            DevServerTarget::File(html)
         };
-
-        serve_file(&mut stream, &path);
     }
 
-    Ok(())
+    DevServerTarget::NotFound
 }
 
-fn serve_file(stream: &mut std::net::TcpStream, path: &str) {
-    use std::io::Write;
+fn serve_file(stream: &mut std::net::TcpStream, root: &Path, path: &str, accept_encoding: &str) {
+    let file_path = match resolve_dev_server_path(root, path) {
+        DevServerTarget::File(file_path) => file_path,
+        DevServerTarget::Redirect(location) => {
+            let headers = format!("Location: {}\r\nContent-Type: text/plain\r\n", location);
+            write_response(stream, "301 MOVED PERMANENTLY", &headers, b"");
+            return;
+        }
+        DevServerTarget::Forbidden => {
+            write_response(
+                stream,
+                "403 FORBIDDEN",
+                "Content-Type: text/plain\r\n",
+                b"403 Forbidden",
+            );
+            return;
+        }
+        DevServerTarget::NotFound => {
+            write_response(
+                stream,
+                "404 NOT FOUND",
+                "Content-Type: text/plain\r\n",
+                b"404 Not Found",
+            );
+            return;
+        }
+    };
 
-    let path = path.split('?').next().unwrap_or(path);
-    let file_path = if path == "/" {
-        "dist/index.html".to_string()
-    } else if path.ends_with('/') {
-        format!("dist{}index.html", path)
-    } else {
-        format!("dist{}", path)
+    let variant = precompressed_variant(&file_path, accept_encoding);
+    let body_path = variant.as_ref().map_or(&file_path, |(_, path)| path);
+    let Ok(body) = std::fs::read(body_path) else {
+        write_response(
+            stream,
+            "404 NOT FOUND",
+            "Content-Type: text/plain\r\n",
+            b"404 Not Found",
+        );
+        return;
     };
 
-    let (status, content_type, body) = if let Ok(contents) = std::fs::read(&file_path) {
-        let content_type = get_content_type(&file_path);
-        ("200 OK", content_type, contents)
+    let file_name = file_path.to_string_lossy();
+    let cache_control = if assets::is_fingerprinted(&file_name) {
+        "public, max-age=31536000, immutable"
     } else {
-        let index_path = format!("{}/index.html", file_path);
-        if let Ok(contents) = std::fs::read(&index_path) {
-            ("200 OK", "text/html", contents)
-        } else {
-            let body = b"404 Not Found".to_vec();
-            ("404 NOT FOUND", "text/plain", body)
-        }
+        "no-cache"
     };
+    let mut headers = format!(
+        "Content-Type: {}\r\nCache-Control: {}\r\n",
+        get_content_type(&file_name),
+        cache_control
+    );
+    if let Some((encoding, _)) = &variant {
+        headers.push_str(&format!("Content-Encoding: {}\r\n", encoding));
+    }
+    if variant.is_some() || has_precompressed_sibling(&file_path) {
+        headers.push_str("Vary: Accept-Encoding\r\n");
+    }
+
+    write_response(stream, "200 OK", &headers, &body);
+}
+
+fn write_response(stream: &mut std::net::TcpStream, status: &str, headers: &str, body: &[u8]) {
+    use std::io::Write;
 
     let response = format!(
-        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\n\r\n",
+        "HTTP/1.1 {}\r\n{}Content-Length: {}\r\n\r\n",
         status,
-        content_type,
+        headers,
         body.len()
     );
 
     let _ = stream.write_all(response.as_bytes());
-    let _ = stream.write_all(&body);
+    let _ = stream.write_all(body);
     let _ = stream.flush();
 }
 
+/// Precompressed siblings the dev server can send, most preferred first
+const PRECOMPRESSED_ENCODINGS: &[(&str, &str)] = &[("br", "br"), ("gzip", "gz")];
+
+/// The best `.br`/`.gz` sibling of `file_path` allowed by the request's
+/// `Accept-Encoding`, as `(encoding, path)`
+fn precompressed_variant(
+    file_path: &Path,
+    accept_encoding: &str,
+) -> Option<(&'static str, PathBuf)> {
+    let accepted: Vec<&str> = accept_encoding
+        .split(',')
+        .filter_map(|entry| {
+            let mut parts = entry.split(';').map(str::trim);
+            let name = parts.next()?;
+            let refused = parts.any(|param| {
+                param
+                    .strip_prefix("q=")
+                    .and_then(|q| q.parse::<f32>().ok())
+                    .is_some_and(|q| q == 0.0)
+            });
+            (!name.is_empty() && !refused).then_some(name)
+        })
+        .collect();
+
+    PRECOMPRESSED_ENCODINGS
+        .iter()
+        .filter(|(encoding, _)| {
+            accepted
+                .iter()
+                .any(|name| name.eq_ignore_ascii_case(encoding) || *name == "*")
+        })
+        .map(|(encoding, extension)| (*encoding, precompressed_path(file_path, extension)))
+        .find(|(_, path)| path.is_file())
+}
+
+fn has_precompressed_sibling(file_path: &Path) -> bool {
+    PRECOMPRESSED_ENCODINGS
+        .iter()
+        .any(|(_, extension)| precompressed_path(file_path, extension).is_file())
+}
+
+fn precompressed_path(file_path: &Path, extension: &str) -> PathBuf {
+    let mut name = file_path.as_os_str().to_os_string();
+    name.push(".");
+    name.push(extension);
+    PathBuf::from(name)
+}
+
 fn get_content_type(path: &str) -> &'static str {
-    if path.ends_with(".html") {
-        "text/html"
-    } else if path.ends_with(".css") {
-        "text/css"
-    } else if path.ends_with(".js") {
-        "application/javascript"
-    } else if path.ends_with(".png") {
-        "image/png"
-    } else if path.ends_with(".jpg") || path.ends_with(".jpeg") {
-        "image/jpeg"
-    } else if path.ends_with(".svg") {
-        "image/svg+xml"
-    } else if path.ends_with(".woff") {
-        "font/woff"
-    } else if path.ends_with(".woff2") {
-        "font/woff2"
-    } else {
-        "application/octet-stream"
+    let extension = Path::new(path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_ascii_lowercase)
+        .unwrap_or_default();
+
+    match extension.as_str() {
+        "html" | "htm" => "text/html; charset=utf-8",
+        "css" => "text/css; charset=utf-8",
+        "js" | "mjs" => "text/javascript; charset=utf-8",
+        "json" | "map" => "application/json; charset=utf-8",
+        "webmanifest" => "application/manifest+json; charset=utf-8",
+        "xml" => "application/xml; charset=utf-8",
+        "txt" => "text/plain; charset=utf-8",
+        "md" => "text/markdown; charset=utf-8",
+        "svg" => "image/svg+xml",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "avif" => "image/avif",
+        "ico" => "image/x-icon",
+        "woff" => "font/woff",
+        "woff2" => "font/woff2",
+        "ttf" => "font/ttf",
+        "otf" => "font/otf",
+        "mp3" => "audio/mpeg",
+        "mp4" => "video/mp4",
+        "webm" => "video/webm",
+        "pdf" => "application/pdf",
+        "wasm" => "application/wasm",
+        _ => "application/octet-stream",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parallel_metadata_matches_serial() {
+        let temp = TempDir::new().unwrap();
+        let posts_dir = temp.path().join("posts");
+        for (category, count) in [("dev", 7), ("life", 5), ("notes", 3)] {
+            let dir = posts_dir.join(category);
+            std::fs::create_dir_all(&dir).unwrap();
+            for i in 0..count {
+                std::fs::write(
+                    dir.join(format!("{}-{}.md", category, i)),
+                    format!(
+                        "---\ntitle: \"{} {}\"\ndate: 2024-01-{:02}T00:00:00Z\ntags: [\"{}\", \"t{}\"]\nhidden: {}\n---\n\nBody of post {}.\n",
+                        category, i, i + 1, category, i % 3, i == 2, i
+                    ),
+                )
+                .unwrap();
+            }
+        }
+        let paths = markdown_files(&posts_dir);
+
+        let mut serial = MetadataCache::new();
+        let (serial_sources, serial_slugs) =
+            populate_metadata(&mut serial, &paths, false, 1).unwrap();
+        let mut parallel = MetadataCache::new();
+        let (parallel_sources, parallel_slugs) =
+            populate_metadata(&mut parallel, &paths, false, 4).unwrap();
+
+        assert_eq!(serial.posts.len(), 12);
+        assert_eq!(
+            serde_json::to_value(&parallel).unwrap(),
+            serde_json::to_value(&serial).unwrap()
+        );
+        assert_eq!(parallel_sources, serial_sources);
+        assert_eq!(parallel_slugs, serial_slugs);
+    }
+
+    #[test]
+    fn test_metadata_parse_error_names_the_file() {
+        let temp = TempDir::new().unwrap();
+        let dir = temp.path().join("posts/dev");
+        std::fs::create_dir_all(&dir).unwrap();
+        for i in 0..4 {
+            std::fs::write(
+                dir.join(format!("ok-{}.md", i)),
+                "---\ntitle: \"Ok\"\ndate: 2024-01-01T00:00:00Z\n---\n\nBody\n",
+            )
+            .unwrap();
+        }
+        std::fs::write(dir.join("broken.md"), "---\ntitle: [\n---\n").unwrap();
+        let paths = markdown_files(&temp.path().join("posts"));
+
+        for threads in [1, 4] {
+            let error =
+                populate_metadata(&mut MetadataCache::new(), &paths, false, threads).unwrap_err();
+            assert!(
+                format!("{:#}", error).contains("broken.md"),
+                "got: {:#}",
+                error
+            );
+        }
+    }
+
+    fn watch_event(kind: notify::EventKind, path: &str) -> notify::Event {
+        notify::Event::new(kind).add_path(PathBuf::from(path))
+    }
+
+    #[test]
+    fn test_rebuild_for_classifies_changes() {
+        use notify::event::{CreateKind, ModifyKind};
+        use notify::EventKind;
+
+        let config_files = vec![PathBuf::from("/site/config.yaml")];
+        let modify = EventKind::Modify(ModifyKind::Any);
+
+        assert_eq!(
+            rebuild_for(
+                &watch_event(modify, "/site/config.yaml"),
+                Path::new("/site"),
+                &config_files
+            ),
+            Some(Rebuild::Full)
+        );
+        assert_eq!(
+            rebuild_for(
+                &watch_event(modify, "/site/content/posts/dev/a.md"),
+                Path::new("/site"),
+                &config_files
+            ),
+            Some(Rebuild::Incremental)
+        );
+        // A config.yaml elsewhere (e.g. under data/) is regular content
+        assert_eq!(
+            rebuild_for(
+                &watch_event(modify, "/site/data/config.yaml"),
+                Path::new("/site"),
+                &config_files
+            ),
+            Some(Rebuild::Incremental)
+        );
+        assert_eq!(
+            rebuild_for(
+                &watch_event(modify, "/site/dist/index.html"),
+                Path::new("/site"),
+                &config_files
+            ),
+            None
+        );
+        assert_eq!(
+            rebuild_for(
+                &watch_event(
+                    EventKind::Access(notify::event::AccessKind::Any),
+                    "/site/config.yaml"
+                ),
+                Path::new("/site"),
+                &config_files
+            ),
+            None
+        );
+
+        for temp in [
+            "/site/content/posts/dev/.a.md.swp",
+            "/site/content/posts/dev/a.md~",
+            "/site/templates/4913",
+        ] {
+            assert_eq!(
+                rebuild_for(
+                    &watch_event(EventKind::Create(CreateKind::File), temp),
+                    Path::new("/site"),
+                    &config_files
+                ),
+                None,
+                "{temp}"
+            );
+        }
+    }
+
+    /// Send a GET for `path` to a one-shot dev server over `root` and return
+    /// the raw response
+    fn dev_server_get(root: &Path, path: &str, accept_encoding: Option<&str>) -> Vec<u8> {
+        use std::io::{Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let root = root.to_path_buf();
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            handle_request(&mut stream, &root);
+        });
+
+        let mut client = std::net::TcpStream::connect(addr).unwrap();
+        let mut request = format!("GET {} HTTP/1.1\r\nHost: localhost\r\n", path);
+        if let Some(encoding) = accept_encoding {
+            request.push_str(&format!("Accept-Encoding: {}\r\n", encoding));
+        }
+        request.push_str("\r\n");
+        client.write_all(request.as_bytes()).unwrap();
+
+        server.join().unwrap();
+        let mut response = Vec::new();
+        client.read_to_end(&mut response).unwrap();
+        response
+    }
+
+    fn split_response(response: &[u8]) -> (String, &[u8]) {
+        let end = response.windows(4).position(|w| w == b"\r\n\r\n").unwrap();
+        (
+            String::from_utf8_lossy(&response[..end]).to_string(),
+            &response[end + 4..],
+        )
+    }
+
+    #[test]
+    fn test_dev_server_serves_precompressed_variant_when_accepted() {
+        let temp = TempDir::new().unwrap();
+        std::fs::write(temp.path().join("index.html"), "<p>plain</p>").unwrap();
+        std::fs::write(temp.path().join("index.html.gz"), b"gzip bytes").unwrap();
+
+        let response = dev_server_get(temp.path(), "/", Some("gzip, deflate"));
+        let (head, body) = split_response(&response);
+        assert!(head.starts_with("HTTP/1.1 200 OK"), "{head}");
+        assert!(head.contains("Content-Type: text/html"), "{head}");
+        assert!(head.contains("Content-Encoding: gzip"), "{head}");
+        assert!(head.contains("Cache-Control: no-cache"), "{head}");
+        assert_eq!(body, b"gzip bytes");
+
+        for refused in [None, Some("br"), Some("gzip;q=0")] {
+            let response = dev_server_get(temp.path(), "/index.html", refused);
+            let (head, body) = split_response(&response);
+            assert!(!head.contains("Content-Encoding"), "{head}");
+            assert!(head.contains("Vary: Accept-Encoding"), "{head}");
+            assert_eq!(body, b"<p>plain</p>");
+        }
+    }
+
+    #[test]
+    fn test_dev_server_caches_fingerprinted_assets() {
+        let temp = TempDir::new().unwrap();
+        std::fs::create_dir_all(temp.path().join("css")).unwrap();
+        std::fs::write(temp.path().join("css/style.0123456789.css"), "a{}").unwrap();
+
+        let response = dev_server_get(temp.path(), "/css/style.0123456789.css", None);
+        let (head, _) = split_response(&response);
+        assert!(
+            head.contains("Cache-Control: public, max-age=31536000, immutable"),
+            "{head}"
+        );
+
+        let response = dev_server_get(temp.path(), "/missing.css", None);
+        let (head, _) = split_response(&response);
+        assert!(head.starts_with("HTTP/1.1 404"), "{head}");
+    }
+
+    #[test]
+    fn test_get_content_type() {
+        assert_eq!(
+            get_content_type("dist/index.html"),
+            "text/html; charset=utf-8"
+        );
+        assert_eq!(
+            get_content_type("dist/js/app.mjs"),
+            "text/javascript; charset=utf-8"
+        );
+        assert_eq!(
+            get_content_type("dist/search-index.json"),
+            "application/json; charset=utf-8"
+        );
+        assert_eq!(
+            get_content_type("dist/feed.xml"),
+            "application/xml; charset=utf-8"
+        );
+        assert_eq!(
+            get_content_type("dist/site.webmanifest"),
+            "application/manifest+json; charset=utf-8"
+        );
+        assert_eq!(get_content_type("dist/images/cover.AVIF"), "image/avif");
+        assert_eq!(get_content_type("dist/fonts/body.ttf"), "font/ttf");
+        assert_eq!(get_content_type("dist/LICENSE"), "application/octet-stream");
+    }
+
+    #[test]
+    fn test_resolve_dev_server_path() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        std::fs::create_dir_all(root.join("dev/test-post")).unwrap();
+        std::fs::write(root.join("index.html"), "home").unwrap();
+        std::fs::write(root.join("dev/test-post/index.html"), "post").unwrap();
+        std::fs::write(root.join("dev/ugly.html"), "ugly").unwrap();
+        std::fs::write(root.join("feed.xml"), "feed").unwrap();
+
+        let file = |path: &str| DevServerTarget::File(root.join(path));
+        assert_eq!(resolve_dev_server_path(root, "/"), file("index.html"));
+        assert_eq!(
+            resolve_dev_server_path(root, "/dev/test-post"),
+            file("dev/test-post/index.html")
+        );
+        assert_eq!(
+            resolve_dev_server_path(root, "/dev/test-post/?page=2"),
+            file("dev/test-post/index.html")
+        );
+        assert_eq!(resolve_dev_server_path(root, "/feed.xml"), file("feed.xml"));
+        assert_eq!(
+            resolve_dev_server_path(root, "/dev/ugly"),
+            file("dev/ugly.html")
+        );
+        assert_eq!(
+            resolve_dev_server_path(root, "/dev/ugly/"),
+            DevServerTarget::Redirect("/dev/ugly".to_string())
+        );
+        assert_eq!(
+            resolve_dev_server_path(root, "/feed.xml/"),
+            DevServerTarget::NotFound
+        );
+        assert_eq!(
+            resolve_dev_server_path(root, "/dev/missing"),
+            DevServerTarget::NotFound
+        );
+    }
+
+    #[test]
+    fn test_dev_server_rejects_traversal() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path().join("dist");
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(temp.path().join("secret.txt"), "secret").unwrap();
+
+        assert_eq!(
+            resolve_dev_server_path(&root, "/../secret.txt"),
+            DevServerTarget::Forbidden
+        );
+        assert_eq!(
+            resolve_dev_server_path(&root, "/dev/../../secret.txt"),
+            DevServerTarget::Forbidden
+        );
+
+        // Percent-encoded dots are decoded before resolving
+        let response = dev_server_get(&root, "/%2e%2e/secret.txt", None);
+        let (head, body) = split_response(&response);
+        assert!(head.starts_with("HTTP/1.1 403"), "{head}");
+        assert_ne!(body, b"secret");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_dev_server_blocks_symlinks_out_of_root() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path().join("dist");
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(temp.path().join("Cargo.toml"), "[package]").unwrap();
+        std::fs::write(root.join("index.html"), "home").unwrap();
+        std::os::unix::fs::symlink(temp.path().join("Cargo.toml"), root.join("leak.toml")).unwrap();
+
+        let response = dev_server_get(&root, "/leak.toml", None);
+        let (head, _) = split_response(&response);
+        assert!(head.starts_with("HTTP/1.1 403"), "{head}");
+
+        let response = dev_server_get(&root, "/index.html", None);
+        let (head, body) = split_response(&response);
+        assert!(head.starts_with("HTTP/1.1 200"), "{head}");
+        assert_eq!(body, b"home");
     }
 }
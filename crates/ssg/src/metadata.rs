@@ -1,8 +1,11 @@
+use crate::reading_time::PostStats;
+use crate::tags::TagNormalizer;
 use crate::types::{Category, Frontmatter};
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PostMetadata {
@@ -11,6 +14,23 @@ pub struct PostMetadata {
     pub frontmatter: Frontmatter,
     #[serde(default)]
     pub reading_time: u32,
+    #[serde(default)]
+    pub word_count: usize,
+    #[serde(default)]
+    pub char_count: usize,
+    /// Plain-text opening of the post, for feeds, search and cards
+    #[serde(default)]
+    pub excerpt: String,
+}
+
+impl PostMetadata {
+    /// The frontmatter description, falling back to the excerpt
+    pub fn summary(&self) -> Option<&str> {
+        self.frontmatter
+            .description
+            .as_deref()
+            .or((!self.excerpt.is_empty()).then_some(self.excerpt.as_str()))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -21,6 +41,8 @@ pub struct MetadataCache {
     pub tags: HashMap<String, usize>,
     #[serde(default)]
     pub category_info: Vec<Category>,
+    #[serde(skip)]
+    tag_normalizer: TagNormalizer,
 }
 
 impl MetadataCache {
@@ -42,9 +64,20 @@ impl MetadataCache {
             categories: HashMap::new(),
             tags: HashMap::new(),
             category_info: Vec::new(),
+            tag_normalizer: TagNormalizer::default(),
         }
     }
 
+    /// Tags of posts inserted afterwards are folded through `normalizer`
+    pub fn set_tag_normalizer(&mut self, normalizer: TagNormalizer) {
+        self.tag_normalizer = normalizer;
+    }
+
+    /// Canonical spelling of `tags`, as stored for posts in this cache
+    pub fn normalize_tags(&self, tags: &[String]) -> Vec<String> {
+        self.tag_normalizer.normalize(tags)
+    }
+
     pub fn set_category_info(&mut self, categories: Vec<Category>) {
         self.category_info = categories;
     }
@@ -57,19 +90,26 @@ impl MetadataCache {
         &mut self,
         slug: String,
         category: String,
-        frontmatter: Frontmatter,
-        reading_time: Option<u32>,
+        mut frontmatter: Frontmatter,
+        stats: Option<PostStats>,
     ) {
-        // A `None` reading_time preserves the value from an earlier pass; the
-        // metadata-population loop always supplies it, later re-upserts don't.
-        let reading_time = reading_time
+        frontmatter.tags = self.normalize_tags(&frontmatter.tags);
+
+        // `None` stats preserve the values from an earlier pass; the
+        // metadata-population loop always supplies them, later re-upserts don't.
+        let stats = stats
             .or_else(|| {
                 self.posts
                     .iter()
                     .find(|p| p.slug == slug)
-                    .map(|p| p.reading_time)
+                    .map(|p| PostStats {
+                        reading_time: p.reading_time,
+                        word_count: p.word_count,
+                        char_count: p.char_count,
+                        excerpt: p.excerpt.clone(),
+                    })
             })
-            .unwrap_or(0);
+            .unwrap_or_default();
 
         self.posts.retain(|p| p.slug != slug);
 
@@ -77,7 +117,10 @@ impl MetadataCache {
             slug,
             category,
             frontmatter,
-            reading_time,
+            reading_time: stats.reading_time,
+            word_count: stats.word_count,
+            char_count: stats.char_count,
+            excerpt: stats.excerpt,
         });
 
         self.recalculate_stats();
@@ -124,6 +167,18 @@ impl MetadataCache {
         posts.into_iter().take(limit).collect()
     }
 
+    /// Like [`Self::get_recent_posts`] but skips hidden posts, which are only
+    /// present when building with `--drafts`
+    pub fn get_recent_visible_posts(&self, limit: usize) -> Vec<&PostMetadata> {
+        let mut posts: Vec<_> = self
+            .posts
+            .iter()
+            .filter(|p| !p.frontmatter.hidden)
+            .collect();
+        posts.sort_by(|a, b| compare_posts_desc(a, b));
+        posts.into_iter().take(limit).collect()
+    }
+
     pub fn get_categories(&self) -> Vec<String> {
         let mut categories: Vec<_> = self.categories.keys().cloned().collect();
         categories.sort();
@@ -159,6 +214,22 @@ impl Default for MetadataCache {
     }
 }
 
+/// Returns every slug claimed by more than one source file, with the files
+/// involved. Slugs identify posts site-wide (metadata, feeds, navigation), so
+/// two posts sharing one would silently replace each other.
+pub fn find_duplicate_slugs(sources: &[(String, PathBuf)]) -> BTreeMap<&str, Vec<&Path>> {
+    let mut by_slug: BTreeMap<&str, Vec<&Path>> = BTreeMap::new();
+    for (slug, path) in sources {
+        by_slug
+            .entry(slug.as_str())
+            .or_default()
+            .push(path.as_path());
+    }
+
+    by_slug.retain(|_, paths| paths.len() > 1);
+    by_slug
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -174,7 +245,12 @@ mod tests {
             description: None,
             display_ad: false,
             hidden: false,
+            unlisted: false,
+            template: None,
             comments: true,
+            audio: None,
+            lang: None,
+            translations: Default::default(),
         };
         (category.to_string(), frontmatter)
     }
@@ -184,13 +260,34 @@ mod tests {
         let mut cache = MetadataCache::new();
 
         let (category, fm) = create_test_post("dev", vec!["rust", "webdev"]);
-        cache.upsert_post("test-post".to_string(), category, fm, Some(1));
+        cache.upsert_post("test-post".to_string(), category, fm, None);
 
         assert_eq!(cache.posts.len(), 1);
         assert_eq!(cache.categories.get("dev"), Some(&1));
         assert_eq!(cache.tags.get("rust"), Some(&1));
     }
 
+    #[test]
+    fn test_upsert_keeps_stats_when_not_supplied() {
+        let mut cache = MetadataCache::new();
+        let (category, fm) = create_test_post("dev", vec![]);
+        let stats = crate::reading_time::analyze("Some words for an excerpt.");
+
+        cache.upsert_post(
+            "post".to_string(),
+            category.clone(),
+            fm.clone(),
+            Some(stats),
+        );
+        cache.upsert_post("post".to_string(), category, fm, None);
+
+        let post = &cache.posts[0];
+        assert_eq!(post.word_count, 5);
+        assert_eq!(post.char_count, "Somewordsforanexcerpt.".len());
+        assert_eq!(post.excerpt, "Some words for an excerpt.");
+        assert_eq!(post.summary(), Some("Some words for an excerpt."));
+    }
+
     #[test]
     fn test_get_posts_by_category() {
         let mut cache = MetadataCache::new();
@@ -199,9 +296,9 @@ mod tests {
         let (cat2, fm2) = create_test_post("chat", vec![]);
         let (cat3, fm3) = create_test_post("dev", vec![]);
 
-        cache.upsert_post("post1".to_string(), cat1, fm1, Some(1));
-        cache.upsert_post("post2".to_string(), cat2, fm2, Some(1));
-        cache.upsert_post("post3".to_string(), cat3, fm3, Some(1));
+        cache.upsert_post("post1".to_string(), cat1, fm1, None);
+        cache.upsert_post("post2".to_string(), cat2, fm2, None);
+        cache.upsert_post("post3".to_string(), cat3, fm3, None);
 
         let dev_posts = cache.get_posts_by_category("dev");
         assert_eq!(dev_posts.len(), 2);
@@ -215,14 +312,32 @@ mod tests {
         let (cat2, fm2) = create_test_post("dev", vec!["rust", "webdev"]);
         let (cat3, fm3) = create_test_post("chat", vec!["webdev"]);
 
-        cache.upsert_post("post1".to_string(), cat1, fm1, Some(1));
-        cache.upsert_post("post2".to_string(), cat2, fm2, Some(1));
-        cache.upsert_post("post3".to_string(), cat3, fm3, Some(1));
+        cache.upsert_post("post1".to_string(), cat1, fm1, None);
+        cache.upsert_post("post2".to_string(), cat2, fm2, None);
+        cache.upsert_post("post3".to_string(), cat3, fm3, None);
 
         let rust_posts = cache.get_posts_by_tag("rust");
         assert_eq!(rust_posts.len(), 2);
     }
 
+    #[test]
+    fn test_aliased_tags_merge_counts() {
+        let mut cache = MetadataCache::new();
+        cache.set_tag_normalizer(TagNormalizer::from_config(&crate::config::BuildConfig {
+            tag_aliases: [("JavaScript".to_string(), vec!["javascript".to_string()])].into(),
+            ..Default::default()
+        }));
+
+        let (cat1, fm1) = create_test_post("dev", vec!["JavaScript"]);
+        let (cat2, fm2) = create_test_post("dev", vec!["javascript", "JavaScript"]);
+        cache.upsert_post("post1".to_string(), cat1, fm1, None);
+        cache.upsert_post("post2".to_string(), cat2, fm2, None);
+
+        assert_eq!(cache.get_tags(), vec!["JavaScript"]);
+        assert_eq!(cache.tags.get("JavaScript"), Some(&2));
+        assert_eq!(cache.get_posts_by_tag("JavaScript").len(), 2);
+    }
+
     #[test]
     fn test_recent_posts_tie_broken_by_slug() {
         let mut cache = MetadataCache::new();
@@ -238,9 +353,14 @@ mod tests {
                 description: None,
                 display_ad: false,
                 hidden: false,
+                unlisted: false,
+                template: None,
                 comments: true,
+                audio: None,
+                lang: None,
+                translations: Default::default(),
             };
-            cache.upsert_post(slug.to_string(), "dev".to_string(), frontmatter, Some(1));
+            cache.upsert_post(slug.to_string(), "dev".to_string(), frontmatter, None);
         }
 
         let recent: Vec<_> = cache
@@ -250,4 +370,33 @@ mod tests {
             .collect();
         assert_eq!(recent, vec!["a-post", "b-post", "c-post"]);
     }
+
+    #[test]
+    fn test_find_duplicate_slugs() {
+        let sources = vec![
+            (
+                "hello".to_string(),
+                PathBuf::from("content/posts/dev/hello.md"),
+            ),
+            (
+                "unique".to_string(),
+                PathBuf::from("content/posts/dev/unique.md"),
+            ),
+            (
+                "hello".to_string(),
+                PathBuf::from("content/posts/chat/hello.md"),
+            ),
+        ];
+
+        let duplicates = find_duplicate_slugs(&sources);
+
+        assert_eq!(duplicates.len(), 1);
+        assert_eq!(
+            duplicates["hello"],
+            vec![
+                Path::new("content/posts/dev/hello.md"),
+                Path::new("content/posts/chat/hello.md")
+            ]
+        );
+    }
 }
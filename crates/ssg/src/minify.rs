@@ -0,0 +1,205 @@
+use crate::config::BuildConfig;
+use anyhow::Result;
+use std::fs;
+use std::path::Path;
+
+/// Elements whose content is whitespace-sensitive or not HTML, copied as-is
+const PRESERVED_TAGS: &[&str] = &["pre", "code", "textarea", "script", "style"];
+
+/// Write a rendered HTML page, creating parent directories and minifying it
+/// first when `build.minify_html` is set
+pub fn write_html(path: &Path, html: &str, build: &BuildConfig) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    if build.minify_html {
+        fs::write(path, minify_html(html))?;
+    } else {
+        fs::write(path, html)?;
+    }
+    Ok(())
+}
+
+/// Collapse whitespace runs to a single space and drop comments, leaving the
+/// content of [`PRESERVED_TAGS`] elements, quoted attribute values and
+/// conditional comments (`<!--[if IE]>`) untouched
+pub fn minify_html(html: &str) -> String {
+    let mut output = String::with_capacity(html.len());
+    let mut rest = html;
+    let mut pending_space = false;
+    let mut in_tag = false;
+
+    while let Some(ch) = rest.chars().next() {
+        if rest.starts_with("<!--") && !is_conditional_comment(rest) {
+            match rest.find("-->") {
+                Some(end) => {
+                    rest = &rest[end + 3..];
+                    continue;
+                }
+                None => {
+                    output.push_str(rest);
+                    break;
+                }
+            }
+        }
+
+        if ch.is_whitespace() {
+            pending_space = true;
+            rest = &rest[ch.len_utf8()..];
+            continue;
+        }
+
+        if pending_space && !output.is_empty() {
+            output.push(' ');
+        }
+        pending_space = false;
+
+        if is_conditional_comment(rest) {
+            let end = rest.find("-->").map_or(rest.len(), |end| end + 3);
+            output.push_str(&rest[..end]);
+            rest = &rest[end..];
+            continue;
+        }
+
+        if let Some(tag) = preserved_tag_at(rest) {
+            let end = preserved_element_end(rest, tag);
+            output.push_str(&rest[..end]);
+            rest = &rest[end..];
+            continue;
+        }
+
+        if in_tag && (ch == '"' || ch == '\'') {
+            let end = rest[1..].find(ch).map_or(rest.len(), |end| end + 2);
+            output.push_str(&rest[..end]);
+            rest = &rest[end..];
+            continue;
+        }
+
+        if ch == '<' {
+            in_tag = rest[1..]
+                .chars()
+                .next()
+                .is_some_and(|c| c.is_ascii_alphabetic() || c == '/');
+        } else if ch == '>' {
+            in_tag = false;
+        }
+
+        output.push(ch);
+        rest = &rest[ch.len_utf8()..];
+    }
+
+    output
+}
+
+/// Whether `html` starts with a conditional comment (`<!--[if IE]>`) or its
+/// `<!--<![endif]-->` closer
+fn is_conditional_comment(html: &str) -> bool {
+    html.strip_prefix("<!--")
+        .is_some_and(|rest| rest.starts_with("[if") || rest.starts_with("<![endif]"))
+}
+
+/// The preserved tag opened at the start of `html`, if any
+fn preserved_tag_at(html: &str) -> Option<&'static str> {
+    let after_lt = html.strip_prefix('<')?;
+    PRESERVED_TAGS.iter().copied().find(|tag| {
+        after_lt
+            .get(..tag.len())
+            .is_some_and(|name| name.eq_ignore_ascii_case(tag))
+            && after_lt[tag.len()..]
+                .chars()
+                .next()
+                .is_some_and(|c| c == '>' || c == '/' || c.is_whitespace())
+    })
+}
+
+/// Byte offset just past the closing tag of the `tag` element that starts
+/// `html`, or the end of input when it's never closed
+fn preserved_element_end(html: &str, tag: &str) -> usize {
+    html.match_indices("</")
+        .map(|(start, _)| start)
+        .find(|&start| {
+            let name_end = start + 2 + tag.len();
+            html.get(start + 2..name_end)
+                .is_some_and(|name| name.eq_ignore_ascii_case(tag))
+                && html[name_end..]
+                    .chars()
+                    .next()
+                    .is_some_and(|c| c == '>' || c.is_whitespace())
+        })
+        .and_then(|start| html[start..].find('>').map(|gt| start + gt + 1))
+        .unwrap_or(html.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collapses_whitespace_and_drops_comments() {
+        let html = "<html>\n  <body>\n    <!-- nav -->\n    <p>Hello,\n      world</p>\n  </body>\n</html>\n";
+        assert_eq!(
+            minify_html(html),
+            "<html> <body> <p>Hello, world</p> </body> </html>"
+        );
+    }
+
+    #[test]
+    fn test_preserves_code_blocks_exactly() {
+        let code =
+            "<pre class=\"code\"><code>fn main() {\n    println!(\"hi\");\n\n}\n</code></pre>";
+        let html = format!("<div>\n    {}\n</div>", code);
+        assert_eq!(minify_html(&html), format!("<div> {} </div>", code));
+    }
+
+    #[test]
+    fn test_preserves_textarea_script_and_style() {
+        let html = "<textarea>  a\n  b</textarea>\n<script>\n  if (a  <  b) {}\n  // <!-- not a comment -->\n</script>\n<STYLE>\n  p  { }\n</STYLE>";
+        assert_eq!(
+            minify_html(html),
+            "<textarea>  a\n  b</textarea> <script>\n  if (a  <  b) {}\n  // <!-- not a comment -->\n</script> <STYLE>\n  p  { }\n</STYLE>"
+        );
+    }
+
+    #[test]
+    fn test_keeps_conditional_comments() {
+        let html = "<head>\n  <!-- meta -->\n  <!--[if lt IE 9]><script src=\"shiv.js\"></script><![endif]-->\n  <!--[if !IE]><!--><p>modern</p><!--<![endif]-->\n</head>";
+        assert_eq!(
+            minify_html(html),
+            "<head> <!--[if lt IE 9]><script src=\"shiv.js\"></script><![endif]--> <!--[if !IE]><!--><p>modern</p><!--<![endif]--> </head>"
+        );
+    }
+
+    #[test]
+    fn test_copies_quoted_attribute_values_verbatim() {
+        let html = "<div  title=\"a   b\n  c\"\n  data-x='  y  '>  text   here  </div>";
+        assert_eq!(
+            minify_html(html),
+            "<div title=\"a   b\n  c\" data-x='  y  '> text here </div>"
+        );
+    }
+
+    #[test]
+    fn test_quotes_in_text_are_not_attribute_values() {
+        assert_eq!(
+            minify_html("<p>it's   \"quoted\"   text</p>"),
+            "<p>it's \"quoted\" text</p>"
+        );
+    }
+
+    #[test]
+    fn test_tag_name_prefixes_are_not_preserved() {
+        assert_eq!(
+            minify_html("<codex>  a  </codex><preview>\n b</preview>"),
+            "<codex> a </codex><preview> b</preview>"
+        );
+    }
+
+    #[test]
+    fn test_unclosed_preserved_element_keeps_rest() {
+        assert_eq!(
+            minify_html("<p> a </p><pre>  x\n  y"),
+            "<p> a </p><pre>  x\n  y"
+        );
+    }
+}
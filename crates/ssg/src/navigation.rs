@@ -1,6 +1,7 @@
+use crate::config::BuildConfig;
 use crate::image::{ImageProcessor, ThumbnailMetadata};
 use crate::metadata::{compare_posts_desc, MetadataCache, PostMetadata};
-use crate::slug;
+use crate::permalink;
 use serde::Serialize;
 use std::path::Path;
 
@@ -20,14 +21,16 @@ pub struct PostLink {
     pub thumbnail_metadata: Option<ThumbnailMetadata>,
 }
 
-fn create_post_link(post: &PostMetadata) -> PostLink {
+fn create_post_link(post: &PostMetadata, build: &BuildConfig) -> PostLink {
     PostLink {
         slug: post.slug.clone(),
         title: post.frontmatter.title.clone(),
-        url: format!(
-            "/{}/{}/",
-            slug::encode_for_url(&post.category),
-            slug::encode_for_url(&post.slug)
+        url: permalink::post_url(
+            build,
+            &post.category,
+            &post.slug,
+            &post.frontmatter.date.posted,
+            true,
         ),
         category: post.category.clone(),
         cover_image: post
@@ -41,10 +44,11 @@ fn create_post_link(post: &PostMetadata) -> PostLink {
 
 fn create_post_link_with_cdn(
     post: &PostMetadata,
+    build: &BuildConfig,
     image_processor: &ImageProcessor,
     content_dir: &Path,
 ) -> PostLink {
-    let mut link = create_post_link(post);
+    let mut link = create_post_link(post, build);
 
     // Try to generate thumbnail metadata for cover image
     if link.cover_image.is_some() {
@@ -92,6 +96,7 @@ pub fn build_post_navigation(
     current_category: &str,
     metadata: &MetadataCache,
     same_category: bool,
+    build: &BuildConfig,
 ) -> PostNavigation {
     let mut posts: Vec<_> = metadata
         .posts
@@ -108,9 +113,9 @@ pub fn build_post_navigation(
         };
     };
 
-    let prev = posts.get(index + 1).map(|p| create_post_link(p));
+    let prev = posts.get(index + 1).map(|p| create_post_link(p, build));
     let next = if index > 0 {
-        posts.get(index - 1).map(|p| create_post_link(p))
+        posts.get(index - 1).map(|p| create_post_link(p, build))
     } else {
         None
     };
@@ -123,6 +128,7 @@ pub fn build_post_navigation_with_cdn(
     current_category: &str,
     metadata: &MetadataCache,
     same_category: bool,
+    build: &BuildConfig,
     image_processor: &ImageProcessor,
     content_dir: &Path,
 ) -> PostNavigation {
@@ -143,11 +149,11 @@ pub fn build_post_navigation_with_cdn(
 
     let prev = posts
         .get(index + 1)
-        .map(|p| create_post_link_with_cdn(p, image_processor, content_dir));
+        .map(|p| create_post_link_with_cdn(p, build, image_processor, content_dir));
     let next = if index > 0 {
         posts
             .get(index - 1)
-            .map(|p| create_post_link_with_cdn(p, image_processor, content_dir))
+            .map(|p| create_post_link_with_cdn(p, build, image_processor, content_dir))
     } else {
         None
     };
@@ -160,7 +166,6 @@ mod tests {
     use super::*;
     use crate::types::{Frontmatter, PostDate};
     use chrono::Utc;
-    use std::collections::HashMap;
 
     fn create_test_metadata() -> MetadataCache {
         let base_date = Utc::now();
@@ -170,6 +175,9 @@ mod tests {
                 slug: "post-1".to_string(),
                 category: "dev".to_string(),
                 reading_time: 1,
+                word_count: 0,
+                char_count: 0,
+                excerpt: String::new(),
                 frontmatter: Frontmatter {
                     title: "Post 1".to_string(),
                     date: PostDate {
@@ -182,13 +190,21 @@ mod tests {
                     og_image: None,
                     display_ad: false,
                     hidden: false,
+                    unlisted: false,
+                    template: None,
                     comments: true,
+                    audio: None,
+                    lang: None,
+                    translations: Default::default(),
                 },
             },
             PostMetadata {
                 slug: "post-2".to_string(),
                 category: "dev".to_string(),
                 reading_time: 1,
+                word_count: 0,
+                char_count: 0,
+                excerpt: String::new(),
                 frontmatter: Frontmatter {
                     title: "Post 2".to_string(),
                     date: PostDate {
@@ -201,13 +217,21 @@ mod tests {
                     og_image: None,
                     display_ad: false,
                     hidden: false,
+                    unlisted: false,
+                    template: None,
                     comments: true,
+                    audio: None,
+                    lang: None,
+                    translations: Default::default(),
                 },
             },
             PostMetadata {
                 slug: "post-3".to_string(),
                 category: "dev".to_string(),
                 reading_time: 1,
+                word_count: 0,
+                char_count: 0,
+                excerpt: String::new(),
                 frontmatter: Frontmatter {
                     title: "Post 3".to_string(),
                     date: PostDate {
@@ -220,24 +244,25 @@ mod tests {
                     og_image: None,
                     display_ad: false,
                     hidden: false,
+                    unlisted: false,
+                    template: None,
                     comments: true,
+                    audio: None,
+                    lang: None,
+                    translations: Default::default(),
                 },
             },
         ];
 
-        MetadataCache {
-            version: "1".to_string(),
-            posts,
-            categories: HashMap::new(),
-            tags: HashMap::new(),
-            category_info: vec![],
-        }
+        let mut metadata = MetadataCache::new();
+        metadata.posts = posts;
+        metadata
     }
 
     #[test]
     fn test_navigation_middle_post() {
         let metadata = create_test_metadata();
-        let nav = build_post_navigation("post-2", "dev", &metadata, true);
+        let nav = build_post_navigation("post-2", "dev", &metadata, true, &BuildConfig::default());
 
         assert!(nav.prev.is_some());
         assert_eq!(nav.prev.as_ref().unwrap().slug, "post-1");
@@ -249,7 +274,7 @@ mod tests {
     #[test]
     fn test_navigation_first_post() {
         let metadata = create_test_metadata();
-        let nav = build_post_navigation("post-3", "dev", &metadata, true);
+        let nav = build_post_navigation("post-3", "dev", &metadata, true, &BuildConfig::default());
 
         assert!(nav.prev.is_some());
         assert_eq!(nav.prev.as_ref().unwrap().slug, "post-2");
@@ -259,7 +284,7 @@ mod tests {
     #[test]
     fn test_navigation_last_post() {
         let metadata = create_test_metadata();
-        let nav = build_post_navigation("post-1", "dev", &metadata, true);
+        let nav = build_post_navigation("post-1", "dev", &metadata, true, &BuildConfig::default());
 
         assert!(nav.prev.is_none());
         assert!(nav.next.is_some());
@@ -269,9 +294,25 @@ mod tests {
     #[test]
     fn test_navigation_url_encoding() {
         let metadata = create_test_metadata();
-        let nav = build_post_navigation("post-2", "dev", &metadata, true);
+        let nav = build_post_navigation("post-2", "dev", &metadata, true, &BuildConfig::default());
 
         assert!(nav.prev.is_some());
         assert_eq!(nav.prev.as_ref().unwrap().url, "/dev/post-1/");
     }
+
+    #[test]
+    fn test_navigation_uses_permalink_pattern() {
+        let metadata = create_test_metadata();
+        let build = BuildConfig {
+            permalink: "/:year/:month/:slug/".to_string(),
+            ..Default::default()
+        };
+        let nav = build_post_navigation("post-2", "dev", &metadata, true, &build);
+
+        let posted = metadata.posts[0].frontmatter.date.posted;
+        assert_eq!(
+            nav.prev.as_ref().unwrap().url,
+            format!("/{}/post-1/", posted.format("%Y/%m"))
+        );
+    }
 }
@@ -1,5 +1,6 @@
 use crate::types::Frontmatter;
-use std::path::PathBuf;
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{mpsc, Arc, Mutex};
 use std::thread::{self, JoinHandle};
@@ -24,6 +25,16 @@ pub enum BuildResult {
     },
 }
 
+impl BuildResult {
+    pub fn path(&self) -> &Path {
+        match self {
+            Self::Success { path, .. } | Self::Skipped { path, .. } | Self::Error { path, .. } => {
+                path
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum SkipReason {
     Cached,
@@ -67,6 +78,53 @@ impl Default for BuildProgress {
     }
 }
 
+const PROGRESS_BAR_WIDTH: usize = 30;
+
+/// `[###-----] 42/128 posts` line redrawn in place on stderr as results
+/// arrive. Stays silent when stderr isn't a terminal (CI logs, pipes).
+pub struct ProgressBar {
+    total: usize,
+    enabled: bool,
+}
+
+impl ProgressBar {
+    pub fn new(total: usize, enabled: bool) -> Self {
+        Self {
+            total,
+            enabled: enabled && std::io::stderr().is_terminal(),
+        }
+    }
+
+    pub fn update(&self, done: usize) {
+        if !self.enabled {
+            return;
+        }
+        let mut stderr = std::io::stderr().lock();
+        let _ = write!(stderr, "\r{}", render_progress(done, self.total));
+        let _ = stderr.flush();
+    }
+
+    pub fn finish(&self) {
+        if self.enabled {
+            eprintln!();
+        }
+    }
+}
+
+fn render_progress(done: usize, total: usize) -> String {
+    let filled = (done * PROGRESS_BAR_WIDTH)
+        .checked_div(total)
+        .unwrap_or(PROGRESS_BAR_WIDTH)
+        .min(PROGRESS_BAR_WIDTH);
+    format!(
+        "[{}{}] {}/{} posts",
+        "#".repeat(filled),
+        "-".repeat(PROGRESS_BAR_WIDTH - filled),
+        done,
+        total
+    )
+}
+
 const DEFAULT_THREAD_FALLBACK: usize = 4;
 
 pub fn get_thread_count() -> usize {
@@ -75,6 +133,33 @@ pub fn get_thread_count() -> usize {
         .unwrap_or(DEFAULT_THREAD_FALLBACK)
 }
 
+/// Apply `f` to every item on up to `threads` scoped threads, returning the
+/// results in input order regardless of which thread finished first
+pub fn parallel_map<T, R, F>(items: &[T], threads: usize, f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    if threads <= 1 || items.len() <= 1 {
+        return items.iter().map(f).collect();
+    }
+
+    let chunk_size = items.len().div_ceil(threads);
+    let f = &f;
+    thread::scope(|scope| {
+        let handles: Vec<_> = items
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || chunk.iter().map(f).collect::<Vec<_>>()))
+            .collect();
+
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().expect("Worker thread panicked"))
+            .collect()
+    })
+}
+
 /// Channel-based work queue for distributing tasks to workers
 pub struct WorkQueue<T> {
     sender: mpsc::Sender<T>,
@@ -137,6 +222,18 @@ impl WorkerPool {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parallel_map_keeps_input_order() {
+        let items: Vec<usize> = (0..103).collect();
+
+        let serial = parallel_map(&items, 1, |n| n * 2);
+        let parallel = parallel_map(&items, 8, |n| n * 2);
+
+        assert_eq!(parallel, serial);
+        assert_eq!(parallel[102], 204);
+        assert!(parallel_map(&Vec::<usize>::new(), 4, |n| *n).is_empty());
+    }
+
     #[test]
     fn test_get_thread_count() {
         let count = get_thread_count();
@@ -154,6 +251,22 @@ mod tests {
         assert_eq!(progress.get_skipped(), 1);
     }
 
+    #[test]
+    fn test_render_progress() {
+        assert_eq!(
+            render_progress(0, 4),
+            format!("[{}] 0/4 posts", "-".repeat(PROGRESS_BAR_WIDTH))
+        );
+        assert_eq!(
+            render_progress(2, 4),
+            format!("[{}{}] 2/4 posts", "#".repeat(15), "-".repeat(15))
+        );
+        assert_eq!(
+            render_progress(0, 0),
+            format!("[{}] 0/0 posts", "#".repeat(PROGRESS_BAR_WIDTH))
+        );
+    }
+
     #[test]
     fn test_work_queue() {
         let queue = WorkQueue::new();
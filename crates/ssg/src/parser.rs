@@ -1,17 +1,76 @@
 use crate::types::{Frontmatter, Page, PageFrontmatter, Post};
 use anyhow::{Context, Result};
+use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Every key `Frontmatter` understands (including serde aliases). `category`
+/// is written by `blog new` for readability; the real category comes from the
+/// directory, so it is accepted and ignored.
+const FRONTMATTER_KEYS: &[&str] = &[
+    "title",
+    "date",
+    "tags",
+    "cover_image",
+    "coverImage",
+    "og_image",
+    "ogImage",
+    "description",
+    "display_ad",
+    "displayAd",
+    "hidden",
+    "unlisted",
+    "comments",
+    "template",
+    "audio",
+    "lang",
+    "translations",
+    "category",
+];
 
 pub struct Parser;
 
+/// Whether `path` is a markdown source file (`.md` or `.markdown`)
+pub fn is_markdown(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext == "md" || ext == "markdown")
+}
+
+/// Maps `category/slug` (the source path relative to the content dir,
+/// minus the extension) to the post file, whichever markdown extension it
+/// uses. Keying by the full path keeps posts that share a slug in different
+/// categories apart.
+pub fn post_source_paths(content_dir: &Path) -> HashMap<String, PathBuf> {
+    let mut map = HashMap::new();
+    for entry in WalkDir::new(content_dir)
+        .follow_links(true)
+        .into_iter()
+        .filter_map(|e| e.ok())
+    {
+        let path = entry.path();
+        if is_markdown(path) {
+            if let Ok(relative) = path.with_extension("").strip_prefix(content_dir) {
+                let key = relative
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                map.insert(key, path.to_path_buf());
+            }
+        }
+    }
+    map
+}
+
 impl Parser {
     pub fn parse_file(path: &Path) -> Result<Post> {
         let content = fs::read_to_string(path)
             .with_context(|| format!("Failed to read {}", path.display()))?;
 
         let (frontmatter_str, markdown) = Self::split_frontmatter(&content)?;
-        let frontmatter = Self::parse_frontmatter(frontmatter_str)?;
+        let frontmatter = Self::parse_frontmatter(frontmatter_str)
+            .with_context(|| format!("Invalid frontmatter in {}", path.display()))?;
         let slug = Self::path_to_slug(path)?;
         let category = Self::extract_category(path)?;
 
@@ -108,7 +167,65 @@ impl Parser {
     }
 
     fn parse_frontmatter(yaml: &str) -> Result<Frontmatter> {
-        serde_yaml::from_str(yaml).context("Failed to parse frontmatter YAML")
+        Self::reject_unknown_keys(yaml)?;
+
+        let frontmatter: Frontmatter =
+            serde_yaml::from_str(yaml).context("Failed to parse frontmatter YAML")?;
+
+        if let Some(modified) = frontmatter.date.modified {
+            if modified < frontmatter.date.posted {
+                anyhow::bail!(
+                    "`date.modified` ({}) is earlier than `date.posted` ({})",
+                    modified.to_rfc3339(),
+                    frontmatter.date.posted.to_rfc3339()
+                );
+            }
+        }
+
+        Ok(frontmatter)
+    }
+
+    /// Misspelled keys (`tag:` for `tags:`) would otherwise be silently
+    /// dropped by serde, so unknown top-level keys fail the parse.
+    fn reject_unknown_keys(yaml: &str) -> Result<()> {
+        let value: serde_yaml::Value =
+            serde_yaml::from_str(yaml).context("Failed to parse frontmatter YAML")?;
+
+        let Some(mapping) = value.as_mapping() else {
+            return Ok(());
+        };
+
+        for key in mapping.keys() {
+            let key = key.as_str().unwrap_or_default();
+            if FRONTMATTER_KEYS.contains(&key) {
+                continue;
+            }
+
+            match Self::closest_key(key) {
+                Some(suggestion) => anyhow::bail!(
+                    "Unknown frontmatter key `{}` (did you mean `{}`?)",
+                    key,
+                    suggestion
+                ),
+                None => anyhow::bail!(
+                    "Unknown frontmatter key `{}`. Expected one of: {}",
+                    key,
+                    FRONTMATTER_KEYS.join(", ")
+                ),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Suggests the known key within two edits of `key`, if any.
+    fn closest_key(key: &str) -> Option<&'static str> {
+        FRONTMATTER_KEYS
+            .iter()
+            .map(|known| (*known, edit_distance(key, known)))
+            .filter(|(_, distance)| *distance <= 2)
+            .min_by_key(|(_, distance)| *distance)
+            .map(|(known, _)| known)
     }
 
     fn parse_page_frontmatter(yaml: &str) -> Result<PageFrontmatter> {
@@ -123,6 +240,23 @@ impl Parser {
     }
 }
 
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut prev_diag = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = prev_diag + usize::from(ca != *cb);
+            prev_diag = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(row[j + 1] + 1);
+        }
+    }
+
+    row[b.len()]
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -191,6 +325,47 @@ tags: []
         assert_eq!(fm.date.modified.unwrap().day(), 15);
     }
 
+    #[test]
+    fn test_parse_frontmatter_tags_list() {
+        let yaml = r#"
+title: Test Post
+date: 2025-01-01T12:00:00Z
+tags: [rust, " ssg ", ""]
+"#;
+        let fm = Parser::parse_frontmatter(yaml).unwrap();
+        assert_eq!(fm.tags, vec!["rust", "ssg"]);
+    }
+
+    #[test]
+    fn test_parse_frontmatter_tags_comma_string() {
+        let yaml = r#"
+title: Test Post
+date: 2025-01-01T12:00:00Z
+tags: rust, static site,, web
+"#;
+        let fm = Parser::parse_frontmatter(yaml).unwrap();
+        assert_eq!(fm.tags, vec!["rust", "static site", "web"]);
+
+        let yaml = r#"
+title: Test Post
+date: 2025-01-01T12:00:00Z
+tags: rust ssg
+"#;
+        let fm = Parser::parse_frontmatter(yaml).unwrap();
+        assert_eq!(fm.tags, vec!["rust", "ssg"]);
+    }
+
+    #[test]
+    fn test_parse_frontmatter_tags_empty_value() {
+        let yaml = r#"
+title: Test Post
+date: 2025-01-01T12:00:00Z
+tags:
+"#;
+        let fm = Parser::parse_frontmatter(yaml).unwrap();
+        assert!(fm.tags.is_empty());
+    }
+
     #[test]
     fn test_parse_frontmatter_missing_title_fails() {
         let yaml = r#"
@@ -222,6 +397,44 @@ tags: []
         assert!(result.is_err(), "Should fail with invalid date format");
     }
 
+    #[test]
+    fn test_parse_frontmatter_unknown_key_fails() {
+        let yaml = r#"
+title: Test Post
+date: 2025-01-01T12:00:00Z
+tag: [rust]
+"#;
+        let err = Parser::parse_frontmatter(yaml).unwrap_err().to_string();
+        assert!(err.contains("`tag`"), "got: {}", err);
+        assert!(err.contains("did you mean `tags`"), "got: {}", err);
+    }
+
+    #[test]
+    fn test_parse_frontmatter_accepts_aliases_and_category() {
+        let yaml = r#"
+title: Test Post
+date: 2025-01-01T12:00:00Z
+category: dev
+coverImage: ./cover.png
+displayAd: true
+"#;
+        let fm = Parser::parse_frontmatter(yaml).unwrap();
+        assert_eq!(fm.cover_image.as_deref(), Some("./cover.png"));
+        assert!(fm.display_ad);
+    }
+
+    #[test]
+    fn test_parse_frontmatter_modified_before_posted_fails() {
+        let yaml = r#"
+title: Test Post
+date:
+  posted: 2025-01-15T12:00:00Z
+  modified: 2025-01-01T12:00:00Z
+"#;
+        let err = Parser::parse_frontmatter(yaml).unwrap_err().to_string();
+        assert!(err.contains("date.modified"), "got: {}", err);
+    }
+
     #[test]
     fn test_split_frontmatter_missing_closing_delimiter_fails() {
         let content = r#"---
@@ -0,0 +1,337 @@
+use crate::config::BuildConfig;
+use crate::shortcodes::escape_html;
+use crate::slug;
+use anyhow::Result;
+use chrono::{DateTime, Datelike, Utc};
+use regex::{Captures, Regex};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+pub const DEFAULT_PERMALINK: &str = "/:category/:slug/";
+
+/// Placeholders that can appear in `build.permalink`
+const PLACEHOLDERS: &[&str] = &["category", "slug", "year", "month", "day"];
+
+/// Reject patterns that would make posts collide or leave a placeholder
+/// unexpanded in the output.
+pub fn validate(pattern: &str) -> Result<()> {
+    if !pattern.contains(":slug") {
+        anyhow::bail!("build.permalink must contain :slug (got '{}')", pattern);
+    }
+
+    for cap in placeholder_re().captures_iter(pattern) {
+        if !PLACEHOLDERS.contains(&&cap[1]) {
+            anyhow::bail!(
+                "Unknown placeholder ':{}' in build.permalink (supported: {})",
+                &cap[1],
+                PLACEHOLDERS
+                    .iter()
+                    .map(|p| format!(":{}", p))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Expand a permalink pattern into raw (unencoded) path segments
+pub fn segments(
+    pattern: &str,
+    category: &str,
+    post_slug: &str,
+    posted: &DateTime<Utc>,
+) -> Vec<String> {
+    pattern
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| {
+            placeholder_re()
+                .replace_all(segment, |cap: &Captures| match &cap[1] {
+                    "category" => category.to_string(),
+                    "slug" => post_slug.to_string(),
+                    "year" => format!("{:04}", posted.year()),
+                    "month" => format!("{:02}", posted.month()),
+                    "day" => format!("{:02}", posted.day()),
+                    _ => cap[0].to_string(),
+                })
+                .into_owned()
+        })
+        .collect()
+}
+
+/// Site-relative URL for a post (`/dev/my-post/`), percent-encoding each
+/// segment when `encode` is set
+pub fn post_url(
+    build: &BuildConfig,
+    category: &str,
+    post_slug: &str,
+    posted: &DateTime<Utc>,
+    encode: bool,
+) -> String {
+    let path = segments(&build.permalink, category, post_slug, posted)
+        .iter()
+        .map(|segment| {
+            if encode {
+                slug::encode_for_url(segment)
+            } else {
+                segment.clone()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("/");
+
+    page_url(&format!("/{}/", path), build.ugly_urls)
+}
+
+/// Rewrite a directory-style path (`/dev/`) as `/dev.html` when ugly URLs
+/// are enabled. The site root always stays `/`.
+pub fn page_url(path: &str, ugly_urls: bool) -> String {
+    if !ugly_urls || path == "/" {
+        return path.to_string();
+    }
+
+    format!("{}.html", path.trim_end_matches('/'))
+}
+
+/// Absolute URL for the site-relative `path` (already run through
+/// [`page_url`] or [`post_url`]). The homepage is `site_url` itself.
+pub fn canonical_url(site_url: &str, path: &str) -> String {
+    if path == "/" {
+        return site_url.to_string();
+    }
+
+    format!("{}{}", site_url.trim_end_matches('/'), path)
+}
+
+/// Output file for the page served at `dir`: `dir/index.html`, or
+/// `dir.html` when ugly URLs are enabled
+pub fn page_file(dir: PathBuf, ugly_urls: bool) -> PathBuf {
+    match dir.file_name() {
+        Some(name) if ugly_urls => {
+            let file_name = format!("{}.html", name.to_string_lossy());
+            dir.with_file_name(file_name)
+        }
+        _ => dir.join("index.html"),
+    }
+}
+
+/// Tera function exposing [`page_url`] to templates as
+/// `url_for(path="/tag/rust/")`. Output is HTML-escaped here and marked safe
+/// so slashes aren't turned into `&#x2F;` by autoescaping.
+pub struct UrlFor {
+    pub ugly_urls: bool,
+}
+
+impl tera::Function for UrlFor {
+    fn call(&self, args: &HashMap<String, tera::Value>) -> tera::Result<tera::Value> {
+        let path = args
+            .get("path")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| tera::Error::msg("url_for requires a `path` argument"))?;
+
+        Ok(tera::Value::String(escape_html(&page_url(
+            path,
+            self.ugly_urls,
+        ))))
+    }
+
+    fn is_safe(&self) -> bool {
+        true
+    }
+}
+
+/// Tera function returning the site-absolute form of a path, as
+/// `url(path="/about/")` -> `https://example.com/about/`. Follows the same
+/// ugly-URL policy as [`UrlFor`].
+pub struct AbsoluteUrl {
+    pub site_url: String,
+    pub ugly_urls: bool,
+}
+
+impl tera::Function for AbsoluteUrl {
+    fn call(&self, args: &HashMap<String, tera::Value>) -> tera::Result<tera::Value> {
+        let path = args
+            .get("path")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| tera::Error::msg("url requires a `path` argument"))?;
+
+        let path = if path.starts_with('/') {
+            path.to_string()
+        } else {
+            format!("/{}", path)
+        };
+        let url = format!(
+            "{}{}",
+            self.site_url.trim_end_matches('/'),
+            page_url(&path, self.ugly_urls)
+        );
+
+        Ok(tera::Value::String(escape_html(&url)))
+    }
+
+    fn is_safe(&self) -> bool {
+        true
+    }
+}
+
+fn placeholder_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r":([a-z]+)").unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn date() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, 7, 12, 0, 0).unwrap()
+    }
+
+    fn build(permalink: &str, ugly_urls: bool) -> BuildConfig {
+        BuildConfig {
+            permalink: permalink.to_string(),
+            ugly_urls,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_default_pattern() {
+        assert_eq!(
+            post_url(
+                &build(DEFAULT_PERMALINK, false),
+                "dev",
+                "hello-world",
+                &date(),
+                true
+            ),
+            "/dev/hello-world/"
+        );
+    }
+
+    #[test]
+    fn test_date_pattern() {
+        assert_eq!(
+            post_url(
+                &build("/:year/:month/:day/:slug/", false),
+                "dev",
+                "hello",
+                &date(),
+                true
+            ),
+            "/2024/03/07/hello/"
+        );
+    }
+
+    #[test]
+    fn test_placeholders_within_segment() {
+        assert_eq!(
+            segments("/posts/:year-:slug", "dev", "hello", &date()),
+            vec!["posts", "2024-hello"]
+        );
+    }
+
+    #[test]
+    fn test_encoding_is_per_segment() {
+        assert_eq!(
+            post_url(
+                &build(DEFAULT_PERMALINK, false),
+                "dev",
+                "한글",
+                &date(),
+                false
+            ),
+            "/dev/한글/"
+        );
+        assert_eq!(
+            post_url(
+                &build(DEFAULT_PERMALINK, false),
+                "dev",
+                "한글",
+                &date(),
+                true
+            ),
+            "/dev/%ED%95%9C%EA%B8%80/"
+        );
+    }
+
+    #[test]
+    fn test_ugly_urls() {
+        assert_eq!(
+            post_url(
+                &build(DEFAULT_PERMALINK, true),
+                "dev",
+                "hello",
+                &date(),
+                true
+            ),
+            "/dev/hello.html"
+        );
+        assert_eq!(page_url("/tag/rust/", true), "/tag/rust.html");
+        assert_eq!(page_url("/tag/rust/", false), "/tag/rust/");
+        assert_eq!(page_url("/", true), "/");
+    }
+
+    #[test]
+    fn test_page_file() {
+        assert_eq!(
+            page_file(PathBuf::from("dist/dev/hello"), false),
+            PathBuf::from("dist/dev/hello/index.html")
+        );
+        assert_eq!(
+            page_file(PathBuf::from("dist/dev/node.js"), true),
+            PathBuf::from("dist/dev/node.js.html")
+        );
+    }
+
+    fn render_url(path: &str, ugly_urls: bool) -> String {
+        let mut tera = tera::Tera::default();
+        tera.register_function(
+            "url",
+            AbsoluteUrl {
+                site_url: "https://example.com/".to_string(),
+                ugly_urls,
+            },
+        );
+        tera.add_raw_template("t", &format!("{{{{ url(path=\"{}\") }}}}", path))
+            .unwrap();
+        tera.render("t", &tera::Context::new()).unwrap()
+    }
+
+    #[test]
+    fn test_absolute_url_function() {
+        assert_eq!(
+            render_url("/tag/rust/", false),
+            "https://example.com/tag/rust/"
+        );
+        assert_eq!(
+            render_url("tag/rust/", true),
+            "https://example.com/tag/rust.html"
+        );
+        assert_eq!(render_url("/", true), "https://example.com/");
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(validate(DEFAULT_PERMALINK).is_ok());
+        assert!(validate("/:year/:month/:slug/").is_ok());
+        assert!(validate("/:category/").is_err());
+        assert!(validate("/:yr/:slug/").is_err());
+    }
+
+    #[test]
+    fn test_canonical_url() {
+        assert_eq!(
+            canonical_url("https://example.com", "/"),
+            "https://example.com"
+        );
+        assert_eq!(
+            canonical_url("https://example.com/", "/dev/post/"),
+            "https://example.com/dev/post/"
+        );
+    }
+}
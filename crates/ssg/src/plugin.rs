@@ -0,0 +1,243 @@
+use crate::config::{BuildConfig, PluginConfig, SsgConfig};
+use anyhow::{Context, Result};
+use serde_json::{Map, Value};
+
+/// The post or page a plugin hook is running for
+// Fields are read by plugins; none of the built-in ones need them yet
+#[allow(dead_code)]
+#[derive(Clone, Copy)]
+pub struct PluginContext<'a> {
+    pub config: &'a SsgConfig,
+    pub slug: &'a str,
+    /// Site-relative URL of the post or page
+    pub url: &'a str,
+    /// The HTML is a partial (`build.generate_partials`), not a full document
+    pub partial: bool,
+    /// This plugin's `settings` from `build.plugins` (null when omitted)
+    pub settings: &'a Value,
+}
+
+/// Compiled-in build extension. Add implementations to [`available`] and
+/// enable them by name in `build.plugins`.
+pub trait Plugin: Send + Sync {
+    fn name(&self) -> &str;
+
+    /// Extra template variables for a post or page. When several plugins set
+    /// the same key, the one listed later in `build.plugins` wins.
+    fn template_context(&self, _ctx: &PluginContext) -> Result<Map<String, Value>> {
+        Ok(Map::new())
+    }
+
+    /// Rewrite a post's or page's final HTML before it's written, e.g. to
+    /// inject analytics or a comment widget
+    fn transform_html(&self, _ctx: &PluginContext, html: &str) -> Result<String> {
+        Ok(html.to_string())
+    }
+}
+
+/// Every plugin that can be enabled from `build.plugins`
+fn available() -> Vec<Box<dyn Plugin>> {
+    Vec::new()
+}
+
+/// The plugins enabled by `build.plugins`, in the order listed there
+pub struct PluginManager {
+    plugins: Vec<(Box<dyn Plugin>, Value)>,
+}
+
+impl PluginManager {
+    pub fn from_config(build: &BuildConfig) -> Result<Self> {
+        Self::select(available(), &build.plugins)
+    }
+
+    /// Pick the configured plugins out of `available`, failing on names that
+    /// aren't available or are listed twice
+    pub fn select(
+        mut available: Vec<Box<dyn Plugin>>,
+        configured: &[PluginConfig],
+    ) -> Result<Self> {
+        let mut plugins = Vec::with_capacity(configured.len());
+
+        for entry in configured {
+            let Some(index) = available.iter().position(|p| p.name() == entry.name) else {
+                if plugins
+                    .iter()
+                    .any(|(p, _): &(Box<dyn Plugin>, Value)| p.name() == entry.name)
+                {
+                    anyhow::bail!("Plugin '{}' is listed twice in build.plugins", entry.name);
+                }
+                anyhow::bail!(
+                    "Unknown plugin '{}' in build.plugins (available: {})",
+                    entry.name,
+                    available_names(&available)
+                );
+            };
+            plugins.push((available.remove(index), entry.settings.clone()));
+        }
+
+        Ok(Self { plugins })
+    }
+
+    /// Merge every plugin's [`Plugin::template_context`], later plugins
+    /// overriding earlier ones
+    pub fn template_context(&self, ctx: &PluginContext) -> Result<Map<String, Value>> {
+        let mut merged = Map::new();
+        for (plugin, settings) in &self.plugins {
+            let ctx = PluginContext { settings, ..*ctx };
+            let values = plugin
+                .template_context(&ctx)
+                .with_context(|| format!("Plugin '{}' failed on {}", plugin.name(), ctx.slug))?;
+            merged.extend(values);
+        }
+        Ok(merged)
+    }
+
+    /// Pass `html` through each plugin's [`Plugin::transform_html`] in order
+    pub fn transform_html(&self, ctx: &PluginContext, html: String) -> Result<String> {
+        self.plugins
+            .iter()
+            .try_fold(html, |html, (plugin, settings)| {
+                let ctx = PluginContext { settings, ..*ctx };
+                plugin
+                    .transform_html(&ctx, &html)
+                    .with_context(|| format!("Plugin '{}' failed on {}", plugin.name(), ctx.slug))
+            })
+    }
+}
+
+fn available_names(plugins: &[Box<dyn Plugin>]) -> String {
+    if plugins.is_empty() {
+        return "none".to_string();
+    }
+    plugins
+        .iter()
+        .map(|p| p.name())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// Appends `settings.suffix` and sets `greeting` to its own name
+    struct Greeter(&'static str);
+
+    impl Plugin for Greeter {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn template_context(&self, _ctx: &PluginContext) -> Result<Map<String, Value>> {
+            let mut values = Map::new();
+            values.insert("greeting".to_string(), json!(self.0));
+            Ok(values)
+        }
+
+        fn transform_html(&self, ctx: &PluginContext, html: &str) -> Result<String> {
+            Ok(format!(
+                "{}{}",
+                html,
+                ctx.settings["suffix"].as_str().unwrap_or("")
+            ))
+        }
+    }
+
+    struct Fail;
+
+    impl Plugin for Fail {
+        fn name(&self) -> &str {
+            "fail"
+        }
+
+        fn transform_html(&self, _ctx: &PluginContext, _html: &str) -> Result<String> {
+            anyhow::bail!("boom")
+        }
+    }
+
+    fn test_plugins() -> Vec<Box<dyn Plugin>> {
+        vec![
+            Box::new(Greeter("a")),
+            Box::new(Greeter("b")),
+            Box::new(Fail),
+        ]
+    }
+
+    fn configured(names: &[&str]) -> Vec<PluginConfig> {
+        names
+            .iter()
+            .map(|name| PluginConfig {
+                name: name.to_string(),
+                settings: json!({ "suffix": name }),
+            })
+            .collect()
+    }
+
+    fn ctx(config: &SsgConfig) -> PluginContext<'_> {
+        PluginContext {
+            config,
+            slug: "hello",
+            url: "/dev/hello/",
+            partial: false,
+            settings: &Value::Null,
+        }
+    }
+
+    #[test]
+    fn test_config_order_sets_precedence() {
+        let config = SsgConfig::default();
+
+        let ab = PluginManager::select(test_plugins(), &configured(&["a", "b"])).unwrap();
+        let ba = PluginManager::select(test_plugins(), &configured(&["b", "a"])).unwrap();
+
+        assert_eq!(ab.template_context(&ctx(&config)).unwrap()["greeting"], "b");
+        assert_eq!(ba.template_context(&ctx(&config)).unwrap()["greeting"], "a");
+        assert_eq!(
+            ab.transform_html(&ctx(&config), String::new()).unwrap(),
+            "ab"
+        );
+        assert_eq!(
+            ba.transform_html(&ctx(&config), String::new()).unwrap(),
+            "ba"
+        );
+    }
+
+    #[test]
+    fn test_only_listed_plugins_run() {
+        let config = SsgConfig::default();
+        let manager = PluginManager::select(test_plugins(), &configured(&["b"])).unwrap();
+
+        assert_eq!(
+            manager
+                .transform_html(&ctx(&config), "<p></p>".to_string())
+                .unwrap(),
+            "<p></p>b"
+        );
+    }
+
+    #[test]
+    fn test_unknown_or_repeated_plugin_errors() {
+        let err = PluginManager::select(test_plugins(), &configured(&["nope"]))
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("Unknown plugin 'nope'"), "{}", err);
+
+        let err = PluginManager::select(test_plugins(), &configured(&["a", "a"]))
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("listed twice"), "{}", err);
+    }
+
+    #[test]
+    fn test_failure_names_plugin() {
+        let config = SsgConfig::default();
+        let manager = PluginManager::select(test_plugins(), &configured(&["fail"])).unwrap();
+
+        let err = manager
+            .transform_html(&ctx(&config), String::new())
+            .unwrap_err();
+
+        assert!(err.to_string().contains("Plugin 'fail'"), "{}", err);
+    }
+}
@@ -0,0 +1,259 @@
+use crate::config::SsgConfig;
+use anyhow::{Context, Result};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Text outputs worth compressing; images and fonts are already compressed
+const COMPRESSIBLE_EXTENSIONS: &[&str] = &["html", "css", "js", "xml", "json", "svg", "txt"];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Format {
+    Gzip,
+    Brotli,
+}
+
+impl Format {
+    const ALL: [Format; 2] = [Format::Gzip, Format::Brotli];
+
+    fn parse(name: &str) -> Result<Self> {
+        match name {
+            "gzip" => Ok(Self::Gzip),
+            "brotli" => Ok(Self::Brotli),
+            other => anyhow::bail!(
+                "Unknown build.precompress format '{}' (expected gzip or brotli)",
+                other
+            ),
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Gzip => "gz",
+            Self::Brotli => "br",
+        }
+    }
+
+    fn compress(self, data: &[u8]) -> Result<Vec<u8>> {
+        let mut output = Vec::new();
+        match self {
+            Self::Gzip => {
+                let mut encoder =
+                    flate2::write::GzEncoder::new(&mut output, flate2::Compression::best());
+                encoder.write_all(data)?;
+                encoder.finish()?;
+            }
+            Self::Brotli => {
+                let mut encoder = brotli::CompressorWriter::new(&mut output, 4096, 11, 22);
+                encoder.write_all(data)?;
+                // Finishes the stream
+                encoder.into_inner();
+            }
+        }
+        Ok(output)
+    }
+}
+
+/// Writes `.gz`/`.br` siblings next to text files in the output directory
+/// for hosts that serve precompressed files
+pub struct Precompressor;
+
+impl Precompressor {
+    /// Compress every eligible file under `output_dir` into each format in
+    /// `build.precompress`, returning the number of files written. Siblings
+    /// newer than their source are kept, so incremental builds only
+    /// recompress what changed; siblings whose source is gone or no longer
+    /// eligible (too small, or its format was turned off) are removed.
+    pub fn generate(config: &SsgConfig, output_dir: &Path) -> Result<usize> {
+        let formats = config
+            .build
+            .precompress
+            .iter()
+            .map(|name| Format::parse(name))
+            .collect::<Result<Vec<_>>>()?;
+        if !output_dir.exists() {
+            return Ok(0);
+        }
+
+        let min_size = config.build.precompress_min_size;
+        Self::remove_stale(output_dir, &formats, min_size);
+
+        let mut written = 0;
+        for path in Self::compressible_files(output_dir, min_size) {
+            let data = fs::read(&path)?;
+            for format in &formats {
+                let target = Self::sibling(&path, *format);
+                if Self::is_fresh(&target, &path) {
+                    continue;
+                }
+                let compressed = format
+                    .compress(&data)
+                    .with_context(|| format!("Failed to compress {}", path.display()))?;
+                fs::write(&target, compressed)?;
+                written += 1;
+            }
+        }
+
+        Ok(written)
+    }
+
+    /// Every precompressed sibling `path` may have, whether or not it exists
+    pub fn siblings(path: &Path) -> impl Iterator<Item = PathBuf> + '_ {
+        Format::ALL
+            .into_iter()
+            .map(move |format| Self::sibling(path, format))
+    }
+
+    fn compressible_files(output_dir: &Path, min_size: u64) -> Vec<PathBuf> {
+        WalkDir::new(output_dir)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+            .filter(|e| Self::is_compressible(e.path()))
+            .filter(|e| e.metadata().is_ok_and(|m| m.len() >= min_size))
+            .map(|e| e.into_path())
+            .collect()
+    }
+
+    fn is_compressible(path: &Path) -> bool {
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| COMPRESSIBLE_EXTENSIONS.contains(&ext))
+    }
+
+    /// Delete `.gz`/`.br` files next to a compressible source that is
+    /// missing, under `min_size`, or no longer in `formats`. Archives such
+    /// as `bundle.tar.gz` don't name a compressible source and are kept.
+    fn remove_stale(output_dir: &Path, formats: &[Format], min_size: u64) {
+        for entry in WalkDir::new(output_dir)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+        {
+            let path = entry.path();
+            let Some(format) = Format::ALL.into_iter().find(|format| {
+                path.extension()
+                    .is_some_and(|ext| ext == format.extension())
+            }) else {
+                continue;
+            };
+            let source = path.with_extension("");
+            if !Self::is_compressible(&source) {
+                continue;
+            }
+
+            let eligible = formats.contains(&format)
+                && fs::metadata(&source).is_ok_and(|m| m.is_file() && m.len() >= min_size);
+            if !eligible && fs::remove_file(path).is_err() {
+                eprintln!("⚠️  Failed to remove stale {}", path.display());
+            }
+        }
+    }
+
+    fn sibling(path: &Path, format: Format) -> PathBuf {
+        let mut name = path.as_os_str().to_os_string();
+        name.push(".");
+        name.push(format.extension());
+        PathBuf::from(name)
+    }
+
+    fn is_fresh(target: &Path, source: &Path) -> bool {
+        let modified = |path: &Path| fs::metadata(path).and_then(|m| m.modified()).ok();
+        match (modified(target), modified(source)) {
+            (Some(target), Some(source)) => target >= source,
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use tempfile::TempDir;
+
+    fn config(formats: &[&str], min_size: u64) -> SsgConfig {
+        let mut config = SsgConfig::default();
+        config.build.precompress = formats.iter().map(|f| f.to_string()).collect();
+        config.build.precompress_min_size = min_size;
+        config
+    }
+
+    #[test]
+    fn test_writes_siblings_that_decompress_to_the_original() {
+        let temp = TempDir::new().unwrap();
+        let html = "<p>hello</p>".repeat(200);
+        fs::write(temp.path().join("index.html"), &html).unwrap();
+
+        let written =
+            Precompressor::generate(&config(&["gzip", "brotli"], 0), temp.path()).unwrap();
+
+        assert_eq!(written, 2);
+        let mut gunzipped = String::new();
+        flate2::read::GzDecoder::new(&fs::read(temp.path().join("index.html.gz")).unwrap()[..])
+            .read_to_string(&mut gunzipped)
+            .unwrap();
+        assert_eq!(gunzipped, html);
+
+        let mut unbrotlied = String::new();
+        brotli::Decompressor::new(
+            &fs::read(temp.path().join("index.html.br")).unwrap()[..],
+            4096,
+        )
+        .read_to_string(&mut unbrotlied)
+        .unwrap();
+        assert_eq!(unbrotlied, html);
+    }
+
+    #[test]
+    fn test_skips_small_and_binary_files() {
+        let temp = TempDir::new().unwrap();
+        fs::write(temp.path().join("tiny.css"), "a{}").unwrap();
+        fs::write(temp.path().join("photo.png"), vec![0u8; 4096]).unwrap();
+
+        let written = Precompressor::generate(&config(&["gzip"], 1024), temp.path()).unwrap();
+
+        assert_eq!(written, 0);
+        assert!(!temp.path().join("tiny.css.gz").exists());
+        assert!(!temp.path().join("photo.png.gz").exists());
+    }
+
+    #[test]
+    fn test_removes_siblings_that_are_no_longer_eligible() {
+        let temp = TempDir::new().unwrap();
+        let html = "<p>hello</p>".repeat(200);
+        fs::write(temp.path().join("kept.html"), &html).unwrap();
+        fs::write(temp.path().join("shrunk.css"), "a{}").unwrap();
+        for name in [
+            "kept.html.gz",
+            "kept.html.br",
+            "deleted.html.gz",
+            "shrunk.css.gz",
+            "bundle.tar.gz",
+        ] {
+            fs::write(temp.path().join(name), "stale").unwrap();
+        }
+
+        Precompressor::generate(&config(&["gzip"], 1024), temp.path()).unwrap();
+
+        assert!(temp.path().join("kept.html.gz").exists());
+        assert!(!temp.path().join("kept.html.br").exists());
+        assert!(!temp.path().join("deleted.html.gz").exists());
+        assert!(!temp.path().join("shrunk.css.gz").exists());
+        assert!(temp.path().join("bundle.tar.gz").exists());
+
+        Precompressor::generate(&config(&[], 1024), temp.path()).unwrap();
+
+        assert!(!temp.path().join("kept.html.gz").exists());
+    }
+
+    #[test]
+    fn test_unknown_format_is_an_error() {
+        let temp = TempDir::new().unwrap();
+
+        let err = Precompressor::generate(&config(&["zip"], 0), temp.path()).unwrap_err();
+
+        assert!(err.to_string().contains("'zip'"), "got: {}", err);
+    }
+}
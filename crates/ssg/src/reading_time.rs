@@ -3,33 +3,90 @@ use pulldown_cmark::{Event, Options, Parser, Tag};
 const CJK_CHARS_PER_MIN: f64 = 500.0;
 const WORDS_PER_MIN: f64 = 200.0;
 
-/// Estimate reading time in minutes for mixed Korean/English markdown.
+/// Maximum length of [`PostStats::excerpt`] in characters, before the ellipsis
+pub const EXCERPT_LENGTH: usize = 160;
+
+/// Text measurements of a post body, computed once during the metadata pass
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PostStats {
+    pub reading_time: u32,
+    /// Whitespace-separated words of prose, code blocks excluded
+    pub word_count: usize,
+    /// Non-whitespace characters of prose, code blocks excluded
+    pub char_count: usize,
+    /// Plain text of the opening paragraphs, cut at [`EXCERPT_LENGTH`]
+    pub excerpt: String,
+}
+
+/// Reading time, word and character counts and excerpt of a markdown body
+/// in a single parse.
 ///
-/// Korean (and other CJK) text is counted per-character (~500 chars/min);
-/// Latin text is counted per-word (~200 words/min). Code blocks are excluded
-/// since readers skim rather than read them word-by-word. Always at least 1.
-pub fn estimate(markdown: &str) -> u32 {
+/// Reading time is in minutes for mixed Korean/English markdown: Korean (and
+/// other CJK) text is counted per-character (~500 chars/min); Latin text is
+/// counted per-word (~200 words/min). Code blocks are excluded since readers
+/// skim rather than read them word-by-word. Always at least 1.
+pub fn analyze(markdown: &str) -> PostStats {
     let parser = Parser::new_ext(markdown, Options::all());
 
     let mut in_code_block = false;
+    let mut in_paragraph = false;
     let mut cjk_chars = 0usize;
     let mut words = 0usize;
+    let mut stats = PostStats::default();
+    let mut excerpt = String::new();
 
     for event in parser {
         match event {
             Event::Start(Tag::CodeBlock(_)) => in_code_block = true,
             Event::End(Tag::CodeBlock(_)) => in_code_block = false,
-            Event::Text(text) if !in_code_block => {
+            Event::Start(Tag::Paragraph) => in_paragraph = true,
+            Event::End(Tag::Paragraph) => {
+                in_paragraph = false;
+                excerpt.push(' ');
+            }
+            Event::SoftBreak | Event::HardBreak if in_paragraph => excerpt.push(' '),
+            Event::Text(text) | Event::Code(text) if !in_code_block => {
                 let (cjk, word_count) = count_text(&text);
                 cjk_chars += cjk;
                 words += word_count;
+                stats.word_count += text
+                    .split_whitespace()
+                    .filter(|token| token.chars().any(char::is_alphanumeric))
+                    .count();
+                stats.char_count += text.chars().filter(|c| !c.is_whitespace()).count();
+
+                // Only the first stretch of prose is needed for the excerpt
+                if in_paragraph && excerpt.chars().count() <= EXCERPT_LENGTH {
+                    excerpt.push_str(&text);
+                }
             }
             _ => {}
         }
     }
 
     let minutes = (cjk_chars as f64 / CJK_CHARS_PER_MIN) + (words as f64 / WORDS_PER_MIN);
-    (minutes.ceil() as u32).max(1)
+    stats.reading_time = (minutes.ceil() as u32).max(1);
+    stats.excerpt = truncate_excerpt(&excerpt);
+    stats
+}
+
+/// Collapse whitespace and cut at [`EXCERPT_LENGTH`] characters, on a word
+/// boundary when there is one
+fn truncate_excerpt(text: &str) -> String {
+    let collapsed = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if collapsed.chars().count() <= EXCERPT_LENGTH {
+        return collapsed;
+    }
+
+    let cut: String = collapsed.chars().take(EXCERPT_LENGTH).collect();
+    let cut = match cut.rfind(' ') {
+        Some(space) if space > 0 => &cut[..space],
+        _ => cut.as_str(),
+    };
+    format!(
+        "{}…",
+        cut.trim_end_matches(|c: char| c.is_ascii_punctuation())
+    )
 }
 
 fn count_text(text: &str) -> (usize, usize) {
@@ -59,25 +116,55 @@ mod tests {
 
     #[test]
     fn test_minimum_one_minute() {
-        assert_eq!(estimate(""), 1);
-        assert_eq!(estimate("짧다"), 1);
+        assert_eq!(analyze("").reading_time, 1);
+        assert_eq!(analyze("짧다").reading_time, 1);
     }
 
     #[test]
     fn test_korean_scales_by_chars() {
         let text = "가".repeat(1000);
-        assert_eq!(estimate(&text), 2);
+        assert_eq!(analyze(&text).reading_time, 2);
     }
 
     #[test]
     fn test_english_scales_by_words() {
         let text = "word ".repeat(400);
-        assert_eq!(estimate(&text), 2);
+        assert_eq!(analyze(&text).reading_time, 2);
     }
 
     #[test]
     fn test_code_blocks_excluded() {
         let with_code = format!("```\n{}\n```", "fn main() {}\n".repeat(200));
-        assert_eq!(estimate(&with_code), 1);
+        assert_eq!(analyze(&with_code).reading_time, 1);
+    }
+
+    #[test]
+    fn test_analyze_counts_prose() {
+        let stats =
+            analyze("# Title\n\nHello `wasm` world, 안녕하세요.\n\n```\nignored code\n```\n");
+
+        assert_eq!(stats.word_count, 5);
+        assert_eq!(
+            stats.char_count,
+            "Title".len()
+                + "Hello".len()
+                + "wasm".len()
+                + "world,".len()
+                + "안녕하세요.".chars().count()
+        );
+        assert_eq!(stats.excerpt, "Hello wasm world, 안녕하세요.");
+        assert_eq!(stats.reading_time, 1);
+    }
+
+    #[test]
+    fn test_excerpt_truncated_on_word_boundary() {
+        let stats = analyze(&"lorem ipsum ".repeat(40));
+
+        assert!(stats.excerpt.ends_with("…"), "{}", stats.excerpt);
+        assert!(stats.excerpt.chars().count() <= EXCERPT_LENGTH + 1);
+        assert!(!stats.excerpt.contains("  "));
+        assert!("lorem ipsum "
+            .repeat(40)
+            .starts_with(stats.excerpt.trim_end_matches('…')));
     }
 }
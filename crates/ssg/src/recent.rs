@@ -1,12 +1,12 @@
 use crate::config::SsgConfig;
-use crate::metadata::{compare_posts_desc, MetadataCache};
-use crate::slug;
+use crate::image::ImageProcessor;
+use crate::metadata::{compare_posts_desc, MetadataCache, PostMetadata};
+use crate::permalink;
+use crate::verbosity::status;
 use anyhow::Result;
 use serde::Serialize;
 use std::fs;
-use std::path::PathBuf;
-
-const RECENT_POSTS_COUNT: usize = 6;
+use std::path::{Component, Path, PathBuf};
 
 #[derive(Debug, Serialize)]
 pub struct RecentPost {
@@ -14,19 +14,53 @@ pub struct RecentPost {
     pub uri: String,
     pub date: String,
     pub desc: String,
+    /// CDN thumbnail of the cover (or OG) image, when a CDN is configured
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thumbnail: Option<String>,
 }
 
 pub struct RecentGenerator {
     config: SsgConfig,
+    image_processor: Option<ImageProcessor>,
+    content_dir: PathBuf,
 }
 
 impl RecentGenerator {
     pub fn new(config: SsgConfig) -> Self {
-        Self { config }
+        let image_processor = ImageProcessor::from_config(&config);
+        let content_dir = PathBuf::from(&config.build.content_dir);
+
+        Self {
+            config,
+            image_processor,
+            content_dir,
+        }
+    }
+
+    /// `build.recent.output_path` under the output directory, which it may
+    /// not escape
+    fn output_path(&self) -> Result<PathBuf> {
+        let relative = Path::new(&self.config.build.recent.output_path);
+        let escapes = relative
+            .components()
+            .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir));
+        if escapes || relative.as_os_str().is_empty() {
+            anyhow::bail!(
+                "build.recent.output_path must be a relative path inside the output directory, got '{}'",
+                self.config.build.recent.output_path
+            );
+        }
+        Ok(PathBuf::from(&self.config.build.output_dir).join(relative))
+    }
+
+    fn thumbnail(&self, post: &PostMetadata) -> Option<String> {
+        self.image_processor
+            .as_ref()?
+            .post_thumbnail(post, &self.content_dir)
     }
 
     pub fn generate(&self, metadata: &MetadataCache) -> Result<()> {
-        println!("\n📋 Generating recent posts...");
+        status!("\n📋 Generating recent posts...");
 
         let mut filtered: Vec<_> = metadata
             .posts
@@ -38,33 +72,39 @@ impl RecentGenerator {
 
         let posts: Vec<RecentPost> = filtered
             .into_iter()
-            .take(RECENT_POSTS_COUNT)
+            .take(self.config.build.recent_count)
             .map(|post| {
-                let uri = if self.config.build.encode_filenames {
-                    format!(
-                        "{}/{}/{}/",
-                        self.config.site.url,
-                        slug::encode_for_url(&post.category),
-                        slug::encode_for_url(&post.slug)
-                    )
+                let path = permalink::post_url(
+                    &self.config.build,
+                    &post.category,
+                    &post.slug,
+                    &post.frontmatter.date.posted,
+                    self.config.build.encode_filenames,
+                );
+                let uri = if self.config.build.recent.absolute_urls {
+                    format!("{}{}", self.config.site.url, path)
                 } else {
-                    format!("{}/{}/{}/", self.config.site.url, post.category, post.slug)
+                    path
                 };
 
                 RecentPost {
                     title: post.frontmatter.title.clone(),
                     uri,
                     date: post.frontmatter.date.posted.to_rfc3339(),
-                    desc: post.frontmatter.description.clone().unwrap_or_default(),
+                    desc: post.summary().unwrap_or_default().to_string(),
+                    thumbnail: self.thumbnail(post),
                 }
             })
             .collect();
 
         let json = serde_json::to_string(&posts)?;
-        let output_path = PathBuf::from(&self.config.build.output_dir).join("recent.json");
+        let output_path = self.output_path()?;
+        if let Some(parent) = output_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
         fs::write(&output_path, json)?;
 
-        println!("   ✓ {} recent posts generated", posts.len());
+        status!("   ✓ {} recent posts generated", posts.len());
 
         Ok(())
     }
@@ -2,12 +2,17 @@ use anyhow::Result;
 use pulldown_cmark::{
     CodeBlockKind, CowStr, Event, HeadingLevel, Options, Parser as MdParser, Tag,
 };
+use regex::{Captures, Regex};
 use serde::Serialize;
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::path::Path;
+use std::sync::OnceLock;
 use tera::{Context, Tera};
 
+use crate::abbreviations;
+use crate::config::SsgConfig;
+use crate::emoji;
 use crate::image::ImageProcessor;
 use crate::slug;
 use crate::syntax_highlighter::SyntaxHighlighter;
@@ -42,13 +47,24 @@ const COMPONENT_TAGS: &[&str] = &[
     "strong",
     "em",
     "del",
+    "dl",
+    "dt",
+    "dd",
+    "details",
     "iframe",
 ];
 
 const RAW_HTML_TAGS: &[&str] = &[
-    "video", "audio", "source", "iframe", "embed", "object", "track",
+    "video", "audio", "source", "iframe", "embed", "object", "track", "img", "picture",
 ];
 
+/// Raw HTML tags that only get their paths resolved, even though a component
+/// exists for the markdown-generated version
+const PATH_ONLY_RAW_TAGS: &[&str] = &["img"];
+
+/// Elements without a closing tag, so there is no inner content to read
+const VOID_TAGS: &[&str] = &["img", "source", "embed", "track"];
+
 struct TagReplacementContext<'a> {
     tera: &'a Tera,
     template_name: &'a str,
@@ -56,10 +72,112 @@ struct TagReplacementContext<'a> {
     base_path: &'a str,
     image_processor: Option<&'a ImageProcessor>,
     content_dir: Option<&'a Path>,
+    /// The post's cover image; a matching `img` is marked as high priority
+    cover_image: Option<&'a str>,
+    /// Host of `site.url`; `a` tags pointing elsewhere are marked external
+    site_host: Option<&'a str>,
+}
+
+/// A raw HTML block opened with `markdown="1"` (Maruku-style), whose
+/// content is rendered as markdown instead of passed through literally.
+/// pulldown-cmark hands HTML blocks over line by line, so the content is
+/// collected until the matching closing tag.
+struct MarkdownBlock {
+    tag: String,
+    /// The opening tag with the `markdown` attribute removed
+    open: String,
+    inner: String,
+}
+
+impl MarkdownBlock {
+    fn open(html: &str) -> Option<Self> {
+        let start = html.find(|c: char| !c.is_whitespace())?;
+        let tag = html[start..].strip_prefix('<')?;
+        let name_len = tag
+            .find(|c: char| !c.is_ascii_alphanumeric())
+            .unwrap_or(tag.len());
+        if name_len == 0 {
+            return None;
+        }
+        let end = start + Renderer::find_tag_end(&html[start..])?;
+        let open_tag = &html[..=end];
+
+        let attr = Renderer::attribute_regex()
+            .captures_iter(open_tag)
+            .find(|caps| {
+                caps[1].eq_ignore_ascii_case("markdown") && Renderer::attribute_value(caps) == "1"
+            })?
+            .get(0)?;
+
+        Some(Self {
+            tag: tag[..name_len].to_ascii_lowercase(),
+            open: format!(
+                "{}{}",
+                open_tag[..attr.start()].trim_end(),
+                &open_tag[attr.end()..]
+            ),
+            inner: html[end + 1..].to_string(),
+        })
+    }
+
+    /// Render the block if its closing tag has arrived, otherwise hand it
+    /// back to keep collecting
+    fn close(self, output: &mut String, strip_comments: bool, options: Options) -> Option<Self> {
+        let Some(close) = self.closing_tag() else {
+            return Some(self);
+        };
+        output.push_str(&self.open);
+        Renderer::push_markdown(output, &self.inner[..close], strip_comments, options);
+        output.push_str(&self.inner[close..]);
+        None
+    }
+
+    /// Emit a block whose closing tag is in a later, separate HTML block;
+    /// the markdown between the two is rendered by the outer document
+    fn flush(self, output: &mut String, strip_comments: bool, options: Options) {
+        output.push_str(&self.open);
+        Renderer::push_markdown(output, &self.inner, strip_comments, options);
+    }
+
+    /// Offset in `inner` of the `</tag>` closing this block, skipping nested
+    /// elements of the same name
+    fn closing_tag(&self) -> Option<usize> {
+        let lower = self.inner.to_ascii_lowercase();
+        let mut depth = 0usize;
+        let mut rest = 0;
+
+        while let Some(offset) = lower[rest..].find('<') {
+            let at = rest + offset;
+            let after = &lower[at + 1..];
+            let (closing, name) = match after.strip_prefix('/') {
+                Some(name) => (true, name),
+                None => (false, after),
+            };
+            let names_tag = name.starts_with(&self.tag)
+                && !name[self.tag.len()..].starts_with(|c: char| c.is_ascii_alphanumeric());
+
+            if names_tag && closing {
+                if depth == 0 {
+                    return Some(at);
+                }
+                depth -= 1;
+            } else if names_tag {
+                depth += 1;
+            }
+            rest = at + 1;
+        }
+        None
+    }
 }
 
 pub struct Renderer {
     highlighter: RefCell<SyntaxHighlighter>,
+    site_host: Option<String>,
+    strip_comments: bool,
+    /// pulldown-cmark smart punctuation: curly quotes, dashes and ellipses
+    typography: bool,
+    /// Wrap only the first occurrence of each `*[ABBR]:` abbreviation
+    abbreviations_first_only: bool,
 }
 
 impl Renderer {
@@ -68,16 +186,34 @@ impl Renderer {
             highlighter: RefCell::new(
                 SyntaxHighlighter::new().expect("Failed to initialize syntax highlighter"),
             ),
+            site_host: None,
+            strip_comments: false,
+            typography: true,
+            abbreviations_first_only: false,
         }
     }
 
+    pub fn from_config(config: &SsgConfig) -> Self {
+        let mut renderer = Self::new().with_site_url(&config.site.url);
+        renderer.strip_comments = config.build.strip_comments;
+        renderer.typography = config.build.typography;
+        renderer.abbreviations_first_only = config.build.abbreviations_first_only;
+        renderer
+    }
+
+    /// Treat links to `site_url`'s host as internal when setting `is_external`
+    pub fn with_site_url(mut self, site_url: &str) -> Self {
+        self.site_host = Self::url_host(site_url).map(str::to_lowercase);
+        self
+    }
+
     pub fn render_markdown_with_components(
         &self,
         markdown: &str,
         tera: &Tera,
         base_path: &str,
     ) -> Result<(String, Vec<HeadingInfo>)> {
-        self.render_markdown_with_components_and_images(markdown, tera, base_path, None, None)
+        self.render_markdown_with_components_and_images(markdown, tera, base_path, None, None, None)
     }
 
     pub fn render_markdown_with_components_and_images(
@@ -85,26 +221,64 @@ impl Renderer {
         markdown: &str,
         tera: &Tera,
         base_path: &str,
-        cdn_url: Option<&str>,
+        image_processor: Option<&ImageProcessor>,
         content_dir: Option<&Path>,
+        cover_image: Option<&str>,
     ) -> Result<(String, Vec<HeadingInfo>)> {
-        let options = Options::all();
-        let headings = Self::collect_headings(markdown);
-        let parser = MdParser::new_ext(markdown, options);
+        let (highlighted, headings) = self.render_markdown_html(markdown);
+        let html = Self::post_process_components(
+            &highlighted,
+            tera,
+            base_path,
+            image_processor,
+            content_dir,
+            cover_image,
+            self.site_host.as_deref(),
+        )?;
+        Ok((html, headings))
+    }
+
+    /// Markdown to highlighted HTML, before components are expanded: the
+    /// whole pipeline from container blocks through footnote ordering to
+    /// code highlighting
+    fn render_markdown_html(&self, markdown: &str) -> (String, Vec<HeadingInfo>) {
+        let options = self.markdown_options();
+        let (markdown, abbreviations) = abbreviations::extract(&Self::expand_containers(markdown));
+        let headings = Self::collect_headings(&markdown, options);
+        let events = Self::order_footnotes(abbreviations::apply(
+            emoji::apply(Self::definition_lists(MdParser::new_ext(
+                &markdown, options,
+            ))),
+            &abbreviations,
+            self.abbreviations_first_only,
+        ));
 
         let mut html_output = String::with_capacity(markdown.len() * 2);
-        Self::push_html_with_markers(&mut html_output, parser, &headings);
+        Self::push_html_with_markers(
+            &mut html_output,
+            events.into_iter(),
+            &headings,
+            self.strip_comments,
+            options,
+        );
 
-        let highlighted = self.highlight_code_blocks(&html_output);
-        let html =
-            Self::post_process_components(&highlighted, tera, base_path, cdn_url, content_dir)?;
-        Ok((html, headings))
+        (self.highlight_code_blocks(&html_output), headings)
+    }
+
+    /// Every pulldown-cmark extension, minus smart punctuation when
+    /// `build.typography` is off. Code spans and blocks are never touched.
+    fn markdown_options(&self) -> Options {
+        let mut options = Options::all();
+        if !self.typography {
+            options.remove(Options::ENABLE_SMART_PUNCTUATION);
+        }
+        options
     }
 
     /// Extract headings from markdown with stable, collision-free anchor slugs.
     /// An explicit `{#id}` attribute wins over the auto-generated slug.
-    fn collect_headings(markdown: &str) -> Vec<HeadingInfo> {
-        let parser = MdParser::new_ext(markdown, Options::all());
+    fn collect_headings(markdown: &str, options: Options) -> Vec<HeadingInfo> {
+        let parser = MdParser::new_ext(markdown, options);
 
         let mut headings = Vec::new();
         let mut seen: HashMap<String, usize> = HashMap::new();
@@ -153,17 +327,258 @@ impl Renderer {
         slug
     }
 
+    /// Expand `::: details Title` ... `:::` containers into a `<details data-md>`
+    /// block whose body is still parsed as markdown. Containers may nest;
+    /// unclosed ones are closed at the end of the post. Lines inside code
+    /// fences are never treated as container markers.
+    fn expand_containers(markdown: &str) -> String {
+        let mut output = String::with_capacity(markdown.len());
+        let mut fence: Option<(char, usize)> = None;
+        let mut open = 0usize;
+
+        for line in markdown.split_inclusive('\n') {
+            let trimmed = line.trim();
+
+            if let Some((fence_char, fence_len)) = fence {
+                let closes = trimmed.len() >= fence_len && trimmed.chars().all(|c| c == fence_char);
+                if closes {
+                    fence = None;
+                }
+                output.push_str(line);
+                continue;
+            }
+
+            let fence_char = trimmed.chars().next().filter(|c| *c == '`' || *c == '~');
+            if let Some(fence_char) = fence_char {
+                let fence_len = trimmed.chars().take_while(|c| *c == fence_char).count();
+                if fence_len >= 3 {
+                    fence = Some((fence_char, fence_len));
+                    output.push_str(line);
+                    continue;
+                }
+            }
+
+            if let Some(title) = trimmed
+                .strip_prefix(":::")
+                .map(str::trim_start)
+                .and_then(|rest| rest.strip_prefix("details"))
+                .filter(|rest| rest.is_empty() || rest.starts_with(char::is_whitespace))
+            {
+                open += 1;
+                output.push_str("\n<details data-md>\n<summary>");
+                Self::escape_html(&mut output, title.trim());
+                output.push_str("</summary>\n\n");
+            } else if trimmed == ":::" && open > 0 {
+                open -= 1;
+                output.push_str("\n</details>\n\n");
+            } else {
+                output.push_str(line);
+            }
+        }
+
+        for _ in 0..open {
+            output.push_str("\n</details>\n");
+        }
+        output
+    }
+
+    /// Turn paragraphs written as definition lists into `<dl data-md>` markup,
+    /// which pulldown-cmark 0.9 doesn't support:
+    ///
+    /// ```text
+    /// Term
+    /// : First definition
+    ///   continued on the next line
+    /// : Second definition
+    /// ```
+    ///
+    /// Lines after the term that start with `: ` open a new `<dd>`; other lines
+    /// continue the previous one. Adjacent definition paragraphs share a `<dl>`.
+    fn definition_lists<'a>(mut events: impl Iterator<Item = Event<'a>>) -> Vec<Event<'a>> {
+        let mut output = Vec::new();
+
+        while let Some(event) = events.next() {
+            if event != Event::Start(Tag::Paragraph) {
+                output.push(event);
+                continue;
+            }
+
+            let mut inline = Vec::new();
+            for event in events.by_ref() {
+                if event == Event::End(Tag::Paragraph) {
+                    break;
+                }
+                inline.push(event);
+            }
+
+            let mut lines: Vec<Vec<Event<'a>>> = vec![Vec::new()];
+            for event in inline {
+                match event {
+                    Event::SoftBreak => lines.push(Vec::new()),
+                    event => lines.last_mut().unwrap().push(event),
+                }
+            }
+
+            let is_definition = lines.len() >= 2
+                && Self::definition_marker(&lines[0]).is_none()
+                && Self::definition_marker(&lines[1]).is_some();
+            if !is_definition {
+                output.push(Event::Start(Tag::Paragraph));
+                output.extend(Self::join_lines(lines));
+                output.push(Event::End(Tag::Paragraph));
+                continue;
+            }
+
+            let mut lines = lines.into_iter();
+            let term = lines.next().unwrap();
+            let mut definitions: Vec<Vec<Vec<Event<'a>>>> = Vec::new();
+            for mut line in lines {
+                match Self::definition_marker(&line) {
+                    Some(rest) => {
+                        line[0] = Event::Text(rest);
+                        definitions.push(vec![line]);
+                    }
+                    // Continuation of the previous definition
+                    None => definitions.last_mut().unwrap().push(line),
+                }
+            }
+
+            if output.last() == Some(&Event::Html(CowStr::Borrowed("</dl>\n"))) {
+                output.pop();
+            } else {
+                output.push(Event::Html(CowStr::Borrowed("<dl data-md>\n")));
+            }
+            output.push(Event::Html(CowStr::Borrowed("<dt data-md>")));
+            output.extend(term);
+            output.push(Event::Html(CowStr::Borrowed("</dt>\n")));
+            for definition in definitions {
+                output.push(Event::Html(CowStr::Borrowed("<dd data-md>")));
+                output.extend(Self::join_lines(definition));
+                output.push(Event::Html(CowStr::Borrowed("</dd>\n")));
+            }
+            output.push(Event::Html(CowStr::Borrowed("</dl>\n")));
+        }
+
+        output
+    }
+
+    /// The line's first text with its leading `: ` removed, if it has one
+    fn definition_marker<'a>(line: &[Event<'a>]) -> Option<CowStr<'a>> {
+        let Some(Event::Text(text)) = line.first() else {
+            return None;
+        };
+        let rest = text.strip_prefix(':')?;
+        if !rest.starts_with([' ', '\t']) {
+            return None;
+        }
+        Some(CowStr::from(rest.trim_start().to_string()))
+    }
+
+    fn join_lines<'a>(lines: Vec<Vec<Event<'a>>>) -> Vec<Event<'a>> {
+        let mut events = Vec::new();
+        for (i, line) in lines.into_iter().enumerate() {
+            if i > 0 {
+                events.push(Event::SoftBreak);
+            }
+            events.extend(line);
+        }
+        events
+    }
+
+    /// Move footnote definitions to the end of the document, ordered by their
+    /// first reference. Definitions that are never referenced follow in the
+    /// order they were written.
+    fn order_footnotes<'a>(events: Vec<Event<'a>>) -> Vec<Event<'a>> {
+        let mut body = Vec::with_capacity(events.len());
+        let mut definitions: Vec<(CowStr<'a>, Vec<Event<'a>>)> = Vec::new();
+        let mut reference_order: Vec<CowStr<'a>> = Vec::new();
+        let mut events = events.into_iter();
+
+        while let Some(event) = events.next() {
+            let name = match &event {
+                Event::Start(Tag::FootnoteDefinition(name)) => name.clone(),
+                Event::FootnoteReference(name) => {
+                    if !reference_order.contains(name) {
+                        reference_order.push(name.clone());
+                    }
+                    body.push(event);
+                    continue;
+                }
+                _ => {
+                    body.push(event);
+                    continue;
+                }
+            };
+
+            let mut definition = vec![event];
+            for event in events.by_ref() {
+                if let Event::FootnoteReference(name) = &event {
+                    if !reference_order.contains(name) {
+                        reference_order.push(name.clone());
+                    }
+                }
+                let end = matches!(event, Event::End(Tag::FootnoteDefinition(_)));
+                definition.push(event);
+                if end {
+                    break;
+                }
+            }
+            definitions.push((name, definition));
+        }
+
+        let rank = |name: &CowStr<'a>| {
+            reference_order
+                .iter()
+                .position(|referenced| referenced == name)
+                .unwrap_or(usize::MAX)
+        };
+        // Stable, so unreferenced definitions keep their written order
+        definitions.sort_by_key(|(name, _)| rank(name));
+
+        body.extend(definitions.into_iter().flat_map(|(_, events)| events));
+        body
+    }
+
+    /// `id` of the `n`th (1-based) reference to footnote `name`
+    fn footnote_reference_id(name: &str, n: usize) -> String {
+        if n == 1 {
+            format!("fnref-{}", name)
+        } else {
+            format!("fnref-{}-{}", name, n)
+        }
+    }
+
     /// Custom HTML writer that adds `data-md` attribute to markdown-generated tags.
     /// This allows component templates to distinguish between markdown syntax
     /// (e.g., `![]()`→`<img>`) and raw HTML tags written directly in markdown.
-    fn push_html_with_markers<'a, I>(output: &mut String, iter: I, headings: &[HeadingInfo])
-    where
+    ///
+    /// With `strip_comments`, HTML comments in raw HTML are dropped (comment-like
+    /// text in code is plain text to the parser, so it survives).
+    fn push_html_with_markers<'a, I>(
+        output: &mut String,
+        iter: I,
+        headings: &[HeadingInfo],
+        strip_comments: bool,
+        options: Options,
+    ) where
         I: Iterator<Item = Event<'a>>,
     {
         let mut in_code_block = false;
         let mut heading_index = 0usize;
+        // Block-level comments arrive one line per event
+        let mut in_comment = false;
+        // A `markdown="1"` block still waiting for its closing tag
+        let mut markdown_block: Option<MarkdownBlock> = None;
+        // References seen so far per footnote, for reference ids and backlinks
+        let mut footnote_references: HashMap<String, usize> = HashMap::new();
 
         for event in iter {
+            if !matches!(event, Event::Html(_)) {
+                if let Some(block) = markdown_block.take() {
+                    block.flush(output, strip_comments, options);
+                }
+            }
+
             match event {
                 Event::Start(tag) => {
                     let heading_slug = match tag {
@@ -173,8 +588,29 @@ impl Renderer {
                     Self::write_start_tag(output, &tag, &mut in_code_block, heading_slug);
                 }
                 Event::End(tag) => {
-                    if let Tag::Heading(..) = tag {
-                        heading_index += 1;
+                    match &tag {
+                        Tag::Heading(..) => heading_index += 1,
+                        Tag::FootnoteDefinition(name) => {
+                            // Backlinks go inside the definition's last paragraph
+                            let closes_paragraph = output.ends_with("</p>\n");
+                            if closes_paragraph {
+                                output.truncate(output.len() - "</p>\n".len());
+                            }
+                            let count = footnote_references.get(name.as_ref()).copied();
+                            for n in 1..=count.unwrap_or(0) {
+                                output.push_str(" <a href=\"#");
+                                Self::escape_html(output, &Self::footnote_reference_id(name, n));
+                                output.push_str("\" class=\"footnote-backref\">↩");
+                                if n > 1 {
+                                    output.push_str(&format!("<sup>{}</sup>", n));
+                                }
+                                output.push_str("</a>");
+                            }
+                            if closes_paragraph {
+                                output.push_str("</p>\n");
+                            }
+                        }
+                        _ => {}
                     }
                     Self::write_end_tag(output, &tag, &mut in_code_block);
                 }
@@ -187,13 +623,34 @@ impl Renderer {
                     output.push_str("</code>");
                 }
                 Event::Html(html) => {
-                    output.push_str(&html);
+                    let block = match markdown_block.take() {
+                        Some(mut block) => {
+                            block.inner.push_str(&html);
+                            Some(block)
+                        }
+                        None if in_comment => None,
+                        None => MarkdownBlock::open(&html),
+                    };
+
+                    match block {
+                        Some(block) => {
+                            markdown_block = block.close(output, strip_comments, options);
+                        }
+                        None if strip_comments => {
+                            Self::push_without_comments(output, &html, &mut in_comment);
+                        }
+                        None => output.push_str(&html),
+                    }
                 }
                 Event::SoftBreak => output.push('\n'),
                 Event::HardBreak => output.push_str("<br />\n"),
                 Event::Rule => output.push_str("<hr data-md />\n"),
                 Event::FootnoteReference(name) => {
-                    output.push_str("<sup class=\"footnote-reference\" data-md><a href=\"#");
+                    let count = footnote_references.entry(name.to_string()).or_default();
+                    *count += 1;
+                    output.push_str("<sup class=\"footnote-reference\" id=\"");
+                    Self::escape_html(output, &Self::footnote_reference_id(&name, *count));
+                    output.push_str("\" data-md><a href=\"#");
                     Self::escape_html(output, &name);
                     output.push_str("\">");
                     Self::escape_html(output, &name);
@@ -208,6 +665,68 @@ impl Renderer {
                 }
             }
         }
+
+        if let Some(block) = markdown_block {
+            block.flush(output, strip_comments, options);
+        }
+    }
+
+    /// Render a fragment of markdown in place, as inside a `markdown="1"`
+    /// block. Headings in it get no anchor ids since the table of contents
+    /// only sees the outer document.
+    fn push_markdown(output: &mut String, markdown: &str, strip_comments: bool, options: Options) {
+        let events = emoji::apply(Self::definition_lists(MdParser::new_ext(markdown, options)));
+        Self::push_html_with_markers(output, events.into_iter(), &[], strip_comments, options);
+    }
+
+    fn attribute_regex() -> &'static Regex {
+        static ATTR_RE: OnceLock<Regex> = OnceLock::new();
+        ATTR_RE.get_or_init(|| {
+            Regex::new(r#"([^\s"'<>/=]+)(\s*=\s*)(?:"([^"]*)"|'([^']*)'|([^\s"'=<>`]+))"#).unwrap()
+        })
+    }
+
+    /// The value of an attribute match from [`Self::attribute_regex`]
+    fn attribute_value<'h>(caps: &Captures<'h>) -> &'h str {
+        caps.get(3)
+            .or_else(|| caps.get(4))
+            .or_else(|| caps.get(5))
+            .map_or("", |value| value.as_str())
+    }
+
+    /// Append `html` minus `<!-- ... -->` comments, carrying an unterminated
+    /// comment over to the next call through `in_comment`. Conditional comments
+    /// (`<!--[if IE]>`) are kept.
+    fn push_without_comments(output: &mut String, html: &str, in_comment: &mut bool) {
+        let mut rest = html;
+        loop {
+            if *in_comment {
+                match rest.find("-->") {
+                    Some(end) => {
+                        *in_comment = false;
+                        rest = &rest[end + 3..];
+                    }
+                    None => return,
+                }
+            }
+
+            let Some(start) = rest.find("<!--") else {
+                output.push_str(rest);
+                return;
+            };
+            output.push_str(&rest[..start]);
+            rest = &rest[start..];
+
+            let conditional = rest[4..].starts_with("[if") || rest[4..].starts_with("<![endif]");
+            if conditional {
+                let end = rest.find("-->").map_or(rest.len(), |end| end + 3);
+                output.push_str(&rest[..end]);
+                rest = &rest[end..];
+            } else {
+                *in_comment = true;
+                rest = &rest[4..];
+            }
+        }
     }
 
     fn write_start_tag(
@@ -235,7 +754,7 @@ impl Renderer {
                 output.push_str("<pre data-md>");
                 match kind {
                     CodeBlockKind::Fenced(info) => {
-                        let lang = info.split(' ').next().unwrap_or("");
+                        let lang = Self::fence_language(info);
                         if lang.is_empty() {
                             output.push_str("<code>");
                         } else {
@@ -290,6 +809,17 @@ impl Renderer {
         }
     }
 
+    /// The language of a fence info string, cut at the first character that
+    /// can't be part of a language name (`rust,ignore` -> `rust`), so nothing
+    /// else from the info string can end up in the `class` attribute
+    fn fence_language(info: &str) -> &str {
+        let lang = info.trim_start();
+        let end = lang
+            .find(|c: char| !(c.is_ascii_alphanumeric() || "+#_.-".contains(c)))
+            .unwrap_or(lang.len());
+        &lang[..end]
+    }
+
     fn write_end_tag(output: &mut String, tag: &Tag<'_>, in_code_block: &mut bool) {
         match tag {
             Tag::Paragraph => output.push_str("</p>\n"),
@@ -373,8 +903,10 @@ impl Renderer {
         html: &str,
         tera: &Tera,
         base_path: &str,
-        cdn_url: Option<&str>,
+        image_processor: Option<&ImageProcessor>,
         content_dir: Option<&Path>,
+        cover_image: Option<&str>,
+        site_host: Option<&str>,
     ) -> Result<String> {
         let mut result = html.to_string();
 
@@ -383,8 +915,6 @@ impl Renderer {
             .map(|(cat, _slug)| cat)
             .unwrap_or(base_path);
 
-        let image_processor = cdn_url.map(|url| ImageProcessor::new(Some(url.to_string())));
-
         for &tag_name in COMPONENT_TAGS {
             let template_name = format!("components/{}.html", tag_name);
 
@@ -397,8 +927,10 @@ impl Renderer {
                 template_name: &template_name,
                 category,
                 base_path,
-                image_processor: image_processor.as_ref(),
+                image_processor,
                 content_dir,
+                cover_image,
+                site_host,
             };
             result = Self::replace_tag(&result, tag_name, &ctx)?;
         }
@@ -429,9 +961,14 @@ impl Renderer {
                     result.push_str(tag);
                 } else {
                     let tag_lower = tag.to_lowercase();
-                    let needs_processing = RAW_HTML_TAGS
-                        .iter()
-                        .any(|&t| tag_lower.starts_with(&format!("<{} ", t)));
+                    // Hand-written blocks often break attributes onto new lines
+                    let needs_processing = RAW_HTML_TAGS.iter().any(|&t| {
+                        tag_lower
+                            .strip_prefix('<')
+                            .and_then(|rest| rest.strip_prefix(t))
+                            .and_then(|rest| rest.chars().next())
+                            .is_some_and(char::is_whitespace)
+                    });
 
                     if needs_processing {
                         result.push_str(&Self::resolve_tag_urls(tag, category));
@@ -449,52 +986,60 @@ impl Renderer {
         result
     }
 
+    /// Index of the `>` closing the tag at the start of `s`. A quote only
+    /// opens an attribute value right after `=`, so a `>` inside a value
+    /// doesn't end the tag but a stray apostrophe (`<3 it's`) can't swallow
+    /// the rest of the document either.
     fn find_tag_end(s: &str) -> Option<usize> {
-        let mut in_quotes = false;
-        let mut quote_char = ' ';
+        let mut quote = None;
+        let mut after_equals = false;
 
         for (i, ch) in s.char_indices() {
-            if ch == '"' || ch == '\'' {
-                if in_quotes && ch == quote_char {
-                    in_quotes = false;
-                } else if !in_quotes {
-                    in_quotes = true;
-                    quote_char = ch;
-                }
-            } else if ch == '>' && !in_quotes {
-                return Some(i);
+            match quote {
+                Some(open) if ch == open => quote = None,
+                Some(_) => {}
+                None if (ch == '"' || ch == '\'') && after_equals => quote = Some(ch),
+                None if ch == '>' => return Some(i),
+                None => {}
             }
+            after_equals = quote.is_none() && (ch == '=' || (after_equals && ch.is_whitespace()));
         }
         None
     }
 
+    /// Resolve relative URLs in a raw tag's `src`, `srcset`, `poster` and
+    /// `data` attributes. Attributes are matched whole, left to right, so a
+    /// `src=` inside another attribute's value or name (`data-src`) is never
+    /// touched, and quoting and spacing around `=` are kept as written.
     fn resolve_tag_urls(tag: &str, category: &str) -> String {
-        let mut result = tag.to_string();
-
-        for attr in &["src", "poster", "data"] {
-            for quote in &['"', '\''] {
-                let pattern = format!("{}={}", attr, quote);
-                if let Some(start) = result.find(&pattern) {
-                    let value_start = start + pattern.len();
-                    if let Some(end_offset) = result[value_start..].find(*quote) {
-                        let value_end = value_start + end_offset;
-                        let value = &result[value_start..value_end];
-
-                        if value.starts_with("./") || value.starts_with("../") {
-                            let resolved = Self::resolve_path(value, category);
-                            result = format!(
-                                "{}{}{}{}",
-                                &result[..value_start],
-                                resolved,
-                                quote,
-                                &result[value_end + 1..]
-                            );
-                        }
+        let is_relative = |url: &str| url.starts_with("./") || url.starts_with("../");
+
+        Self::attribute_regex()
+            .replace_all(tag, |caps: &Captures| {
+                let name = caps[1].to_ascii_lowercase();
+                let value = Self::attribute_value(caps);
+                let quote = match (caps.get(3), caps.get(4)) {
+                    (Some(_), _) => "\"",
+                    (_, Some(_)) => "'",
+                    _ => "",
+                };
+
+                let resolved = match name.as_str() {
+                    "srcset" => Self::srcset_urls(value)
+                        .any(is_relative)
+                        .then(|| Self::resolve_srcset(value, category)),
+                    "src" | "poster" | "data" => {
+                        is_relative(value).then(|| Self::resolve_path(value, category))
                     }
+                    _ => None,
+                };
+
+                match resolved {
+                    Some(resolved) => format!("{}{}{quote}{}{quote}", &caps[1], &caps[2], resolved),
+                    None => caps[0].to_string(),
                 }
-            }
-        }
-        result
+            })
+            .into_owned()
     }
 
     fn replace_tag(html: &str, tag_name: &str, ctx: &TagReplacementContext) -> Result<String> {
@@ -534,13 +1079,14 @@ impl Renderer {
                     || tag_content == format!("<{}>", tag_name);
                 let has_md_marker =
                     tag_content.contains(" data-md") || tag_content.contains(" data-md ");
-                let is_raw_html_tag = RAW_HTML_TAGS.contains(&tag_name);
+                let is_raw_html_tag =
+                    RAW_HTML_TAGS.contains(&tag_name) && !PATH_ONLY_RAW_TAGS.contains(&tag_name);
 
                 if is_target_tag && (has_md_marker || is_raw_html_tag) {
                     let attrs = Self::extract_attributes(&tag_content);
                     let mut inner_content = String::new();
 
-                    if !tag_content.ends_with("/>") {
+                    if !tag_content.ends_with("/>") && !VOID_TAGS.contains(&tag_name) {
                         let mut depth = 1;
                         let close_tag = format!("</{}>", tag_name);
 
@@ -585,7 +1131,11 @@ impl Renderer {
                             continue;
                         }
                         if Self::is_url_attribute(key) {
-                            let resolved = Self::resolve_path(value, ctx.category);
+                            let resolved = if key == "srcset" {
+                                Self::resolve_srcset(value, ctx.category)
+                            } else {
+                                Self::resolve_path(value, ctx.category)
+                            };
                             context.insert(key, &resolved);
                             if key == "src" {
                                 original_src = value.clone();
@@ -595,7 +1145,20 @@ impl Renderer {
                         }
                     }
 
+                    if tag_name == "a" {
+                        let href = attrs.get("href").map(String::as_str).unwrap_or_default();
+                        context.insert("is_external", &Self::is_external(href, ctx.site_host));
+                    }
+
                     if tag_name == "img" {
+                        // The cover loads eagerly for LCP; everything else stays lazy
+                        let priority = !original_src.is_empty()
+                            && ctx.cover_image.is_some_and(|cover| {
+                                Self::resolve_path(cover, ctx.category)
+                                    == Self::resolve_path(&original_src, ctx.category)
+                            });
+                        context.insert("priority", &priority);
+
                         if let (Some(processor), Some(content_path)) =
                             (ctx.image_processor, ctx.content_dir)
                         {
@@ -609,8 +1172,10 @@ impl Renderer {
                             ) {
                                 context.insert("cdn_src", &metadata.src);
                                 context.insert("lqip", &metadata.lqip);
+                                context.insert("lqip_blurhash", &metadata.lqip_blurhash);
+                                context.insert("placeholder_color", &metadata.placeholder_color);
                                 context.insert("sources", &metadata.sources);
-                                context.insert("webp_sources", &metadata.webp_sources);
+                                context.insert("format_sources", &metadata.format_sources);
                                 context.insert("width", &metadata.width);
                                 context.insert("height", &metadata.height);
                                 context.insert("has_srcset", &true);
@@ -715,6 +1280,53 @@ impl Renderer {
         attrs
     }
 
+    /// Host of an absolute or protocol-relative URL, without port or userinfo
+    fn url_host(url: &str) -> Option<&str> {
+        let rest = ["https://", "http://", "//"].iter().find_map(|scheme| {
+            url.get(..scheme.len())
+                .filter(|prefix| prefix.eq_ignore_ascii_case(scheme))
+                .map(|_| &url[scheme.len()..])
+        })?;
+        let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+        let host = authority.rsplit('@').next().unwrap_or_default();
+        let host = host.split(':').next().unwrap_or_default();
+        (!host.is_empty()).then_some(host)
+    }
+
+    /// An absolute link to a host other than the site's. Relative links,
+    /// anchors and non-web schemes like `mailto:` are internal.
+    fn is_external(href: &str, site_host: Option<&str>) -> bool {
+        match Self::url_host(href) {
+            Some(host) => site_host.is_none_or(|site| !host.eq_ignore_ascii_case(site)),
+            None => false,
+        }
+    }
+
+    /// The URL of each candidate in a `srcset` list
+    fn srcset_urls(srcset: &str) -> impl Iterator<Item = &str> {
+        srcset
+            .split(',')
+            .filter_map(|candidate| candidate.split_whitespace().next())
+    }
+
+    /// Resolve every candidate URL of a `srcset` (`./a.jpg 1x, ./b.jpg 2x`)
+    /// against the post's category, keeping width/density descriptors
+    fn resolve_srcset(srcset: &str, base_path: &str) -> String {
+        srcset
+            .split(',')
+            .filter_map(|candidate| {
+                let mut parts = candidate.split_whitespace();
+                let url = parts.next()?;
+                let resolved = Self::resolve_path(url, base_path);
+                Some(match parts.next() {
+                    Some(descriptor) => format!("{} {}", resolved, descriptor),
+                    None => resolved,
+                })
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
     fn is_url_attribute(attr: &str) -> bool {
         matches!(attr, "src" | "href" | "data" | "poster" | "srcset")
     }
@@ -949,15 +1561,23 @@ mod tests {
 
     impl Renderer {
         fn render_markdown(&self, markdown: &str) -> String {
-            let options = Options::all();
-            let headings = Self::collect_headings(markdown);
-            let parser = MdParser::new_ext(markdown, options);
-            let mut html_output = String::with_capacity(markdown.len() * 2);
-            Self::push_html_with_markers(&mut html_output, parser, &headings);
-            self.highlight_code_blocks(&html_output)
+            self.render_markdown_html(markdown).0
         }
     }
 
+    #[test]
+    fn test_emoji_codes_expanded_in_text_only() {
+        let renderer = Renderer::new();
+        let html = renderer.render_markdown(
+            "Done :white_check_mark: but `:not:emoji:` and [a](https://x.io/:fire:) :nope:",
+        );
+
+        assert!(html.contains("Done ✅ but"));
+        assert!(html.contains("<code data-md>:not:emoji:</code>"));
+        assert!(html.contains("href=\"https://x.io/:fire:\""));
+        assert!(html.contains(":nope:"));
+    }
+
     #[test]
     fn test_render_markdown() {
         let renderer = Renderer::new();
@@ -988,7 +1608,7 @@ mod tests {
     #[test]
     fn test_collect_headings_matches_injected_ids() {
         let md = "# Title\n\n## Section A\n\n## Section A";
-        let headings = Renderer::collect_headings(md);
+        let headings = Renderer::collect_headings(md, Options::all());
 
         assert_eq!(headings.len(), 3);
         assert_eq!(headings[1].slug, "section-a");
@@ -1031,6 +1651,106 @@ mod tests {
         assert!(html.contains("Click here"));
     }
 
+    #[test]
+    fn test_markdown_attribute_renders_block_content() {
+        let renderer = Renderer::new();
+        let html = renderer.render_markdown(r#"<div class="note" markdown="1">**bold**</div>"#);
+
+        assert_eq!(
+            html,
+            "<div class=\"note\"><p data-md><strong data-md>bold</strong></p>\n</div>"
+        );
+    }
+
+    #[test]
+    fn test_raw_html_block_without_markdown_attribute_stays_literal() {
+        let renderer = Renderer::new();
+        let html = renderer.render_markdown(r#"<div class="note">**bold**</div>"#);
+
+        assert_eq!(html, "<div class=\"note\">**bold**</div>");
+    }
+
+    #[test]
+    fn test_markdown_attribute_spans_lines() {
+        let renderer = Renderer::new();
+        let md = "<div markdown='1'>\n- one\n- two\n</div>\n\nAfter";
+        let html = renderer.render_markdown(md);
+
+        assert_eq!(
+            html,
+            "<div><ul data-md>\n<li data-md>one</li>\n<li data-md>two</li>\n</ul>\n</div>\n<p data-md>After</p>\n"
+        );
+    }
+
+    #[test]
+    fn test_markdown_attribute_skips_nested_closing_tags() {
+        let renderer = Renderer::new();
+        let html = renderer.render_markdown(r#"<div markdown="1">*a* <div>b</div> c</div>"#);
+
+        assert_eq!(
+            html,
+            "<div><p data-md><em data-md>a</em> <div>b</div> c</p>\n</div>"
+        );
+    }
+
+    #[test]
+    fn test_markdown_attribute_closed_in_later_block() {
+        let renderer = Renderer::new();
+        let md = "<section markdown=\"1\">\n*one*\n\nTwo\n\n</section>";
+        let html = renderer.render_markdown(md);
+
+        assert_eq!(
+            html,
+            "<section><p data-md><em data-md>one</em></p>\n<p data-md>Two</p>\n</section>"
+        );
+    }
+
+    #[test]
+    fn test_footnote_backlinks() {
+        let renderer = Renderer::new();
+        let html = renderer.render_markdown("One[^a] and again[^a].\n\n[^a]: The note.");
+
+        assert!(html
+            .contains("<sup class=\"footnote-reference\" id=\"fnref-a\" data-md><a href=\"#a\">"));
+        assert!(html.contains("<sup class=\"footnote-reference\" id=\"fnref-a-2\" data-md>"));
+        assert!(html.contains(concat!(
+            "<p data-md>The note.",
+            " <a href=\"#fnref-a\" class=\"footnote-backref\">↩</a>",
+            " <a href=\"#fnref-a-2\" class=\"footnote-backref\">↩<sup>2</sup></a></p>\n</div>"
+        )));
+    }
+
+    #[test]
+    fn test_footnote_definitions_follow_reference_order() {
+        let renderer = Renderer::new();
+        let md = "[^second]: Second.\n\n[^unused]: Unused.\n\n[^first]: First.\n\nSee[^first] then[^second].\n\nEnd.";
+        let html = renderer.render_markdown(md);
+
+        let first = html.find("id=\"first\"").unwrap();
+        let second = html.find("id=\"second\"").unwrap();
+        let unused = html.find("id=\"unused\"").unwrap();
+        let end = html.find("End.").unwrap();
+        assert!(end < first && first < second && second < unused);
+        assert!(!html[unused..].contains("footnote-backref"));
+    }
+
+    #[test]
+    fn test_fence_language_is_sanitized() {
+        let renderer = Renderer::new();
+
+        let html = renderer.render_markdown("```rust\" onload=\"alert(1)\nfn main() {}\n```");
+        assert!(html.contains("class=\"language-rust"), "{}", html);
+        assert!(!html.contains("onload"));
+
+        let html = renderer.render_markdown("```\"><script>x</script>\ncode\n```");
+        assert!(!html.contains("language-"), "{}", html);
+        assert!(!html.contains("<script>"));
+
+        assert_eq!(Renderer::fence_language("c++ title=\"x\""), "c++");
+        assert_eq!(Renderer::fence_language("rust,ignore"), "rust");
+        assert_eq!(Renderer::fence_language("objective-c"), "objective-c");
+    }
+
     #[test]
     fn test_markdown_vs_raw_html_distinction() {
         let renderer = Renderer::new();
@@ -1262,4 +1982,341 @@ mod tests {
             r#"<video src="/dev/video.mp4" poster="/dev/thumb.jpg"></video>"#
         );
     }
+
+    #[test]
+    fn test_resolve_raw_html_paths_srcset_density() {
+        let html = r#"<img srcset="./a.jpg 1x, ../b.jpg 2x" src="./a.jpg" alt="">"#;
+        let result = Renderer::resolve_raw_html_paths(html, "dev/rust");
+        assert_eq!(
+            result,
+            r#"<img srcset="/dev/rust/a.jpg 1x, /dev/b.jpg 2x" src="/dev/rust/a.jpg" alt="">"#
+        );
+    }
+
+    #[test]
+    fn test_resolve_raw_html_paths_srcset_width() {
+        let html = r#"<picture><source srcset='./small.webp 480w,./large.webp 1200w' type="image/webp"></picture>"#;
+        let result = Renderer::resolve_raw_html_paths(html, "dev");
+        assert_eq!(
+            result,
+            r#"<picture><source srcset='/dev/small.webp 480w, /dev/large.webp 1200w' type="image/webp"></picture>"#
+        );
+    }
+
+    #[test]
+    fn test_resolve_raw_html_paths_picture_sources() {
+        let html = concat!(
+            "<picture class=\"hero\">\n",
+            "<source srcset=\"./hero.avif\" type=\"image/avif\">\n",
+            "<source\n  srcset=\"./hero.webp 1x, ./hero@2x.webp 2x\"\n  type=\"image/webp\">\n",
+            "<img src=\"./hero.jpg\" alt=\"Hero\">\n",
+            "</picture>"
+        );
+        let result = Renderer::resolve_raw_html_paths(html, "dev");
+        assert_eq!(
+            result,
+            concat!(
+                "<picture class=\"hero\">\n",
+                "<source srcset=\"/dev/hero.avif\" type=\"image/avif\">\n",
+                "<source\n  srcset=\"/dev/hero.webp 1x, /dev/hero@2x.webp 2x\"\n  type=\"image/webp\">\n",
+                "<img src=\"/dev/hero.jpg\" alt=\"Hero\">\n",
+                "</picture>"
+            )
+        );
+    }
+
+    #[test]
+    fn test_resolve_raw_html_paths_picture_webp_fallback() {
+        let html = r#"<picture><source srcset="../shared/photo.webp" type="image/webp"><img src="../shared/photo.png" alt=""></picture>"#;
+        let result = Renderer::resolve_raw_html_paths(html, "dev/rust");
+        assert_eq!(
+            result,
+            r#"<picture><source srcset="/dev/shared/photo.webp" type="image/webp"><img src="/dev/shared/photo.png" alt=""></picture>"#
+        );
+    }
+
+    #[test]
+    fn test_resolve_raw_html_paths_self_closing_source() {
+        let html = r#"<video controls><source src="./clip.webm" type="video/webm"/><source src='./clip.mp4'/></video>"#;
+        let result = Renderer::resolve_raw_html_paths(html, "dev");
+        assert_eq!(
+            result,
+            r#"<video controls><source src="/dev/clip.webm" type="video/webm"/><source src='/dev/clip.mp4'/></video>"#
+        );
+    }
+
+    #[test]
+    fn test_resolve_raw_html_paths_value_containing_gt() {
+        let html = r#"<video title="a -> b" src="./a.mp4" poster="./a.jpg"></video>"#;
+        let result = Renderer::resolve_raw_html_paths(html, "dev");
+        assert_eq!(
+            result,
+            r#"<video title="a -> b" src="/dev/a.mp4" poster="/dev/a.jpg"></video>"#
+        );
+    }
+
+    #[test]
+    fn test_resolve_raw_html_paths_multiline_tag() {
+        let html = "<video\n    controls\n    src = \"./a.mp4\"\n    poster=./a.jpg\n></video>";
+        let result = Renderer::resolve_raw_html_paths(html, "dev");
+        assert_eq!(
+            result,
+            "<video\n    controls\n    src = \"/dev/a.mp4\"\n    poster=/dev/a.jpg\n></video>"
+        );
+    }
+
+    #[test]
+    fn test_resolve_raw_html_paths_matches_whole_attributes() {
+        let html = r#"<img data-src="./lazy.jpg" alt='src="./no.jpg"' src="./a.jpg">"#;
+        let result = Renderer::resolve_raw_html_paths(html, "dev");
+        assert_eq!(
+            result,
+            r#"<img data-src="./lazy.jpg" alt='src="./no.jpg"' src="/dev/a.jpg">"#
+        );
+    }
+
+    #[test]
+    fn test_find_tag_end_ignores_stray_apostrophe() {
+        let html = "<3 it's fine> <video src=\"./a.mp4\"></video>";
+        assert_eq!(Renderer::find_tag_end(html), Some(12));
+    }
+
+    #[test]
+    fn test_resolve_raw_html_paths_srcset_keeps_absolute_candidates() {
+        let html = r#"<img srcset="https://cdn.example.com/a.jpg 1x, /b.jpg 2x">"#;
+        let result = Renderer::resolve_raw_html_paths(html, "dev");
+        assert_eq!(result, html);
+    }
+
+    #[test]
+    fn test_raw_img_keeps_following_content() {
+        let renderer = Renderer::new();
+        let mut tera = Tera::default();
+        tera.add_raw_template(
+            "components/img.html",
+            r#"<figure>{{ src | safe }}</figure>"#,
+        )
+        .unwrap();
+
+        let md = "<img src=\"./a.jpg\" srcset=\"./a.jpg 1x, ./a@2x.jpg 2x\" alt=\"x\">\n\nAfter the image.\n\n![Body](./b.png)";
+        let (html, _) = renderer
+            .render_markdown_with_components_and_images(md, &tera, "dev", None, None, None)
+            .unwrap();
+
+        assert!(
+            html.contains(
+                r#"<img src="/dev/a.jpg" srcset="/dev/a.jpg 1x, /dev/a@2x.jpg 2x" alt="x">"#
+            ),
+            "got: {}",
+            html
+        );
+        assert!(html.contains("After the image."), "got: {}", html);
+        assert!(
+            html.contains("<figure>/dev/b.png</figure>"),
+            "got: {}",
+            html
+        );
+    }
+
+    #[test]
+    fn test_cover_image_component_gets_priority() {
+        let renderer = Renderer::new();
+        let mut tera = Tera::default();
+        tera.add_raw_template(
+            "components/img.html",
+            r#"<img alt="{{ alt }}" data-priority="{{ priority }}">"#,
+        )
+        .unwrap();
+
+        let md = "![Cover](./images/cover.png)\n\n![Body](./images/body.png)";
+        let (html, _) = renderer
+            .render_markdown_with_components_and_images(
+                md,
+                &tera,
+                "dev",
+                None,
+                None,
+                Some("/dev/images/cover.png"),
+            )
+            .unwrap();
+
+        assert!(
+            html.contains(r#"alt="Cover" data-priority="true""#),
+            "got: {}",
+            html
+        );
+        assert!(html.contains(r#"alt="Body" data-priority="false""#));
+    }
+
+    #[test]
+    fn test_callout_body_renders_markdown() {
+        let registry = crate::shortcodes::ShortcodeRegistry::new();
+        let renderer = Renderer::new();
+
+        let md = registry
+            .process(
+                r#"[callout type="info"]See **bold** and [docs](https://example.com).[/callout]"#,
+            )
+            .unwrap();
+        let html = Renderer::sanitize(&renderer.render_markdown(&md));
+
+        assert!(html.contains(r#"<div class="callout callout-info">"#));
+        assert!(html.contains("<strong>bold</strong>"), "got: {}", html);
+        assert!(
+            html.contains(r#"href="https://example.com""#),
+            "got: {}",
+            html
+        );
+    }
+
+    #[test]
+    fn test_code_shortcode_body_stays_raw() {
+        let registry = crate::shortcodes::ShortcodeRegistry::new();
+        let renderer = Renderer::new();
+
+        let md = registry
+            .process(r#"[code lang="md"]**not bold**[/code]"#)
+            .unwrap();
+        let html = renderer.render_markdown(&md);
+
+        assert!(html.contains("**not bold**"), "got: {}", html);
+        assert!(!html.contains("<strong>"));
+    }
+
+    #[test]
+    fn test_definition_list_single_term() {
+        let renderer = Renderer::new();
+        let html = renderer.render_markdown("Rust\n: A **systems** language\n");
+
+        assert_eq!(
+            html,
+            "<dl data-md>\n<dt data-md>Rust</dt>\n<dd data-md>A <strong data-md>systems</strong> language</dd>\n</dl>\n"
+        );
+    }
+
+    #[test]
+    fn test_definition_list_multiple_definitions() {
+        let renderer = Renderer::new();
+        let md = "Tera\n: A template engine\n  inspired by Jinja2\n: Also a unit prefix\n\nCDN\n: Content delivery network\n";
+        let html = renderer.render_markdown(md);
+
+        assert_eq!(html.matches("<dl data-md>").count(), 1);
+        assert_eq!(html.matches("<dt data-md>").count(), 2);
+        assert!(html.contains("<dd data-md>A template engine\ninspired by Jinja2</dd>"));
+        assert!(html.contains("<dd data-md>Also a unit prefix</dd>"));
+        assert!(html.contains("<dt data-md>CDN</dt>\n<dd data-md>Content delivery network</dd>"));
+    }
+
+    #[test]
+    fn test_definition_syntax_left_alone_elsewhere() {
+        let renderer = Renderer::new();
+
+        let html = renderer.render_markdown("Just a paragraph\nwith two lines\n");
+        assert!(html.starts_with("<p data-md>"));
+        assert!(!html.contains("<dl"));
+
+        let html = renderer.render_markdown("```\nTerm\n: not a definition\n```\n");
+        assert!(!html.contains("<dl"));
+    }
+
+    #[test]
+    fn test_details_container() {
+        let renderer = Renderer::new();
+        let html =
+            renderer.render_markdown("::: details Spoiler <b>\n- one\n- **two**\n:::\n\nAfter\n");
+
+        assert!(html.contains("<details data-md>\n<summary>Spoiler &lt;b&gt;</summary>"));
+        assert!(html.contains("<ul data-md>\n<li data-md>one</li>\n<li data-md><strong data-md>two</strong></li>\n</ul>\n</details>"));
+        assert!(html.contains("<p data-md>After</p>"));
+    }
+
+    #[test]
+    fn test_details_container_nesting_and_fences() {
+        let renderer = Renderer::new();
+        let md = "::: details Outer\n::: details Inner\nDeep\n:::\n```\n::: details not a container\n:::\n```\n:::\n";
+        let html = renderer.render_markdown(md);
+
+        assert_eq!(html.matches("<details data-md>").count(), 2);
+        assert_eq!(html.matches("</details>").count(), 2);
+        assert!(html.contains("<p data-md>Deep</p>\n</details>"));
+        assert!(html.contains("::: details not a container"));
+    }
+
+    #[test]
+    fn test_is_external() {
+        let site = Some("blog.example.com");
+
+        assert!(Renderer::is_external("https://github.com/marshallku", site));
+        assert!(Renderer::is_external("//cdn.other.com/x.js", site));
+        assert!(!Renderer::is_external(
+            "https://Blog.example.com:443/dev/",
+            site
+        ));
+        assert!(!Renderer::is_external("/dev/post/", site));
+        assert!(!Renderer::is_external("./other-post", site));
+        assert!(!Renderer::is_external("#heading", site));
+        assert!(!Renderer::is_external("mailto:me@example.com", site));
+        assert!(Renderer::is_external("https://blog.example.com/", None));
+    }
+
+    #[test]
+    fn test_link_component_gets_is_external() {
+        let renderer = Renderer::new().with_site_url("https://blog.example.com");
+        let mut tera = Tera::default();
+        tera.add_raw_template(
+            "components/a.html",
+            r#"<a href="{{ href }}" data-external="{{ is_external }}">{{ content | safe }}</a>"#,
+        )
+        .unwrap();
+
+        let md = "[off](https://github.com) [self](https://blog.example.com/dev/) [rel](./post)";
+        let (html, _) = renderer
+            .render_markdown_with_components(md, &tera, "dev")
+            .unwrap();
+
+        assert!(
+            html.contains(r#"data-external="true">off</a>"#),
+            "got: {}",
+            html
+        );
+        assert!(html.contains(r#"data-external="false">self</a>"#));
+        assert!(html.contains(r#"data-external="false">rel</a>"#));
+    }
+
+    #[test]
+    fn test_strip_comments() {
+        let mut renderer = Renderer::new();
+        renderer.strip_comments = true;
+        let md = "Before <!-- TODO fix --> after\n\n<!--\ndraft\nnotes\n-->\n\n<!--[if IE]><p>old</p><![endif]-->\n\n```\n<!-- x -->\n```\n\n`<!-- y -->`\n";
+        let html = renderer.render_markdown(md);
+
+        assert!(!html.contains("TODO"), "got: {}", html);
+        assert!(!html.contains("draft"));
+        assert!(html.contains("Before  after"));
+        assert!(html.contains("<!--[if IE]><p>old</p><![endif]-->"));
+        assert!(html.contains("&lt;!-- x --&gt;"));
+        assert!(html.contains("&lt;!-- y --&gt;"));
+
+        renderer.strip_comments = false;
+        assert!(renderer.render_markdown(md).contains("<!-- TODO fix -->"));
+    }
+
+    #[test]
+    fn test_typography() {
+        let mut renderer = Renderer::new();
+        let md = "\"It's *done*,\" she said --- pages 3--5...\n\n`\"raw\" -- code`\n\n```\nlet s = \"x\"; // ...\n```\n";
+
+        let html = renderer.render_markdown(md);
+        assert!(
+            html.contains("“It’s <em data-md>done</em>,” she said — pages 3–5…"),
+            "got: {}",
+            html
+        );
+        assert!(html.contains("<code data-md>&quot;raw&quot; -- code</code>"));
+        assert!(html.contains("let s = &quot;x&quot;; // ..."));
+
+        renderer.typography = false;
+        let html = renderer.render_markdown(md);
+        assert!(html.contains("&quot;It's <em data-md>done</em>,&quot; she said --- pages 3--5..."));
+    }
 }
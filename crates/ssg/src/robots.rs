@@ -7,15 +7,11 @@ pub struct RobotsGenerator;
 
 impl RobotsGenerator {
     pub fn generate(config: &SsgConfig, output_dir: &Path) -> Result<()> {
-        let sitemap_url = format!("{}/sitemap.xml", config.site.url);
-
-        let robots_txt = format!(
-            "User-agent: *\n\
-             Allow: /\n\
-             \n\
-             Sitemap: {}\n",
-            sitemap_url
-        );
+        let robots_txt = if config.build.noindex {
+            "User-agent: *\nDisallow: /\n".to_string()
+        } else {
+            Self::render(config)
+        };
 
         fs::create_dir_all(output_dir)?;
         let output_path = output_dir.join("robots.txt");
@@ -23,12 +19,45 @@ impl RobotsGenerator {
 
         Ok(())
     }
+
+    fn render(config: &SsgConfig) -> String {
+        let robots = &config.build.robots;
+        let mut output = String::new();
+
+        if robots.user_agents.is_empty() {
+            output.push_str("User-agent: *\nAllow: /\n");
+        }
+
+        for (i, rule) in robots.user_agents.iter().enumerate() {
+            if i > 0 {
+                output.push('\n');
+            }
+            output.push_str(&format!("User-agent: {}\n", rule.agent));
+            for path in &rule.allow {
+                output.push_str(&format!("Allow: {}\n", path));
+            }
+            for path in &rule.disallow {
+                output.push_str(&format!("Disallow: {}\n", path));
+            }
+            if rule.allow.is_empty() && rule.disallow.is_empty() {
+                output.push_str("Allow: /\n");
+            }
+        }
+
+        output.push('\n');
+        output.push_str(&format!("Sitemap: {}/sitemap.xml\n", config.site.url));
+        for sitemap in &robots.sitemaps {
+            output.push_str(&format!("Sitemap: {}\n", sitemap));
+        }
+
+        output
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::{BuildConfig, SiteConfig};
+    use crate::config::{BuildConfig, RobotsConfig, RobotsRule, SiteConfig};
 
     #[test]
     fn test_robots_txt_contains_sitemap() {
@@ -54,4 +83,84 @@ mod tests {
 
         let _ = fs::remove_dir_all(&temp_dir);
     }
+
+    #[test]
+    fn test_robots_txt_default_output_unchanged() {
+        let config = SsgConfig {
+            site: SiteConfig {
+                url: "https://example.com".to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        assert_eq!(
+            RobotsGenerator::render(&config),
+            "User-agent: *\nAllow: /\n\nSitemap: https://example.com/sitemap.xml\n"
+        );
+    }
+
+    #[test]
+    fn test_robots_txt_custom_rules() {
+        let config = SsgConfig {
+            site: SiteConfig {
+                url: "https://example.com".to_string(),
+                ..Default::default()
+            },
+            build: BuildConfig {
+                robots: RobotsConfig {
+                    user_agents: vec![
+                        RobotsRule {
+                            agent: "GPTBot".to_string(),
+                            allow: vec![],
+                            disallow: vec!["/".to_string()],
+                        },
+                        RobotsRule {
+                            agent: "*".to_string(),
+                            allow: vec!["/".to_string()],
+                            disallow: vec!["/drafts/".to_string()],
+                        },
+                    ],
+                    sitemaps: vec!["https://example.com/extra-sitemap.xml".to_string()],
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        assert_eq!(
+            RobotsGenerator::render(&config),
+            "User-agent: GPTBot\n\
+             Disallow: /\n\
+             \n\
+             User-agent: *\n\
+             Allow: /\n\
+             Disallow: /drafts/\n\
+             \n\
+             Sitemap: https://example.com/sitemap.xml\n\
+             Sitemap: https://example.com/extra-sitemap.xml\n"
+        );
+    }
+
+    #[test]
+    fn test_robots_txt_noindex_disallows_all() {
+        let config = SsgConfig {
+            build: BuildConfig {
+                noindex: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let temp_dir = std::env::temp_dir().join("robots_test_noindex");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+
+        RobotsGenerator::generate(&config, &temp_dir).unwrap();
+
+        let content = fs::read_to_string(temp_dir.join("robots.txt")).unwrap();
+        assert_eq!(content, "User-agent: *\nDisallow: /\n");
+
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
 }
@@ -1,10 +1,12 @@
 use crate::config::SsgConfig;
 use crate::metadata::{compare_posts_desc, MetadataCache};
-use crate::slug;
+use crate::permalink;
+use crate::verbosity::status;
 use anyhow::Result;
 use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Serialize)]
 pub struct SearchIndex {
@@ -21,6 +23,30 @@ pub struct SearchEntry {
     pub category: String,
     pub tags: Vec<String>,
     pub date: String,
+    /// Lowercased match terms, present with `build.search.cjk_ngram`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tokens: Vec<String>,
+}
+
+/// `search-index.json` when the index is split by `build.search.shard_size`
+#[derive(Debug, Serialize)]
+pub struct SearchManifest {
+    pub version: String,
+    pub shards: Vec<SearchShard>,
+    /// Category slug to the shards holding its posts
+    pub categories: BTreeMap<String, Vec<usize>>,
+    /// Tag to the shards holding its posts
+    pub tags: BTreeMap<String, Vec<usize>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SearchShard {
+    /// File name relative to `search-index.json`
+    pub file: String,
+    pub posts: usize,
+    /// Newest and oldest post date in the shard; shards run newest first
+    pub from: String,
+    pub to: String,
 }
 
 pub struct SearchIndexGenerator {
@@ -33,7 +59,7 @@ impl SearchIndexGenerator {
     }
 
     pub fn generate(&self, metadata: &MetadataCache) -> Result<()> {
-        println!("\n🔍 Generating search index...");
+        status!("\n🔍 Generating search index...");
 
         let mut source_posts: Vec<_> = metadata
             .posts
@@ -45,43 +71,156 @@ impl SearchIndexGenerator {
         let posts: Vec<SearchEntry> = source_posts
             .into_iter()
             .map(|post| {
-                let url = if self.config.build.encode_filenames {
-                    format!(
-                        "/{}/{}/",
-                        slug::encode_for_url(&post.category),
-                        slug::encode_for_url(&post.slug)
-                    )
+                let url = permalink::post_url(
+                    &self.config.build,
+                    &post.category,
+                    &post.slug,
+                    &post.frontmatter.date.posted,
+                    self.config.build.encode_filenames,
+                );
+
+                let description = post.summary().map(str::to_string);
+                let tokens = if self.config.build.search.cjk_ngram {
+                    let text = [&post.frontmatter.title]
+                        .into_iter()
+                        .chain(&description)
+                        .chain(&post.frontmatter.tags);
+                    tokenize(text)
                 } else {
-                    format!("/{}/{}/", post.category, post.slug)
+                    Vec::new()
                 };
 
                 SearchEntry {
                     title: post.frontmatter.title.clone(),
-                    description: post.frontmatter.description.clone(),
+                    description,
                     url,
                     category: post.category.clone(),
                     tags: post.frontmatter.tags.clone(),
                     date: post.frontmatter.date.posted.format("%Y-%m-%d").to_string(),
+                    tokens,
                 }
             })
             .collect();
 
-        let index = SearchIndex {
+        let output_dir = PathBuf::from(&self.config.build.output_dir);
+        let post_count = posts.len();
+
+        match self.config.build.search.shard_size {
+            Some(0) => anyhow::bail!("build.search.shard_size must be greater than 0"),
+            Some(shard_size) if post_count > shard_size => {
+                let shard_count = Self::write_shards(&output_dir, posts, shard_size)?;
+                status!(
+                    "   ✓ {} posts indexed in {} shards",
+                    post_count,
+                    shard_count
+                );
+            }
+            _ => {
+                let index = SearchIndex {
+                    version: "1.0".to_string(),
+                    posts,
+                };
+                let json = serde_json::to_string(&index)?;
+                fs::write(output_dir.join("search-index.json"), json)?;
+                status!("   ✓ {} posts indexed", post_count);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Write `search-index-<n>.json` shards of `shard_size` posts and a
+    /// `search-index.json` manifest describing them. Returns the shard count.
+    fn write_shards(
+        output_dir: &Path,
+        posts: Vec<SearchEntry>,
+        shard_size: usize,
+    ) -> Result<usize> {
+        let mut manifest = SearchManifest {
             version: "1.0".to_string(),
-            posts,
+            shards: Vec::new(),
+            categories: BTreeMap::new(),
+            tags: BTreeMap::new(),
         };
 
-        let json = serde_json::to_string(&index)?;
-        let output_path = PathBuf::from(&self.config.build.output_dir).join("search-index.json");
+        let mut posts = posts.into_iter().peekable();
+        while posts.peek().is_some() {
+            let shard_posts: Vec<SearchEntry> = posts.by_ref().take(shard_size).collect();
+            let index = manifest.shards.len();
+
+            for post in &shard_posts {
+                let shards = manifest
+                    .categories
+                    .entry(post.category.clone())
+                    .or_default();
+                if shards.last() != Some(&index) {
+                    shards.push(index);
+                }
+                for tag in &post.tags {
+                    let shards = manifest.tags.entry(tag.clone()).or_default();
+                    if shards.last() != Some(&index) {
+                        shards.push(index);
+                    }
+                }
+            }
 
-        fs::write(&output_path, json)?;
+            let file = format!("search-index-{}.json", index);
+            manifest.shards.push(SearchShard {
+                file: file.clone(),
+                posts: shard_posts.len(),
+                from: shard_posts[0].date.clone(),
+                to: shard_posts[shard_posts.len() - 1].date.clone(),
+            });
 
-        println!("   ✓ {} posts indexed", index.posts.len());
+            let shard = SearchIndex {
+                version: "1.0".to_string(),
+                posts: shard_posts,
+            };
+            fs::write(output_dir.join(file), serde_json::to_string(&shard)?)?;
+        }
 
-        Ok(())
+        fs::write(
+            output_dir.join("search-index.json"),
+            serde_json::to_string(&manifest)?,
+        )?;
+
+        Ok(manifest.shards.len())
     }
 }
 
+/// Sorted, deduplicated match terms for `texts`: every whitespace-separated
+/// word lowercased, plus the bigrams and trigrams of each CJK run, since
+/// Korean attaches particles to nouns without a space
+fn tokenize<'a>(texts: impl IntoIterator<Item = &'a String>) -> Vec<String> {
+    let mut tokens = BTreeSet::new();
+
+    for word in texts.into_iter().flat_map(|t| t.split_whitespace()) {
+        let word = word.to_lowercase();
+        let chars: Vec<char> = word.chars().collect();
+
+        for run in chars.split(|c| !is_cjk(*c)).filter(|run| run.len() > 1) {
+            for n in [2, 3] {
+                tokens.extend(run.windows(n).map(|gram| gram.iter().collect::<String>()));
+            }
+        }
+        tokens.insert(word);
+    }
+
+    tokens.into_iter().collect()
+}
+
+/// Hangul, kana and CJK ideographs, which aren't space-delimited per noun
+fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{1100}'..='\u{11FF}'
+        | '\u{3040}'..='\u{30FF}'
+        | '\u{3130}'..='\u{318F}'
+        | '\u{3400}'..='\u{4DBF}'
+        | '\u{4E00}'..='\u{9FFF}'
+        | '\u{AC00}'..='\u{D7AF}'
+        | '\u{F900}'..='\u{FAFF}')
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -105,14 +244,19 @@ mod tests {
             description: Some("A test post".to_string()),
             display_ad: false,
             hidden: false,
+            unlisted: false,
+            template: None,
             comments: true,
+            audio: None,
+            lang: None,
+            translations: Default::default(),
         };
 
         metadata.upsert_post(
             "test-post".to_string(),
             "dev".to_string(),
             frontmatter,
-            Some(1),
+            None,
         );
 
         metadata
@@ -133,10 +277,67 @@ mod tests {
             category: post.category.clone(),
             tags: post.frontmatter.tags.clone(),
             date: post.frontmatter.date.posted.format("%Y-%m-%d").to_string(),
+            tokens: Vec::new(),
         };
 
         assert_eq!(entry.title, "Test Post");
         assert_eq!(entry.url, "/dev/test-post/");
         assert_eq!(entry.tags.len(), 2);
     }
+
+    fn tokens(text: &str) -> Vec<String> {
+        tokenize([&text.to_string()])
+    }
+
+    #[test]
+    fn test_korean_substring_is_a_token() {
+        let tokens = tokens("정적 사이트생성기를 만들었습니다");
+
+        assert!(tokens.contains(&"사이트".to_string()));
+        assert!(tokens.contains(&"생성기".to_string()));
+        assert!(tokens.contains(&"정적".to_string()));
+        assert!(tokens.contains(&"사이트생성기를".to_string()));
+    }
+
+    #[test]
+    fn test_ascii_words_are_not_split() {
+        assert_eq!(tokens("Rust SSG"), vec!["rust", "ssg"]);
+        // Only the Hangul run is n-grammed
+        assert_eq!(
+            tokens("Rust로"),
+            vec!["rust로".to_string()],
+            "single-character CJK runs add nothing"
+        );
+        assert!(tokens("Rust블로그").contains(&"블로".to_string()));
+        assert!(!tokens("Rust블로그")
+            .iter()
+            .any(|t| t == "ru" || t == "st블"));
+    }
+
+    #[test]
+    fn test_tokens_only_with_cjk_ngram() {
+        let metadata = create_test_metadata();
+        let temp = tempfile::TempDir::new().unwrap();
+        let mut config = create_test_config();
+        config.build.output_dir = temp.path().to_string_lossy().to_string();
+
+        SearchIndexGenerator::new(config.clone())
+            .generate(&metadata)
+            .unwrap();
+        let index = fs::read_to_string(temp.path().join("search-index.json")).unwrap();
+        assert!(!index.contains("\"tokens\""));
+
+        config.build.search.cjk_ngram = true;
+        SearchIndexGenerator::new(config)
+            .generate(&metadata)
+            .unwrap();
+        let index: serde_json::Value = serde_json::from_str(
+            &fs::read_to_string(temp.path().join("search-index.json")).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            index["posts"][0]["tokens"],
+            serde_json::json!(["a", "post", "rust", "test"])
+        );
+    }
 }
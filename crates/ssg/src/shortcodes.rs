@@ -1,8 +1,14 @@
-use anyhow::{anyhow, Result};
+use crate::config::SsgConfig;
+use anyhow::{anyhow, Context, Result};
 use regex::Regex;
 use std::collections::HashMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
 use std::sync::OnceLock;
 
+/// Maximum nesting of `[include]` shortcodes, guarding against include cycles
+const MAX_INCLUDE_DEPTH: usize = 8;
+
 /// Shortcode handler function type
 pub type ShortcodeHandler =
     Box<dyn Fn(&HashMap<String, String>, Option<&str>) -> Result<String> + Send + Sync>;
@@ -10,12 +16,14 @@ pub type ShortcodeHandler =
 /// Registry for shortcode handlers
 pub struct ShortcodeRegistry {
     handlers: HashMap<String, ShortcodeHandler>,
+    includes_dir: PathBuf,
 }
 
 impl ShortcodeRegistry {
     pub fn new() -> Self {
         let mut registry = Self {
             handlers: HashMap::new(),
+            includes_dir: PathBuf::from("content/includes"),
         };
 
         // Register built-in shortcodes
@@ -24,6 +32,44 @@ impl ShortcodeRegistry {
         registry
     }
 
+    /// Built-in shortcodes plus the ones declared under `build.shortcodes`
+    pub fn from_config(config: &SsgConfig) -> Self {
+        let mut registry = Self::new();
+        registry.includes_dir = PathBuf::from(&config.build.includes_dir);
+
+        if config.build.react.components.is_some() {
+            registry.register_react(config.build.react.components.clone());
+        }
+
+        for (name, template) in &config.build.shortcodes {
+            registry.register_template(name, template.clone());
+        }
+
+        registry
+    }
+
+    /// Register a shortcode that expands `template`, replacing `{attr}` with
+    /// the HTML-escaped attribute value (empty when missing) and `{content}`
+    /// with the block body.
+    pub fn register_template(&mut self, name: &str, template: String) {
+        static PLACEHOLDER_RE: OnceLock<Regex> = OnceLock::new();
+        let re = PLACEHOLDER_RE.get_or_init(|| Regex::new(r"\{(\w+)\}").unwrap());
+
+        self.register(
+            name,
+            Box::new(move |attrs, content| {
+                let html = re.replace_all(&template, |cap: &regex::Captures| {
+                    let key = &cap[1];
+                    match (key, content) {
+                        ("content", Some(body)) => body.to_string(),
+                        _ => attrs.get(key).map(|v| escape_html(v)).unwrap_or_default(),
+                    }
+                });
+                Ok(html.into_owned())
+            }),
+        );
+    }
+
     fn register_builtin(&mut self) {
         // Figure shortcode: [figure src="..." alt="..." caption="..."]
         self.register(
@@ -81,8 +127,10 @@ impl ShortcodeRegistry {
                     ));
                 }
 
+                // Blank lines end the HTML block so the body is rendered as
+                // markdown by the renderer
                 html.push_str(&format!(
-                    r#"<div class="callout-content">{}</div></div>"#,
+                    "<div class=\"callout-content\">\n\n{}\n\n</div></div>",
                     content
                 ));
 
@@ -137,32 +185,78 @@ impl ShortcodeRegistry {
             }),
         );
 
-        // React island shortcode: [react component="..." data="..." title="..."]
-        // Generates a placeholder div that will be hydrated by React on the client
-        // All attributes except 'component' and 'loading' are passed as props
+        // Tabs shortcode: [tabs][tab title="..."]content[/tab]...[/tabs]
+        // Bodies are wrapped in blank lines so markdown inside still renders
         self.register(
-            "react",
+            "tabs",
+            Box::new(|_attrs, content| {
+                Ok(format!(
+                    "<div class=\"tabs\">\n\n{}\n\n</div>",
+                    content.unwrap_or("")
+                ))
+            }),
+        );
+
+        self.register(
+            "tab",
             Box::new(|attrs, content| {
+                let title = attrs.get("title").map(|s| s.as_str()).unwrap_or("");
+
+                Ok(format!(
+                    "<div class=\"tab\" data-title=\"{}\">\n\n{}\n\n</div>",
+                    escape_html(title),
+                    content.unwrap_or("")
+                ))
+            }),
+        );
+
+        self.register_react(None);
+    }
+
+    /// React island shortcode: [react component="..." data="..." title="..."]
+    /// Generates a placeholder div that will be hydrated by React on the client.
+    /// All attributes except 'component' and 'loading' are passed as props;
+    /// `true`/`false` and plain numbers become JSON booleans and numbers unless
+    /// listed in `string_props="year,zip"`.
+    /// With an allowlist (`build.react.components`), any other component is an
+    /// error instead of an island that never hydrates.
+    fn register_react(&mut self, components: Option<Vec<String>>) {
+        self.register(
+            "react",
+            Box::new(move |attrs, content| {
                 let component = attrs
                     .get("component")
                     .ok_or_else(|| anyhow!("React shortcode requires 'component' attribute"))?;
+                if let Some(allowed) = &components {
+                    if !allowed.contains(component) {
+                        anyhow::bail!(
+                            "Unknown React component '{}' (build.react.components allows: {})",
+                            component,
+                            allowed.join(", ")
+                        );
+                    }
+                }
                 let loading = attrs.get("loading").map(|s| s.as_str()).unwrap_or("lazy");
 
+                let string_props: Vec<&str> = attrs
+                    .get("string_props")
+                    .map(|names| names.split(',').map(str::trim).collect())
+                    .unwrap_or_default();
+
                 // Build props JSON from all other attributes
-                let mut entries: Vec<_> = attrs.iter().collect();
-                entries.sort_by_key(|(key, _)| key.as_str());
-
-                let mut props_parts: Vec<String> = Vec::new();
-                for (key, value) in entries {
-                    if key != "component" && key != "loading" {
-                        // Escape the value for JSON string
-                        let escaped_value = value
-                            .replace('\\', "\\\\")
-                            .replace('"', "\\\"");
-                        props_parts.push(format!(r#""{}":"{}""#, key, escaped_value));
-                    }
-                }
-                let props_json = format!("{{{}}}", props_parts.join(","));
+                let props: serde_json::Map<String, serde_json::Value> = attrs
+                    .iter()
+                    .filter(|(key, _)| !matches!(key.as_str(), "component" | "loading" | "string_props"))
+                    .map(|(key, value)| {
+                        let value = if string_props.contains(&key.as_str()) {
+                            serde_json::Value::String(value.clone())
+                        } else {
+                            prop_value(value)
+                        };
+                        (key.clone(), value)
+                    })
+                    .collect();
+                let props_json = serde_json::Value::Object(props).to_string();
 
                 let mut html = format!(
                     r#"<div class="react-island" data-component="{}" data-props='{}' data-loading="{}">"#,
@@ -192,11 +286,14 @@ impl ShortcodeRegistry {
         self.handlers.insert(name.to_string(), handler);
     }
 
-    /// Process all shortcodes in content. Fenced code blocks and inline code
-    /// spans are masked first so shortcode syntax can be shown as code
-    /// without being executed.
+    /// Process all shortcodes in content. Fenced code
+    /// blocks, inline code spans and backslash-escaped brackets (`\[info]`)
+    /// are masked first so shortcode syntax can be shown literally without
+    /// being executed.
     pub fn process(&self, content: &str) -> Result<String> {
-        let (masked, code_regions) = mask_code_regions(content);
+        let content = self.expand_includes(content, 0)?;
+        let (masked, mut code_regions) = mask_code_regions(&content);
+        let masked = mask_escaped_brackets(&masked, &mut code_regions);
 
         let mut result = self.process_block_shortcodes(&masked)?;
         result = self.process_inline_shortcodes(&result)?;
@@ -204,6 +301,91 @@ impl ShortcodeRegistry {
         Ok(restore_code_regions(result, &code_regions))
     }
 
+    /// Inlines `[include file="..."]` snippets from the includes dir,
+    /// recursively. The result is processed like the rest of the post, so
+    /// snippets may use shortcodes and markdown.
+    fn expand_includes(&self, content: &str, depth: usize) -> Result<String> {
+        static INCLUDE_RE: OnceLock<Regex> = OnceLock::new();
+        let re = INCLUDE_RE
+            .get_or_init(|| Regex::new(r#"\[include((?:[^\]"']|"[^"]*"|'[^']*')*)\]"#).unwrap());
+
+        if !content.contains("[include") {
+            return Ok(content.to_string());
+        }
+
+        let (masked, mut regions) = mask_code_regions(content);
+        let masked = mask_escaped_brackets(&masked, &mut regions);
+
+        let mut result = String::with_capacity(masked.len());
+        let mut last_end = 0;
+
+        for cap in re.captures_iter(&masked) {
+            let full_match = cap.get(0).unwrap();
+            let attrs = parse_attributes(cap.get(1).map(|m| m.as_str()).unwrap_or(""));
+            let file = attrs
+                .get("file")
+                .ok_or_else(|| anyhow!("Include shortcode requires 'file' attribute"))?;
+
+            if depth >= MAX_INCLUDE_DEPTH {
+                anyhow::bail!(
+                    "Includes nested deeper than {} levels at `{}` (recursive include?)",
+                    MAX_INCLUDE_DEPTH,
+                    file
+                );
+            }
+
+            let path = self.resolve_include(file)?;
+            let snippet = fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read include `{}`", path.display()))?;
+            let expanded = self
+                .expand_includes(snippet.trim_end(), depth + 1)
+                .with_context(|| format!("In include `{}`", file))?;
+
+            result.push_str(&masked[last_end..full_match.start()]);
+            result.push_str(&expanded);
+            last_end = full_match.end();
+        }
+
+        result.push_str(&masked[last_end..]);
+        Ok(restore_code_regions(result, &regions))
+    }
+
+    /// Resolves an include path, rejecting anything outside the includes dir
+    fn resolve_include(&self, file: &str) -> Result<PathBuf> {
+        let relative = Path::new(file);
+        let escapes = relative
+            .components()
+            .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir));
+        if escapes {
+            anyhow::bail!(
+                "Include `{}` must be a relative path inside {}",
+                file,
+                self.includes_dir.display()
+            );
+        }
+
+        let path = self.includes_dir.join(relative);
+        if !path.is_file() {
+            anyhow::bail!(
+                "Include `{}` not found in {}",
+                file,
+                self.includes_dir.display()
+            );
+        }
+
+        // Symlinks could still point elsewhere
+        let root = self.includes_dir.canonicalize()?;
+        if !path.canonicalize()?.starts_with(&root) {
+            anyhow::bail!(
+                "Include `{}` resolves outside {}",
+                file,
+                self.includes_dir.display()
+            );
+        }
+
+        Ok(path)
+    }
+
     fn process_block_shortcodes(&self, content: &str) -> Result<String> {
         static OPEN_RE: OnceLock<Regex> = OnceLock::new();
         let open_re = OPEN_RE
@@ -229,7 +411,7 @@ impl ShortcodeRegistry {
                 let close_tag = format!("[/{}]", name);
                 let search_start = open_match.end();
 
-                if let Some(close_pos) = content_clone[search_start..].find(&close_tag) {
+                if let Some(close_pos) = find_matching_close(&content_clone[search_start..], name) {
                     let inner_content = &content_clone[search_start..search_start + close_pos];
                     let full_end = search_start + close_pos + close_tag.len();
 
@@ -296,6 +478,43 @@ impl Default for ShortcodeRegistry {
     }
 }
 
+/// Finds the `[/name]` that closes an already-opened `[name]`, skipping over
+/// nested blocks of the same name. Returns its offset in `content`.
+fn find_matching_close(content: &str, name: &str) -> Option<usize> {
+    let open_prefix = format!("[{}", name);
+    let close_tag = format!("[/{}]", name);
+    let mut depth = 1;
+    let mut pos = 0;
+
+    while pos < content.len() {
+        let rest = &content[pos..];
+        let next_close = rest.find(&close_tag)?;
+        let next_open = rest.match_indices(&open_prefix).find(|(i, _)| {
+            // `[tab` must not match `[tabs`
+            matches!(
+                rest[i + open_prefix.len()..].chars().next(),
+                Some(']') | Some(' ') | Some('\t')
+            )
+        });
+
+        match next_open {
+            Some((open_pos, _)) if open_pos < next_close => {
+                depth += 1;
+                pos += open_pos + open_prefix.len();
+            }
+            _ => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(pos + next_close);
+                }
+                pos += next_close + close_tag.len();
+            }
+        }
+    }
+
+    None
+}
+
 /// Parse shortcode attributes: key="value" key2='value2'
 fn parse_attributes(s: &str) -> HashMap<String, String> {
     static ATTR_RE: OnceLock<Regex> = OnceLock::new();
@@ -316,6 +535,22 @@ fn parse_attributes(s: &str) -> HashMap<String, String> {
     attrs
 }
 
+/// JSON value for a `react` shortcode prop: booleans and numbers written
+/// exactly as JSON would print them (`3`, `-1.5`, but not `007` or `1e3`)
+/// keep their type, everything else is a string
+fn prop_value(value: &str) -> serde_json::Value {
+    match value {
+        "true" => return serde_json::Value::Bool(true),
+        "false" => return serde_json::Value::Bool(false),
+        _ => {}
+    }
+
+    match serde_json::from_str::<serde_json::Number>(value) {
+        Ok(number) if number.to_string() == value => serde_json::Value::Number(number),
+        _ => serde_json::Value::String(value.to_string()),
+    }
+}
+
 /// Escape HTML special characters
 // Private-use-area sentinel; effectively impossible in real markdown content
 const CODE_PLACEHOLDER_MARK: char = '\u{E000}';
@@ -330,7 +565,7 @@ fn code_placeholder(index: usize) -> String {
 /// Replaces fenced code blocks (``` / ~~~) and inline code spans with
 /// placeholders so shortcode processing never touches code content.
 /// Indented (4-space) code blocks are not masked — this blog uses fences.
-fn mask_code_regions(content: &str) -> (String, Vec<String>) {
+pub(crate) fn mask_code_regions(content: &str) -> (String, Vec<String>) {
     let mut regions: Vec<String> = Vec::new();
     let mut out = String::with_capacity(content.len());
     let mut open_fence: Option<(char, usize)> = None;
@@ -454,14 +689,32 @@ fn mask_inline_code(line: &str, regions: &mut Vec<String>) -> String {
     out
 }
 
-fn restore_code_regions(mut content: String, regions: &[String]) -> String {
+/// Masks `\[` so an escaped shortcode is never matched. The backslash is
+/// kept; markdown renders `\[` as a literal `[`.
+pub(crate) fn mask_escaped_brackets(content: &str, regions: &mut Vec<String>) -> String {
+    if !content.contains("\\[") {
+        return content.to_string();
+    }
+
+    let mut out = String::with_capacity(content.len());
+    for (i, part) in content.split("\\[").enumerate() {
+        if i > 0 {
+            out.push_str(&code_placeholder(regions.len()));
+            regions.push("\\[".to_string());
+        }
+        out.push_str(part);
+    }
+    out
+}
+
+pub(crate) fn restore_code_regions(mut content: String, regions: &[String]) -> String {
     for (index, region) in regions.iter().enumerate() {
         content = content.replace(&code_placeholder(index), region);
     }
     content
 }
 
-fn escape_html(s: &str) -> String {
+pub(crate) fn escape_html(s: &str) -> String {
     s.replace('&', "&amp;")
         .replace('<', "&lt;")
         .replace('>', "&gt;")
@@ -542,6 +795,53 @@ mod tests {
         assert!(result.contains(r#"data-loading="lazy""#));
     }
 
+    #[test]
+    fn test_react_shortcode_typed_props() {
+        let registry = ShortcodeRegistry::new();
+        let result = registry
+            .process(r#"[react component="Chart" count="3" ratio="0.5" zip="007" live="true" year="2024" string_props="year"]"#)
+            .unwrap();
+        assert!(result.contains(
+            "data-props='{&quot;count&quot;:3,&quot;live&quot;:true,&quot;ratio&quot;:0.5,\
+             &quot;year&quot;:&quot;2024&quot;,&quot;zip&quot;:&quot;007&quot;}'"
+        ));
+    }
+
+    #[test]
+    fn test_react_shortcode_props_with_quotes() {
+        let registry = ShortcodeRegistry::new();
+        let result = registry
+            .process(r#"[react component="Quote" text='He said "it&#39;s" \ done']"#)
+            .unwrap();
+        assert!(result.contains(
+            "data-props='{&quot;text&quot;:&quot;He said \\&quot;it&amp;#39;s\\&quot; \\\\ done&quot;}'"
+        ));
+        assert!(!result.contains("text\":"));
+    }
+
+    #[test]
+    fn test_react_shortcode_props_with_script_close() {
+        let registry = ShortcodeRegistry::new();
+        let result = registry
+            .process(r#"[react component="Note" body="</script><script>alert(1)</script>"]"#)
+            .unwrap();
+        assert!(!result.contains("</script>"));
+        assert!(result.contains("&lt;/script&gt;&lt;script&gt;alert(1)&lt;/script&gt;"));
+    }
+
+    #[test]
+    fn test_react_prop_value() {
+        assert_eq!(prop_value("42"), serde_json::json!(42));
+        assert_eq!(prop_value("-1.5"), serde_json::json!(-1.5));
+        assert_eq!(prop_value("false"), serde_json::json!(false));
+        assert_eq!(prop_value("1e3"), serde_json::json!("1e3"));
+        assert_eq!(prop_value("line\nbreak"), serde_json::json!("line\nbreak"));
+        assert_eq!(
+            serde_json::Value::String("a\u{1}b".to_string()).to_string(),
+            r#""a\u0001b""#
+        );
+    }
+
     #[test]
     fn test_react_shortcode_with_fallback() {
         let registry = ShortcodeRegistry::new();
@@ -563,6 +863,30 @@ mod tests {
         assert!(result.contains(r#"data-loading="eager""#));
     }
 
+    fn registry_allowing(components: &[&str]) -> ShortcodeRegistry {
+        let mut config = SsgConfig::default();
+        config.build.react.components = Some(components.iter().map(|c| c.to_string()).collect());
+        ShortcodeRegistry::from_config(&config)
+    }
+
+    #[test]
+    fn test_react_shortcode_allowed_component() {
+        let registry = registry_allowing(&["Chart", "CodeEditor"]);
+        let result = registry
+            .process(r#"[react component="Chart" data="1,2,3"]"#)
+            .unwrap();
+        assert!(result.contains(r#"data-component="Chart""#));
+    }
+
+    #[test]
+    fn test_react_shortcode_unknown_component_under_allowlist() {
+        let registry = registry_allowing(&["Chart"]);
+        let err = registry
+            .process(r#"[react component="Chrat"]"#)
+            .unwrap_err();
+        assert!(format!("{:#}", err).contains("Unknown React component 'Chrat'"));
+    }
+
     #[test]
     fn test_react_shortcode_missing_component() {
         let registry = ShortcodeRegistry::new();
@@ -671,4 +995,162 @@ mod tests {
             .unwrap();
         assert!(result.contains(r#"src="image[1].jpg""#), "got: {}", result);
     }
+
+    #[test]
+    fn test_tabs_shortcode() {
+        let registry = ShortcodeRegistry::new();
+        let input = "[tabs]\n[tab title=\"Rust\"]\n**fast**\n[/tab]\n[tab title=\"Go\"]\nsimple\n[/tab]\n[/tabs]";
+        let result = registry.process(input).unwrap();
+
+        assert!(
+            result.starts_with(r#"<div class="tabs">"#),
+            "got: {}",
+            result
+        );
+        assert!(result.contains(r#"<div class="tab" data-title="Rust">"#));
+        assert!(result.contains(r#"<div class="tab" data-title="Go">"#));
+        assert!(result.contains("**fast**"));
+        assert!(result.contains("simple"));
+        assert!(!result.contains("[tab"), "got: {}", result);
+        assert!(!result.contains("[/tab"), "got: {}", result);
+    }
+
+    #[test]
+    fn test_nested_same_name_blocks() {
+        let registry = ShortcodeRegistry::new();
+        let input =
+            "[tabs][tab title=\"Outer\"][tabs][tab title=\"Inner\"]x[/tab][/tabs][/tab][/tabs]";
+        let result = registry.process(input).unwrap();
+
+        assert_eq!(result.matches(r#"<div class="tabs">"#).count(), 2);
+        assert!(result.contains(r#"data-title="Outer""#));
+        assert!(result.contains(r#"data-title="Inner""#));
+        assert!(!result.contains("[/"), "got: {}", result);
+    }
+
+    #[test]
+    fn test_find_matching_close_skips_nested() {
+        assert_eq!(find_matching_close("a[/x]", "x"), Some(1));
+        assert_eq!(find_matching_close("[x]b[/x]c[/x]", "x"), Some(9));
+        // `[xy]` is a different shortcode
+        assert_eq!(find_matching_close("[xy]b[/x]", "x"), Some(5));
+        assert_eq!(find_matching_close("[x]b[/x]", "x"), None);
+    }
+
+    #[test]
+    fn test_escaped_shortcode_left_literal() {
+        let registry = ShortcodeRegistry::new();
+        let input = r#"Write \[youtube id="abc"] to embed a video."#;
+        let result = registry.process(input).unwrap();
+        assert_eq!(result, input);
+    }
+
+    #[test]
+    fn test_escaped_block_shortcode_left_literal() {
+        let registry = ShortcodeRegistry::new();
+        let input = r"Use \[callout]text\[/callout] for notes.";
+        let result = registry.process(input).unwrap();
+        assert_eq!(result, input);
+    }
+
+    #[test]
+    fn test_callout_inside_code_fence_untouched() {
+        let registry = ShortcodeRegistry::new();
+        let input = "```markdown\n[callout type=\"info\"]Hi[/callout]\n```\n";
+        assert_eq!(registry.process(input).unwrap(), input);
+    }
+
+    #[test]
+    fn test_custom_shortcode_from_config() {
+        let mut config = SsgConfig::default();
+        config.build.shortcodes.insert(
+            "note".to_string(),
+            r#"<aside class="note note-{color}" title="{title}">{content}</aside>"#.to_string(),
+        );
+        let registry = ShortcodeRegistry::from_config(&config);
+
+        let result = registry
+            .process(r#"[note color="red"]Careful[/note]"#)
+            .unwrap();
+        assert_eq!(
+            result,
+            r#"<aside class="note note-red" title="">Careful</aside>"#
+        );
+
+        let result = registry.process(r#"[note color="<b>" title="x"]"#).unwrap();
+        assert_eq!(
+            result,
+            r#"<aside class="note note-&lt;b&gt;" title="x"></aside>"#
+        );
+    }
+
+    fn registry_with_includes(dir: &Path) -> ShortcodeRegistry {
+        let mut config = SsgConfig::default();
+        config.build.includes_dir = dir.to_string_lossy().to_string();
+        ShortcodeRegistry::from_config(&config)
+    }
+
+    #[test]
+    fn test_include_shortcode() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        fs::create_dir_all(temp_dir.path().join("snippets")).unwrap();
+        fs::write(
+            temp_dir.path().join("snippets/disclaimer.md"),
+            "**Disclaimer**: [youtube id=\"abc\"]\n",
+        )
+        .unwrap();
+        let registry = registry_with_includes(temp_dir.path());
+
+        let result = registry
+            .process("Intro\n\n[include file=\"snippets/disclaimer.md\"]\n\nOutro")
+            .unwrap();
+
+        assert!(result.contains("**Disclaimer**"), "got: {}", result);
+        assert!(result.contains("youtube.com/embed/abc"), "got: {}", result);
+        assert!(result.ends_with("\n\nOutro"));
+    }
+
+    #[test]
+    fn test_include_rejects_path_traversal() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let includes = temp_dir.path().join("includes");
+        fs::create_dir_all(&includes).unwrap();
+        fs::write(temp_dir.path().join("secret.md"), "secret").unwrap();
+        let registry = registry_with_includes(&includes);
+
+        let err = registry
+            .process(r#"[include file="../secret.md"]"#)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("../secret.md"), "got: {}", err);
+
+        assert!(registry.process(r#"[include file="/etc/passwd"]"#).is_err());
+    }
+
+    #[test]
+    fn test_include_recursion_and_missing_file() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("loop.md"),
+            r#"[include file="loop.md"]"#,
+        )
+        .unwrap();
+        let registry = registry_with_includes(temp_dir.path());
+
+        let err = registry.process(r#"[include file="loop.md"]"#).unwrap_err();
+        assert!(format!("{:#}", err).contains("nested deeper"));
+
+        let err = registry
+            .process(r#"[include file="missing.md"]"#)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("missing.md"), "got: {}", err);
+    }
+
+    #[test]
+    fn test_include_inside_code_untouched() {
+        let registry = ShortcodeRegistry::new();
+        let input = "`[include file=\"x.md\"]`";
+        assert_eq!(registry.process(input).unwrap(), input);
+    }
 }
@@ -1,9 +1,15 @@
 use crate::config::SsgConfig;
+use crate::git;
 use crate::metadata::MetadataCache;
+use crate::parser;
+use crate::permalink;
 use crate::slug::encode_for_url;
-use anyhow::Result;
-use std::fs;
-use std::path::Path;
+use crate::types::Alternate;
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
 
 pub struct SitemapGenerator;
@@ -18,11 +24,26 @@ impl SitemapGenerator {
         Self::add_tags(&mut urls, config, metadata);
         Self::add_pages(&mut urls, config);
 
-        let sitemap_xml = Self::build_sitemap_xml(&urls);
-
         fs::create_dir_all(output_dir)?;
         let output_path = output_dir.join("sitemap.xml");
-        fs::write(&output_path, sitemap_xml)?;
+
+        // Stream into a temp file next to the sitemap and rename it over the
+        // old one, so a failure partway through never leaves it truncated
+        let tmp_path = output_path.with_extension("xml.tmp");
+        let written = File::create(&tmp_path)
+            .with_context(|| format!("Failed to create {}", tmp_path.display()))
+            .and_then(|file| {
+                let mut out = BufWriter::new(file);
+                Self::write_sitemap_xml(&mut out, &urls)?;
+                out.flush()?;
+                Ok(())
+            });
+        if let Err(e) = written {
+            let _ = fs::remove_file(&tmp_path);
+            return Err(e);
+        }
+        fs::rename(&tmp_path, &output_path)
+            .with_context(|| format!("Failed to write {}", output_path.display()))?;
 
         Ok(())
     }
@@ -33,32 +54,50 @@ impl SitemapGenerator {
             lastmod: None,
             changefreq: Some("daily".to_string()),
             priority: Some(1.0),
+            alternates: Vec::new(),
         });
     }
 
     fn add_posts(urls: &mut Vec<SitemapUrl>, config: &SsgConfig, metadata: &MetadataCache) {
+        let content_dir = Path::new(&config.build.content_dir);
+        let sources = if config.build.git_lastmod {
+            parser::post_source_paths(content_dir)
+        } else {
+            HashMap::new()
+        };
+
         for post in &metadata.posts {
             if post.frontmatter.hidden {
                 continue;
             }
 
-            let encoded_category = encode_for_url(&post.category);
-            let encoded_slug = encode_for_url(&post.slug);
-            let url = format!("{}/{}/{}/", config.site.url, encoded_category, encoded_slug);
-
-            let lastmod = post
-                .frontmatter
-                .date
-                .modified
-                .as_ref()
-                .unwrap_or(&post.frontmatter.date.posted)
-                .to_rfc3339();
-
+            let url = format!(
+                "{}{}",
+                config.site.url,
+                permalink::post_url(
+                    &config.build,
+                    &post.category,
+                    &post.slug,
+                    &post.frontmatter.date.posted,
+                    true,
+                )
+            );
+
+            let source = sources.get(&format!("{}/{}", post.category, post.slug));
+            let lastmod = git::resolve_modified(
+                &post.frontmatter.date,
+                source.map(PathBuf::as_path),
+                config.build.git_lastmod,
+            )
+            .to_rfc3339();
+
+            let alternates = post.frontmatter.alternates(&config.site.url, &url);
             urls.push(SitemapUrl {
                 loc: url,
                 lastmod: Some(lastmod),
                 changefreq: Some("monthly".to_string()),
                 priority: Some(0.8),
+                alternates,
             });
         }
     }
@@ -72,12 +111,13 @@ impl SitemapGenerator {
             }
 
             let encoded_slug = encode_for_url(&category.slug);
-            let category_url = format!("{}/{}/", config.site.url, encoded_slug);
+            let category_url = Self::page_loc(config, &format!("/{}/", encoded_slug));
             urls.push(SitemapUrl {
                 loc: category_url,
                 lastmod: None,
                 changefreq: Some("weekly".to_string()),
                 priority: Some(0.7),
+                alternates: Vec::new(),
             });
 
             let post_count = metadata
@@ -88,12 +128,13 @@ impl SitemapGenerator {
             let total_pages = post_count.div_ceil(posts_per_page);
 
             for page in 2..=total_pages {
-                let page_url = format!("{}/{}/page/{}/", config.site.url, encoded_slug, page);
+                let page_url = Self::page_loc(config, &format!("/{}/page/{}/", encoded_slug, page));
                 urls.push(SitemapUrl {
                     loc: page_url,
                     lastmod: None,
                     changefreq: Some("weekly".to_string()),
                     priority: Some(0.5),
+                    alternates: Vec::new(),
                 });
             }
         }
@@ -103,97 +144,133 @@ impl SitemapGenerator {
         let posts_per_page = config.build.posts_per_page;
 
         urls.push(SitemapUrl {
-            loc: format!("{}/tags/", config.site.url),
+            loc: Self::page_loc(config, "/tags/"),
             lastmod: None,
             changefreq: Some("weekly".to_string()),
             priority: Some(0.6),
+            alternates: Vec::new(),
         });
 
         for tag in metadata.get_tags() {
+            let post_count = metadata
+                .get_posts_by_tag(&tag)
+                .into_iter()
+                .filter(|p| !p.frontmatter.hidden)
+                .count();
+            // Tags used only by drafts would expose them through the sitemap
+            if post_count == 0 {
+                continue;
+            }
+
             let encoded_tag = encode_for_url(&tag);
-            let tag_url = format!("{}/tags/{}/", config.site.url, encoded_tag);
+            let tag_url = Self::page_loc(config, &format!("/tags/{}/", encoded_tag));
             urls.push(SitemapUrl {
                 loc: tag_url,
                 lastmod: None,
                 changefreq: Some("weekly".to_string()),
                 priority: Some(0.5),
+                alternates: Vec::new(),
             });
 
-            let post_count = metadata
-                .get_posts_by_tag(&tag)
-                .into_iter()
-                .filter(|p| !p.frontmatter.hidden)
-                .count();
             let total_pages = post_count.div_ceil(posts_per_page);
 
             for page in 2..=total_pages {
-                let page_url = format!("{}/tags/{}/page/{}/", config.site.url, encoded_tag, page);
+                let page_url =
+                    Self::page_loc(config, &format!("/tags/{}/page/{}/", encoded_tag, page));
                 urls.push(SitemapUrl {
                     loc: page_url,
                     lastmod: None,
                     changefreq: Some("weekly".to_string()),
                     priority: Some(0.4),
+                    alternates: Vec::new(),
                 });
             }
         }
     }
 
     fn add_pages(urls: &mut Vec<SitemapUrl>, config: &SsgConfig) {
-        let pages_dir = Path::new("content/pages");
+        let pages_dir = config.build.pages_dir();
         if !pages_dir.exists() {
             return;
         }
 
-        for entry in WalkDir::new(pages_dir)
+        for entry in WalkDir::new(&pages_dir)
             .into_iter()
             .filter_map(|e| e.ok())
-            .filter(|e| e.path().extension().is_some_and(|ext| ext == "md"))
+            .filter(|e| parser::is_markdown(e.path()))
         {
             let path = entry.path();
 
             if let Some(stem) = path.file_stem() {
                 let slug = stem.to_string_lossy();
                 let encoded_slug = encode_for_url(&slug);
-                let page_url = format!("{}/{}/", config.site.url, encoded_slug);
+                let page_url = Self::page_loc(config, &format!("/{}/", encoded_slug));
 
                 urls.push(SitemapUrl {
                     loc: page_url,
                     lastmod: None,
                     changefreq: Some("monthly".to_string()),
                     priority: Some(0.6),
+                    alternates: Vec::new(),
                 });
             }
         }
     }
 
-    fn build_sitemap_xml(urls: &[SitemapUrl]) -> String {
-        let mut xml = String::from(
+    /// Absolute URL for a directory-style site path in the configured URL style
+    fn page_loc(config: &SsgConfig, path: &str) -> String {
+        format!(
+            "{}{}",
+            config.site.url,
+            permalink::page_url(path, config.build.ugly_urls)
+        )
+    }
+
+    /// Write the sitemap one `<url>` at a time rather than building the
+    /// whole document in memory
+    fn write_sitemap_xml<W: Write>(out: &mut W, urls: &[SitemapUrl]) -> io::Result<()> {
+        // The xhtml namespace is only declared when translated posts need it
+        let xhtml_ns = if urls.iter().any(|url| !url.alternates.is_empty()) {
+            r#" xmlns:xhtml="http://www.w3.org/1999/xhtml""#
+        } else {
+            ""
+        };
+        writeln!(
+            out,
             r#"<?xml version="1.0" encoding="UTF-8"?>
-<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
-"#,
-        );
+<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9"{}>"#,
+            xhtml_ns
+        )?;
 
         for url in urls {
-            xml.push_str("  <url>\n");
-            xml.push_str(&format!("    <loc>{}</loc>\n", Self::escape_xml(&url.loc)));
+            out.write_all(b"  <url>\n")?;
+            writeln!(out, "    <loc>{}</loc>", Self::escape_xml(&url.loc))?;
+
+            for alternate in &url.alternates {
+                writeln!(
+                    out,
+                    r#"    <xhtml:link rel="alternate" hreflang="{}" href="{}" />"#,
+                    Self::escape_xml(&alternate.hreflang),
+                    Self::escape_xml(&alternate.href)
+                )?;
+            }
 
             if let Some(ref lastmod) = url.lastmod {
-                xml.push_str(&format!("    <lastmod>{}</lastmod>\n", lastmod));
+                writeln!(out, "    <lastmod>{}</lastmod>", lastmod)?;
             }
 
             if let Some(ref changefreq) = url.changefreq {
-                xml.push_str(&format!("    <changefreq>{}</changefreq>\n", changefreq));
+                writeln!(out, "    <changefreq>{}</changefreq>", changefreq)?;
             }
 
             if let Some(priority) = url.priority {
-                xml.push_str(&format!("    <priority>{:.1}</priority>\n", priority));
+                writeln!(out, "    <priority>{:.1}</priority>", priority)?;
             }
 
-            xml.push_str("  </url>\n");
+            out.write_all(b"  </url>\n")?;
         }
 
-        xml.push_str("</urlset>\n");
-        xml
+        out.write_all(b"</urlset>\n")
     }
 
     fn escape_xml(s: &str) -> String {
@@ -210,6 +287,8 @@ struct SitemapUrl {
     lastmod: Option<String>,
     changefreq: Option<String>,
     priority: Option<f32>,
+    /// `hreflang` links to the other language versions of a post
+    alternates: Vec<Alternate>,
 }
 
 #[cfg(test)]
@@ -242,6 +321,9 @@ mod tests {
             slug: slug.to_string(),
             category: category.to_string(),
             reading_time: 1,
+            word_count: 0,
+            char_count: 0,
+            excerpt: String::new(),
             frontmatter: Frontmatter {
                 title: format!("Test Post {}", slug),
                 date: PostDate::new(Utc::now()),
@@ -251,7 +333,12 @@ mod tests {
                 description: None,
                 display_ad: false,
                 hidden,
+                unlisted: false,
+                template: None,
                 comments: true,
+                audio: None,
+                lang: None,
+                translations: Default::default(),
             },
         }
     }
@@ -269,6 +356,111 @@ mod tests {
         }
     }
 
+    fn sitemap_xml(urls: &[SitemapUrl]) -> String {
+        let mut out = Vec::new();
+        SitemapGenerator::write_sitemap_xml(&mut out, urls).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_sitemap_xml_output() {
+        let urls = vec![
+            SitemapUrl {
+                loc: "https://example.com".to_string(),
+                lastmod: None,
+                changefreq: Some("daily".to_string()),
+                priority: Some(1.0),
+                alternates: Vec::new(),
+            },
+            SitemapUrl {
+                loc: "https://example.com/dev/a&b/".to_string(),
+                lastmod: Some("2024-01-15T10:00:00+00:00".to_string()),
+                changefreq: Some("monthly".to_string()),
+                priority: Some(0.8),
+                alternates: Vec::new(),
+            },
+            SitemapUrl {
+                loc: "https://example.com/about/".to_string(),
+                lastmod: None,
+                changefreq: None,
+                priority: None,
+                alternates: Vec::new(),
+            },
+        ];
+
+        assert_eq!(
+            sitemap_xml(&urls),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+  <url>
+    <loc>https://example.com</loc>
+    <changefreq>daily</changefreq>
+    <priority>1.0</priority>
+  </url>
+  <url>
+    <loc>https://example.com/dev/a&amp;b/</loc>
+    <lastmod>2024-01-15T10:00:00+00:00</lastmod>
+    <changefreq>monthly</changefreq>
+    <priority>0.8</priority>
+  </url>
+  <url>
+    <loc>https://example.com/about/</loc>
+  </url>
+</urlset>
+"#
+        );
+    }
+
+    #[test]
+    fn test_generate_replaces_sitemap_without_leftovers() {
+        let dir = tempfile::TempDir::new().unwrap();
+        fs::write(dir.path().join("sitemap.xml"), "stale").unwrap();
+
+        SitemapGenerator::generate(&create_test_config(), &MetadataCache::new(), dir.path())
+            .unwrap();
+
+        let xml = fs::read_to_string(dir.path().join("sitemap.xml")).unwrap();
+        assert!(xml.contains("<loc>https://example.com</loc>"));
+        assert!(!dir.path().join("sitemap.xml.tmp").exists());
+    }
+
+    #[test]
+    fn test_translated_post_lists_hreflang_alternates() {
+        let config = create_test_config();
+        let mut metadata = MetadataCache::new();
+        let mut post = create_test_post("hello", "dev", false);
+        post.frontmatter.lang = Some("ko".to_string());
+        post.frontmatter.translations = [
+            ("en".to_string(), "/en/hello/".to_string()),
+            (
+                "ja".to_string(),
+                "https://ja.example.com/hello/".to_string(),
+            ),
+        ]
+        .into();
+        metadata.posts.push(post);
+        metadata
+            .posts
+            .push(create_test_post("untranslated", "dev", false));
+
+        let mut urls = Vec::new();
+        SitemapGenerator::add_posts(&mut urls, &config, &metadata);
+        let xml = sitemap_xml(&urls);
+
+        assert!(xml.contains(r#"xmlns:xhtml="http://www.w3.org/1999/xhtml""#));
+        assert!(xml.contains(
+            r#"<xhtml:link rel="alternate" hreflang="en" href="https://example.com/en/hello/" />"#
+        ));
+        assert!(xml.contains(
+            r#"<xhtml:link rel="alternate" hreflang="ja" href="https://ja.example.com/hello/" />"#
+        ));
+        assert!(xml.contains(
+            r#"<xhtml:link rel="alternate" hreflang="ko" href="https://example.com/dev/hello/" />"#
+        ));
+        assert_eq!(xml.matches("<xhtml:link").count(), 3);
+        assert!(urls[1].alternates.is_empty());
+    }
+
     #[test]
     fn test_escape_xml() {
         let input = "https://example.com/path?a=1&b=2";
@@ -276,6 +468,51 @@ mod tests {
         assert_eq!(SitemapGenerator::escape_xml(input), expected);
     }
 
+    #[test]
+    fn test_git_lastmod_finds_markdown_extension_sources() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let content_dir = temp.path().join("posts");
+        fs::create_dir_all(content_dir.join("dev")).unwrap();
+        fs::write(content_dir.join("dev/long-ext.markdown"), "---\n---\n").unwrap();
+        fs::write(content_dir.join("dev/short-ext.md"), "---\n---\n").unwrap();
+        let git = |args: &[&str]| {
+            let status = std::process::Command::new("git")
+                .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+                .args(args)
+                .current_dir(temp.path())
+                .env("GIT_COMMITTER_DATE", "2023-05-06T07:08:09Z")
+                .status()
+                .unwrap();
+            assert!(status.success());
+        };
+        git(&["init", "-q"]);
+        git(&["add", "."]);
+        git(&["commit", "-q", "-m", "posts"]);
+
+        let mut config = create_test_config();
+        config.build.content_dir = content_dir.to_string_lossy().into_owned();
+        config.build.git_lastmod = true;
+        let mut metadata = MetadataCache::new();
+        metadata
+            .posts
+            .push(create_test_post("long-ext", "dev", false));
+        metadata
+            .posts
+            .push(create_test_post("short-ext", "dev", false));
+
+        let mut urls = Vec::new();
+        SitemapGenerator::add_posts(&mut urls, &config, &metadata);
+
+        for url in &urls {
+            assert_eq!(
+                url.lastmod.as_deref(),
+                Some("2023-05-06T07:08:09+00:00"),
+                "{}",
+                url.loc
+            );
+        }
+    }
+
     #[test]
     fn test_hidden_posts_excluded_from_sitemap() {
         let config = create_test_config();
@@ -401,7 +638,7 @@ mod tests {
         assert!(!urls.is_empty(), "Should at least have homepage");
         assert!(urls.iter().any(|u| u.loc == "https://example.com"));
 
-        let xml = SitemapGenerator::build_sitemap_xml(&urls);
+        let xml = sitemap_xml(&urls);
         assert!(xml.starts_with("<?xml version"));
         assert!(xml.contains("<urlset"));
         assert!(xml.contains("</urlset>"));
@@ -414,9 +651,10 @@ mod tests {
             lastmod: Some("2025-01-01T00:00:00+00:00".to_string()),
             changefreq: Some("weekly".to_string()),
             priority: Some(0.8),
+            alternates: Vec::new(),
         }];
 
-        let xml = SitemapGenerator::build_sitemap_xml(&urls);
+        let xml = sitemap_xml(&urls);
 
         assert!(xml.contains("<loc>https://example.com/test/</loc>"));
         assert!(xml.contains("<lastmod>2025-01-01T00:00:00+00:00</lastmod>"));
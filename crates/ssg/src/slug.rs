@@ -22,13 +22,14 @@ pub fn encode_for_url(input: &str) -> String {
     }
 }
 
-/// Turn a heading's text into a stable anchor slug.
+/// Turn free text into a readable slug.
 /// - ASCII letters/digits are lowercased and kept readable
 /// - Runs of spaces/separators collapse into a single hyphen
 /// - Punctuation is dropped
-/// - Non-ASCII letters (e.g. Korean) are preserved, then percent-encoded so the
-///   result is safe as both an `id` attribute and a URL fragment
-pub fn slugify_heading(text: &str) -> String {
+/// - Non-ASCII letters (e.g. Korean) are kept as-is
+///
+/// The result may be empty when the text has no letters or digits.
+pub fn slugify(text: &str) -> String {
     let mut slug = String::with_capacity(text.len());
     let mut pending_separator = false;
 
@@ -44,6 +45,28 @@ pub fn slugify_heading(text: &str) -> String {
         }
     }
 
+    slug
+}
+
+/// Slug for a new post or page file. Korean titles keep their Hangul (URLs
+/// are percent-encoded later by `encode_for_url`); titles without any
+/// letters or digits fall back to "untitled".
+pub fn slugify_title(title: &str) -> String {
+    let slug = slugify(title);
+
+    if slug.is_empty() {
+        return "untitled".to_string();
+    }
+
+    slug
+}
+
+/// Turn a heading's text into a stable anchor slug. Same rules as
+/// [`slugify`], then percent-encoded so the result is safe as both an `id`
+/// attribute and a URL fragment.
+pub fn slugify_heading(text: &str) -> String {
+    let slug = slugify(text);
+
     if slug.is_empty() {
         return "section".to_string();
     }
@@ -106,6 +129,31 @@ mod tests {
         assert_eq!(slugify_heading(""), "section");
     }
 
+    #[test]
+    fn test_slugify_title_english() {
+        assert_eq!(slugify_title("Building a Rust SSG"), "building-a-rust-ssg");
+        assert_eq!(slugify_title("What's new - v2.0"), "whats-new-v20");
+    }
+
+    #[test]
+    fn test_slugify_title_korean() {
+        assert_eq!(slugify_title("안녕 세계"), "안녕-세계");
+    }
+
+    #[test]
+    fn test_slugify_title_mixed() {
+        assert_eq!(
+            slugify_title("Rust로 만드는 블로그!"),
+            "rust로-만드는-블로그"
+        );
+    }
+
+    #[test]
+    fn test_slugify_title_empty_fallback() {
+        assert_eq!(slugify_title("!!!"), "untitled");
+        assert_eq!(slugify_title("   "), "untitled");
+    }
+
     #[test]
     fn test_encode_long_string() {
         let long_string = "가".repeat(100); // 100 Korean characters
@@ -1,6 +1,7 @@
 use crate::config::SsgConfig;
 use crate::metadata::MetadataCache;
-use crate::slug;
+use crate::permalink;
+use crate::verbosity::status;
 use anyhow::Result;
 use serde::Serialize;
 use std::collections::BTreeMap;
@@ -28,7 +29,7 @@ impl SlugIndexGenerator {
     }
 
     pub fn generate(&self, metadata: &MetadataCache) -> Result<()> {
-        println!("\n🔑 Generating slug index...");
+        status!("\n🔑 Generating slug index...");
 
         // BTreeMap keeps the JSON key order stable across builds (determinism).
         let mut index: BTreeMap<String, SlugIndexEntry> = BTreeMap::new();
@@ -38,15 +39,13 @@ impl SlugIndexGenerator {
             // `/{category}/{slug}` with raw (decoded) segments.
             let key = format!("/{}/{}", post.category, post.slug);
 
-            let url = if self.config.build.encode_filenames {
-                format!(
-                    "/{}/{}/",
-                    slug::encode_for_url(&post.category),
-                    slug::encode_for_url(&post.slug)
-                )
-            } else {
-                format!("/{}/{}/", post.category, post.slug)
-            };
+            let url = permalink::post_url(
+                &self.config.build,
+                &post.category,
+                &post.slug,
+                &post.frontmatter.date.posted,
+                self.config.build.encode_filenames,
+            );
 
             let thumbnail = post
                 .frontmatter
@@ -68,7 +67,7 @@ impl SlugIndexGenerator {
         let output_path = PathBuf::from(&self.config.build.output_dir).join("slug-index.json");
         fs::write(&output_path, json)?;
 
-        println!("   ✓ {} entries indexed", index.len());
+        status!("   ✓ {} entries indexed", index.len());
 
         Ok(())
     }
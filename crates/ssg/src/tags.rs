@@ -0,0 +1,116 @@
+use crate::config::BuildConfig;
+use std::collections::HashMap;
+
+/// Folds tag spellings into one canonical tag, following `build.tag_aliases`
+/// and `build.case_insensitive_tags`
+#[derive(Debug, Clone, Default)]
+pub struct TagNormalizer {
+    /// Alias (lowercased when case-insensitive) -> canonical tag
+    aliases: HashMap<String, String>,
+    case_insensitive: bool,
+}
+
+impl TagNormalizer {
+    pub fn from_config(build: &BuildConfig) -> Self {
+        let mut normalizer = Self {
+            aliases: HashMap::new(),
+            case_insensitive: build.case_insensitive_tags,
+        };
+
+        for (canonical, aliases) in &build.tag_aliases {
+            // The canonical spelling maps to itself so that, when matching
+            // case-insensitively, other casings of it fold in too
+            for alias in std::iter::once(canonical).chain(aliases) {
+                let key = normalizer.key(alias);
+                normalizer.aliases.insert(key, canonical.clone());
+            }
+        }
+
+        normalizer
+    }
+
+    pub fn canonicalize(&self, tag: &str) -> String {
+        let key = self.key(tag);
+        match self.aliases.get(&key) {
+            Some(canonical) => canonical.clone(),
+            None => key,
+        }
+    }
+
+    /// Canonicalize every tag, dropping duplicates created by folding while
+    /// keeping the original order
+    pub fn normalize(&self, tags: &[String]) -> Vec<String> {
+        let mut normalized: Vec<String> = Vec::with_capacity(tags.len());
+        for tag in tags {
+            let canonical = self.canonicalize(tag);
+            if !normalized.contains(&canonical) {
+                normalized.push(canonical);
+            }
+        }
+        normalized
+    }
+
+    fn key(&self, tag: &str) -> String {
+        if self.case_insensitive {
+            tag.to_lowercase()
+        } else {
+            tag.to_string()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn normalizer(aliases: &[(&str, &[&str])], case_insensitive: bool) -> TagNormalizer {
+        TagNormalizer::from_config(&BuildConfig {
+            tag_aliases: aliases
+                .iter()
+                .map(|(canonical, aliases)| {
+                    (
+                        canonical.to_string(),
+                        aliases.iter().map(|a| a.to_string()).collect(),
+                    )
+                })
+                .collect(),
+            case_insensitive_tags: case_insensitive,
+            ..Default::default()
+        })
+    }
+
+    fn tags(tags: &[&str]) -> Vec<String> {
+        tags.iter().map(|t| t.to_string()).collect()
+    }
+
+    #[test]
+    fn test_default_keeps_tags() {
+        let normalizer = TagNormalizer::default();
+        assert_eq!(
+            normalizer.normalize(&tags(&["Rust", "rust"])),
+            tags(&["Rust", "rust"])
+        );
+    }
+
+    #[test]
+    fn test_aliases_fold_into_canonical() {
+        let normalizer = normalizer(&[("JavaScript", &["javascript", "js"])], false);
+        assert_eq!(
+            normalizer.normalize(&tags(&["js", "javascript", "Rust", "JavaScript"])),
+            tags(&["JavaScript", "Rust"])
+        );
+        // Case-sensitive by default: unlisted casings stay separate
+        assert_eq!(normalizer.canonicalize("JS"), "JS");
+    }
+
+    #[test]
+    fn test_case_insensitive() {
+        let normalizer = normalizer(&[("JavaScript", &["js"])], true);
+        assert_eq!(normalizer.canonicalize("JAVASCRIPT"), "JavaScript");
+        assert_eq!(normalizer.canonicalize("JS"), "JavaScript");
+        assert_eq!(
+            normalizer.normalize(&tags(&["Rust", "rust"])),
+            tags(&["rust"])
+        );
+    }
+}
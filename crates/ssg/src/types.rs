@@ -1,5 +1,6 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::BTreeMap;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
 pub struct PostDate {
@@ -42,12 +43,42 @@ where
     })
 }
 
+/// Accepts `tags` as either a YAML sequence or a single string (`tags: rust,
+/// ssg, web`). A string is split on commas when it has any (so multi-word tags
+/// survive), otherwise on whitespace. Entries are trimmed and empties dropped.
+fn deserialize_tags<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum TagsFormat {
+        List(Vec<String>),
+        Joined(String),
+    }
+
+    let tags = match Option::<TagsFormat>::deserialize(deserializer)? {
+        Some(TagsFormat::List(tags)) => tags,
+        Some(TagsFormat::Joined(joined)) if joined.contains(',') => {
+            joined.split(',').map(str::to_string).collect()
+        }
+        Some(TagsFormat::Joined(joined)) => joined.split_whitespace().map(str::to_string).collect(),
+        None => Vec::new(),
+    };
+
+    Ok(tags
+        .into_iter()
+        .map(|tag| tag.trim().to_string())
+        .filter(|tag| !tag.is_empty())
+        .collect())
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Frontmatter {
     pub title: String,
     #[serde(deserialize_with = "deserialize_post_date")]
     pub date: PostDate,
-    #[serde(default)]
+    #[serde(default, deserialize_with = "deserialize_tags")]
     pub tags: Vec<String>,
     #[serde(alias = "coverImage", skip_serializing_if = "Option::is_none")]
     pub cover_image: Option<String>,
@@ -59,8 +90,77 @@ pub struct Frontmatter {
     pub display_ad: bool,
     #[serde(default)]
     pub hidden: bool,
+    /// Build the post but keep it out of listings, navigation, feeds, the
+    /// sitemap and search, with a `noindex` robots tag
+    #[serde(default)]
+    pub unlisted: bool,
     #[serde(default = "default_true")]
     pub comments: bool,
+    /// Alternate template: `wide` renders with `post-wide.html` (and
+    /// `partials/post-wide.html` when present)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+    /// Audio episode published as an RSS `<enclosure>`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub audio: Option<Audio>,
+    /// Language of the post (e.g. "ko"), used as its own `hreflang`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lang: Option<String>,
+    /// Other language versions of the post, language code to URL
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub translations: BTreeMap<String, String>,
+}
+
+/// One `hreflang` alternate of a translated post
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Alternate {
+    pub hreflang: String,
+    /// Absolute URL
+    pub href: String,
+}
+
+impl Frontmatter {
+    /// `hreflang` alternates for a translated post, including the post itself
+    /// under `lang`, sorted by language. Site-relative URLs are prefixed with
+    /// `site_url`. Empty without `translations`.
+    pub fn alternates(&self, site_url: &str, own_url: &str) -> Vec<Alternate> {
+        if self.translations.is_empty() {
+            return Vec::new();
+        }
+
+        let mut links = self.translations.clone();
+        if let Some(lang) = &self.lang {
+            links
+                .entry(lang.clone())
+                .or_insert_with(|| own_url.to_string());
+        }
+
+        links
+            .into_iter()
+            .map(|(hreflang, href)| {
+                let href = if href.contains("://") {
+                    href
+                } else {
+                    format!("{}/{}", site_url, href.trim_start_matches('/'))
+                };
+                Alternate { hreflang, href }
+            })
+            .collect()
+    }
+}
+
+/// Audio attached to a post with `audio: { url, length, type }`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Audio {
+    /// Resolved like image paths (`./episode.mp3` is beside the post), or an
+    /// absolute URL
+    pub url: String,
+    /// Size in bytes; read from the file when omitted and it's beside the post
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub length: Option<u64>,
+    /// MIME type; guessed from the extension when omitted
+    #[serde(default, rename = "type", skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize)]
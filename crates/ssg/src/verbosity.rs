@@ -0,0 +1,80 @@
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// How much build output to print, selected with `--quiet` / `--verbose`.
+/// Set once at startup with [`set`]; the [`status!`] and [`detail!`] macros
+/// gate `println!`s on it so generators don't need it threaded through.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Verbosity {
+    /// Errors only
+    Quiet,
+    #[default]
+    Normal,
+    /// Per-post lines in addition to the normal output
+    Verbose,
+}
+
+impl Verbosity {
+    pub fn from_flags(quiet: bool, verbose: bool) -> Self {
+        if quiet {
+            Self::Quiet
+        } else if verbose {
+            Self::Verbose
+        } else {
+            Self::Normal
+        }
+    }
+
+    pub fn is_quiet(self) -> bool {
+        self == Self::Quiet
+    }
+
+    pub fn is_verbose(self) -> bool {
+        self == Self::Verbose
+    }
+}
+
+static LEVEL: AtomicU8 = AtomicU8::new(Verbosity::Normal as u8);
+
+pub fn set(verbosity: Verbosity) {
+    LEVEL.store(verbosity as u8, Ordering::Relaxed);
+}
+
+pub fn current() -> Verbosity {
+    match LEVEL.load(Ordering::Relaxed) {
+        0 => Verbosity::Quiet,
+        2 => Verbosity::Verbose,
+        _ => Verbosity::Normal,
+    }
+}
+
+/// `println!` for phase summaries; silenced by `--quiet`
+macro_rules! status {
+    ($($arg:tt)*) => {
+        if !$crate::verbosity::current().is_quiet() {
+            println!($($arg)*);
+        }
+    };
+}
+
+/// `println!` for per-file lines; only shown with `--verbose`
+macro_rules! detail {
+    ($($arg:tt)*) => {
+        if $crate::verbosity::current().is_verbose() {
+            println!($($arg)*);
+        }
+    };
+}
+
+pub(crate) use {detail, status};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_flags() {
+        assert_eq!(Verbosity::from_flags(false, false), Verbosity::Normal);
+        assert_eq!(Verbosity::from_flags(true, false), Verbosity::Quiet);
+        assert_eq!(Verbosity::from_flags(false, true), Verbosity::Verbose);
+    }
+}
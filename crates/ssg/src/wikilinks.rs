@@ -0,0 +1,154 @@
+use crate::config::BuildConfig;
+use crate::metadata::MetadataCache;
+use crate::permalink;
+use crate::shortcodes::{
+    escape_html, mask_code_regions, mask_escaped_brackets, restore_code_regions,
+};
+use regex::Regex;
+use std::sync::OnceLock;
+
+/// Resolve Obsidian-style `[[slug]]`, `[[slug|label]]` and `[[slug#heading]]`
+/// links against the posts in `metadata`. Resolved links become markdown
+/// links (labelled with the post title unless a label is given); unresolved
+/// ones become `<span class="broken-wikilink">` and are returned by target so
+/// the caller can report them. Code is left untouched.
+pub fn resolve(
+    content: &str,
+    metadata: &MetadataCache,
+    build: &BuildConfig,
+) -> (String, Vec<String>) {
+    static WIKILINK_RE: OnceLock<Regex> = OnceLock::new();
+    let re = WIKILINK_RE
+        .get_or_init(|| Regex::new(r"\[\[([^\[\]|\n]+)(?:\|([^\[\]\n]+))?\]\]").unwrap());
+
+    let mut unresolved = Vec::new();
+    if !content.contains("[[") {
+        return (content.to_string(), unresolved);
+    }
+
+    let (masked, mut regions) = mask_code_regions(content);
+    let masked = mask_escaped_brackets(&masked, &mut regions);
+
+    let replaced = re.replace_all(&masked, |cap: &regex::Captures| {
+        let target = cap[1].trim();
+        let label = cap.get(2).map(|m| m.as_str().trim());
+        let (slug, fragment) = match target.split_once('#') {
+            Some((slug, fragment)) => (slug.trim(), Some(fragment.trim())),
+            None => (target, None),
+        };
+
+        let Some(post) = metadata.posts.iter().find(|p| p.slug == slug) else {
+            unresolved.push(target.to_string());
+            return format!(
+                "<span class=\"broken-wikilink\">{}</span>",
+                escape_html(label.unwrap_or(target))
+            );
+        };
+
+        let mut url = permalink::post_url(
+            build,
+            &post.category,
+            &post.slug,
+            &post.frontmatter.date.posted,
+            build.encode_filenames,
+        );
+        if let Some(fragment) = fragment.filter(|f| !f.is_empty()) {
+            url.push('#');
+            url.push_str(&crate::slug::slugify_heading(fragment));
+        }
+
+        format!(
+            "[{}](<{}>)",
+            escape_label(label.unwrap_or(&post.frontmatter.title)),
+            url
+        )
+    });
+
+    (
+        restore_code_regions(replaced.into_owned(), &regions),
+        unresolved,
+    )
+}
+
+/// Backslash-escape characters that would end or nest a markdown link label
+fn escape_label(label: &str) -> String {
+    let mut escaped = String::with_capacity(label.len());
+    for c in label.chars() {
+        if matches!(c, '[' | ']' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Frontmatter, PostDate};
+    use chrono::{TimeZone, Utc};
+
+    fn metadata() -> MetadataCache {
+        let mut metadata = MetadataCache::new();
+        let frontmatter = Frontmatter {
+            title: "Hello [World]".to_string(),
+            date: PostDate::new(Utc.with_ymd_and_hms(2024, 1, 15, 0, 0, 0).unwrap()),
+            tags: vec![],
+            cover_image: None,
+            og_image: None,
+            description: None,
+            display_ad: false,
+            hidden: false,
+            unlisted: false,
+            template: None,
+            comments: true,
+            audio: None,
+            lang: None,
+            translations: Default::default(),
+        };
+        metadata.upsert_post("hello".to_string(), "dev".to_string(), frontmatter, None);
+        metadata
+    }
+
+    #[test]
+    fn test_resolves_to_post_url_with_title() {
+        let (content, unresolved) = resolve("See [[hello]].", &metadata(), &BuildConfig::default());
+
+        assert_eq!(content, r"See [Hello \[World\]](</dev/hello/>).");
+        assert!(unresolved.is_empty());
+    }
+
+    #[test]
+    fn test_label_and_heading() {
+        let (content, _) = resolve(
+            "[[hello|the intro]] and [[hello#Getting Started]]",
+            &metadata(),
+            &BuildConfig::default(),
+        );
+
+        assert!(
+            content.starts_with("[the intro](</dev/hello/>)"),
+            "{}",
+            content
+        );
+        assert!(
+            content.contains("(</dev/hello/#getting-started>)"),
+            "{}",
+            content
+        );
+    }
+
+    #[test]
+    fn test_unresolved_and_code_untouched() {
+        let (content, unresolved) = resolve(
+            "[[missing|Gone <soon>]] `[[hello]]`\n\n```\n[[hello]]\n```\n",
+            &metadata(),
+            &BuildConfig::default(),
+        );
+
+        assert!(content
+            .starts_with("<span class=\"broken-wikilink\">Gone &lt;soon&gt;</span> `[[hello]]`"));
+        assert!(content.contains("```\n[[hello]]\n```"));
+        assert_eq!(unresolved, vec!["missing"]);
+    }
+}
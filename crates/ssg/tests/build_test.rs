@@ -1,5 +1,6 @@
 mod common;
 
+use chrono::Datelike;
 use common::{assert_failure, assert_success, stderr_contains, stdout_contains, TestEnvironment};
 
 #[test]
@@ -50,6 +51,508 @@ fn should_generate_sitemap_with_all_posts() {
     assert!(sitemap.contains("<loc>https://test.example.com/tutorials/test-tutorials/</loc>"));
 }
 
+#[test]
+fn should_build_posts_with_markdown_extension() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+    env.write_file(
+        "content/posts/dev/long-ext.markdown",
+        "---\ntitle: \"Long Extension\"\ndate: 2024-02-01T10:00:00Z\n---\n\nWritten in a .markdown file.\n",
+    );
+
+    // Act
+    let result = env.run_build();
+
+    // Assert
+    assert_success(&result);
+    assert!(env.output_exists("dev/long-ext/index.html"));
+    assert!(env
+        .read_output("sitemap.xml")
+        .contains("<loc>https://test.example.com/dev/long-ext/</loc>"));
+    assert!(env.read_output("feed.xml").contains("Long Extension"));
+}
+
+#[test]
+fn should_keep_content_after_raw_img_with_srcset() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+    env.write_file(
+        "templates/components/img.html",
+        r#"<figure class="image-block"><img src="{{ src }}" alt="{{ alt }}"></figure>"#,
+    );
+    env.write_file(
+        "content/posts/dev/raw-img.md",
+        concat!(
+            "---\ntitle: \"Raw Image\"\ndate: 2024-02-01T10:00:00Z\n---\n\n",
+            "<img src=\"./a.jpg\" srcset=\"./a.jpg 1x, ./a@2x.jpg 2x\" alt=\"x\">\n\n",
+            "Text after the image.[^1]\n\n",
+            "```\nlet kept = true;\n```\n\n",
+            "[^1]: A footnote.\n",
+        ),
+    );
+
+    // Act
+    let result = env.run_build();
+
+    // Assert
+    assert_success(&result);
+    let html = env.read_output("dev/raw-img/index.html");
+    assert!(
+        html.contains(r#"<img src="/dev/a.jpg" srcset="/dev/a.jpg 1x, /dev/a@2x.jpg 2x" alt="x">"#),
+        "got: {}",
+        html
+    );
+    assert!(!html.contains("image-block"), "got: {}", html);
+    assert!(html.contains("Text after the image."));
+    assert!(html.contains("let kept = true;"));
+    assert!(html.contains("A footnote."));
+}
+
+#[test]
+fn should_keep_raw_picture_fallback_img_as_written() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+    env.write_file(
+        "templates/components/img.html",
+        r#"<figure class="image-block"><img src="{{ src }}" alt="{{ alt }}"></figure>"#,
+    );
+    env.write_file(
+        "content/posts/dev/raw-picture.md",
+        concat!(
+            "---\ntitle: \"Raw Picture\"\ndate: 2024-02-01T10:00:00Z\n---\n\n",
+            "<picture><source srcset=\"./x.webp\" type=\"image/webp\"><img src=\"./x.jpg\" alt=\"x\"></picture>\n\n",
+            "Text after the picture.\n",
+        ),
+    );
+
+    // Act
+    let result = env.run_build();
+
+    // Assert
+    assert_success(&result);
+    let html = env.read_output("dev/raw-picture/index.html");
+    assert!(
+        html.contains(concat!(
+            r#"<picture><source srcset="/dev/x.webp" type="image/webp">"#,
+            r#"<img src="/dev/x.jpg" alt="x"></picture>"#
+        )),
+        "got: {}",
+        html
+    );
+    assert!(!html.contains("image-block"), "got: {}", html);
+    assert!(html.contains("Text after the picture."));
+}
+
+#[test]
+fn should_write_gzip_siblings_when_precompress_is_set() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+    env.set_build_option("precompress", "[gzip]");
+    env.set_build_option("precompress_min_size", "0");
+
+    // Act
+    let result = env.run_build();
+
+    // Assert
+    assert_success(&result);
+    assert!(env.output_exists("index.html.gz"));
+    assert!(env.output_exists("dev/test-post/index.html.gz"));
+
+    let compressed = std::fs::read(env.root.join("dist/dev/test-post/index.html.gz")).unwrap();
+    let mut decompressed = String::new();
+    std::io::Read::read_to_string(
+        &mut flate2::read::GzDecoder::new(&compressed[..]),
+        &mut decompressed,
+    )
+    .unwrap();
+    assert_eq!(decompressed, env.read_output("dev/test-post/index.html"));
+}
+
+fn canonical_href(html: &str) -> String {
+    let start = html.find(r#"rel="canonical" href=""#).unwrap() + r#"rel="canonical" href=""#.len();
+    html[start..start + html[start..].find('"').unwrap()].replace("&#x2F;", "/")
+}
+
+#[test]
+fn should_expose_canonical_urls() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+    env.set_build_option("posts_per_page", "1");
+    env.create_post("dev", "second-post", "Second Post");
+
+    // Act
+    let result = env.run_build();
+
+    // Assert
+    assert_success(&result);
+    assert_eq!(
+        canonical_href(&env.read_output("dev/test-post/index.html")),
+        "https://test.example.com/dev/test-post/"
+    );
+    assert_eq!(
+        canonical_href(&env.read_output("dev/page/2/index.html")),
+        "https://test.example.com/dev/page/2/"
+    );
+    assert_eq!(
+        canonical_href(&env.read_output("index.html")),
+        "https://test.example.com"
+    );
+}
+
+#[test]
+fn should_point_paginated_canonicals_at_first_page_when_configured() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+    env.set_build_option("posts_per_page", "1");
+    env.set_build_option("canonical_first_page", "true");
+    env.create_post("dev", "second-post", "Second Post");
+
+    // Act
+    let result = env.run_build();
+
+    // Assert
+    assert_success(&result);
+    assert_eq!(
+        canonical_href(&env.read_output("dev/page/2/index.html")),
+        "https://test.example.com/dev/"
+    );
+}
+
+fn write_listing_partials(env: &TestEnvironment) {
+    env.write_file("templates/partials/index.html", "home");
+    env.write_file(
+        "templates/partials/category.html",
+        "{{ category.slug }}:{% for post in posts %}{{ post.slug }}{% endfor %}",
+    );
+    env.write_file("templates/partials/tag.html", "{{ tag }}");
+    env.write_file("templates/partials/tags.html", "tags");
+}
+
+#[test]
+fn should_generate_paginated_listing_partials() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+    env.set_build_option("posts_per_page", "1");
+    env.set_build_option("generate_partials", "true");
+    write_listing_partials(&env);
+    env.write_file("templates/partials/post.html", "{{ content | safe }}");
+    env.create_post("dev", "second-post", "Second Post");
+
+    // Act
+    let result = env.run_build();
+
+    // Assert
+    assert_success(&result);
+    assert_eq!(env.read_output("html/dev/index.html"), "dev:second-post");
+    assert_eq!(
+        env.read_output("html/dev/page/2/index.html"),
+        "dev:test-post"
+    );
+    assert_eq!(env.read_output("html/tag/test/index.html"), "test");
+    assert_eq!(env.read_output("html/index.html"), "home");
+    assert_eq!(env.read_output("html/tags/index.html"), "tags");
+}
+
+fn between<'a>(html: &'a str, start: &str, end: &str) -> &'a str {
+    let from = html.find(start).unwrap() + start.len();
+    &html[from..from + html[from..].find(end).unwrap()]
+}
+
+#[test]
+fn should_render_post_partials_matching_the_full_page_content() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+    env.set_build_option("generate_partials", "true");
+    write_listing_partials(&env);
+    env.write_file(
+        "templates/includes/post-content.html",
+        r#"<article>
+    <h1>{{ post.frontmatter.title }}</h1>
+    <div class="content">{{ content | safe }}</div>
+    {% if prev_post %}<a href="{{ prev_post.url }}">Previous</a>{% endif %}
+    {% if next_post %}<a href="{{ next_post.url }}">Next</a>{% endif %}
+    <link rel="canonical" href="{{ canonical_url }}">
+</article>"#,
+    );
+    env.write_file(
+        "templates/post.html",
+        r#"{% extends "base.html" %}{% block content %}{% include "includes/post-content.html" %}{% endblock %}"#,
+    );
+    env.write_file(
+        "templates/partials/post.html",
+        r#"<div id="partial-content">{% include "includes/post-content.html" %}</div>"#,
+    );
+    env.create_post("dev", "second-post", "Second Post");
+
+    // Act
+    let result = env.run_build();
+
+    // Assert
+    assert_success(&result);
+    let full = env.read_output("dev/test-post/index.html");
+    let partial = env.read_output("html/dev/test-post/index.html");
+    let content = between(&full, "<main>", "</main>");
+    assert!(content.contains("Next"), "got: {}", content);
+    assert_eq!(
+        partial,
+        format!(r#"<div id="partial-content">{}</div>"#, content)
+    );
+}
+
+#[test]
+fn should_build_unlisted_posts_without_listing_them() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+    env.write_file(
+        "content/posts/dev/share-link.md",
+        "---\ntitle: \"Share Link\"\ndate: 2024-02-01T10:00:00Z\ntags: [secret-tag]\nunlisted: true\n---\n\nOnly for people with the link.\n",
+    );
+
+    // Act
+    let result = env.run_build();
+
+    // Assert
+    assert_success(&result);
+    let page = env.read_output("dev/share-link/index.html");
+    assert!(page.contains("Only for people with the link."));
+    assert!(page.contains(r#"<meta name="robots" content="noindex">"#));
+    assert!(!env
+        .read_output("dev/test-post/index.html")
+        .contains("noindex"));
+
+    assert!(!env.read_output("sitemap.xml").contains("share-link"));
+    assert!(!env.read_output("feed.xml").contains("Share Link"));
+    assert!(!env.read_output("search-index.json").contains("share-link"));
+    assert!(!env.read_output("index.html").contains("Share Link"));
+    assert!(!env.read_output("dev/index.html").contains("Share Link"));
+    assert!(!env.output_exists("tag/secret-tag/index.html"));
+}
+
+#[test]
+fn should_use_date_based_permalink() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+    env.set_build_option("permalink", "\"/:year/:month/:slug/\"");
+
+    // Act
+    let result = env.run_build();
+
+    // Assert
+    assert_success(&result);
+    assert!(env.output_exists("2024/01/test-post/index.html"));
+    assert!(!env.output_exists("dev/test-post/index.html"));
+
+    let sitemap = env.read_output("sitemap.xml");
+    assert!(sitemap.contains("<loc>https://test.example.com/2024/01/test-post/</loc>"));
+    let feed = env.read_output("feed.xml");
+    assert!(feed.contains("https://test.example.com/2024/01/test-post/"));
+}
+
+#[test]
+fn should_emit_html_files_with_ugly_urls() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+    env.set_build_option("ugly_urls", "true");
+
+    // Act
+    let result = env.run_build();
+
+    // Assert
+    assert_success(&result);
+    assert!(env.output_exists("dev/test-post.html"));
+    assert!(!env.output_exists("dev/test-post/index.html"));
+    assert!(env.output_exists("dev.html"));
+    assert!(env.output_exists("tag/test.html"));
+    assert!(env.output_exists("index.html"));
+
+    let sitemap = env.read_output("sitemap.xml");
+    assert!(sitemap.contains("<loc>https://test.example.com/dev/test-post.html</loc>"));
+    assert!(sitemap.contains("<loc>https://test.example.com/dev.html</loc>"));
+    let feed = env.read_output("feed.xml");
+    assert!(feed.contains("<link>https://test.example.com/dev/test-post.html</link>"));
+}
+
+#[test]
+fn should_apply_env_config_overlay() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+    env.write_file(
+        "config.staging.yaml",
+        "site:\n  url: \"https://staging.example.com\"\n",
+    );
+
+    // Act
+    let result = env.run_build_with_env("staging");
+
+    // Assert
+    assert_success(&result);
+    let sitemap = env.read_output("sitemap.xml");
+    assert!(sitemap.contains("<loc>https://staging.example.com/dev/test-post/</loc>"));
+    assert!(!sitemap.contains("test.example.com"));
+}
+
+#[test]
+fn should_fail_when_env_config_is_missing() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+
+    // Act
+    let result = env.run_build_with_env("prod");
+
+    // Assert
+    assert_failure(&result);
+    assert!(stderr_contains(&result, "config.prod.yaml"));
+}
+
+#[test]
+fn should_shard_search_index_when_over_shard_size() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+    env.create_category("life");
+    for i in 0..4 {
+        env.create_post("life", &format!("life-{}", i), &format!("Life {}", i));
+    }
+    env.set_build_option("search", "{ enabled: true, shard_size: 2 }");
+
+    // Act
+    let result = env.run_build();
+
+    // Assert
+    assert_success(&result);
+    let manifest: serde_json::Value =
+        serde_json::from_str(&env.read_output("search-index.json")).unwrap();
+    let shards = manifest["shards"].as_array().unwrap();
+    assert_eq!(shards.len(), 3);
+
+    let mut indexed = 0;
+    for (i, shard) in shards.iter().enumerate() {
+        let file = format!("search-index-{}.json", i);
+        assert_eq!(shard["file"], file.as_str());
+        let shard_index: serde_json::Value = serde_json::from_str(&env.read_output(&file)).unwrap();
+        let posts = shard_index["posts"].as_array().unwrap().len();
+        assert_eq!(shard["posts"], posts);
+        indexed += posts;
+    }
+    assert_eq!(indexed, 5);
+    assert!(!env.output_exists("search-index-3.json"));
+    assert!(!manifest["categories"]["life"]
+        .as_array()
+        .unwrap()
+        .is_empty());
+    assert!(manifest["categories"]["dev"].is_array());
+    assert_eq!(manifest["tags"]["test"], serde_json::json!([0, 1, 2]));
+}
+
+#[test]
+fn should_keep_single_search_index_under_shard_size() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+    env.set_build_option("search", "{ enabled: true, shard_size: 2 }");
+
+    // Act
+    let result = env.run_build();
+
+    // Assert
+    assert_success(&result);
+    assert!(env.read_output("search-index.json").contains("Test Post"));
+    assert!(!env.output_exists("search-index-0.json"));
+}
+
+#[test]
+fn should_paginate_posts_json_api() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+    for i in 0..11 {
+        env.create_post("dev", &format!("post-{:02}", i), &format!("Post {}", i));
+    }
+    env.create_hidden_post("dev", "hidden-post", "Hidden Post");
+
+    // Act
+    let result = env.run_build();
+
+    // Assert
+    assert_success(&result);
+    let first: serde_json::Value =
+        serde_json::from_str(&env.read_output("api/posts.json")).unwrap();
+    let second: serde_json::Value =
+        serde_json::from_str(&env.read_output("api/posts/page/2.json")).unwrap();
+    assert!(!env.output_exists("api/posts/page/1.json"));
+    assert!(!env.output_exists("api/posts/page/3.json"));
+
+    assert_eq!(first["posts"].as_array().unwrap().len(), 10);
+    assert_eq!(second["posts"].as_array().unwrap().len(), 2);
+    assert_eq!(first["total_posts"], 12);
+    assert_eq!(first["total_pages"], 2);
+    assert_eq!(
+        first["next"],
+        "https://test.example.com/api/posts/page/2.json"
+    );
+    assert!(first["prev"].is_null());
+    assert_eq!(second["page"], 2);
+    assert_eq!(second["prev"], "https://test.example.com/api/posts.json");
+    assert!(second["next"].is_null());
+
+    let post = &first["posts"][0];
+    assert!(post["url"]
+        .as_str()
+        .unwrap()
+        .starts_with("https://test.example.com/dev/"));
+    assert!(post["tags"].is_array());
+    assert!(post["excerpt"].is_string());
+    let all = env.read_output("api/posts.json") + &env.read_output("api/posts/page/2.json");
+    assert!(!all.contains("Hidden Post"));
+}
+
+#[test]
+fn should_aggregate_nested_category_json() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+    env.write_file(
+        "content/posts/dev/.category.yaml",
+        "name: Development\ndescription: Code and tools\ncover_image: ./cover.png\n",
+    );
+    env.create_post("dev/rust", "ownership", "Ownership");
+    env.create_post("dev/rust", "lifetimes", "Lifetimes");
+    env.write_file("content/posts/secret/.category.yaml", "hidden: true\n");
+    env.create_post("secret", "plans", "Plans");
+
+    // Act
+    let result = env.run_build();
+
+    // Assert
+    assert_success(&result);
+    let dev: serde_json::Value =
+        serde_json::from_str(&env.read_output("api/category/dev.json")).unwrap();
+    assert_eq!(dev["category"]["name"], "Development");
+    assert_eq!(dev["category"]["description"], "Code and tools");
+    assert_eq!(dev["category"]["url"], "https://test.example.com/dev/");
+    assert_eq!(
+        dev["category"]["cover_image"],
+        "https://test.example.com/dev/cover.png"
+    );
+    assert_eq!(dev["total_posts"], 3);
+    let titles: Vec<_> = dev["posts"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|p| p["title"].as_str().unwrap())
+        .collect();
+    assert!(titles.contains(&"Ownership"));
+    assert!(titles.contains(&"Lifetimes"));
+    assert!(titles.contains(&"Test Post"));
+
+    let rust: serde_json::Value =
+        serde_json::from_str(&env.read_output("api/category/dev/rust.json")).unwrap();
+    assert_eq!(rust["category"]["slug"], "dev/rust");
+    assert_eq!(rust["total_posts"], 2);
+
+    assert!(!env.output_exists("api/category/secret.json"));
+    assert!(env
+        .read_output("api/posts.json")
+        .find("\"category\":{")
+        .is_none());
+}
+
 #[test]
 fn should_generate_search_index() {
     // Arrange
@@ -60,126 +563,720 @@ fn should_generate_search_index() {
 
     // Assert
     assert_success(&result);
-    assert!(env.output_exists("search-index.json"));
+    assert!(env.output_exists("search-index.json"));
+
+    let search_index = env.read_output("search-index.json");
+    assert!(search_index.contains("Test Post"));
+}
+
+#[test]
+fn should_exclude_hidden_posts_from_all_outputs() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+    env.create_hidden_post("dev", "hidden-post", "Hidden Post");
+
+    // Act
+    let result = env.run_build();
+
+    // Assert
+    assert_success(&result);
+
+    // Hidden post should not have output file
+    assert!(!env.output_exists("dev/hidden-post/index.html"));
+
+    // Hidden post should not be in sitemap
+    let sitemap = env.read_output("sitemap.xml");
+    assert!(!sitemap.contains("hidden-post"));
+
+    // Hidden post should not be in search index
+    let search_index = env.read_output("search-index.json");
+    assert!(!search_index.contains("Hidden Post"));
+
+    // Hidden post should not be in RSS
+    let rss = env.read_output("feed.xml");
+    assert!(!rss.contains("Hidden Post"));
+}
+
+#[test]
+fn should_build_hidden_posts_with_drafts_flag() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+    env.create_hidden_post("dev", "hidden-post", "Hidden Post");
+
+    // Act
+    let result = env.run_build_with_drafts();
+
+    // Assert
+    assert_success(&result);
+    assert!(env.output_exists("dev/hidden-post/index.html"));
+    assert!(env.read_output("dev/index.html").contains("Hidden Post"));
+
+    // Drafts never leak into the sitemap or feeds
+    let sitemap = env.read_output("sitemap.xml");
+    assert!(!sitemap.contains("hidden-post"));
+    let rss = env.read_output("feed.xml");
+    assert!(!rss.contains("Hidden Post"));
+}
+
+#[test]
+fn should_generate_category_index_pages() {
+    // Arrange
+    let env = TestEnvironment::with_categories(&["dev", "tutorials"]);
+
+    // Act
+    let result = env.run_build();
+
+    // Assert
+    assert_success(&result);
+    assert!(env.output_exists("dev/index.html"));
+    assert!(env.output_exists("tutorials/index.html"));
+
+    let dev_index = env.read_output("dev/index.html");
+    assert!(dev_index.contains("test-dev"));
+}
+
+#[test]
+fn should_generate_tag_pages() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+
+    // Act
+    let result = env.run_build();
+
+    // Assert
+    assert_success(&result);
+    assert!(env.output_exists("tags/index.html"));
+    assert!(env.output_exists("tag/test/index.html"));
+
+    let tag_page = env.read_output("tag/test/index.html");
+    assert!(tag_page.contains("Test Post"));
+}
+
+#[test]
+fn should_merge_aliased_tags_into_one_tag_page() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+    env.set_build_option("tag_aliases", "{ JavaScript: [javascript, js] }");
+    for (slug, tag) in [
+        ("lower", "javascript"),
+        ("short", "js"),
+        ("canon", "JavaScript"),
+    ] {
+        env.write_file(
+            &format!("content/posts/dev/{}.md", slug),
+            &format!(
+                "---\ntitle: \"Post {}\"\ndate: 2024-02-01T10:00:00Z\ntags: [{}]\n---\n\nBody.\n",
+                slug, tag
+            ),
+        );
+    }
+
+    // Act
+    let result = env.run_build();
+
+    // Assert
+    assert_success(&result);
+    let tag_page = env.read_output("tag/JavaScript/index.html");
+    for slug in ["lower", "short", "canon"] {
+        assert!(tag_page.contains(&format!("Post {}", slug)));
+    }
+    assert!(!env.output_exists("tag/javascript/index.html"));
+    assert!(!env.output_exists("tag/js/index.html"));
+
+    let sitemap = env.read_output("sitemap.xml");
+    assert!(sitemap.contains("/JavaScript/</loc>"));
+    assert!(!sitemap.contains("/js/</loc>"));
+    assert!(!sitemap.contains("/javascript/</loc>"));
+    assert!(!env.read_output("feed.xml").contains("[js]"));
+}
+
+#[test]
+fn should_minify_html_but_keep_code_blocks() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+    env.set_build_option("minify_html", "true");
+    env.write_file(
+        "content/posts/dev/code-post.md",
+        "---\ntitle: \"Code Post\"\ndate: 2024-02-01T10:00:00Z\ntags: [test]\n---\n\nSome   text.\n\n```\nline one\n    indented  line\n```\n",
+    );
+
+    // Act
+    let result = env.run_build();
+
+    // Assert
+    assert_success(&result);
+    let post = env.read_output("dev/code-post/index.html");
+    assert!(post.contains("line one\n    indented  line"));
+    let outside_code = post.replace(
+        &post[post.find("<pre").unwrap()..post.find("</pre>").unwrap()],
+        "",
+    );
+    assert!(!outside_code.contains("\n"));
+    assert!(!outside_code.contains("  "));
+    assert!(!env.read_output("index.html").contains("\n"));
+}
+
+#[test]
+fn should_copy_static_files() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+    env.write_file("static/style.css", "body { color: black; }");
+    env.write_file("static/js/app.js", "console.log('test');");
+
+    // Act
+    let result = env.run_build();
+
+    // Assert
+    assert_success(&result);
+    assert!(env.output_exists("style.css"));
+    assert!(env.output_exists("js/app.js"));
+}
+
+#[test]
+fn should_fingerprint_static_assets() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+    env.set_build_option("fingerprint_assets", "true");
+    env.write_file("static/css/style.css", "body { color: black; }");
+    env.write_file(
+        "templates/index.html",
+        r#"<link rel="stylesheet" href="{{ config.assets.static["css/style.css"] }}">"#,
+    );
+
+    // Act
+    let result = env.run_build();
+
+    // Assert
+    assert_success(&result);
+    let index = env.read_output("index.html");
+    let href_start = index.find("href=\"").unwrap() + "href=\"".len();
+    let href = &index[href_start..href_start + index[href_start..].find('"').unwrap()];
+    let href = href.replace("&#x2F;", "/");
+    assert!(href.starts_with("/css/style."), "unexpected href: {}", href);
+    assert_ne!(href, "/css/style.css");
+    assert_eq!(
+        env.read_output(href.trim_start_matches('/')),
+        "body { color: black; }"
+    );
+    assert!(env.output_exists("css/style.css"));
+}
+
+#[test]
+fn should_render_asset_and_url_functions() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+    env.write_file(
+        "manifest.json",
+        r#"{ "styles": { "theme": "/styles/0.1.0/theme.css" } }"#,
+    );
+    env.write_file(
+        "templates/index.html",
+        r#"<link href="{{ asset(pkg="styles", key="theme") }}"><a href="{{ url(path="/about/") }}"></a>"#,
+    );
+
+    // Act
+    let result = env.run_build();
+
+    // Assert
+    assert_success(&result);
+    let index = env.read_output("index.html");
+    assert!(index.contains(r#"<link href="/styles/0.1.0/theme.css">"#));
+    assert!(index.contains(r#"<a href="https://test.example.com/about/">"#));
+}
+
+#[test]
+fn should_fail_on_missing_asset_key() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+    env.write_file("manifest.json", r#"{ "styles": {} }"#);
+    env.write_file(
+        "templates/index.html",
+        r#"<link href="{{ asset(pkg="styles", key="theme") }}">"#,
+    );
+
+    // Act
+    let result = env.run_build();
+
+    // Assert
+    assert_failure(&result);
+}
+
+#[test]
+fn should_copy_fonts_and_data_next_to_posts() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+    env.set_build_option("copy_extensions", "[\".csv\"]");
+    env.write_file("content/posts/dev/font.woff2", "woff2");
+    env.write_file("content/posts/dev/data.json", "{}");
+    env.write_file("content/posts/dev/table.csv", "a,b");
+
+    // Act
+    let result = env.run_build();
+
+    // Assert
+    assert_success(&result);
+    assert_eq!(env.read_output("dev/font.woff2"), "woff2");
+    assert_eq!(env.read_output("dev/data.json"), "{}");
+    assert_eq!(env.read_output("dev/table.csv"), "a,b");
+    assert!(!env.output_exists("dev/test-post.md"));
+}
+
+#[test]
+fn should_expose_data_files_to_templates() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+    env.write_file(
+        "data/links.yaml",
+        "- name: GitHub\n  url: https://github.com/marshallku\n",
+    );
+    env.write_file(
+        "templates/index.html",
+        "{% for link in data.links %}<a href=\"{{ link.url | safe }}\">{{ link.name }}</a>{% endfor %}",
+    );
+
+    // Act
+    let result = env.run_build();
+
+    // Assert
+    assert_success(&result);
+    assert!(env
+        .read_output("index.html")
+        .contains(r#"<a href="https://github.com/marshallku">GitHub</a>"#));
+}
+
+#[test]
+fn should_fail_on_react_component_outside_allowlist() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+    env.set_build_option("react", "{ components: [Chart] }");
+    env.write_file(
+        "content/posts/dev/island.md",
+        "---\ntitle: \"Island\"\ndate: 2024-02-01T10:00:00Z\n---\n\n[react component=\"Chrat\"]\n",
+    );
+
+    // Act
+    let result = env.run_build();
+
+    // Assert
+    assert_failure(&result);
+    assert!(stderr_contains(&result, "island.md"));
+    assert!(stderr_contains(&result, "Unknown React component 'Chrat'"));
+}
+
+#[test]
+fn should_fail_on_malformed_data_file() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+    env.write_file("data/links.json", "{ not json");
+
+    // Act
+    let result = env.run_build();
+
+    // Assert
+    assert_failure(&result);
+    assert!(stderr_contains(&result, "links.json"));
+}
+
+#[test]
+fn should_expose_api_url_and_analytics_id_to_templates() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+    let config = env
+        .read_file("config.yaml")
+        .replace(
+            "  description: \"A test blog\"\n",
+            "  description: \"A test blog\"\n  api_url: \"https://api.example.com\"\n  google_analytics_id: \"G-TEST123\"\n",
+        );
+    env.write_file("config.yaml", &config);
+    let probe = "<span data-api=\"{{ config.api_url | safe }}\" data-ga=\"{{ config.google_analytics_id }}\"></span>";
+    env.write_file("templates/post.html", probe);
+    env.write_file("templates/index.html", probe);
+
+    // Act
+    let result = env.run_build();
+
+    // Assert
+    assert_success(&result);
+    let expected = r#"<span data-api="https://api.example.com" data-ga="G-TEST123"></span>"#;
+    assert_eq!(env.read_output("dev/test-post/index.html"), expected);
+    assert_eq!(env.read_output("index.html"), expected);
+}
+
+#[test]
+fn should_expose_build_year_to_templates() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+    let probe = "<footer data-built=\"{{ build_time }}\">{{ build_year }}</footer>";
+    env.write_file("templates/post.html", probe);
+    env.write_file("templates/index.html", probe);
+
+    // Act
+    let result = env.run_build();
+
+    // Assert
+    assert_success(&result);
+    let year = chrono::Utc::now().year().to_string();
+    let post = env.read_output("dev/test-post/index.html");
+    let index = env.read_output("index.html");
+    assert!(post.ends_with(&format!(">{}</footer>", year)));
+    assert!(post.contains(&format!("data-built=\"{}-", year)));
+    // Every page sees the same timestamp captured when the build started
+    assert_eq!(post, index);
+}
+
+#[test]
+fn should_generate_robots_txt() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+
+    // Act
+    let result = env.run_build();
+
+    // Assert
+    assert_success(&result);
+    assert!(env.output_exists("robots.txt"));
+
+    let robots = env.read_output("robots.txt");
+    assert!(robots.contains("Sitemap:"));
+    assert!(robots.contains("sitemap.xml"));
+}
+
+#[test]
+fn should_generate_atom_feed() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+
+    // Act
+    let result = env.run_build();
+
+    // Assert
+    assert_success(&result);
+    assert!(env.output_exists("atom.xml"));
+
+    let atom = env.read_output("atom.xml");
+    assert!(atom.contains("<feed"));
+    assert!(atom.contains("Test Post"));
+}
+
+#[test]
+fn should_generate_recent_json() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+
+    // Act
+    let result = env.run_build();
+
+    // Assert
+    assert_success(&result);
+    assert!(env.output_exists("recent.json"));
 
-    let search_index = env.read_output("search-index.json");
-    assert!(search_index.contains("Test Post"));
+    let recent = env.read_output("recent.json");
+    assert!(recent.contains("Test Post"));
 }
 
 #[test]
-fn should_exclude_hidden_posts_from_all_outputs() {
+fn should_fall_back_to_excerpt_without_description() {
     // Arrange
     let env = TestEnvironment::minimal();
-    env.create_hidden_post("dev", "hidden-post", "Hidden Post");
 
     // Act
     let result = env.run_build();
 
     // Assert
     assert_success(&result);
+    let excerpt = "This is a test post content. Some more content here.";
+    let recent: serde_json::Value = serde_json::from_str(&env.read_output("recent.json")).unwrap();
+    assert_eq!(recent[0]["desc"], excerpt);
+    let search: serde_json::Value =
+        serde_json::from_str(&env.read_output("search-index.json")).unwrap();
+    assert_eq!(search["posts"][0]["description"], excerpt);
+    assert!(env.read_output("feed.xml").contains(excerpt));
+}
 
-    // Hidden post should not have output file
-    assert!(!env.output_exists("dev/hidden-post/index.html"));
+#[test]
+fn should_limit_recent_json_to_configured_count() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+    for i in 1..=4 {
+        env.create_post("dev", &format!("extra-{}", i), &format!("Extra {}", i));
+    }
+    env.set_build_option("recent_count", "3");
 
-    // Hidden post should not be in sitemap
-    let sitemap = env.read_output("sitemap.xml");
-    assert!(!sitemap.contains("hidden-post"));
+    // Act
+    let result = env.run_build();
 
-    // Hidden post should not be in search index
-    let search_index = env.read_output("search-index.json");
-    assert!(!search_index.contains("Hidden Post"));
+    // Assert
+    assert_success(&result);
+    let recent: serde_json::Value = serde_json::from_str(&env.read_output("recent.json")).unwrap();
+    assert_eq!(recent.as_array().unwrap().len(), 3);
+}
 
-    // Hidden post should not be in RSS
-    let rss = env.read_output("feed.xml");
-    assert!(!rss.contains("Hidden Post"));
+#[test]
+fn should_write_empty_recent_json_for_zero_count() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+    env.set_build_option("recent_count", "0");
+
+    // Act
+    let result = env.run_build();
+
+    // Assert
+    assert_success(&result);
+    assert_eq!(env.read_output("recent.json"), "[]");
 }
 
 #[test]
-fn should_generate_category_index_pages() {
+fn should_write_recent_json_to_configured_path() {
     // Arrange
-    let env = TestEnvironment::with_categories(&["dev", "tutorials"]);
+    let env = TestEnvironment::minimal();
+    env.set_build_option("recent", "{ output_path: api/posts/recent.json }");
 
     // Act
     let result = env.run_build();
 
     // Assert
     assert_success(&result);
-    assert!(env.output_exists("dev/index.html"));
-    assert!(env.output_exists("tutorials/index.html"));
+    assert!(!env.output_exists("recent.json"));
+    assert!(env
+        .read_output("api/posts/recent.json")
+        .contains("\"uri\":\"https://test.example.com/dev/test-post"));
+}
 
-    let dev_index = env.read_output("dev/index.html");
-    assert!(dev_index.contains("test-dev"));
+#[test]
+fn should_write_site_relative_urls_to_recent_json() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+    env.set_build_option("recent", "{ absolute_urls: false }");
+
+    // Act
+    let result = env.run_build();
+
+    // Assert
+    assert_success(&result);
+    let recent: serde_json::Value = serde_json::from_str(&env.read_output("recent.json")).unwrap();
+    assert!(recent[0]["uri"]
+        .as_str()
+        .unwrap()
+        .starts_with("/dev/test-post"));
 }
 
 #[test]
-fn should_generate_tag_pages() {
+fn should_add_media_thumbnail_to_feed_items_with_cover() {
     // Arrange
     let env = TestEnvironment::minimal();
+    env.write_file(
+        "content/posts/dev/with-cover.md",
+        r#"---
+title: "With Cover"
+date: 2024-01-16T10:00:00Z
+tags: [test]
+coverImage: ./cover.png
+---
+
+Has a cover.
+"#,
+    );
+    env.write_file("content/posts/dev/cover.png", "png");
 
     // Act
     let result = env.run_build();
 
     // Assert
     assert_success(&result);
-    assert!(env.output_exists("tags/index.html"));
-    assert!(env.output_exists("tag/test/index.html"));
+    for feed in ["feed.xml", "dev/feed.xml"] {
+        let rss = env.read_output(feed);
+        assert!(rss.contains(r#"xmlns:media="http://search.yahoo.com/mrss/""#));
+        assert_eq!(rss.matches("<media:thumbnail").count(), 1, "{}", feed);
+        assert!(
+            rss.contains(r#"<media:thumbnail url="https://test.example.com/dev/cover.png" />"#),
+            "{}",
+            rss
+        );
+    }
+}
 
-    let tag_page = env.read_output("tag/test/index.html");
-    assert!(tag_page.contains("Test Post"));
+#[test]
+fn should_use_cdn_thumbnail_in_feed_items() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+    let config = env.read_file("config.yaml").replace(
+        "  description: \"A test blog\"\n",
+        "  description: \"A test blog\"\n  cdn_url: \"https://cdn.example.com\"\n",
+    );
+    env.write_file("config.yaml", &config);
+    env.write_file("content/posts/dev/cover.png", "png");
+    env.write_file(
+        "content/posts/dev/with-cover.md",
+        r#"---
+title: "With Cover"
+date: 2024-01-16T10:00:00Z
+tags: [test]
+coverImage: ./cover.png
+---
+
+Has a cover.
+"#,
+    );
+
+    // Act
+    let result = env.run_build();
+
+    // Assert
+    assert_success(&result);
+    let rss = env.read_output("feed.xml");
+    assert!(
+        rss.contains(r#"<media:thumbnail url="https://cdn.example.com/images/dev/cover."#),
+        "{}",
+        rss
+    );
 }
 
 #[test]
-fn should_copy_static_files() {
+fn should_add_enclosure_to_feed_items_with_audio() {
     // Arrange
     let env = TestEnvironment::minimal();
-    env.write_file("static/style.css", "body { color: black; }");
-    env.write_file("static/js/app.js", "console.log('test');");
+    env.write_file("content/posts/dev/episode-1.mp3", "0123456789");
+    env.write_file(
+        "content/posts/dev/episode-1.md",
+        r#"---
+title: "Episode 1"
+date: 2024-01-16T10:00:00Z
+tags: [podcast]
+audio:
+  url: ./episode-1.mp3
+---
+
+Show notes.
+"#,
+    );
+    env.write_file(
+        "content/posts/dev/episode-2.md",
+        r#"---
+title: "Episode 2"
+date: 2024-01-17T10:00:00Z
+tags: [podcast]
+audio: { url: "https://cdn.example.com/e2.m4a", length: 1234, type: audio/x-m4a }
+---
+
+Show notes.
+"#,
+    );
 
     // Act
     let result = env.run_build();
 
     // Assert
     assert_success(&result);
-    assert!(env.output_exists("style.css"));
-    assert!(env.output_exists("js/app.js"));
+    let rss = env.read_output("feed.xml");
+    assert!(
+        rss.contains(r#"<enclosure url="https://test.example.com/dev/episode-1.mp3" length="10" type="audio/mpeg" />"#),
+        "{}",
+        rss
+    );
+    assert!(rss.contains(
+        r#"<enclosure url="https://cdn.example.com/e2.m4a" length="1234" type="audio/x-m4a" />"#
+    ));
+    assert_eq!(rss.matches("<enclosure").count(), 2);
+    assert_eq!(
+        env.read_output("dev/feed.xml")
+            .matches("<enclosure")
+            .count(),
+        2
+    );
 }
 
 #[test]
-fn should_generate_robots_txt() {
+fn should_use_configured_site_language_in_feeds() {
     // Arrange
     let env = TestEnvironment::minimal();
+    let config = env.read_file("config.yaml").replace(
+        "  description: \"A test blog\"\n",
+        "  description: \"A test blog\"\n  language: \"en-US\"\n",
+    );
+    env.write_file("config.yaml", &config);
 
     // Act
     let result = env.run_build();
 
     // Assert
     assert_success(&result);
-    assert!(env.output_exists("robots.txt"));
+    let rss = env.read_output("feed.xml");
+    assert!(rss.contains("<language>en-US</language>"));
+    assert!(!rss.contains("ko-KR"));
+    assert!(env
+        .read_output("dev/feed.xml")
+        .contains("<language>en-US</language>"));
+    assert!(env.read_output("atom.xml").contains(r#"xml:lang="en-US""#));
+}
 
-    let robots = env.read_output("robots.txt");
-    assert!(robots.contains("Sitemap:"));
-    assert!(robots.contains("sitemap.xml"));
+#[test]
+fn should_announce_websub_hub_in_site_feeds() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+    env.set_build_option("feeds", "{ hub_url: \"https://hub.example.com/\" }");
+
+    // Act
+    let result = env.run_build();
+
+    // Assert
+    assert_success(&result);
+    let rss = env.read_output("feed.xml");
+    assert!(rss.contains(r#"<atom:link href="https://hub.example.com/" rel="hub" />"#));
+    assert!(rss.contains(
+        r#"<atom:link href="https://test.example.com/feed.xml" rel="self" type="application/rss+xml" />"#
+    ));
+    let atom = env.read_output("atom.xml");
+    assert!(atom.contains(r#"<link href="https://hub.example.com/" rel="hub" />"#));
+    assert!(atom.contains(r#"<link href="https://test.example.com/atom.xml" rel="self""#));
+    assert!(!env.read_output("dev/feed.xml").contains("rel=\"hub\""));
 }
 
 #[test]
-fn should_generate_atom_feed() {
+fn should_reference_feed_stylesheet_in_every_feed() {
     // Arrange
     let env = TestEnvironment::minimal();
+    env.set_build_option("feeds", "{ stylesheet: /feed.xsl }");
+    env.write_file("static/feed.xsl", "<xsl:stylesheet />");
 
     // Act
     let result = env.run_build();
 
     // Assert
     assert_success(&result);
-    assert!(env.output_exists("atom.xml"));
+    for feed in ["feed.xml", "atom.xml", "dev/feed.xml"] {
+        let xml = env.read_output(feed);
+        assert!(
+            xml.starts_with(
+                "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<?xml-stylesheet type=\"text/xsl\" href=\"/feed.xsl\"?>\n"
+            ),
+            "{}: {}",
+            feed,
+            xml
+        );
+    }
+    assert_eq!(env.read_output("feed.xsl"), "<xsl:stylesheet />");
+    assert!(!stderr_contains(&result, "Feed stylesheet"));
+}
 
-    let atom = env.read_output("atom.xml");
-    assert!(atom.contains("<feed"));
-    assert!(atom.contains("Test Post"));
+#[test]
+fn should_warn_about_missing_feed_stylesheet() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+    env.set_build_option("feeds", "{ stylesheet: /feed.xsl }");
+
+    // Act
+    let result = env.run_build();
+
+    // Assert
+    assert_success(&result);
+    assert!(stderr_contains(
+        &result,
+        "Feed stylesheet /feed.xsl not found in static/"
+    ));
 }
 
 #[test]
-fn should_generate_recent_json() {
+fn should_omit_websub_hub_by_default() {
     // Arrange
     let env = TestEnvironment::minimal();
 
@@ -188,10 +1285,111 @@ fn should_generate_recent_json() {
 
     // Assert
     assert_success(&result);
-    assert!(env.output_exists("recent.json"));
+    assert!(!env.read_output("feed.xml").contains("rel=\"hub\""));
+    assert!(!env.read_output("atom.xml").contains("rel=\"hub\""));
+}
 
-    let recent = env.read_output("recent.json");
-    assert!(recent.contains("Test Post"));
+#[test]
+fn should_include_cover_thumbnail_in_recent_json() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+    let config = env.read_file("config.yaml").replace(
+        "  description: \"A test blog\"\n",
+        "  description: \"A test blog\"\n  cdn_url: \"https://cdn.example.com\"\n",
+    );
+    env.write_file("config.yaml", &config);
+    env.write_file("content/posts/dev/cover.png", "png");
+    env.write_file(
+        "content/posts/dev/with-cover.md",
+        r#"---
+title: "With Cover"
+date: 2024-01-16T10:00:00Z
+tags: [test]
+coverImage: ./cover.png
+---
+
+Has a cover.
+"#,
+    );
+
+    // Act
+    let result = env.run_build();
+
+    // Assert
+    assert_success(&result);
+    let recent: serde_json::Value = serde_json::from_str(&env.read_output("recent.json")).unwrap();
+    let posts = recent.as_array().unwrap();
+    let with_cover = posts.iter().find(|p| p["title"] == "With Cover").unwrap();
+    let without_cover = posts.iter().find(|p| p["title"] == "Test Post").unwrap();
+    let thumbnail = with_cover["thumbnail"].as_str().unwrap();
+    assert!(
+        thumbnail.starts_with("https://cdn.example.com/images/dev/cover."),
+        "{}",
+        thumbnail
+    );
+    assert!(without_cover.get("thumbnail").is_none());
+}
+
+#[test]
+fn should_resolve_wikilinks_between_posts() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+    env.write_file(
+        "content/posts/dev/linking.md",
+        r#"---
+title: "Linking"
+date: 2024-01-16T10:00:00Z
+tags: [test]
+---
+
+Read [[test-post|the first post]] but not [[no-such-post]].
+"#,
+    );
+
+    // Act
+    let result = env.run_build();
+
+    // Assert
+    assert_success(&result);
+    let html = env.read_output("dev/linking/index.html");
+    assert!(html.contains(r#"href="/dev/test-post/""#), "{}", html);
+    assert!(html.contains(">the first post</a>"));
+    assert!(html.contains(r#"<span class="broken-wikilink">no-such-post</span>"#));
+    assert!(stderr_contains(
+        &result,
+        "Unresolved wikilink [[no-such-post]]"
+    ));
+}
+
+#[test]
+fn should_warn_only_about_missing_cover_images() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+    env.write_file("content/posts/dev/present.png", "png");
+    for (slug, cover) in [
+        ("with-cover", "./present.png"),
+        ("lost-cover", "./gone.png"),
+    ] {
+        env.write_file(
+            &format!("content/posts/dev/{}.md", slug),
+            &format!(
+                "---\ntitle: \"{}\"\ndate: 2024-01-16T10:00:00Z\ntags: []\ncoverImage: {}\n---\n\nBody\n",
+                slug, cover
+            ),
+        );
+    }
+
+    // Act
+    let result = env.run_build();
+
+    // Assert
+    assert_success(&result);
+    assert!(stderr_contains(
+        &result,
+        "Missing cover image /dev/gone.png"
+    ));
+    assert!(!stderr_contains(&result, "present.png"));
+    assert!(env.output_exists("dev/lost-cover/index.html"));
 }
 
 #[test]
@@ -223,6 +1421,56 @@ fn should_build_with_parallel_flag() {
     assert!(stdout_contains(&result, "threads"));
 }
 
+#[test]
+fn should_produce_identical_json_across_parallel_builds() {
+    // Arrange
+    let env = TestEnvironment::with_categories(&["dev", "tutorials", "chat"]);
+    for i in 1..=6 {
+        env.create_post("dev", &format!("extra-{}", i), &format!("Extra {}", i));
+    }
+
+    let result = env.run_build_parallel();
+    assert_success(&result);
+    let first_recent = env.read_output("recent.json");
+    let first_search = env.read_output("search-index.json");
+
+    // Act
+    let result = env.run_build_parallel();
+
+    // Assert
+    assert_success(&result);
+    assert_eq!(env.read_output("recent.json"), first_recent);
+    assert_eq!(env.read_output("search-index.json"), first_search);
+}
+
+#[test]
+fn should_hide_per_post_lines_with_quiet_flag() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+
+    // Act
+    let result = env.run_build_parallel_with_args(&["--quiet"]);
+
+    // Assert
+    assert_success(&result);
+    assert!(env.output_exists("dev/test-post/index.html"));
+    assert!(!stdout_contains(&result, "🔨 Built:"));
+}
+
+#[test]
+fn should_show_per_post_lines_with_verbose_flag() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+
+    // Act
+    let result = env.run_build_parallel_with_args(&["--verbose"]);
+
+    // Assert
+    assert_success(&result);
+    assert!(stdout_contains(&result, "🔨 Built:"));
+    assert!(stdout_contains(&result, "test-post.md"));
+}
+
 #[test]
 fn should_emit_feed_description_unescaped_inside_cdata() {
     // Arrange
@@ -262,6 +1510,36 @@ Body with `]]>` inside code.
     );
 }
 
+#[test]
+fn should_build_pages_next_to_custom_content_dir() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+    std::fs::create_dir_all(env.root.join("site")).unwrap();
+    std::fs::rename(env.root.join("content/posts"), env.root.join("site/posts")).unwrap();
+    env.set_build_option("content_dir", "\"site/posts\"");
+    env.write_file(
+        "site/pages/about.md",
+        "---\ntitle: \"About\"\n---\n\nAbout this blog.\n",
+    );
+    env.write_file(
+        "content/pages/stale.md",
+        "---\ntitle: \"Stale\"\n---\n\nFrom the default tree.\n",
+    );
+
+    // Act
+    let result = env.run_build();
+
+    // Assert
+    assert_success(&result);
+    assert!(env
+        .read_output("about/index.html")
+        .contains("About this blog."));
+    assert!(!env.output_exists("stale/index.html"));
+    let sitemap = env.read_output("sitemap.xml");
+    assert!(sitemap.contains("https://test.example.com/about/"));
+    assert!(!sitemap.contains("/stale/"));
+}
+
 #[test]
 fn should_generate_feeds_and_indices_even_when_a_page_fails() {
     // Arrange - page requests a template that doesn't exist
@@ -3,7 +3,7 @@
 mod common;
 
 use assert_cmd::Command;
-use common::{assert_success, stdout_contains, TestEnvironment};
+use common::{assert_failure, assert_success, stderr_contains, stdout_contains, TestEnvironment};
 use predicates::prelude::*;
 
 #[test]
@@ -51,6 +51,142 @@ fn should_create_new_post_in_existing_category() {
     assert!(content.contains("category: dev"));
 }
 
+#[test]
+fn should_create_new_post_in_configured_content_dir() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+    std::fs::create_dir_all(env.root.join("site")).unwrap();
+    std::fs::rename(env.root.join("content/posts"), env.root.join("site/posts")).unwrap();
+    env.set_build_option("content_dir", "\"site/posts\"");
+
+    // Act
+    let result = env.run_new_post("dev", "My New Post");
+
+    // Assert
+    assert_success(&result);
+    assert!(env.file_exists("site/posts/dev/my-new-post.md"));
+    assert!(!env.file_exists("content/posts/dev/my-new-post.md"));
+}
+
+#[test]
+fn should_create_new_post_with_tags_description_and_date() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+
+    // Act
+    let result = env.run_new_post_with_args(
+        "dev",
+        "Tagged Post",
+        &[
+            "--tags",
+            "rust, web",
+            "--description",
+            "A \"quoted\" summary",
+            "--date",
+            "2024-03-01",
+        ],
+    );
+
+    // Assert
+    assert_success(&result);
+    let content = env.read_file("content/posts/dev/tagged-post.md");
+    assert!(content.contains(r#"tags: ["rust","web"]"#));
+    assert!(content.contains(r#"description: "A \"quoted\" summary""#));
+    assert!(content.contains("date: 2024-03-01T00:00:00+00:00"));
+
+    // The generated frontmatter must round-trip through a build
+    assert_success(&env.run_build());
+}
+
+#[test]
+fn should_reject_invalid_new_post_date() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+
+    // Act
+    let result = env.run_new_post_with_args("dev", "Bad Date", &["--date", "03/01/2024"]);
+
+    // Assert
+    assert_failure(&result);
+    assert!(stderr_contains(&result, "Invalid --date '03/01/2024'"));
+    assert!(!env.file_exists("content/posts/dev/bad-date.md"));
+}
+
+#[test]
+fn should_create_new_page() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+
+    // Act
+    let result = env.run_new_page("About Me");
+
+    // Assert
+    assert_success(&result);
+    assert!(env.file_exists("content/pages/about-me.md"));
+
+    let content = env.read_file("content/pages/about-me.md");
+    assert!(content.contains("title: \"About Me\""));
+    assert!(content.contains("hidden: false"));
+}
+
+#[test]
+fn should_create_new_page_with_quoted_title() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+
+    // Act
+    let result = env.run_new_page(r#"Say "hi" \ wave"#);
+
+    // Assert
+    assert_success(&result);
+    let content = env.read_file("content/pages/say-hi-wave.md");
+    assert!(
+        content.contains(r#"title: "Say \"hi\" \\ wave""#),
+        "got: {}",
+        content
+    );
+
+    // The generated frontmatter must round-trip through a build
+    assert_success(&env.run_build());
+    assert!(env
+        .read_output("say-hi-wave/index.html")
+        .contains("Say &quot;hi&quot; \\ wave"));
+}
+
+#[test]
+fn should_create_new_page_next_to_content_dir_override() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+
+    // Act
+    let output = Command::cargo_bin("blog")
+        .expect("Failed to find blog binary")
+        .current_dir(&env.root)
+        .args(["new-page", "About Me", "--content-dir", "site/posts"])
+        .output()
+        .expect("Failed to execute new-page command");
+
+    // Assert
+    assert_success(&output);
+    assert!(env.file_exists("site/pages/about-me.md"));
+    assert!(!env.file_exists("content/pages/about-me.md"));
+}
+
+#[test]
+fn should_refuse_to_overwrite_existing_page() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+    env.write_file("content/pages/about-me.md", "keep me");
+
+    // Act
+    let result = env.run_new_page("About Me");
+
+    // Assert
+    assert_failure(&result);
+    assert!(stderr_contains(&result, "Page already exists"));
+    assert_eq!(env.read_file("content/pages/about-me.md"), "keep me");
+}
+
 #[test]
 fn should_show_available_categories_for_nonexistent_category() {
     // Arrange
@@ -91,6 +227,49 @@ fn should_accept_parallel_flag() {
     assert!(stdout_contains(&result, "threads"));
 }
 
+#[test]
+fn should_print_only_errors_with_quiet_flag() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+
+    // Act
+    let result = env.run_build_with_args(&["--quiet"]);
+
+    // Assert
+    assert_success(&result);
+    assert!(env.output_exists("dev/test-post/index.html"));
+    assert_eq!(String::from_utf8_lossy(&result.stdout).trim(), "");
+}
+
+#[test]
+fn should_print_phase_summaries_without_per_post_lines_by_default() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+
+    // Act
+    let result = env.run_build();
+
+    // Assert
+    assert_success(&result);
+    assert!(stdout_contains(&result, "Build complete"));
+    assert!(!stdout_contains(&result, "🔨 Building:"));
+}
+
+#[test]
+fn should_print_per_post_lines_with_verbose_flag() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+
+    // Act
+    let result = env.run_build_with_args(&["--verbose"]);
+
+    // Assert
+    assert_success(&result);
+    assert!(stdout_contains(&result, "🔨 Building:"));
+    assert!(stdout_contains(&result, "test-post.md"));
+    assert!(stdout_contains(&result, "Build complete"));
+}
+
 #[test]
 fn should_show_build_subcommand_help() {
     // Arrange & Act
@@ -104,6 +283,31 @@ fn should_show_build_subcommand_help() {
         .stdout(predicate::str::contains("parallel"));
 }
 
+#[test]
+fn should_build_with_overridden_directories() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+    std::fs::create_dir_all(env.root.join("site")).unwrap();
+    std::fs::rename(env.root.join("templates"), env.root.join("site/theme")).unwrap();
+    std::fs::rename(env.root.join("content/posts"), env.root.join("site/posts")).unwrap();
+
+    // Act
+    let result = env.run_build_with_args(&[
+        "--content-dir",
+        "site/posts",
+        "--template-dir",
+        "site/theme",
+        "--output-dir",
+        "public",
+    ]);
+
+    // Assert
+    assert_success(&result);
+    assert!(env.file_exists("public/dev/test-post/index.html"));
+    assert!(env.file_exists("public/sitemap.xml"));
+    assert!(!env.file_exists("dist"));
+}
+
 #[test]
 fn should_show_new_subcommand_help() {
     // Arrange & Act
@@ -124,5 +328,9 @@ fn should_show_watch_subcommand_help() {
     let assert = cmd.args(["watch", "--help"]).assert();
 
     // Assert
-    assert.success().stdout(predicate::str::contains("port"));
+    assert
+        .success()
+        .stdout(predicate::str::contains("port"))
+        .stdout(predicate::str::contains("--content-dir"))
+        .stdout(predicate::str::contains("--output-dir"));
 }
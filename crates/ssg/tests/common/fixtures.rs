@@ -64,6 +64,15 @@ tags: [
 Malformed YAML frontmatter.
 "#;
 
+pub const POST_UNKNOWN_KEY: &str = r#"---
+title: "Misspelled Key Post"
+date: 2024-01-15T10:00:00Z
+tag: [test]
+---
+
+Post with a misspelled frontmatter key.
+"#;
+
 pub const MINIMAL_TEMPLATES: &[(&str, &str)] = &[
     ("base.html", BASE_TEMPLATE),
     ("post.html", POST_TEMPLATE),
@@ -80,6 +89,8 @@ const BASE_TEMPLATE: &str = r#"<!DOCTYPE html>
 <head>
     <meta charset="UTF-8">
     <title>{% block title %}{{ config.site_title }}{% endblock %}</title>
+    <link rel="canonical" href="{{ canonical_url }}">
+    {% if noindex | default(value=false) %}<meta name="robots" content="noindex">{% endif %}
 </head>
 <body>
     <main>{% block content %}{% endblock %}</main>
@@ -105,11 +116,11 @@ const POST_TEMPLATE: &str = r#"{% extends "base.html" %}
 "#;
 
 const PAGE_TEMPLATE: &str = r#"{% extends "base.html" %}
-{% block title %}{{ page.frontmatter.title }} | {{ config.site_title }}{% endblock %}
+{% block title %}{{ page.title }} | {{ config.site_title }}{% endblock %}
 {% block content %}
 <article>
-    <h1>{{ page.frontmatter.title }}</h1>
-    <div class="content">{{ page.rendered_html | safe }}</div>
+    <h1>{{ page.title }}</h1>
+    <div class="content">{{ content | safe }}</div>
 </article>
 {% endblock %}
 "#;
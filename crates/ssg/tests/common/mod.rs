@@ -124,7 +124,8 @@ Hidden content for {}.
         Command::cargo_bin("blog")
             .expect("Failed to find blog binary")
             .current_dir(&self.root)
-            .args(["build", "--incremental", "--parallel=false"])
+            // Verbose so cache hits/misses show up as per-post lines
+            .args(["build", "--incremental", "--parallel=false", "--verbose"])
             .output()
             .expect("Failed to execute build command")
     }
@@ -138,6 +139,60 @@ Hidden content for {}.
             .expect("Failed to execute build command")
     }
 
+    pub fn run_build_with_args(&self, extra_args: &[&str]) -> std::process::Output {
+        Command::cargo_bin("blog")
+            .expect("Failed to find blog binary")
+            .current_dir(&self.root)
+            .args(["build", "--parallel=false"])
+            .args(extra_args)
+            .output()
+            .expect("Failed to execute build command")
+    }
+
+    pub fn run_build_parallel_with_args(&self, extra_args: &[&str]) -> std::process::Output {
+        Command::cargo_bin("blog")
+            .expect("Failed to find blog binary")
+            .current_dir(&self.root)
+            .args(["build", "--parallel=true"])
+            .args(extra_args)
+            .output()
+            .expect("Failed to execute build command")
+    }
+
+    pub fn run_build_with_env(&self, env: &str) -> std::process::Output {
+        Command::cargo_bin("blog")
+            .expect("Failed to find blog binary")
+            .current_dir(&self.root)
+            .args(["build", "--parallel=false", "--env", env])
+            .output()
+            .expect("Failed to execute build command")
+    }
+
+    pub fn run_build_incremental_with_env(&self, env: &str) -> std::process::Output {
+        Command::cargo_bin("blog")
+            .expect("Failed to find blog binary")
+            .current_dir(&self.root)
+            .args([
+                "build",
+                "--incremental",
+                "--parallel=false",
+                "--verbose",
+                "--env",
+                env,
+            ])
+            .output()
+            .expect("Failed to execute build command")
+    }
+
+    pub fn run_build_with_drafts(&self) -> std::process::Output {
+        Command::cargo_bin("blog")
+            .expect("Failed to find blog binary")
+            .current_dir(&self.root)
+            .args(["build", "--parallel=false", "--drafts"])
+            .output()
+            .expect("Failed to execute build command")
+    }
+
     pub fn run_new_post(&self, category: &str, title: &str) -> std::process::Output {
         Command::cargo_bin("blog")
             .expect("Failed to find blog binary")
@@ -147,6 +202,30 @@ Hidden content for {}.
             .expect("Failed to execute new command")
     }
 
+    pub fn run_new_post_with_args(
+        &self,
+        category: &str,
+        title: &str,
+        extra_args: &[&str],
+    ) -> std::process::Output {
+        Command::cargo_bin("blog")
+            .expect("Failed to find blog binary")
+            .current_dir(&self.root)
+            .args(["new", category, title])
+            .args(extra_args)
+            .output()
+            .expect("Failed to execute new command")
+    }
+
+    pub fn run_new_page(&self, title: &str) -> std::process::Output {
+        Command::cargo_bin("blog")
+            .expect("Failed to find blog binary")
+            .current_dir(&self.root)
+            .args(["new-page", title])
+            .output()
+            .expect("Failed to execute new-page command")
+    }
+
     pub fn run_help(&self) -> std::process::Output {
         Command::cargo_bin("blog")
             .expect("Failed to find blog binary")
@@ -188,6 +267,35 @@ Hidden content for {}.
         self.write_file("config.yaml", &content);
     }
 
+    /// Set `build.<key>` in config.yaml to the YAML `value`, replacing the
+    /// key (and anything nested under it) if it's already there. Nested
+    /// options are passed as flow mappings, e.g. `"{ shard_size: 2 }"`.
+    pub fn set_build_option(&self, key: &str, value: &str) {
+        let config = self.read_file("config.yaml");
+        let mut lines: Vec<&str> = config.lines().collect();
+        let build = lines
+            .iter()
+            .position(|line| *line == "build:")
+            .expect("config.yaml has no build section");
+        let block_end = (build + 1..lines.len())
+            .find(|&i| !lines[i].is_empty() && !lines[i].starts_with(' '))
+            .unwrap_or(lines.len());
+        let prefix = format!("  {}:", key);
+        let entry = format!("{} {}", prefix, value);
+
+        match (build + 1..block_end).find(|&i| lines[i].starts_with(&prefix)) {
+            Some(start) => {
+                let end = (start + 1..block_end)
+                    .find(|&i| !lines[i].starts_with("   "))
+                    .unwrap_or(block_end);
+                lines.splice(start..end, [entry.as_str()]);
+            }
+            None => lines.insert(build + 1, &entry),
+        }
+
+        self.write_file("config.yaml", &(lines.join("\n") + "\n"));
+    }
+
     pub fn delete_post(&self, category: &str, slug: &str) {
         let path = self
             .root
@@ -2,8 +2,10 @@
 
 mod common;
 
-use common::fixtures::{POST_INVALID_DATE, POST_MALFORMED_YAML, POST_MISSING_TITLE};
-use common::{assert_failure, stderr_contains, TestEnvironment};
+use common::fixtures::{
+    POST_INVALID_DATE, POST_MALFORMED_YAML, POST_MISSING_TITLE, POST_UNKNOWN_KEY,
+};
+use common::{assert_failure, assert_success, stderr_contains, TestEnvironment};
 
 #[test]
 fn should_error_on_missing_frontmatter_title() {
@@ -90,6 +92,21 @@ fn should_error_on_malformed_yaml_frontmatter() {
     assert_failure(&result);
 }
 
+#[test]
+fn should_error_on_unknown_frontmatter_key_with_file_and_key() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+    env.write_file("content/posts/dev/unknown-key.md", POST_UNKNOWN_KEY);
+
+    // Act
+    let result = env.run_build_parallel();
+
+    // Assert
+    assert_failure(&result);
+    assert!(stderr_contains(&result, "unknown-key.md"));
+    assert!(stderr_contains(&result, "`tag`"));
+}
+
 #[test]
 fn should_error_on_missing_content_directory() {
     // Arrange
@@ -123,3 +140,141 @@ fn should_error_on_post_file_not_found_for_single_build() {
     assert_failure(&result);
     assert!(stderr_contains(&result, "not found") || stderr_contains(&result, "exist"));
 }
+
+#[test]
+fn should_error_on_duplicate_slugs_across_categories() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+    env.create_category("chat");
+    env.create_post("chat", "test-post", "Same Slug, Other Category");
+
+    // Act
+    let result = env.run_build();
+    let parallel_result = env.run_build_parallel();
+
+    // Assert
+    for result in [result, parallel_result] {
+        assert_failure(&result);
+        assert!(stderr_contains(&result, "Duplicate post slugs"));
+        assert!(stderr_contains(&result, "dev/test-post.md"));
+        assert!(stderr_contains(&result, "chat/test-post.md"));
+    }
+}
+
+#[test]
+fn should_error_on_unknown_plugin() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+    env.set_build_option("plugins", "[{ name: no-such-plugin }]");
+
+    // Act
+    let result = env.run_build();
+
+    // Assert
+    assert_failure(&result);
+    assert!(stderr_contains(&result, "Unknown plugin 'no-such-plugin'"));
+}
+
+#[test]
+fn should_error_on_recent_output_path_outside_output_dir() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+    env.set_build_option("recent", "{ output_path: ../recent.json }");
+
+    // Act
+    let result = env.run_build();
+
+    // Assert
+    assert_failure(&result);
+    assert!(stderr_contains(&result, "build.recent.output_path"));
+    assert!(!env.file_exists("recent.json"));
+}
+
+#[test]
+fn should_fail_strict_build_on_missing_og_image() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+    env.write_file(
+        "content/posts/dev/lost-og.md",
+        "---\ntitle: \"Lost\"\ndate: 2024-01-16T10:00:00Z\ntags: []\nogImage: ./gone.png\n---\n\nBody\n",
+    );
+
+    // Act
+    let result = env.run_build_with_args(&["--strict"]);
+    let parallel_result = env.run_build_parallel_with_args(&["--strict"]);
+
+    // Assert
+    for result in [result, parallel_result] {
+        assert_failure(&result);
+        assert!(stderr_contains(&result, "Missing OG image /dev/gone.png"));
+        assert!(stderr_contains(&result, "lost-og.md"));
+    }
+}
+
+#[test]
+fn should_fail_strict_build_on_unresolved_wikilink() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+    env.write_file(
+        "content/posts/dev/linking.md",
+        "---\ntitle: \"Linking\"\ndate: 2024-01-16T10:00:00Z\ntags: []\n---\n\nSee [[nowhere]].\n",
+    );
+
+    // Act
+    let result = env.run_build_with_args(&["--strict"]);
+
+    // Assert
+    assert_failure(&result);
+    assert!(stderr_contains(&result, "Unresolved wikilinks"));
+    assert!(stderr_contains(&result, "[[nowhere]]"));
+}
+
+#[test]
+fn should_skip_post_outside_category_directory_with_warning() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+    env.write_file(
+        "content/posts/stray.md",
+        "---\ntitle: \"Stray\"\ndate: 2024-01-16T10:00:00Z\ntags: []\n---\n\nBody\n",
+    );
+
+    // Act
+    let result = env.run_build();
+    let parallel_result = env.run_build_parallel();
+
+    // Assert
+    for result in [result, parallel_result] {
+        assert_success(&result);
+        assert!(stderr_contains(&result, "Skipping"));
+        assert!(stderr_contains(&result, "stray.md"));
+    }
+    assert!(env.file_exists("dist/dev/test-post/index.html"));
+    assert!(!env.file_exists("dist/stray/index.html"));
+}
+
+#[test]
+fn should_fail_strict_build_listing_posts_outside_category_directory() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+    for name in ["stray", "lost"] {
+        env.write_file(
+            &format!("content/posts/{}.md", name),
+            "---\ntitle: \"Stray\"\ndate: 2024-01-16T10:00:00Z\ntags: []\n---\n\nBody\n",
+        );
+    }
+
+    // Act
+    let result = env.run_build_with_args(&["--strict"]);
+    let parallel_result = env.run_build_parallel_with_args(&["--strict"]);
+
+    // Assert
+    for result in [result, parallel_result] {
+        assert_failure(&result);
+        assert!(stderr_contains(
+            &result,
+            "Posts outside a category directory"
+        ));
+        assert!(stderr_contains(&result, "stray.md"));
+        assert!(stderr_contains(&result, "lost.md"));
+    }
+}
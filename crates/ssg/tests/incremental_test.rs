@@ -118,6 +118,26 @@ fn should_rebuild_when_config_changes() {
     assert!(!stdout_contains(&result, "Skipping"));
 }
 
+#[test]
+fn should_rebuild_when_env_config_overlay_changes() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+    env.write_file("config.staging.yaml", "build:\n  posts_per_page: 5\n");
+
+    let result = env.run_build_incremental_with_env("staging");
+    assert_success(&result);
+
+    env.write_file("config.staging.yaml", "build:\n  posts_per_page: 3\n");
+
+    // Act
+    let result = env.run_build_incremental_with_env("staging");
+
+    // Assert - config.yaml is untouched, but the effective config changed
+    assert_success(&result);
+    assert!(stdout_contains(&result, "Building"));
+    assert!(!stdout_contains(&result, "Skipping"));
+}
+
 #[test]
 fn should_remove_output_when_post_deleted() {
     // Arrange
@@ -139,6 +159,58 @@ fn should_remove_output_when_post_deleted() {
     assert!(env.output_exists("dev/test-post/index.html"));
 }
 
+#[test]
+fn should_remove_precompressed_siblings_when_post_deleted() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+    env.set_build_option("precompress", "[gzip, brotli]");
+    env.set_build_option("precompress_min_size", "0");
+    env.create_post("dev", "doomed-post", "Doomed Post");
+
+    let result = env.run_build_incremental();
+    assert_success(&result);
+    assert!(env.output_exists("dev/doomed-post/index.html.gz"));
+
+    env.delete_post("dev", "doomed-post");
+
+    // Act
+    let result = env.run_build_incremental();
+
+    // Assert
+    assert_success(&result);
+    assert!(!env.output_exists("dev/doomed-post/index.html.gz"));
+    assert!(!env.output_exists("dev/doomed-post/index.html.br"));
+    assert!(!env.output_exists("dev/doomed-post"));
+    assert!(env.output_exists("dev/test-post/index.html.gz"));
+}
+
+#[test]
+fn should_drop_deleted_post_from_aggregates_on_incremental_build() {
+    // Arrange
+    let env = TestEnvironment::minimal();
+    env.create_post("dev", "doomed-post", "Doomed Post");
+
+    let result = env.run_build_incremental();
+    assert_success(&result);
+    assert!(env.read_output("sitemap.xml").contains("doomed-post"));
+
+    env.delete_post("dev", "doomed-post");
+
+    // Act
+    let result = env.run_build_incremental();
+
+    // Assert - the unchanged post is skipped, yet aggregates reflect the deletion
+    assert_success(&result);
+    assert!(stdout_contains(&result, "Skipping"));
+    assert!(!env.output_exists("dev/doomed-post/index.html"));
+    assert!(!env.read_output("sitemap.xml").contains("doomed-post"));
+    assert!(!env.read_output("feed.xml").contains("Doomed Post"));
+    assert!(!env.read_output("dev/index.html").contains("Doomed Post"));
+    assert!(!env
+        .read_file(".build-cache/metadata.json")
+        .contains("doomed-post"));
+}
+
 #[test]
 fn should_remove_output_when_post_becomes_hidden() {
     // Arrange